@@ -0,0 +1,25 @@
+use std::process::Command;
+
+/// Expose the commit being built as `GIT_HASH`, taken from the environment
+/// when set, e.g. when building from an archive, or from git.
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_HASH");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    if let Ok(head) = std::fs::read_to_string(".git/HEAD") {
+        if let Some(reference) = head.trim().strip_prefix("ref: ") {
+            println!("cargo:rerun-if-changed=.git/{}", reference);
+        }
+    }
+    let git_hash = std::env::var("GIT_HASH").ok().or_else(|| {
+        let output = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .ok()?;
+        let hash = String::from_utf8(output.stdout).ok()?;
+        output.status.success().then(|| hash.trim().to_string())
+    });
+    println!(
+        "cargo:rustc-env=GIT_HASH={}",
+        git_hash.unwrap_or_else(|| "unknown".to_string())
+    );
+}
@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+
+use crate::models::comment::Comment;
+
+/// Hook called when a user is mentioned in a comment, to let them know.
+#[async_trait]
+pub trait MentionNotifier {
+    /// Notify `user_id` that they were mentioned in the comment. Failing to
+    /// deliver a notification must not fail the comment, so nothing is
+    /// returned.
+    async fn notify_mention(&self, _user_id: ObjectId, _comment: &Comment) {}
+}
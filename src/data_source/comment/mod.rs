@@ -1,12 +1,14 @@
 pub mod bookmark;
 pub mod error;
 pub mod like;
+pub mod mention;
 use async_graphql::futures_util::stream::BoxStream;
 use async_trait::async_trait;
 use mongodb::bson::{self, oid::ObjectId};
 
 use crate::{
-    models::comment::{Comment, CreateCommentInput, UpdateCommentInput},
+    common::DateTime,
+    models::comment::{Comment, CommentVersion, CreateCommentInput, Mention, UpdateCommentInput},
     services::cv_service::comment_service::CommentServiceError,
 };
 
@@ -36,6 +38,33 @@ pub trait CommentDataSource {
         input: UpdateCommentInput,
     ) -> Result<Comment, Self::Error>;
 
+    /// Replace the content and mentions of a comment that is not deleted,
+    /// keeping `previous` in its history.
+    async fn edit_comment(
+        &self,
+        id: bson::oid::ObjectId,
+        content: String,
+        mentions: Vec<Mention>,
+        edited_at: DateTime,
+        previous: CommentVersion,
+    ) -> Result<Comment, Self::Error>;
+
+    /// Turn the comment into a tombstone: its content and history are
+    /// cleared, its replies are kept.
+    async fn delete_comment(
+        &self,
+        id: bson::oid::ObjectId,
+        deleted_at: DateTime,
+    ) -> Result<Comment, Self::Error>;
+
+    /// Hide the content of the comment when `hidden_at` is given, show it
+    /// again otherwise.
+    async fn set_comment_hidden(
+        &self,
+        id: bson::oid::ObjectId,
+        hidden_at: Option<DateTime>,
+    ) -> Result<Comment, Self::Error>;
+
     async fn add_reply_to_comment(
         &self,
         comment_id: bson::oid::ObjectId,
@@ -47,4 +76,12 @@ pub trait CommentDataSource {
         comment_id: bson::oid::ObjectId,
         reply_id: bson::oid::ObjectId,
     ) -> Result<Comment, Self::Error>;
+
+    /// Return the comment starting the thread and the comments in the thread,
+    /// oldest first, at most `limit` comments in all.
+    async fn get_thread(
+        &self,
+        root_id: bson::oid::ObjectId,
+        limit: usize,
+    ) -> Result<Vec<Comment>, Self::Error>;
 }
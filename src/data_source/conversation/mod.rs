@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    common::DateTime,
+    models::conversation::{Conversation, Message},
+    services::conversation_service::error::ConversationServiceError,
+};
+
+#[async_trait]
+pub trait ConversationDataSource {
+    type Error: std::error::Error + Send + Sync + Into<ConversationServiceError>;
+
+    /// Store the conversation unless there already is one between the same
+    /// users, and return the stored conversation.
+    async fn add_conversation(
+        &self,
+        conversation: Conversation,
+    ) -> Result<Conversation, Self::Error>;
+
+    async fn get_conversation(&self, id: ObjectId) -> Result<Option<Conversation>, Self::Error>;
+
+    /// Return the conversation between exactly these users, if any. The ids
+    /// must be sorted, as in `Conversation::participant_ids`.
+    async fn find_conversation(
+        &self,
+        participant_ids: Vec<ObjectId>,
+    ) -> Result<Option<Conversation>, Self::Error>;
+
+    /// Return the conversations of the user, most recently active first.
+    async fn get_conversations_by_user(
+        &self,
+        user_id: ObjectId,
+    ) -> Result<Vec<Conversation>, Self::Error>;
+
+    /// Store the message and move its conversation to the top of the
+    /// conversation list of its participants. The conversation is only
+    /// touched once the message is stored.
+    async fn add_message(&self, message: Message) -> Result<(), Self::Error>;
+
+    /// Return the messages of the conversation, newest first.
+    async fn get_messages(&self, conversation_id: ObjectId) -> Result<Vec<Message>, Self::Error>;
+
+    async fn get_last_message(
+        &self,
+        conversation_id: ObjectId,
+    ) -> Result<Option<Message>, Self::Error>;
+
+    /// Mark the unread messages of the conversation that were not sent by
+    /// `reader_id` as read, return how many were marked.
+    async fn mark_messages_read(
+        &self,
+        conversation_id: ObjectId,
+        reader_id: ObjectId,
+        read_at: DateTime,
+    ) -> Result<u64, Self::Error>;
+
+    /// Count the unread messages of the conversation that were not sent by
+    /// `reader_id`.
+    async fn count_unread_messages(
+        &self,
+        conversation_id: ObjectId,
+        reader_id: ObjectId,
+    ) -> Result<u64, Self::Error>;
+}
@@ -38,6 +38,9 @@ pub enum CVDataSourceError {
     // Cannot find CV
     QueryFail,
 
+    /// The CV already has a revision with this number.
+    RevisionNumberTaken(u32),
+
     /// Database error
     DatabaseError,
 }
@@ -81,6 +84,9 @@ impl fmt::Display for CVDataSourceError {
             CVDataSourceError::QueryFail => {
                 write!(f, "Fail to find CV")
             }
+            CVDataSourceError::RevisionNumberTaken(number) => {
+                write!(f, "Revision {} already exists", number)
+            }
         }
     }
 }
@@ -149,4 +155,10 @@ mod tests {
         let err = CVDataSourceError::QueryFail;
         assert_eq!(format!("{}", err), format!("Fail to find CV"));
     }
+
+    #[test]
+    fn test_revision_number_taken() {
+        let err = CVDataSourceError::RevisionNumberTaken(2);
+        assert_eq!(format!("{}", err), format!("Revision 2 already exists"));
+    }
 }
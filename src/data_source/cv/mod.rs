@@ -2,7 +2,11 @@ mod error;
 pub mod like;
 pub mod bookmark;
 pub mod share;
+pub mod revision;
+pub mod share_link;
+pub mod text_extraction;
 
+use crate::common::DateTime;
 use crate::models::{
     comment::Comment,
     cv::{CreateCVInput, UpdateCVInput, CV},
@@ -21,6 +25,10 @@ pub trait CVDataSource {
         unimplemented!()
     }
 
+    /// Return the CV the comment was left on. Replies are not part of the
+    /// comments of a CV, the comment starting their thread is.
+    async fn get_cv_by_comment_id(&self, comment_id: ObjectId) -> Result<CV, CVDataSourceError>;
+
     /// Add CV with the provided input.
     async fn create_cv(&self, _input: CreateCVInput) -> Result<CV, CVDataSourceError> {
         unimplemented!()
@@ -72,4 +80,14 @@ pub trait CVDataSource {
         &self,
         _user_id: ObjectId,
     ) -> Result<BoxStream<Result<CV, CVDataSourceError>>, CVDataSourceError>;
+
+    /// Hide the CV from everyone but its author when `hidden_at` is given,
+    /// show it again otherwise.
+    async fn set_cv_hidden(
+        &self,
+        _cv_id: ObjectId,
+        _hidden_at: Option<DateTime>,
+    ) -> Result<CV, CVDataSourceError> {
+        unimplemented!()
+    }
 }
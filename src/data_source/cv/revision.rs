@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+
+use crate::models::cv::{CVRevision, CV};
+
+use super::{CVDataSource, CVDataSourceError};
+
+/// Stores the revision history of CVs. Revisions are immutable, so there is
+/// no way to update or delete a single revision.
+#[async_trait]
+pub trait CVRevisionDataSource: CVDataSource {
+    /// Store the revision. Return `RevisionNumberTaken` if the CV already has
+    /// a revision with the same number.
+    async fn add_cv_revision(&self, revision: CVRevision) -> Result<(), CVDataSourceError>;
+
+    /// Return the number of the newest revision of the CV, 0 if there is none.
+    async fn last_cv_revision_number(&self, cv_id: ObjectId) -> Result<u32, CVDataSourceError>;
+
+    async fn get_cv_revision_by_id(&self, id: ObjectId) -> Result<CVRevision, CVDataSourceError>;
+
+    /// Return the revisions of the CV, oldest first.
+    async fn get_cv_revisions(&self, cv_id: ObjectId) -> Result<Vec<CVRevision>, CVDataSourceError>;
+
+    /// Overwrite the metadata of the CV with the one of the revision. Unlike
+    /// `find_and_update_cv`, fields the revision does not have are removed.
+    async fn restore_cv_revision(&self, revision: &CVRevision) -> Result<CV, CVDataSourceError>;
+}
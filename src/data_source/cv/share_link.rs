@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+
+use crate::{models::cv::ShareLink, services::cv_service::error::CVServiceError};
+
+use super::CVDataSource;
+
+#[async_trait]
+pub trait ShareLinkDataSource: CVDataSource {
+    type Error: std::error::Error + Send + Sync + Into<CVServiceError>;
+
+    /// Store a newly created share link.
+    async fn add_share_link(&self, link: ShareLink) -> Result<(), Self::Error>;
+
+    async fn get_share_link_by_id(&self, id: ObjectId) -> Result<ShareLink, Self::Error>;
+
+    async fn get_share_link_by_token(&self, token: &str) -> Result<ShareLink, Self::Error>;
+
+    /// Increase the view count of the link with the provided token and return
+    /// the updated link. Fails if the link is revoked or has no views left, so
+    /// concurrent views cannot exceed the limit.
+    async fn record_share_link_view(&self, token: &str) -> Result<ShareLink, Self::Error>;
+
+    async fn revoke_share_link(&self, id: ObjectId) -> Result<(), Self::Error>;
+
+    async fn get_share_links_of_cv(&self, cv_id: ObjectId) -> Result<Vec<ShareLink>, Self::Error>;
+}
@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+
+use crate::models::cv::TextExtraction;
+
+use super::{CVDataSource, CVDataSourceError};
+
+/// Stores the text extracted from the files of CVs.
+#[async_trait]
+pub trait CVTextDataSource: CVDataSource {
+    /// Store the outcome of the text extraction of the CV. Nothing is changed
+    /// if another file has been attached to the CV in the meantime, so a slow
+    /// extraction cannot overwrite the outcome for a newer file.
+    async fn update_cv_text_extraction(
+        &self,
+        cv_id: ObjectId,
+        extraction: TextExtraction,
+    ) -> Result<(), CVDataSourceError>;
+}
@@ -1,6 +1,8 @@
 use std::fmt;
 
-#[derive(Debug)]
+use async_graphql::ErrorExtensions;
+
+#[derive(Debug, PartialEq)]
 pub enum FriendsListError {
     UserNotFound,
 
@@ -18,6 +20,16 @@ pub enum FriendsListError {
 
     // Database error
     DatabaseError,
+
+    /// The users are already friends.
+    AlreadyFriends,
+
+    /// The previous request was rejected too recently, holds the number of
+    /// seconds left before a new request can be sent.
+    FriendRequestCooldown(u64),
+
+    /// One of the users has blocked the other.
+    UserBlocked,
 }
 
 impl fmt::Display for FriendsListError {
@@ -51,6 +63,45 @@ impl fmt::Display for FriendsListError {
             FriendsListError::DatabaseError => {
                 write!(f, "Database error")
             }
+
+            FriendsListError::AlreadyFriends => {
+                write!(f, "Already friends")
+            }
+
+            FriendsListError::FriendRequestCooldown(seconds) => {
+                write!(
+                    f,
+                    "Friend request was rejected recently, try again in {} seconds",
+                    seconds
+                )
+            }
+
+            FriendsListError::UserBlocked => {
+                write!(f, "User is blocked")
+            }
         }
     }
 }
+
+impl ErrorExtensions for FriendsListError {
+    fn extend(&self) -> async_graphql::Error {
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| match self {
+            FriendsListError::UserNotFound => e.set("code", "USER_NOT_FOUND"),
+            FriendsListError::AddFriendFailed => e.set("code", "ADD_FRIEND_FAILED"),
+            FriendsListError::FriendRequestNotFound => e.set("code", "FRIEND_REQUEST_NOT_FOUND"),
+            FriendsListError::FriendRequestAlreadyExist => {
+                e.set("code", "FRIEND_REQUEST_ALREADY_EXIST")
+            }
+            FriendsListError::UpdateFriendRequestFailed => {
+                e.set("code", "UPDATE_FRIEND_REQUEST_FAILED")
+            }
+            FriendsListError::DatabaseError => e.set("code", "DATABASE_ERROR"),
+            FriendsListError::AlreadyFriends => e.set("code", "ALREADY_FRIENDS"),
+            FriendsListError::FriendRequestCooldown(seconds) => {
+                e.set("code", "FRIEND_REQUEST_COOLDOWN");
+                e.set("retryAfter", *seconds);
+            }
+            FriendsListError::UserBlocked => e.set("code", "USER_BLOCKED"),
+        })
+    }
+}
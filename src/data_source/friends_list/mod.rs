@@ -25,6 +25,15 @@ pub trait FriendsListDataSource {
         unimplemented!()
     }
 
+    /// Delete the friend request sent by `from` to `to`.
+    async fn delete_friend_request(
+        &self,
+        _from: bson::oid::ObjectId,
+        _to: bson::oid::ObjectId,
+    ) -> Result<(), FriendsListError> {
+        unimplemented!()
+    }
+
     async fn get_friend_request(
         &self,
         _from: bson::oid::ObjectId,
@@ -55,4 +64,11 @@ pub trait FriendsListDataSource {
     ) -> BoxStream<Result<FriendRequest, FriendsListError>> {
         unimplemented!()
     }
+
+    /// Return the accepted friend requests of any of the users, in a single
+    /// query.
+    async fn accepted_friend_requests_of_users<'a>(
+        &'a self,
+        user_ids: Vec<bson::oid::ObjectId>,
+    ) -> BoxStream<'a, Result<FriendRequest, FriendsListError>>;
 }
@@ -1,14 +1,23 @@
 mod comment;
+mod conversation;
 pub mod cv;
 mod cv_details;
 mod friends_list;
 pub mod mongo;
+mod reaction;
+mod report;
 mod user;
 
 pub use comment::bookmark::BookmarkDataSource;
 pub use comment::like::LikeDataSource;
+pub use comment::mention::MentionNotifier;
 pub use comment::CommentDataSource;
+pub use conversation::ConversationDataSource;
 pub use cv::{CVDataSource, CVDataSourceError};
 pub use cv_details::CVDetailsDataSource;
 pub use friends_list::{FriendsListDataSource, FriendsListError};
+pub use reaction::ReactionDataSource;
+pub use report::ReportDataSource;
+pub use user::block::BlockDataSource;
+pub use user::login_attempt::LoginAttemptDataSource;
 pub use user::{UserDataSource, UserDataSourceError};
@@ -0,0 +1,264 @@
+//! Implements the `ConversationDataSource` trait for `MongoDB`.
+
+use std::fmt::Display;
+
+use async_graphql::futures_util::stream::TryStreamExt;
+use mongodb::{
+    bson::{self, oid::ObjectId},
+    error::{ErrorKind, WriteFailure},
+    options::{FindOneAndUpdateOptions, FindOneOptions, FindOptions, IndexOptions, ReturnDocument},
+    IndexModel,
+};
+
+use crate::{
+    common::DateTime,
+    data_source::ConversationDataSource,
+    models::conversation::{Conversation, Message},
+    services::conversation_service::error::ConversationServiceError,
+};
+
+use super::MongoDB;
+
+const CONVERSATION_COLLECTION: &str = "conversations";
+const MESSAGE_COLLECTION: &str = "messages";
+
+/// Error type for `ConversationDataSource` operations.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ConversationError {
+    // fail to add conversation
+    AddConversationFail,
+
+    // fail to add message
+    AddMessageFail,
+
+    // cannot find conversation
+    ConversationNotFound(ObjectId),
+
+    // fail to update messages or conversations
+    UpdateFail,
+
+    // fail to do queries
+    QueryFail,
+}
+
+impl Display for ConversationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversationError::AddConversationFail => {
+                write!(f, "fail to add conversation!")
+            }
+            ConversationError::AddMessageFail => {
+                write!(f, "fail to add message!")
+            }
+            ConversationError::ConversationNotFound(id) => {
+                write!(f, "cannot find conversation {:?}!", id)
+            }
+            ConversationError::UpdateFail => {
+                write!(f, "fail to update!")
+            }
+            ConversationError::QueryFail => {
+                write!(f, "fail to do queries!")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConversationError {}
+
+/// Whether the write failed on a unique index.
+pub(super) fn is_duplicate_key(error: &mongodb::error::Error) -> bool {
+    matches!(
+        *error.kind,
+        ErrorKind::Write(WriteFailure::WriteError(ref error)) if error.code == 11000
+    ) || matches!(*error.kind, ErrorKind::Command(ref error) if error.code == 11000)
+}
+
+impl MongoDB {
+    /// Create the unique index which keeps two users from having several
+    /// conversations. Conversations created before `participants_key` existed
+    /// are left out of it.
+    pub(super) async fn create_conversation_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let collection = self.db.collection::<Conversation>(CONVERSATION_COLLECTION);
+        let options = IndexOptions::builder()
+            .unique(true)
+            .partial_filter_expression(bson::doc! {"participants_key": {"$type": "string"}})
+            .build();
+        let index = IndexModel::builder()
+            .keys(bson::doc! {"participants_key": 1})
+            .options(options)
+            .build();
+        collection.create_index(index, None).await.map(|_| ())
+    }
+}
+
+impl From<ConversationError> for ConversationServiceError {
+    fn from(value: ConversationError) -> Self {
+        match value {
+            ConversationError::ConversationNotFound(id) => {
+                ConversationServiceError::ConversationNotFound(id)
+            }
+            _ => ConversationServiceError::DatabaseError,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ConversationDataSource for MongoDB {
+    type Error = ConversationError;
+
+    async fn add_conversation(
+        &self,
+        conversation: Conversation,
+    ) -> Result<Conversation, Self::Error> {
+        let collection = self.db.collection::<Conversation>(CONVERSATION_COLLECTION);
+        let filter = bson::doc! {"participant_ids": &conversation.participant_ids};
+        let update = bson::doc! {
+            "$setOnInsert": bson::to_document(&conversation)
+                .map_err(|_| ConversationError::AddConversationFail)?
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .build();
+        match collection.find_one_and_update(filter, update, options).await {
+            Ok(Some(conversation)) => Ok(conversation),
+            // the insert of a concurrent upsert won, read its conversation
+            Err(error) if is_duplicate_key(&error) => self
+                .find_conversation(conversation.participant_ids)
+                .await?
+                .ok_or(ConversationError::AddConversationFail),
+            _ => Err(ConversationError::AddConversationFail),
+        }
+    }
+
+    async fn get_conversation(&self, id: ObjectId) -> Result<Option<Conversation>, Self::Error> {
+        let collection = self.db.collection::<Conversation>(CONVERSATION_COLLECTION);
+        collection
+            .find_one(bson::doc! {"_id": id}, None)
+            .await
+            .map_err(|_| ConversationError::QueryFail)
+    }
+
+    async fn find_conversation(
+        &self,
+        participant_ids: Vec<ObjectId>,
+    ) -> Result<Option<Conversation>, Self::Error> {
+        let collection = self.db.collection::<Conversation>(CONVERSATION_COLLECTION);
+        collection
+            .find_one(bson::doc! {"participant_ids": participant_ids}, None)
+            .await
+            .map_err(|_| ConversationError::QueryFail)
+    }
+
+    async fn get_conversations_by_user(
+        &self,
+        user_id: ObjectId,
+    ) -> Result<Vec<Conversation>, Self::Error> {
+        let collection = self.db.collection::<Conversation>(CONVERSATION_COLLECTION);
+        let options = FindOptions::builder()
+            .sort(bson::doc! {"updated": -1, "_id": -1})
+            .build();
+        let cursor = collection
+            .find(bson::doc! {"participant_ids": user_id}, options)
+            .await
+            .map_err(|_| ConversationError::QueryFail)?;
+        cursor
+            .try_collect()
+            .await
+            .map_err(|_| ConversationError::QueryFail)
+    }
+
+    async fn add_message(&self, message: Message) -> Result<(), Self::Error> {
+        let conversations = self.db.collection::<Conversation>(CONVERSATION_COLLECTION);
+        let conversation_id = *message.conversation_id;
+        if conversations
+            .count_documents(bson::doc! {"_id": conversation_id}, None)
+            .await
+            .map_err(|_| ConversationError::QueryFail)?
+            == 0
+        {
+            return Err(ConversationError::ConversationNotFound(conversation_id));
+        }
+        let created = bson::DateTime::from(message.created);
+        let messages = self.db.collection::<Message>(MESSAGE_COLLECTION);
+        messages
+            .insert_one(message, None)
+            .await
+            .map_err(|_| ConversationError::AddMessageFail)?;
+        conversations
+            .update_one(
+                bson::doc! {"_id": conversation_id},
+                bson::doc! {"$set": {"updated": created}},
+                None,
+            )
+            .await
+            .map(|_| ())
+            .map_err(|_| ConversationError::UpdateFail)
+    }
+
+    async fn get_messages(&self, conversation_id: ObjectId) -> Result<Vec<Message>, Self::Error> {
+        let collection = self.db.collection::<Message>(MESSAGE_COLLECTION);
+        let options = FindOptions::builder()
+            .sort(bson::doc! {"created": -1, "_id": -1})
+            .build();
+        let cursor = collection
+            .find(bson::doc! {"conversation_id": conversation_id}, options)
+            .await
+            .map_err(|_| ConversationError::QueryFail)?;
+        cursor
+            .try_collect()
+            .await
+            .map_err(|_| ConversationError::QueryFail)
+    }
+
+    async fn get_last_message(
+        &self,
+        conversation_id: ObjectId,
+    ) -> Result<Option<Message>, Self::Error> {
+        let collection = self.db.collection::<Message>(MESSAGE_COLLECTION);
+        let options = FindOneOptions::builder()
+            .sort(bson::doc! {"created": -1, "_id": -1})
+            .build();
+        collection
+            .find_one(bson::doc! {"conversation_id": conversation_id}, options)
+            .await
+            .map_err(|_| ConversationError::QueryFail)
+    }
+
+    async fn mark_messages_read(
+        &self,
+        conversation_id: ObjectId,
+        reader_id: ObjectId,
+        read_at: DateTime,
+    ) -> Result<u64, Self::Error> {
+        let collection = self.db.collection::<Message>(MESSAGE_COLLECTION);
+        let filter = bson::doc! {
+            "conversation_id": conversation_id,
+            "sender_id": {"$ne": reader_id},
+            "read_at": null
+        };
+        let update = bson::doc! {"$set": {"read_at": bson::DateTime::from(read_at)}};
+        collection
+            .update_many(filter, update, None)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|_| ConversationError::UpdateFail)
+    }
+
+    async fn count_unread_messages(
+        &self,
+        conversation_id: ObjectId,
+        reader_id: ObjectId,
+    ) -> Result<u64, Self::Error> {
+        let collection = self.db.collection::<Message>(MESSAGE_COLLECTION);
+        let filter = bson::doc! {
+            "conversation_id": conversation_id,
+            "sender_id": {"$ne": reader_id},
+            "read_at": null
+        };
+        collection
+            .count_documents(filter, None)
+            .await
+            .map_err(|_| ConversationError::QueryFail)
+    }
+}
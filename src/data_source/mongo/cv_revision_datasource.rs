@@ -0,0 +1,113 @@
+//! Implements the `CVRevisionDataSource` trait for `MongoDB`.
+
+use async_graphql::futures_util::stream::TryStreamExt;
+use mongodb::{
+    bson::{self, oid::ObjectId},
+    options::{FindOneAndUpdateOptions, FindOneOptions, FindOptions, IndexOptions, ReturnDocument},
+    IndexModel,
+};
+
+use crate::{
+    data_source::{cv::revision::CVRevisionDataSource, CVDataSourceError},
+    models::cv::{CVRevision, CV},
+};
+
+use super::{conversation_datasource::is_duplicate_key, MongoDB};
+
+const CV_COLLECTION: &str = "cvs";
+const CV_REVISION_COLLECTION: &str = "cv_revisions";
+
+#[async_trait::async_trait]
+impl CVRevisionDataSource for MongoDB {
+    async fn add_cv_revision(&self, revision: CVRevision) -> Result<(), CVDataSourceError> {
+        let collection = self.db.collection::<CVRevision>(CV_REVISION_COLLECTION);
+        let number = revision.number;
+        match collection.insert_one(revision, None).await {
+            Ok(_) => Ok(()),
+            Err(err) if is_duplicate_key(&err) => {
+                Err(CVDataSourceError::RevisionNumberTaken(number))
+            }
+            Err(_) => Err(CVDataSourceError::DatabaseError),
+        }
+    }
+
+    async fn last_cv_revision_number(&self, cv_id: ObjectId) -> Result<u32, CVDataSourceError> {
+        let collection = self.db.collection::<CVRevision>(CV_REVISION_COLLECTION);
+        let filter = bson::doc! {"cv_id": cv_id};
+        let options = FindOneOptions::builder()
+            .sort(bson::doc! {"number": -1})
+            .build();
+        match collection.find_one(filter, options).await {
+            Ok(revision) => Ok(revision.map_or(0, |revision| revision.number)),
+            Err(_) => Err(CVDataSourceError::QueryFail),
+        }
+    }
+
+    async fn get_cv_revision_by_id(&self, id: ObjectId) -> Result<CVRevision, CVDataSourceError> {
+        let collection = self.db.collection::<CVRevision>(CV_REVISION_COLLECTION);
+        let filter = bson::doc! {"_id": id};
+        match collection.find_one(filter, None).await {
+            Ok(Some(revision)) => Ok(revision),
+            Ok(None) => Err(CVDataSourceError::IdNotFound(id)),
+            Err(_) => Err(CVDataSourceError::QueryFail),
+        }
+    }
+
+    async fn get_cv_revisions(&self, cv_id: ObjectId) -> Result<Vec<CVRevision>, CVDataSourceError> {
+        let collection = self.db.collection::<CVRevision>(CV_REVISION_COLLECTION);
+        let filter = bson::doc! {"cv_id": cv_id};
+        let options = FindOptions::builder().sort(bson::doc! {"number": 1}).build();
+        match collection.find(filter, options).await {
+            Ok(cursor) => cursor
+                .try_collect()
+                .await
+                .map_err(|_| CVDataSourceError::QueryFail),
+            Err(_) => Err(CVDataSourceError::QueryFail),
+        }
+    }
+
+    async fn restore_cv_revision(&self, revision: &CVRevision) -> Result<CV, CVDataSourceError> {
+        let collection = self.db.collection::<CV>(CV_COLLECTION);
+        let filter = bson::doc! {"_id": *revision.cv_id};
+        let mut set = bson::doc! {
+            "title": &revision.title,
+            "tags": &revision.tags,
+            "visibility": bson::to_bson(&revision.visibility).unwrap(),
+        };
+        let mut unset = bson::doc! {};
+        match &revision.description {
+            Some(description) => set.insert("description", description),
+            None => unset.insert("description", ""),
+        };
+        match revision.cv {
+            Some(cv) => set.insert("cv", cv),
+            None => unset.insert("cv", ""),
+        };
+        let mut update = bson::doc! {"$set": set};
+        if !unset.is_empty() {
+            update.insert("$unset", unset);
+        }
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        match collection.find_one_and_update(filter, update, options).await {
+            Ok(Some(cv)) => Ok(cv),
+            Ok(None) => Err(CVDataSourceError::IdNotFound(revision.cv_id.into())),
+            Err(_) => Err(CVDataSourceError::DatabaseError),
+        }
+    }
+}
+
+impl MongoDB {
+    /// Create the unique index which keeps two revisions of a CV from having
+    /// the same number.
+    pub(super) async fn create_cv_revision_indexes(&self) -> Result<(), mongodb::error::Error> {
+        let collection = self.db.collection::<CVRevision>(CV_REVISION_COLLECTION);
+        let options = IndexOptions::builder().unique(true).build();
+        let index = IndexModel::builder()
+            .keys(bson::doc! {"cv_id": 1, "number": 1})
+            .options(options)
+            .build();
+        collection.create_index(index, None).await.map(|_| ())
+    }
+}
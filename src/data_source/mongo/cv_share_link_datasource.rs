@@ -0,0 +1,156 @@
+//! Implements the `ShareLinkDataSource` trait for `MongoDB`.
+
+use std::fmt::Display;
+
+use async_graphql::futures_util::stream::TryStreamExt;
+use mongodb::{
+    bson::{self, oid::ObjectId},
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+};
+
+use crate::{
+    data_source::cv::share_link::ShareLinkDataSource, models::cv::ShareLink,
+    services::cv_service::error::CVServiceError,
+};
+
+use super::MongoDB;
+
+const CV_SHARE_LINK_COLLECTION: &str = "share_links";
+
+/// Error type for `ShareLinkDataSource` operations.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ShareLinkError {
+    // fail to add share link
+    AddShareLinkFail,
+
+    // fail to update share link
+    UpdateShareLinkFail,
+
+    // cannot find share link
+    ShareLinkNotFound,
+
+    // share link has no views left
+    ShareLinkExhausted,
+
+    // fail to do queries
+    QueryFail,
+}
+
+impl Display for ShareLinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShareLinkError::AddShareLinkFail => {
+                write!(f, "fail to add share link!")
+            }
+            ShareLinkError::UpdateShareLinkFail => {
+                write!(f, "fail to update share link!")
+            }
+            ShareLinkError::ShareLinkNotFound => {
+                write!(f, "cannot find share link!")
+            }
+            ShareLinkError::ShareLinkExhausted => {
+                write!(f, "share link has no views left!")
+            }
+            ShareLinkError::QueryFail => {
+                write!(f, "fail to do queries!")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShareLinkError {}
+
+impl From<ShareLinkError> for CVServiceError {
+    fn from(value: ShareLinkError) -> Self {
+        match value {
+            ShareLinkError::AddShareLinkFail => CVServiceError::UpdateShareLinkFailed,
+            ShareLinkError::UpdateShareLinkFail => CVServiceError::UpdateShareLinkFailed,
+            ShareLinkError::ShareLinkNotFound => CVServiceError::ShareLinkNotFound,
+            ShareLinkError::ShareLinkExhausted => CVServiceError::ShareLinkExpired,
+            ShareLinkError::QueryFail => CVServiceError::QueryFail,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ShareLinkDataSource for MongoDB {
+    type Error = ShareLinkError;
+
+    async fn add_share_link(&self, link: ShareLink) -> Result<(), Self::Error> {
+        let collection = self.db.collection::<ShareLink>(CV_SHARE_LINK_COLLECTION);
+        match collection.insert_one(link, None).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ShareLinkError::AddShareLinkFail),
+        }
+    }
+
+    async fn get_share_link_by_id(&self, id: ObjectId) -> Result<ShareLink, Self::Error> {
+        let collection = self.db.collection::<ShareLink>(CV_SHARE_LINK_COLLECTION);
+        let filter = bson::doc! {"_id": id};
+        match collection.find_one(filter, None).await {
+            Ok(Some(link)) => Ok(link),
+            Ok(None) => Err(ShareLinkError::ShareLinkNotFound),
+            Err(_) => Err(ShareLinkError::QueryFail),
+        }
+    }
+
+    async fn get_share_link_by_token(&self, token: &str) -> Result<ShareLink, Self::Error> {
+        let collection = self.db.collection::<ShareLink>(CV_SHARE_LINK_COLLECTION);
+        let filter = bson::doc! {"token": token};
+        match collection.find_one(filter, None).await {
+            Ok(Some(link)) => Ok(link),
+            Ok(None) => Err(ShareLinkError::ShareLinkNotFound),
+            Err(_) => Err(ShareLinkError::QueryFail),
+        }
+    }
+
+    async fn record_share_link_view(&self, token: &str) -> Result<ShareLink, Self::Error> {
+        let collection = self.db.collection::<ShareLink>(CV_SHARE_LINK_COLLECTION);
+        let filter = bson::doc! {
+            "token": token,
+            "revoked": false,
+            "$and": [
+                {"$or": [
+                    {"max_views": null},
+                    {"$expr": {"$lt": ["$view_count", "$max_views"]}}
+                ]},
+                {"$or": [
+                    {"expires_at": null},
+                    {"expires_at": {"$gt": bson::DateTime::now()}}
+                ]}
+            ]
+        };
+        let update = bson::doc! {"$inc": {"view_count": 1}};
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        match collection.find_one_and_update(filter, update, options).await {
+            Ok(Some(link)) => Ok(link),
+            Ok(None) => Err(ShareLinkError::ShareLinkExhausted),
+            Err(_) => Err(ShareLinkError::UpdateShareLinkFail),
+        }
+    }
+
+    async fn revoke_share_link(&self, id: ObjectId) -> Result<(), Self::Error> {
+        let collection = self.db.collection::<ShareLink>(CV_SHARE_LINK_COLLECTION);
+        let filter = bson::doc! {"_id": id};
+        let update = bson::doc! {"$set": {"revoked": true}};
+        match collection.update_one(filter, update, None).await {
+            Ok(result) if result.matched_count == 0 => Err(ShareLinkError::ShareLinkNotFound),
+            Ok(_) => Ok(()),
+            Err(_) => Err(ShareLinkError::UpdateShareLinkFail),
+        }
+    }
+
+    async fn get_share_links_of_cv(&self, cv_id: ObjectId) -> Result<Vec<ShareLink>, Self::Error> {
+        let collection = self.db.collection::<ShareLink>(CV_SHARE_LINK_COLLECTION);
+        let filter = bson::doc! {"cv_id": cv_id};
+        match collection.find(filter, None).await {
+            Ok(cursor) => cursor
+                .try_collect()
+                .await
+                .map_err(|_| ShareLinkError::QueryFail),
+            Err(_) => Err(ShareLinkError::QueryFail),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Implements the `CVTextDataSource` trait for `MongoDB`.
+
+use mongodb::bson::{self, oid::ObjectId};
+
+use crate::{
+    data_source::{cv::text_extraction::CVTextDataSource, CVDataSourceError},
+    models::cv::{TextExtraction, CV},
+};
+
+use super::MongoDB;
+
+const CV_COLLECTION: &str = "cvs";
+
+#[async_trait::async_trait]
+impl CVTextDataSource for MongoDB {
+    async fn update_cv_text_extraction(
+        &self,
+        cv_id: ObjectId,
+        extraction: TextExtraction,
+    ) -> Result<(), CVDataSourceError> {
+        let collection = self.db.collection::<CV>(CV_COLLECTION);
+        let filter = bson::doc! {"_id": cv_id, "cv": extraction.file_id};
+        let extraction =
+            bson::to_bson(&extraction).map_err(|_| CVDataSourceError::DatabaseError)?;
+        let update = bson::doc! {"$set": {"text_extraction": extraction}};
+        match collection.update_one(filter, update, None).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(CVDataSourceError::DatabaseError),
+        }
+    }
+}
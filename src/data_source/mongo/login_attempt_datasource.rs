@@ -0,0 +1,136 @@
+//! Implements the `LoginAttemptDataSource` trait for `MongoDB`.
+
+use std::fmt::Display;
+
+use mongodb::{
+    bson,
+    options::{FindOneAndUpdateOptions, ReplaceOptions, ReturnDocument},
+};
+
+use crate::{
+    common::DateTime, data_source::LoginAttemptDataSource, models::users::LoginAttempts,
+    services::user_service::error::UserServiceError,
+};
+
+use super::MongoDB;
+
+const LOGIN_ATTEMPT_COLLECTION: &str = "login_attempts";
+
+/// Error type for `LoginAttemptDataSource` operations.
+#[derive(Debug, PartialEq, Clone)]
+pub enum LoginAttemptError {
+    // fail to store login attempts
+    SetAttemptsFail,
+
+    // fail to do queries
+    QueryFail,
+}
+
+impl Display for LoginAttemptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoginAttemptError::SetAttemptsFail => {
+                write!(f, "fail to store login attempts!")
+            }
+            LoginAttemptError::QueryFail => {
+                write!(f, "fail to do queries!")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoginAttemptError {}
+
+impl From<LoginAttemptError> for UserServiceError {
+    fn from(_: LoginAttemptError) -> Self {
+        UserServiceError::DatabaseError
+    }
+}
+
+#[async_trait::async_trait]
+impl LoginAttemptDataSource for MongoDB {
+    type Error = LoginAttemptError;
+
+    async fn get_login_attempts(&self, key: &str) -> Result<Option<LoginAttempts>, Self::Error> {
+        let collection = self
+            .db
+            .collection::<LoginAttempts>(LOGIN_ATTEMPT_COLLECTION);
+        collection
+            .find_one(bson::doc! {"_id": key}, None)
+            .await
+            .map_err(|_| LoginAttemptError::QueryFail)
+    }
+
+    async fn set_login_attempts(&self, attempts: LoginAttempts) -> Result<(), Self::Error> {
+        let collection = self
+            .db
+            .collection::<LoginAttempts>(LOGIN_ATTEMPT_COLLECTION);
+        let filter = bson::doc! {"_id": &attempts.key};
+        let options = ReplaceOptions::builder().upsert(true).build();
+        match collection.replace_one(filter, attempts, options).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(LoginAttemptError::SetAttemptsFail),
+        }
+    }
+
+    async fn add_login_failure(
+        &self,
+        key: &str,
+        now: DateTime,
+        forget_before: DateTime,
+    ) -> Result<LoginAttempts, Self::Error> {
+        let collection = self
+            .db
+            .collection::<LoginAttempts>(LOGIN_ATTEMPT_COLLECTION);
+        let forget_before = bson::DateTime::from(forget_before);
+        let stale = bson::doc! {
+            "_id": key,
+            "last_failure": {"$lte": forget_before},
+            "$or": [{"locked_until": null}, {"locked_until": {"$lte": forget_before}}]
+        };
+        collection
+            .delete_one(stale, None)
+            .await
+            .map_err(|_| LoginAttemptError::SetAttemptsFail)?;
+        let update = bson::doc! {
+            "$inc": {"failures": 1},
+            "$set": {"last_failure": bson::DateTime::from(now)},
+            "$setOnInsert": {"locked_until": null}
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .build();
+        match collection
+            .find_one_and_update(bson::doc! {"_id": key}, update, options)
+            .await
+        {
+            Ok(Some(attempts)) => Ok(attempts),
+            _ => Err(LoginAttemptError::SetAttemptsFail),
+        }
+    }
+
+    async fn lock_login_attempts(&self, key: &str, until: DateTime) -> Result<(), Self::Error> {
+        let collection = self
+            .db
+            .collection::<LoginAttempts>(LOGIN_ATTEMPT_COLLECTION);
+        let update = bson::doc! {"$max": {"locked_until": bson::DateTime::from(until)}};
+        match collection
+            .update_one(bson::doc! {"_id": key}, update, None)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(_) => Err(LoginAttemptError::SetAttemptsFail),
+        }
+    }
+
+    async fn delete_login_attempts(&self, key: &str) -> Result<(), Self::Error> {
+        let collection = self
+            .db
+            .collection::<LoginAttempts>(LOGIN_ATTEMPT_COLLECTION);
+        match collection.delete_one(bson::doc! {"_id": key}, None).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(LoginAttemptError::QueryFail),
+        }
+    }
+}
@@ -1,7 +1,15 @@
 mod mongo;
 mod cv_share_datasource;
+mod cv_share_link_datasource;
+mod cv_revision_datasource;
+mod cv_text_extraction_datasource;
 mod cv_like_datasource;
 mod cv_bookmark_datasource;
+mod user_block_datasource;
+mod login_attempt_datasource;
+mod conversation_datasource;
+mod reaction_datasource;
+mod report_datasource;
 mod mongo_for_testing;
 
 #[cfg(test)]
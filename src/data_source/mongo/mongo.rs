@@ -1,5 +1,7 @@
 use mongodb::bson::oid::ObjectId;
-use mongodb::options::{FindOneAndUpdateOptions, ReturnDocument};
+use std::collections::HashMap;
+
+use mongodb::options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument, UpdateOptions};
 use mongodb::{options::ClientOptions, Client, Database};
 
 use crate::common::DateTime;
@@ -23,10 +25,13 @@ use crate::{
         CVDetailsDataSource, CommentDataSource, FriendsListDataSource, FriendsListError,
         UserDataSource, UserDataSourceError,
     },
-    models::comment::{Comment, CreateCommentInput, Like, UpdateCommentInput},
+    models::comment::{
+        Comment, CommentVersion, CreateCommentInput, Like, Mention, UpdateCommentInput,
+    },
 };
 use async_graphql::futures_util::stream::BoxStream;
 use async_graphql::futures_util::stream::StreamExt;
+use async_graphql::futures_util::stream::TryStreamExt;
 
 use async_trait::async_trait;
 
@@ -36,6 +41,7 @@ use crate::models::cv::{self, CV};
 use crate::models::users::{self, User};
 
 use crate::data_source::CVDataSource;
+use crate::data_source::MentionNotifier;
 use crate::data_source::CVDataSourceError;
 
 const FRIEND_REQUEST_COLLECTION: &str = "friend_requests";
@@ -45,6 +51,8 @@ const APP_NAME: &str = "SeeVi";
 const COMMENT_COLLECTION: &str = "comments";
 const LIKE_COLLECTION: &str = "likes";
 const BOOKMARK_COLLECTION: &str = "bookmarks";
+const MIGRATION_COLLECTION: &str = "migrations";
+const COMMENT_THREADS_MIGRATION: &str = "comment_threads";
 
 #[derive(Clone)]
 pub struct MongoDB {
@@ -76,7 +84,69 @@ impl MongoDB {
         let client = Client::with_options(client_options).expect("Failed to initialize database!");
         let db = client.database(name);
         db.drop(None).await.unwrap();
-        MongoDB { client, db }
+        let mongo = MongoDB { client, db };
+        mongo.create_indexes().await.unwrap();
+        mongo
+    }
+
+    /// Create the indexes the data sources rely on. Existing indexes are
+    /// left as they are.
+    pub async fn create_indexes(&self) -> Result<(), mongodb::error::Error> {
+        self.create_conversation_indexes().await?;
+        self.create_cv_revision_indexes().await
+    }
+
+    /// Check that the database answers.
+    pub async fn ping(&self) -> Result<(), mongodb::error::Error> {
+        self.db
+            .run_command(bson::doc! {"ping": 1}, None)
+            .await
+            .map(|_| ())
+    }
+
+    /// Fill in `parent_id`, `root_id` and `depth` for the replies stored
+    /// before threads were tracked, from the `replies` of their parents.
+    /// This only runs once, return how many replies were updated.
+    pub async fn migrate_comment_threads(&self) -> Result<u64, mongodb::error::Error> {
+        let migrations = self.db.collection::<bson::Document>(MIGRATION_COLLECTION);
+        let marker = bson::doc! {"_id": COMMENT_THREADS_MIGRATION};
+        if migrations.find_one(marker.clone(), None).await?.is_some() {
+            return Ok(0);
+        }
+        let collection = self.db.collection::<Comment>(COMMENT_COLLECTION);
+        let mut parents = HashMap::new();
+        let mut cursor = collection
+            .find(bson::doc! {"replies.0": {"$exists": true}}, None)
+            .await?;
+        while let Some(comment) = cursor.try_next().await? {
+            for reply_id in comment.replies {
+                parents.insert(*reply_id, *comment.id);
+            }
+        }
+        let mut updated = 0;
+        for (&reply_id, &parent_id) in &parents {
+            // walk up to the comment on the CV, a broken chain cannot be
+            // longer than the number of replies
+            let (mut root_id, mut depth) = (parent_id, 1);
+            while let Some(&grand_parent_id) = parents.get(&root_id) {
+                if depth > parents.len() {
+                    break;
+                }
+                root_id = grand_parent_id;
+                depth += 1;
+            }
+            let filter = bson::doc! {"_id": reply_id, "root_id": null};
+            let update = bson::doc! {"$set": {
+                "parent_id": parent_id,
+                "root_id": root_id,
+                "depth": depth as i64
+            }};
+            updated += collection.update_one(filter, update, None).await?.modified_count;
+        }
+        let done = bson::doc! {"$set": {"done": bson::DateTime::now()}};
+        let options = UpdateOptions::builder().upsert(true).build();
+        migrations.update_one(marker, done, options).await?;
+        Ok(updated)
     }
 }
 
@@ -115,6 +185,44 @@ fn update_input_to_bson(input: users::UpdateUserInput) -> bson::Document {
     update
 }
 
+/// Escape the characters that have a special meaning in regular expressions.
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Set the photo `field` of the user to `photo_id` and return the updated user.
+async fn set_user_photo(
+    mongo: &MongoDB,
+    user_id: bson::oid::ObjectId,
+    field: &str,
+    photo_id: bson::Uuid,
+) -> Result<users::User, UserDataSourceError> {
+    let collection: mongodb::Collection<users::User> = mongo.db.collection(USER_COLLECTION);
+    let filter = bson::doc! {"_id": user_id};
+    let update = bson::doc! {"$set": {field: photo_id}};
+    let result = collection
+        .find_one_and_update(
+            filter,
+            update,
+            FindOneAndUpdateOptions::builder()
+                .return_document(ReturnDocument::After)
+                .build(),
+        )
+        .await;
+    match result {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err(UserDataSourceError::IdNotFound(user_id)),
+        Err(_) => Err(UserDataSourceError::UpdateUserFailed),
+    }
+}
+
 impl From<UserDataSourceError> for UserServiceError {
     fn from(error: UserDataSourceError) -> Self {
         match error {
@@ -238,12 +346,20 @@ impl UserDataSource for MongoDB {
         }
     }
 
-    async fn update_avatar(&self, _photo_id: bson::Uuid) -> Result<(), Self::Error> {
-        unimplemented!()
+    async fn update_avatar(
+        &self,
+        user_id: bson::oid::ObjectId,
+        photo_id: bson::Uuid,
+    ) -> Result<users::User, Self::Error> {
+        set_user_photo(self, user_id, "avatar", photo_id).await
     }
 
-    async fn update_cover_photo(&self, _photo_id: bson::Uuid) -> Result<(), Self::Error> {
-        unimplemented!()
+    async fn update_cover_photo(
+        &self,
+        user_id: bson::oid::ObjectId,
+        photo_id: bson::Uuid,
+    ) -> Result<users::User, Self::Error> {
+        set_user_photo(self, user_id, "cover_photo", photo_id).await
     }
 
     async fn add_other_email(&self, _email: String) -> Result<(), Self::Error> {
@@ -274,6 +390,112 @@ impl UserDataSource for MongoDB {
             .boxed();
         stream
     }
+
+    async fn set_suspended_until(
+        &self,
+        user_id: bson::oid::ObjectId,
+        until: Option<DateTime>,
+    ) -> Result<User, Self::Error> {
+        let collection: mongodb::Collection<users::User> = self.db.collection(USER_COLLECTION);
+        let filter = bson::doc! {"_id": user_id};
+        let update = bson::doc! {"$set": {"suspended_until": until.map(bson::DateTime::from)}};
+        let result = collection
+            .find_one_and_update(
+                filter,
+                update,
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await;
+        match result {
+            Ok(Some(user)) => Ok(user),
+            Ok(None) => Err(UserDataSourceError::IdNotFound(user_id)),
+            Err(_) => Err(UserDataSourceError::DatabaseError),
+        }
+    }
+
+    async fn set_role(
+        &self,
+        user_id: bson::oid::ObjectId,
+        role: users::Role,
+    ) -> Result<User, Self::Error> {
+        let collection: mongodb::Collection<users::User> = self.db.collection(USER_COLLECTION);
+        let filter = bson::doc! {"_id": user_id};
+        let role = bson::to_bson(&role).map_err(|_| UserDataSourceError::UpdateUserFailed)?;
+        let update = bson::doc! {"$set": {"role": role}};
+        let result = collection
+            .find_one_and_update(
+                filter,
+                update,
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await;
+        match result {
+            Ok(Some(user)) => Ok(user),
+            Ok(None) => Err(UserDataSourceError::IdNotFound(user_id)),
+            Err(_) => Err(UserDataSourceError::DatabaseError),
+        }
+    }
+
+    async fn search_users(&self, search: Option<String>) -> Result<Vec<User>, Self::Error> {
+        let collection: mongodb::Collection<users::User> = self.db.collection(USER_COLLECTION);
+        let filter = match search {
+            Some(search) => {
+                let pattern = doc! {"$regex": escape_regex(&search), "$options": "i"};
+                doc! {"$or": [
+                    {"username": pattern.clone()},
+                    {"first_name": pattern.clone()},
+                    {"last_name": pattern.clone()},
+                    {"primary_email": pattern},
+                ]}
+            }
+            None => doc! {},
+        };
+        let options = FindOptions::builder().sort(doc! {"username": 1}).build();
+        let cursor = collection
+            .find(filter, options)
+            .await
+            .map_err(|_| UserDataSourceError::DatabaseError)?;
+        cursor
+            .try_collect()
+            .await
+            .map_err(|_| UserDataSourceError::DatabaseError)
+    }
+
+    async fn get_users_with_shared_attributes(
+        &self,
+        user: &User,
+        limit: i64,
+    ) -> Result<Vec<User>, Self::Error> {
+        let collection: mongodb::Collection<users::User> = self.db.collection(USER_COLLECTION);
+        let schools = user.educations.iter().map(|e| e.school.clone());
+        let majors = user.educations.iter().map(|e| e.major.clone());
+        let companies = user.experiences.iter().map(|e| e.company.clone());
+        let mut conditions = vec![
+            doc! {"educations.school": {"$in": schools.collect::<Vec<_>>()}},
+            doc! {"educations.major": {"$in": majors.collect::<Vec<_>>()}},
+            doc! {"experiences.company": {"$in": companies.collect::<Vec<_>>()}},
+            doc! {"skills": {"$in": user.skills.clone()}},
+        ];
+        if let Some(city) = &user.city {
+            conditions.push(doc! {"city": city});
+        }
+        let filter = doc! {"_id": {"$ne": *user.id}, "$or": conditions};
+        let options = mongodb::options::FindOptions::builder().limit(limit).build();
+        let cursor = collection
+            .find(filter, options)
+            .await
+            .map_err(|_| UserDataSourceError::DatabaseError)?;
+        cursor
+            .map(|result| result.map_err(|_| UserDataSourceError::DatabaseError))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -346,6 +568,18 @@ impl CVDataSource for MongoDB {
         }
     }
 
+    async fn get_cv_by_comment_id(
+        &self,
+        comment_id: ObjectId,
+    ) -> Result<cv::CV, CVDataSourceError> {
+        let collection: mongodb::Collection<cv::CV> = self.db.collection(CV_COLLECTION);
+        match collection.find_one(bson::doc! {"comments": comment_id}, None).await {
+            Ok(Some(cv)) => Ok(cv),
+            Ok(None) => Err(CVDataSourceError::IdNotFound(comment_id)),
+            Err(_) => Err(CVDataSourceError::DatabaseError),
+        }
+    }
+
     async fn create_cv(&self, _input: cv::CreateCVInput) -> Result<cv::CV, CVDataSourceError> {
         let collection: mongodb::Collection<cv::CV> = self.db.collection(CV_COLLECTION);
         let collection_user: mongodb::Collection<users::User> = self.db.collection(USER_COLLECTION);
@@ -356,8 +590,11 @@ impl CVDataSource for MongoDB {
             description: _input.description,
             tags: _input.tags,
             comments: vec![],
-            cv: Some(bson::Uuid::new()),
+            cv: None,
             created: DateTime::now(),
+            visibility: _input.visibility.unwrap_or_default(),
+            text_extraction: None,
+            hidden_at: None,
         };
 
         let filter = bson::doc! {"_id": _input.author_id};
@@ -405,6 +642,10 @@ impl CVDataSource for MongoDB {
             .description
             .map(|description| update.insert("description", description));
         _input.tags.map(|tags| update.insert("tags", tags));
+        _input.visibility.map(|visibility| {
+            update.insert("visibility", bson::to_bson(&visibility).unwrap())
+        });
+        _input.cv.map(|cv| update.insert("cv", cv));
         let update = bson::doc! {"$set": update};
         let result = collection
             .find_one_and_update(
@@ -423,6 +664,29 @@ impl CVDataSource for MongoDB {
             Err(_) => Err(CVDataSourceError::DatabaseError),
         }
     }
+    async fn set_cv_hidden(
+        &self,
+        cv_id: bson::oid::ObjectId,
+        hidden_at: Option<DateTime>,
+    ) -> Result<cv::CV, CVDataSourceError> {
+        let collection = self.db.collection::<cv::CV>(CV_COLLECTION);
+        let filter = bson::doc! {"_id": cv_id};
+        let update = bson::doc! {"$set": {"hidden_at": hidden_at.map(bson::DateTime::from)}};
+        let result = collection
+            .find_one_and_update(
+                filter,
+                update,
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await;
+        match result {
+            Ok(Some(cv)) => Ok(cv),
+            Ok(None) => Err(CVDataSourceError::IdNotFound(cv_id)),
+            Err(_) => Err(CVDataSourceError::DatabaseError),
+        }
+    }
 }
 
 #[async_trait]
@@ -472,8 +736,10 @@ impl FriendsListDataSource for MongoDB {
         match find {
             Ok(_) => match find.unwrap() {
                 Some(_) => {
-                    let update =
-                        bson::doc! {"$set": {"status": _friend_request.status.to_string()}};
+                    let update = bson::doc! {"$set": {
+                        "status": _friend_request.status.to_string(),
+                        "updated_at": _friend_request.updated_at,
+                    }};
                     let result = collection
                         .find_one_and_update(
                             filter,
@@ -494,6 +760,23 @@ impl FriendsListDataSource for MongoDB {
         }
     }
 
+    async fn delete_friend_request(
+        &self,
+        from: bson::oid::ObjectId,
+        to: bson::oid::ObjectId,
+    ) -> Result<(), FriendsListError> {
+        let collection = self
+            .db
+            .collection::<FriendRequest>(FRIEND_REQUEST_COLLECTION);
+        let filter = bson::doc! {"_id.from": from, "_id.to": to};
+        let result = collection.delete_one(filter, None).await;
+        match result {
+            Ok(result) if result.deleted_count == 0 => Err(FriendsListError::FriendRequestNotFound),
+            Ok(_) => Ok(()),
+            Err(_) => Err(FriendsListError::DatabaseError),
+        }
+    }
+
     async fn get_friend_request(
         &self,
         from: bson::oid::ObjectId,
@@ -574,6 +857,32 @@ impl FriendsListDataSource for MongoDB {
             .boxed();
         stream
     }
+
+    async fn accepted_friend_requests_of_users<'a>(
+        &'a self,
+        user_ids: Vec<bson::oid::ObjectId>,
+    ) -> BoxStream<'a, Result<FriendRequest, FriendsListError>> {
+        let collection: mongodb::Collection<FriendRequest> =
+            self.db.collection(FRIEND_REQUEST_COLLECTION);
+        let filter = bson::doc! {
+            "status": "Accepted",
+            "$or": [
+                {"_id.from": {"$in": &user_ids}},
+                {"_id.to": {"$in": &user_ids}}
+            ]
+        };
+        match collection.find(filter, None).await {
+            Ok(cursor) => cursor
+                .map(|result| result.map_err(|_| FriendsListError::DatabaseError))
+                .boxed(),
+            Err(_) => {
+                async_graphql::futures_util::stream::once(async {
+                    Err(FriendsListError::DatabaseError)
+                })
+                .boxed()
+            }
+        }
+    }
 }
 
 impl From<CVDataSourceError> for CVServiceError {
@@ -593,6 +902,7 @@ impl From<CVDataSourceError> for CVServiceError {
             CVDataSourceError::QueryFail => CVServiceError::QueryFail,
             CVDataSourceError::AddCommentFailed => CVServiceError::AddCommentFailed,
             CVDataSourceError::RemoveCommentFailed => CVServiceError::RemoveCommentFailed,
+            CVDataSourceError::RevisionNumberTaken(_) => CVServiceError::DatabaseError,
         }
     }
 }
@@ -633,13 +943,20 @@ impl CVDetailsDataSource for MongoDB {
                 if list_author_id.is_empty() {
                     return Err(CVDataSourceError::QueryFail);
                 }
+                let mut search_filters = vec![
+                    bson::doc! {"tags": {"$in": cv_details.search_words.clone()}},
+                    bson::doc! {"title": {"$in": cv_details.search_words.clone()}},
+                ];
+                // match words inside the extracted text of the current file
+                search_filters.extend(cv_details.search_words.iter().map(|word| {
+                    bson::doc! {
+                        "text_extraction.text": {"$regex": escape_regex(word), "$options": "i"},
+                        "$expr": {"$eq": ["$text_extraction.file_id", "$cv"]},
+                    }
+                }));
                 let cv_filter = bson::doc! {
                     "author_id": {"$in": list_author_id},
-                    "$or" :[
-                        {"tags": {"$in": cv_details.search_words.clone()}},
-                        {"title": {"$in": cv_details.search_words.clone()}},
-                        ],
-
+                    "$or": search_filters,
                 };
                 let cv_cursor_result = cv_collection.find(cv_filter, None).await;
                 match cv_cursor_result {
@@ -759,6 +1076,101 @@ impl CommentDataSource for MongoDB {
         }
     }
 
+    async fn edit_comment(
+        &self,
+        id: bson::oid::ObjectId,
+        content: String,
+        mentions: Vec<Mention>,
+        edited_at: DateTime,
+        previous: CommentVersion,
+    ) -> Result<Comment, Self::Error> {
+        let collection = self.db.collection::<Comment>(COMMENT_COLLECTION);
+        let filter = bson::doc! {"_id": id, "deleted_at": null};
+        let previous =
+            bson::to_bson(&previous).map_err(|_| CommentDataSourceError::UpdateCommentFailed)?;
+        let mentions =
+            bson::to_bson(&mentions).map_err(|_| CommentDataSourceError::UpdateCommentFailed)?;
+        let update = bson::doc! {
+            "$set": {
+                "content": content,
+                "mentions": mentions,
+                "edited_at": bson::DateTime::from(edited_at)
+            },
+            "$push": {"history": previous}
+        };
+        let result = collection
+            .find_one_and_update(
+                filter,
+                update,
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await;
+        match result {
+            Ok(comment) => match comment {
+                Some(comment) => Ok(comment),
+                None => Err(CommentDataSourceError::IdNotFound(id)),
+            },
+            Err(_) => Err(CommentDataSourceError::DatabaseError),
+        }
+    }
+
+    async fn delete_comment(
+        &self,
+        id: bson::oid::ObjectId,
+        deleted_at: DateTime,
+    ) -> Result<Comment, Self::Error> {
+        let collection = self.db.collection::<Comment>(COMMENT_COLLECTION);
+        let filter = bson::doc! {"_id": id};
+        let update = bson::doc! {"$set": {
+            "content": "",
+            "mentions": [],
+            "history": [],
+            "deleted_at": bson::DateTime::from(deleted_at)
+        }};
+        let result = collection
+            .find_one_and_update(
+                filter,
+                update,
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await;
+        match result {
+            Ok(comment) => match comment {
+                Some(comment) => Ok(comment),
+                None => Err(CommentDataSourceError::IdNotFound(id)),
+            },
+            Err(_) => Err(CommentDataSourceError::DatabaseError),
+        }
+    }
+
+    async fn set_comment_hidden(
+        &self,
+        id: bson::oid::ObjectId,
+        hidden_at: Option<DateTime>,
+    ) -> Result<Comment, Self::Error> {
+        let collection = self.db.collection::<Comment>(COMMENT_COLLECTION);
+        let filter = bson::doc! {"_id": id};
+        let update = bson::doc! {"$set": {"hidden_at": hidden_at.map(bson::DateTime::from)}};
+        let result = collection
+            .find_one_and_update(
+                filter,
+                update,
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await;
+        match result {
+            Ok(Some(comment)) => Ok(comment),
+            Ok(None) => Err(CommentDataSourceError::IdNotFound(id)),
+            Err(_) => Err(CommentDataSourceError::DatabaseError),
+        }
+    }
+
     async fn add_reply_to_comment(
         &self,
         comment_id: bson::oid::ObjectId,
@@ -810,6 +1222,27 @@ impl CommentDataSource for MongoDB {
             Err(_) => Err(CommentDataSourceError::DatabaseError),
         }
     }
+
+    async fn get_thread(
+        &self,
+        root_id: bson::oid::ObjectId,
+        limit: usize,
+    ) -> Result<Vec<Comment>, Self::Error> {
+        let collection = self.db.collection::<Comment>(COMMENT_COLLECTION);
+        let filter = bson::doc! {"$or": [{"_id": root_id}, {"root_id": root_id}]};
+        let options = FindOptions::builder()
+            .sort(bson::doc! {"created": 1, "_id": 1})
+            .limit(i64::try_from(limit).unwrap_or(i64::MAX))
+            .build();
+        let cursor = collection
+            .find(filter, options)
+            .await
+            .map_err(|_| CommentDataSourceError::DatabaseError)?;
+        cursor
+            .try_collect()
+            .await
+            .map_err(|_| CommentDataSourceError::DatabaseError)
+    }
 }
 
 impl std::error::Error for LikeDataSourceError {}
@@ -829,6 +1262,14 @@ impl From<LikeDataSourceError> for CommentServiceError {
     }
 }
 
+/// There is no notification delivery yet, mentions are only logged.
+#[async_trait]
+impl MentionNotifier for MongoDB {
+    async fn notify_mention(&self, user_id: ObjectId, comment: &Comment) {
+        log::info!("User {} was mentioned in comment {}", user_id, *comment.id);
+    }
+}
+
 #[async_trait]
 impl LikeDataSource for MongoDB {
     type Error = LikeDataSourceError;
@@ -0,0 +1,286 @@
+//! Implements the `ReactionDataSource` trait for `MongoDB`.
+
+use std::{collections::HashMap, fmt::Display};
+
+use async_graphql::futures_util::stream::TryStreamExt;
+use mongodb::{
+    bson::{self, oid::ObjectId},
+    options::{FindOptions, ReplaceOptions, UpdateOptions},
+};
+use serde::Deserialize;
+
+use crate::{
+    data_source::ReactionDataSource,
+    models::{
+        comment, cv,
+        reaction::{Reaction, ReactionCount, ReactionKind, ReactionTarget},
+    },
+    services::reaction_service::error::ReactionServiceError,
+};
+
+use super::MongoDB;
+
+const REACTION_COLLECTION: &str = "reactions";
+const CV_LIKE_COLLECTION: &str = "cv_likes";
+const COMMENT_LIKE_COLLECTION: &str = "likes";
+const MIGRATION_COLLECTION: &str = "migrations";
+const LEGACY_LIKES_MIGRATION: &str = "legacy_likes";
+
+/// Error type for `ReactionDataSource` operations.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ReactionError {
+    // fail to add or replace reaction
+    SetReactionFail,
+
+    // fail to remove reaction
+    DeleteReactionFail,
+
+    // fail to do queries
+    QueryFail,
+
+    // the counts returned by the database cannot be read
+    MalformedCount,
+}
+
+impl Display for ReactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReactionError::SetReactionFail => {
+                write!(f, "fail to set reaction!")
+            }
+            ReactionError::DeleteReactionFail => {
+                write!(f, "fail to remove reaction!")
+            }
+            ReactionError::QueryFail => {
+                write!(f, "fail to do queries!")
+            }
+            ReactionError::MalformedCount => {
+                write!(f, "cannot read reaction counts!")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReactionError {}
+
+impl From<ReactionError> for ReactionServiceError {
+    fn from(_: ReactionError) -> Self {
+        ReactionServiceError::DatabaseError
+    }
+}
+
+/// Result of grouping reactions by target.
+#[derive(Deserialize)]
+struct TargetCount {
+    #[serde(rename = "_id")]
+    target_id: ObjectId,
+    count: u64,
+}
+
+/// Result of grouping the reactions of a target by kind.
+#[derive(Deserialize)]
+struct KindCount {
+    #[serde(rename = "_id")]
+    kind: ReactionKind,
+    count: u64,
+}
+
+impl MongoDB {
+    /// Copy the likes stored before reactions existed, in the `cv_likes` and
+    /// `likes` collections, into `Like` reactions. Reactions the users already
+    /// left are kept. This only runs once, return how many reactions were added.
+    pub async fn migrate_legacy_likes(&self) -> Result<u64, mongodb::error::Error> {
+        let migrations = self.db.collection::<bson::Document>(MIGRATION_COLLECTION);
+        let filter = bson::doc! {"_id": LEGACY_LIKES_MIGRATION};
+        if migrations.find_one(filter.clone(), None).await?.is_some() {
+            return Ok(0);
+        }
+        let mut added = 0;
+        let mut cv_likes = self
+            .db
+            .collection::<cv::Like>(CV_LIKE_COLLECTION)
+            .find(None, None)
+            .await?;
+        while let Some(like) = cv_likes.try_next().await? {
+            let mut reaction = Reaction::new(
+                *like.user_id(),
+                ReactionTarget::Cv,
+                *like.cv_id(),
+                ReactionKind::Like,
+            );
+            reaction.created = like.created;
+            added += self.add_legacy_like(reaction).await?;
+        }
+        let mut comment_likes = self
+            .db
+            .collection::<comment::Like>(COMMENT_LIKE_COLLECTION)
+            .find(None, None)
+            .await?;
+        while let Some(like) = comment_likes.try_next().await? {
+            let mut reaction = Reaction::new(
+                *like.key.user_id,
+                ReactionTarget::Comment,
+                *like.key.comment_id,
+                ReactionKind::Like,
+            );
+            reaction.created = like.created;
+            added += self.add_legacy_like(reaction).await?;
+        }
+        let done = bson::doc! {"$set": {"done": bson::DateTime::now()}};
+        let options = UpdateOptions::builder().upsert(true).build();
+        migrations.update_one(filter, done, options).await?;
+        Ok(added)
+    }
+
+    /// Store the reaction unless the user already reacted to the target,
+    /// return 1 if it was stored.
+    async fn add_legacy_like(&self, reaction: Reaction) -> Result<u64, mongodb::error::Error> {
+        let collection = self.db.collection::<Reaction>(REACTION_COLLECTION);
+        let filter = bson::doc! {
+            "_id": {"user_id": reaction.user_id(), "target_id": reaction.target_id()}
+        };
+        let update = bson::doc! {
+            "$setOnInsert": {
+                "target_type": bson::to_bson(&reaction.target_type)?,
+                "kind": bson::to_bson(&reaction.kind)?,
+                "created": bson::DateTime::from(reaction.created)
+            }
+        };
+        let options = UpdateOptions::builder().upsert(true).build();
+        let result = collection.update_one(filter, update, options).await?;
+        Ok(u64::from(result.upserted_id.is_some()))
+    }
+}
+
+#[async_trait::async_trait]
+impl ReactionDataSource for MongoDB {
+    type Error = ReactionError;
+
+    async fn set_reaction(&self, reaction: Reaction) -> Result<(), Self::Error> {
+        let collection = self.db.collection::<Reaction>(REACTION_COLLECTION);
+        let filter = bson::doc! {
+            "_id.user_id": reaction.user_id(),
+            "_id.target_id": reaction.target_id()
+        };
+        let options = ReplaceOptions::builder().upsert(true).build();
+        match collection.replace_one(filter, reaction, options).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ReactionError::SetReactionFail),
+        }
+    }
+
+    async fn delete_reaction(
+        &self,
+        user_id: ObjectId,
+        target_id: ObjectId,
+    ) -> Result<Option<Reaction>, Self::Error> {
+        let collection = self.db.collection::<Reaction>(REACTION_COLLECTION);
+        let filter = bson::doc! {
+            "_id.user_id": user_id,
+            "_id.target_id": target_id
+        };
+        collection
+            .find_one_and_delete(filter, None)
+            .await
+            .map_err(|_| ReactionError::DeleteReactionFail)
+    }
+
+    async fn get_reaction(
+        &self,
+        user_id: ObjectId,
+        target_id: ObjectId,
+    ) -> Result<Option<Reaction>, Self::Error> {
+        let collection = self.db.collection::<Reaction>(REACTION_COLLECTION);
+        let filter = bson::doc! {
+            "_id.user_id": user_id,
+            "_id.target_id": target_id
+        };
+        collection
+            .find_one(filter, None)
+            .await
+            .map_err(|_| ReactionError::QueryFail)
+    }
+
+    async fn get_reactions(
+        &self,
+        target_id: ObjectId,
+        kind: Option<ReactionKind>,
+    ) -> Result<Vec<Reaction>, Self::Error> {
+        let collection = self.db.collection::<Reaction>(REACTION_COLLECTION);
+        let mut filter = bson::doc! {"_id.target_id": target_id};
+        if let Some(kind) = kind {
+            let kind = bson::to_bson(&kind).map_err(|_| ReactionError::QueryFail)?;
+            filter.insert("kind", kind);
+        }
+        let options = FindOptions::builder()
+            .sort(bson::doc! {"created": -1})
+            .build();
+        let cursor = collection
+            .find(filter, options)
+            .await
+            .map_err(|_| ReactionError::QueryFail)?;
+        cursor
+            .try_collect()
+            .await
+            .map_err(|_| ReactionError::QueryFail)
+    }
+
+    async fn count_reactions(
+        &self,
+        target_id: ObjectId,
+    ) -> Result<Vec<ReactionCount>, Self::Error> {
+        let collection = self.db.collection::<Reaction>(REACTION_COLLECTION);
+        let pipeline = vec![
+            bson::doc! {"$match": {"_id.target_id": target_id}},
+            bson::doc! {"$group": {"_id": "$kind", "count": {"$sum": 1}}},
+        ];
+        let cursor = collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|_| ReactionError::QueryFail)?;
+        let documents: Vec<bson::Document> = cursor
+            .try_collect()
+            .await
+            .map_err(|_| ReactionError::QueryFail)?;
+        documents
+            .into_iter()
+            .map(|document| {
+                bson::from_document::<KindCount>(document)
+                    .map(|count| ReactionCount {
+                        kind: count.kind,
+                        count: count.count,
+                    })
+                    .map_err(|_| ReactionError::MalformedCount)
+            })
+            .collect()
+    }
+
+    async fn count_reactions_of_targets(
+        &self,
+        target_ids: Vec<ObjectId>,
+        kind: ReactionKind,
+    ) -> Result<HashMap<ObjectId, u64>, Self::Error> {
+        let collection = self.db.collection::<Reaction>(REACTION_COLLECTION);
+        let kind = bson::to_bson(&kind).map_err(|_| ReactionError::QueryFail)?;
+        let pipeline = vec![
+            bson::doc! {"$match": {"_id.target_id": {"$in": target_ids}, "kind": kind}},
+            bson::doc! {"$group": {"_id": "$_id.target_id", "count": {"$sum": 1}}},
+        ];
+        let cursor = collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|_| ReactionError::QueryFail)?;
+        let documents: Vec<bson::Document> = cursor
+            .try_collect()
+            .await
+            .map_err(|_| ReactionError::QueryFail)?;
+        documents
+            .into_iter()
+            .map(|document| {
+                bson::from_document::<TargetCount>(document)
+                    .map(|count| (count.target_id, count.count))
+                    .map_err(|_| ReactionError::MalformedCount)
+            })
+            .collect()
+    }
+}
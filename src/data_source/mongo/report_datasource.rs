@@ -0,0 +1,189 @@
+//! Implements the `ReportDataSource` trait for `MongoDB`.
+
+use std::fmt::Display;
+
+use async_graphql::futures_util::stream::TryStreamExt;
+use mongodb::{
+    bson::{self, oid::ObjectId},
+    options::FindOptions,
+};
+
+use crate::{
+    common::DateTime,
+    data_source::ReportDataSource,
+    models::report::{ModerationDecision, Report, ReportStatus},
+    services::moderation_service::error::ModerationServiceError,
+};
+
+use super::MongoDB;
+
+const REPORT_COLLECTION: &str = "reports";
+const DECISION_COLLECTION: &str = "moderation_decisions";
+
+/// Error type for `ReportDataSource` operations.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ReportError {
+    // fail to add report
+    AddReportFail,
+
+    // fail to close reports
+    CloseReportsFail,
+
+    // fail to add moderation decision
+    AddDecisionFail,
+
+    // fail to do queries
+    QueryFail,
+
+    // the report status cannot be written to the database
+    InvalidStatus,
+}
+
+impl Display for ReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportError::AddReportFail => {
+                write!(f, "fail to add report!")
+            }
+            ReportError::CloseReportsFail => {
+                write!(f, "fail to close reports!")
+            }
+            ReportError::AddDecisionFail => {
+                write!(f, "fail to add moderation decision!")
+            }
+            ReportError::QueryFail => {
+                write!(f, "fail to do queries!")
+            }
+            ReportError::InvalidStatus => {
+                write!(f, "invalid report status!")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReportError {}
+
+impl From<ReportError> for ModerationServiceError {
+    fn from(_: ReportError) -> Self {
+        ModerationServiceError::DatabaseError
+    }
+}
+
+fn status_to_bson(status: ReportStatus) -> Result<bson::Bson, ReportError> {
+    bson::to_bson(&status).map_err(|_| ReportError::InvalidStatus)
+}
+
+#[async_trait::async_trait]
+impl ReportDataSource for MongoDB {
+    type Error = ReportError;
+
+    async fn add_report(&self, report: Report) -> Result<(), Self::Error> {
+        let collection = self.db.collection::<Report>(REPORT_COLLECTION);
+        match collection.insert_one(report, None).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ReportError::AddReportFail),
+        }
+    }
+
+    async fn get_report(&self, id: ObjectId) -> Result<Option<Report>, Self::Error> {
+        let collection = self.db.collection::<Report>(REPORT_COLLECTION);
+        collection
+            .find_one(bson::doc! {"_id": id}, None)
+            .await
+            .map_err(|_| ReportError::QueryFail)
+    }
+
+    async fn get_reports(&self, status: Option<ReportStatus>) -> Result<Vec<Report>, Self::Error> {
+        let collection = self.db.collection::<Report>(REPORT_COLLECTION);
+        let mut filter = bson::doc! {};
+        if let Some(status) = status {
+            filter.insert("status", status_to_bson(status)?);
+        }
+        let options = FindOptions::builder()
+            .sort(bson::doc! {"created": 1, "_id": 1})
+            .build();
+        let cursor = collection
+            .find(filter, options)
+            .await
+            .map_err(|_| ReportError::QueryFail)?;
+        cursor
+            .try_collect()
+            .await
+            .map_err(|_| ReportError::QueryFail)
+    }
+
+    async fn get_open_reports(&self, target_id: ObjectId) -> Result<Vec<Report>, Self::Error> {
+        let collection = self.db.collection::<Report>(REPORT_COLLECTION);
+        let filter = bson::doc! {
+            "target_id": target_id,
+            "status": status_to_bson(ReportStatus::Open)?
+        };
+        let options = FindOptions::builder()
+            .sort(bson::doc! {"created": 1, "_id": 1})
+            .build();
+        let cursor = collection
+            .find(filter, options)
+            .await
+            .map_err(|_| ReportError::QueryFail)?;
+        cursor
+            .try_collect()
+            .await
+            .map_err(|_| ReportError::QueryFail)
+    }
+
+    async fn close_reports(
+        &self,
+        target_id: ObjectId,
+        status: ReportStatus,
+        resolved_at: DateTime,
+    ) -> Result<u64, Self::Error> {
+        let collection = self.db.collection::<Report>(REPORT_COLLECTION);
+        let filter = bson::doc! {
+            "target_id": target_id,
+            "status": status_to_bson(ReportStatus::Open)?
+        };
+        let update = bson::doc! {"$set": {
+            "status": status_to_bson(status)?,
+            "resolved_at": bson::DateTime::from(resolved_at)
+        }};
+        collection
+            .update_many(filter, update, None)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|_| ReportError::CloseReportsFail)
+    }
+
+    async fn add_decision(&self, decision: ModerationDecision) -> Result<(), Self::Error> {
+        let collection = self
+            .db
+            .collection::<ModerationDecision>(DECISION_COLLECTION);
+        match collection.insert_one(decision, None).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ReportError::AddDecisionFail),
+        }
+    }
+
+    async fn get_decisions(
+        &self,
+        target_id: Option<ObjectId>,
+    ) -> Result<Vec<ModerationDecision>, Self::Error> {
+        let collection = self
+            .db
+            .collection::<ModerationDecision>(DECISION_COLLECTION);
+        let filter = match target_id {
+            Some(target_id) => bson::doc! {"target_id": target_id},
+            None => bson::doc! {},
+        };
+        let options = FindOptions::builder()
+            .sort(bson::doc! {"created": -1, "_id": -1})
+            .build();
+        let cursor = collection
+            .find(filter, options)
+            .await
+            .map_err(|_| ReportError::QueryFail)?;
+        cursor
+            .try_collect()
+            .await
+            .map_err(|_| ReportError::QueryFail)
+    }
+}
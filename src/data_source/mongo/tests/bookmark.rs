@@ -89,6 +89,14 @@ fn create_test_comment(
         content,
         created: DateTime::now(),
         replies: vec![],
+        edited_at: None,
+        deleted_at: None,
+        hidden_at: None,
+        mentions: vec![],
+        history: vec![],
+        parent_id: None,
+        root_id: None,
+        depth: 0,
     }
 }
 
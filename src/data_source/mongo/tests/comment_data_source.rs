@@ -1,7 +1,7 @@
 use crate::common::DateTime;
 use crate::data_source::CommentDataSource;
 use crate::models::comment::update_comment_input::UpdateCommentInputBuilder;
-use crate::models::comment::Comment;
+use crate::models::comment::{Comment, CommentVersion, Mention};
 use crate::mongo::MongoForTesting;
 use crate::object_id::ScalarObjectId;
 use async_graphql::futures_util::StreamExt;
@@ -18,6 +18,14 @@ fn create_test_comment(
         content,
         created: DateTime::now(),
         replies: vec![],
+        edited_at: None,
+        deleted_at: None,
+        hidden_at: None,
+        mentions: vec![],
+        history: vec![],
+        parent_id: None,
+        root_id: None,
+        depth: 0,
     }
 }
 
@@ -153,3 +161,118 @@ async fn test_remove_reply_from_comment() {
     let find_comment = mongodb.get_comment_by_id(comment_id.into()).await;
     assert_eq!(find_comment.unwrap().replies.len(), 0);
 }
+
+#[tokio::test]
+async fn test_edit_and_delete_comment() {
+    let mongodb = MongoForTesting::init().await;
+    let comment_id: ScalarObjectId = ObjectId::new().into();
+    let reply_id: ScalarObjectId = ObjectId::new().into();
+    let author_id: ScalarObjectId = ObjectId::new().into();
+    let comment = create_test_comment(comment_id, author_id, "content".to_string());
+    let previous = CommentVersion {
+        content: comment.content.clone(),
+        created: comment.created,
+    };
+    let mention = Mention {
+        user_id: ObjectId::new(),
+        username: "someone".to_string(),
+        start: 7,
+        end: 15,
+    };
+    mongodb.add_comment(comment).await.unwrap();
+    mongodb
+        .add_reply_to_comment(comment_id.into(), reply_id.into())
+        .await
+        .unwrap();
+
+    let edited = mongodb
+        .edit_comment(
+            comment_id.into(),
+            "edited @someone".to_string(),
+            vec![mention.clone()],
+            DateTime::now(),
+            previous.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!("edited @someone", edited.content);
+    assert_eq!(vec![mention], edited.mentions);
+    assert!(edited.edited_at.is_some());
+    assert_eq!(vec![previous.clone()], edited.history);
+
+    let deleted = mongodb
+        .delete_comment(comment_id.into(), DateTime::now())
+        .await
+        .unwrap();
+    assert_eq!("", deleted.content);
+    assert!(deleted.deleted_at.is_some());
+    assert!(deleted.history.is_empty());
+    assert!(deleted.mentions.is_empty());
+    assert_eq!(vec![reply_id], deleted.replies);
+    // deleted comments cannot be edited
+    mongodb
+        .edit_comment(
+            comment_id.into(),
+            "back".to_string(),
+            vec![],
+            DateTime::now(),
+            previous,
+        )
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn test_get_thread() {
+    let mongodb = MongoForTesting::init().await;
+    let author_id: ScalarObjectId = ObjectId::new().into();
+    let root = create_test_comment(ObjectId::new().into(), author_id, "root".to_string());
+    let reply = Comment::reply_to(&root, "reply".to_string(), *author_id);
+    let nested = Comment::reply_to(&reply, "nested".to_string(), *author_id);
+    let other = create_test_comment(ObjectId::new().into(), author_id, "other".to_string());
+    for comment in [&root, &reply, &nested, &other] {
+        mongodb.add_comment(comment.clone()).await.unwrap();
+    }
+    assert_eq!(Some(root.id), nested.root_id);
+    assert_eq!(2, nested.depth);
+
+    let thread = mongodb.get_thread(*root.id, 10).await.unwrap();
+    assert_eq!(
+        vec![root.id, reply.id, nested.id],
+        thread.iter().map(|comment| comment.id).collect::<Vec<_>>()
+    );
+    let thread = mongodb.get_thread(*root.id, 2).await.unwrap();
+    assert_eq!(
+        vec![root.id, reply.id],
+        thread.iter().map(|comment| comment.id).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_comment_threads() {
+    let mongodb = MongoForTesting::init().await;
+    let author_id: ScalarObjectId = ObjectId::new().into();
+    // comments stored before threads were tracked only know their replies
+    let mut root = create_test_comment(ObjectId::new().into(), author_id, "root".to_string());
+    let mut reply = create_test_comment(ObjectId::new().into(), author_id, "reply".to_string());
+    let nested = create_test_comment(ObjectId::new().into(), author_id, "nested".to_string());
+    root.replies.push(reply.id);
+    reply.replies.push(nested.id);
+    for comment in [&root, &reply, &nested] {
+        mongodb.add_comment(comment.clone()).await.unwrap();
+    }
+
+    assert_eq!(2, mongodb.migrate_comment_threads().await.unwrap());
+    let reply = mongodb.get_comment_by_id(*reply.id).await.unwrap();
+    assert_eq!((Some(root.id), Some(root.id), 1), (reply.parent_id, reply.root_id, reply.depth));
+    let nested = mongodb.get_comment_by_id(*nested.id).await.unwrap();
+    assert_eq!(
+        (Some(reply.id), Some(root.id), 2),
+        (nested.parent_id, nested.root_id, nested.depth)
+    );
+    let thread = mongodb.get_thread(*root.id, 10).await.unwrap();
+    assert_eq!(3, thread.len());
+
+    // the migration only runs once
+    assert_eq!(0, mongodb.migrate_comment_threads().await.unwrap());
+}
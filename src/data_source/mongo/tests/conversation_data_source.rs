@@ -0,0 +1,119 @@
+use mongodb::bson::oid::ObjectId;
+use serial_test::serial;
+
+use crate::{
+    common::DateTime,
+    data_source::{mongo::MongoForTesting, ConversationDataSource},
+    models::conversation::{Conversation, Message},
+};
+
+#[tokio::test]
+#[serial]
+async fn test_conversation_and_messages() {
+    let mongodb = MongoForTesting::init().await;
+    let (user, friend) = (ObjectId::new(), ObjectId::new());
+    let conversation = Conversation::new(vec![user, friend]);
+    mongodb
+        .add_conversation(conversation.clone())
+        .await
+        .unwrap();
+
+    let mut participant_ids = vec![friend, user];
+    participant_ids.sort();
+    let found = mongodb.find_conversation(participant_ids).await.unwrap();
+    assert_eq!(Some(conversation.id), found.map(|c| c.id));
+    assert_eq!(
+        1,
+        mongodb
+            .get_conversations_by_user(friend)
+            .await
+            .unwrap()
+            .len()
+    );
+
+    let first = Message::new(*conversation.id, user, "hi");
+    let reply = Message::new(*conversation.id, friend, "hello");
+    mongodb.add_message(first.clone()).await.unwrap();
+    mongodb.add_message(reply.clone()).await.unwrap();
+    let messages = mongodb.get_messages(*conversation.id).await.unwrap();
+    assert_eq!(
+        vec![reply.id, first.id],
+        messages.iter().map(|m| m.id).collect::<Vec<_>>()
+    );
+    let last = mongodb.get_last_message(*conversation.id).await.unwrap();
+    assert_eq!(Some(reply.id), last.map(|m| m.id));
+    let updated = mongodb
+        .get_conversation(*conversation.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .updated;
+    assert_eq!(reply.created, updated);
+
+    assert_eq!(
+        1,
+        mongodb
+            .count_unread_messages(*conversation.id, user)
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        1,
+        mongodb
+            .mark_messages_read(*conversation.id, user, DateTime::now())
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        0,
+        mongodb
+            .count_unread_messages(*conversation.id, user)
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        1,
+        mongodb
+            .count_unread_messages(*conversation.id, friend)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_add_conversation_twice() {
+    let mongodb = MongoForTesting::init().await;
+    let (user, friend) = (ObjectId::new(), ObjectId::new());
+    let first = mongodb
+        .add_conversation(Conversation::new(vec![user, friend]))
+        .await
+        .unwrap();
+    let second = mongodb
+        .add_conversation(Conversation::new(vec![friend, user]))
+        .await
+        .unwrap();
+    assert_eq!(first.id, second.id);
+    assert_eq!(
+        1,
+        mongodb
+            .get_conversations_by_user(user)
+            .await
+            .unwrap()
+            .len()
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_add_message_to_missing_conversation() {
+    let mongodb = MongoForTesting::init().await;
+    let conversation_id = ObjectId::new();
+    let message = Message::new(conversation_id, ObjectId::new(), "hi");
+    assert!(mongodb.add_message(message).await.is_err());
+    assert!(mongodb
+        .get_last_message(conversation_id)
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,79 @@
+use crate::{
+    data_source::{
+        cv::revision::CVRevisionDataSource,
+        mongo::{
+            tests::{create_demo_cv_input, create_demo_user_input},
+            MongoForTesting,
+        },
+        CVDataSource, CVDataSourceError, UserDataSource,
+    },
+    models::cv::CVRevision,
+};
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn test_add_and_get_cv_revisions() {
+    let mongodb = MongoForTesting::init().await;
+    let user = mongodb.create_user(create_demo_user_input()).await.unwrap();
+    let cv = mongodb
+        .create_cv(create_demo_cv_input(user.id.into()))
+        .await
+        .unwrap();
+    let first = CVRevision::new(&cv, 1, user.id.into(), None);
+    let second = CVRevision::new(&cv, 2, user.id.into(), Some(first.id.into()));
+    mongodb.add_cv_revision(second.clone()).await.unwrap();
+    mongodb.add_cv_revision(first.clone()).await.unwrap();
+
+    let revisions = mongodb.get_cv_revisions(cv.id.into()).await.unwrap();
+    assert_eq!(revisions, vec![first.clone(), second]);
+    let revision = mongodb.get_cv_revision_by_id(first.id.into()).await.unwrap();
+    assert_eq!(revision, first);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_revision_numbers_are_unique() {
+    let mongodb = MongoForTesting::init().await;
+    let user = mongodb.create_user(create_demo_user_input()).await.unwrap();
+    let cv = mongodb
+        .create_cv(create_demo_cv_input(user.id.into()))
+        .await
+        .unwrap();
+    assert_eq!(0, mongodb.last_cv_revision_number(cv.id.into()).await.unwrap());
+    mongodb
+        .add_cv_revision(CVRevision::new(&cv, 1, user.id.into(), None))
+        .await
+        .unwrap();
+    mongodb
+        .add_cv_revision(CVRevision::new(&cv, 2, user.id.into(), None))
+        .await
+        .unwrap();
+    assert_eq!(2, mongodb.last_cv_revision_number(cv.id.into()).await.unwrap());
+
+    // an edit made at the same time cannot take the same number
+    let rs = mongodb
+        .add_cv_revision(CVRevision::new(&cv, 2, user.id.into(), None))
+        .await;
+    assert_eq!(Err(CVDataSourceError::RevisionNumberTaken(2)), rs);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_restore_cv_revision() {
+    let mongodb = MongoForTesting::init().await;
+    let user = mongodb.create_user(create_demo_user_input()).await.unwrap();
+    let cv = mongodb
+        .create_cv(create_demo_cv_input(user.id.into()))
+        .await
+        .unwrap();
+    let mut revision = CVRevision::new(&cv, 1, user.id.into(), None);
+    revision.title = "restored".to_string();
+    revision.description = None;
+    revision.cv = None;
+
+    let restored = mongodb.restore_cv_revision(&revision).await.unwrap();
+    assert_eq!(restored.title, "restored");
+    assert_eq!(restored.description, None);
+    assert_eq!(restored.cv, None);
+}
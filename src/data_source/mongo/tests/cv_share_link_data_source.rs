@@ -0,0 +1,90 @@
+use crate::{
+    data_source::{
+        cv::share_link::ShareLinkDataSource,
+        mongo::{
+            cv_share_link_datasource::ShareLinkError,
+            tests::{create_demo_cv_input, create_demo_user_input},
+            MongoForTesting,
+        },
+        CVDataSource, UserDataSource,
+    },
+    models::cv::ShareLink,
+};
+use mongodb::bson;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn test_add_and_get_share_link() {
+    let mongodb = MongoForTesting::init().await;
+    let user = mongodb.create_user(create_demo_user_input()).await.unwrap();
+    let cv = mongodb
+        .create_cv(create_demo_cv_input(user.id.into()))
+        .await
+        .unwrap();
+    let link = ShareLink::new(cv.id.into(), user.id.into(), Some(3600), None);
+    mongodb.add_share_link(link.clone()).await.unwrap();
+
+    let by_token = mongodb.get_share_link_by_token(&link.token).await.unwrap();
+    assert_eq!(by_token, link);
+    let by_id = mongodb.get_share_link_by_id(link.id.into()).await.unwrap();
+    assert_eq!(by_id, link);
+    let links = mongodb.get_share_links_of_cv(cv.id.into()).await.unwrap();
+    assert_eq!(links, vec![link]);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_record_share_link_view() {
+    let mongodb = MongoForTesting::init().await;
+    let user = mongodb.create_user(create_demo_user_input()).await.unwrap();
+    let cv = mongodb
+        .create_cv(create_demo_cv_input(user.id.into()))
+        .await
+        .unwrap();
+    let link = ShareLink::new(cv.id.into(), user.id.into(), None, Some(1));
+    mongodb.add_share_link(link.clone()).await.unwrap();
+
+    let viewed = mongodb.record_share_link_view(&link.token).await.unwrap();
+    assert_eq!(viewed.view_count, 1);
+    let rs = mongodb.record_share_link_view(&link.token).await;
+    assert_eq!(rs, Err(ShareLinkError::ShareLinkExhausted));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_record_expired_share_link_view() {
+    let mongodb = MongoForTesting::init().await;
+    let user = mongodb.create_user(create_demo_user_input()).await.unwrap();
+    let cv = mongodb
+        .create_cv(create_demo_cv_input(user.id.into()))
+        .await
+        .unwrap();
+    let mut link = ShareLink::new(cv.id.into(), user.id.into(), Some(60), None);
+    link.expires_at = Some(bson::DateTime::from_millis(0).into());
+    mongodb.add_share_link(link.clone()).await.unwrap();
+
+    let rs = mongodb.record_share_link_view(&link.token).await;
+    assert_eq!(rs, Err(ShareLinkError::ShareLinkExhausted));
+    let stored = mongodb.get_share_link_by_token(&link.token).await.unwrap();
+    assert_eq!(stored.view_count, 0);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_revoke_share_link() {
+    let mongodb = MongoForTesting::init().await;
+    let user = mongodb.create_user(create_demo_user_input()).await.unwrap();
+    let cv = mongodb
+        .create_cv(create_demo_cv_input(user.id.into()))
+        .await
+        .unwrap();
+    let link = ShareLink::new(cv.id.into(), user.id.into(), None, None);
+    mongodb.add_share_link(link.clone()).await.unwrap();
+
+    mongodb.revoke_share_link(link.id.into()).await.unwrap();
+    let revoked = mongodb.get_share_link_by_token(&link.token).await.unwrap();
+    assert!(revoked.revoked);
+    let rs = mongodb.record_share_link_view(&link.token).await;
+    assert_eq!(rs, Err(ShareLinkError::ShareLinkExhausted));
+}
@@ -0,0 +1,61 @@
+use mongodb::bson::{self, oid::ObjectId};
+use serial_test::serial;
+
+use crate::{
+    common::DateTime,
+    data_source::{mongo::MongoForTesting, LoginAttemptDataSource},
+    models::users::LoginAttempts,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_login_attempts() {
+    let mongodb = MongoForTesting::init().await;
+    let key = LoginAttempts::account_key(ObjectId::new());
+    assert_eq!(None, mongodb.get_login_attempts(&key).await.unwrap());
+
+    let mut attempts = LoginAttempts::new(key.clone());
+    attempts.failures = 1;
+    mongodb.set_login_attempts(attempts.clone()).await.unwrap();
+    // storing again replaces the attempts
+    attempts.failures = 2;
+    attempts.locked_until = Some(DateTime::now());
+    mongodb.set_login_attempts(attempts.clone()).await.unwrap();
+    assert_eq!(
+        Some(attempts),
+        mongodb.get_login_attempts(&key).await.unwrap()
+    );
+
+    mongodb.delete_login_attempts(&key).await.unwrap();
+    assert_eq!(None, mongodb.get_login_attempts(&key).await.unwrap());
+    // deleting missing attempts is not an error
+    mongodb.delete_login_attempts(&key).await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_add_login_failure() {
+    let mongodb = MongoForTesting::init().await;
+    let key = LoginAttempts::account_key(ObjectId::new());
+    let now = DateTime::now();
+    let long_ago = bson::DateTime::from_millis(now.timestamp_millis() - 60_000).into();
+
+    let attempts = mongodb.add_login_failure(&key, now, long_ago).await.unwrap();
+    assert_eq!(1, attempts.failures);
+    assert_eq!(None, attempts.locked_until);
+    let attempts = mongodb.add_login_failure(&key, now, long_ago).await.unwrap();
+    assert_eq!(2, attempts.failures);
+    assert_eq!(now, attempts.last_failure);
+
+    // locking never shortens a lockout
+    mongodb.lock_login_attempts(&key, now).await.unwrap();
+    mongodb.lock_login_attempts(&key, long_ago).await.unwrap();
+    let attempts = mongodb.get_login_attempts(&key).await.unwrap().unwrap();
+    assert_eq!(Some(now), attempts.locked_until);
+
+    // failures are forgotten once both the last one and the lockout are old
+    let later = bson::DateTime::from_millis(now.timestamp_millis() + 60_000).into();
+    let attempts = mongodb.add_login_failure(&key, later, now).await.unwrap();
+    assert_eq!(1, attempts.failures);
+    assert_eq!(None, attempts.locked_until);
+}
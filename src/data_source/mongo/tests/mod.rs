@@ -10,11 +10,18 @@ use crate::{models::{
 
 mod bookmark;
 mod comment_data_source;
+mod conversation_data_source;
 mod cv_bookmark_data_source;
 mod cv_data_source;
 mod cv_like_data_source;
+mod cv_revision_data_source;
 mod cv_share_data_source;
+mod cv_share_link_data_source;
 mod like;
+mod login_attempt_data_source;
+mod reaction_data_source;
+mod report_data_source;
+mod user_block_data_source;
 mod user_data_source;
 
 fn create_demo_user_input() -> CreateUserInput {
@@ -88,5 +95,13 @@ fn create_test_comment(
         content,
         created: DateTime::now(),
         replies: vec![],
+        edited_at: None,
+        deleted_at: None,
+        hidden_at: None,
+        mentions: vec![],
+        history: vec![],
+        parent_id: None,
+        root_id: None,
+        depth: 0,
     }
 }
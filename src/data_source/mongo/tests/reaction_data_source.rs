@@ -0,0 +1,134 @@
+use mongodb::bson::oid::ObjectId;
+use serial_test::serial;
+
+use crate::{
+    data_source::{mongo::MongoForTesting, ReactionDataSource},
+    models::{
+        comment, cv,
+        reaction::{Reaction, ReactionCount, ReactionKind, ReactionTarget},
+    },
+};
+
+#[tokio::test]
+#[serial]
+async fn test_reactions() {
+    let mongodb = MongoForTesting::init().await;
+    let (user, other) = (ObjectId::new(), ObjectId::new());
+    let cv_id = ObjectId::new();
+    mongodb
+        .set_reaction(Reaction::new(
+            user,
+            ReactionTarget::Cv,
+            cv_id,
+            ReactionKind::Like,
+        ))
+        .await
+        .unwrap();
+    mongodb
+        .set_reaction(Reaction::new(
+            other,
+            ReactionTarget::Cv,
+            cv_id,
+            ReactionKind::Like,
+        ))
+        .await
+        .unwrap();
+    // reacting again replaces the reaction
+    mongodb
+        .set_reaction(Reaction::new(
+            user,
+            ReactionTarget::Cv,
+            cv_id,
+            ReactionKind::Wow,
+        ))
+        .await
+        .unwrap();
+
+    let reaction = mongodb.get_reaction(user, cv_id).await.unwrap().unwrap();
+    assert_eq!(ReactionKind::Wow, reaction.kind);
+    assert_eq!(2, mongodb.get_reactions(cv_id, None).await.unwrap().len());
+    let likes = mongodb
+        .get_reactions(cv_id, Some(ReactionKind::Like))
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![other],
+        likes.iter().map(|r| r.user_id()).collect::<Vec<_>>()
+    );
+
+    let mut counts = mongodb.count_reactions(cv_id).await.unwrap();
+    counts.sort_by_key(|count| count.kind as u8);
+    assert_eq!(
+        vec![
+            ReactionCount {
+                kind: ReactionKind::Like,
+                count: 1
+            },
+            ReactionCount {
+                kind: ReactionKind::Wow,
+                count: 1
+            },
+        ],
+        counts
+    );
+
+    let removed = mongodb.delete_reaction(user, cv_id).await.unwrap();
+    assert_eq!(Some(ReactionKind::Wow), removed.map(|r| r.kind));
+    assert_eq!(None, mongodb.delete_reaction(user, cv_id).await.unwrap());
+    assert_eq!(1, mongodb.get_reactions(cv_id, None).await.unwrap().len());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_migrate_legacy_likes() {
+    let mongodb = MongoForTesting::init().await;
+    let (user, other) = (ObjectId::new(), ObjectId::new());
+    let (cv_id, comment_id) = (ObjectId::new(), ObjectId::new());
+    mongodb
+        .db
+        .collection::<cv::Like>("cv_likes")
+        .insert_many(vec![cv::Like::new(user, cv_id), cv::Like::new(other, cv_id)], None)
+        .await
+        .unwrap();
+    mongodb
+        .db
+        .collection::<comment::Like>("likes")
+        .insert_one(comment::Like::new(user.into(), comment_id.into()), None)
+        .await
+        .unwrap();
+    // reactions left since are kept
+    mongodb
+        .set_reaction(Reaction::new(
+            other,
+            ReactionTarget::Cv,
+            cv_id,
+            ReactionKind::Love,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(2, mongodb.migrate_legacy_likes().await.unwrap());
+    let mut counts = mongodb.count_reactions(cv_id).await.unwrap();
+    counts.sort_by_key(|count| count.kind as u8);
+    assert_eq!(
+        vec![
+            ReactionCount {
+                kind: ReactionKind::Like,
+                count: 1
+            },
+            ReactionCount {
+                kind: ReactionKind::Love,
+                count: 1
+            },
+        ],
+        counts
+    );
+    let reaction = mongodb.get_reaction(user, comment_id).await.unwrap().unwrap();
+    assert_eq!(ReactionTarget::Comment, reaction.target_type);
+    assert_eq!(ReactionKind::Like, reaction.kind);
+
+    // the migration only runs once
+    mongodb.delete_reaction(user, comment_id).await.unwrap();
+    assert_eq!(0, mongodb.migrate_legacy_likes().await.unwrap());
+    assert!(mongodb.get_reaction(user, comment_id).await.unwrap().is_none());
+}
@@ -0,0 +1,66 @@
+use mongodb::bson::oid::ObjectId;
+use serial_test::serial;
+
+use crate::{
+    common::DateTime,
+    data_source::{mongo::MongoForTesting, ReportDataSource},
+    models::report::{ModerationAction, ModerationDecision, Report, ReportStatus, ReportTarget},
+};
+
+#[tokio::test]
+#[serial]
+async fn test_reports() {
+    let mongodb = MongoForTesting::init().await;
+    let (alice, bob) = (ObjectId::new(), ObjectId::new());
+    let (cv_id, comment_id) = (ObjectId::new(), ObjectId::new());
+    let report = Report::new(alice, ReportTarget::Cv, cv_id, "spam".to_string());
+    mongodb.add_report(report.clone()).await.unwrap();
+    mongodb
+        .add_report(Report::new(bob, ReportTarget::Cv, cv_id, "spam".to_string()))
+        .await
+        .unwrap();
+    mongodb
+        .add_report(Report::new(
+            bob,
+            ReportTarget::Comment,
+            comment_id,
+            "rude".to_string(),
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        Some(report.clone()),
+        mongodb.get_report(*report.id).await.unwrap()
+    );
+    assert_eq!(2, mongodb.get_open_reports(cv_id).await.unwrap().len());
+    assert_eq!(
+        2,
+        mongodb
+            .close_reports(cv_id, ReportStatus::ContentHidden, DateTime::now())
+            .await
+            .unwrap()
+    );
+    assert!(mongodb.get_open_reports(cv_id).await.unwrap().is_empty());
+    let open = mongodb
+        .get_reports(Some(ReportStatus::Open))
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![comment_id],
+        open.iter().map(|r| *r.target_id).collect::<Vec<_>>()
+    );
+    assert_eq!(3, mongodb.get_reports(None).await.unwrap().len());
+
+    let decision = ModerationDecision::new(&report, None, ModerationAction::HideContent, None);
+    mongodb.add_decision(decision.clone()).await.unwrap();
+    assert_eq!(
+        vec![decision],
+        mongodb.get_decisions(Some(cv_id)).await.unwrap()
+    );
+    assert!(mongodb
+        .get_decisions(Some(comment_id))
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,35 @@
+use mongodb::bson::oid::ObjectId;
+use serial_test::serial;
+
+use crate::data_source::{mongo::MongoForTesting, BlockDataSource};
+
+#[tokio::test]
+#[serial]
+async fn test_add_and_delete_block() {
+    let mongodb = MongoForTesting::init().await;
+    let (user, other, third) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+    mongodb.add_block(user, other).await.unwrap();
+    mongodb.add_block(third, user).await.unwrap();
+    // blocking twice keeps a single block
+    mongodb.add_block(user, other).await.unwrap();
+
+    let block = mongodb.get_block(user, other).await.unwrap().unwrap();
+    assert_eq!(other, block.key.blocked_id);
+    assert_eq!(None, mongodb.get_block(other, user).await.unwrap());
+    assert_eq!(1, mongodb.get_blocks_by_user(user).await.unwrap().len());
+    let mut others = mongodb
+        .get_blocks_involving(user)
+        .await
+        .unwrap()
+        .iter()
+        .map(|block| block.other(user))
+        .collect::<Vec<_>>();
+    others.sort();
+    let mut expected = vec![other, third];
+    expected.sort();
+    assert_eq!(expected, others);
+
+    mongodb.delete_block(user, other).await.unwrap();
+    assert_eq!(None, mongodb.get_block(user, other).await.unwrap());
+    mongodb.delete_block(user, other).await.unwrap_err();
+}
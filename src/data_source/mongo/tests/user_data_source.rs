@@ -340,3 +340,55 @@ async fn test_find_friend_requests_accepted() {
         .await;
     assert_eq!(request_list.len(), 1);
 }
+
+#[tokio::test]
+async fn test_find_friend_requests_accepted_of_users() {
+    let mongodb = MongoForTesting::init().await;
+    let user1: ObjectId = ObjectId::new();
+    let user2: ObjectId = ObjectId::new();
+    let user3: ObjectId = ObjectId::new();
+    let user4: ObjectId = ObjectId::new();
+    let friend_requests = [
+        FriendRequest::new(user1, user3, None::<String>).accept(),
+        FriendRequest::new(user4, user2, None::<String>).accept(),
+        FriendRequest::new(user2, user3, None::<String>),
+    ];
+    for friend_request in friend_requests {
+        mongodb.add_friend_request(friend_request).await.unwrap();
+    }
+    let request_list = mongodb
+        .accepted_friend_requests_of_users(vec![user1, user2])
+        .await
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(request_list.len(), 2);
+}
+
+#[tokio::test]
+async fn test_update_avatar_and_cover_photo() {
+    let mongodb = MongoForTesting::init().await;
+    let input = CreateUserInputBuilder::default()
+        .with_username("photo_user")
+        .with_password("password")
+        .with_primary_email("photo@mail.com")
+        .with_sex(Sex::Male)
+        .build()
+        .unwrap();
+    let user = mongodb.create_user(input).await.unwrap();
+    let avatar = Uuid::new();
+    let cover_photo = Uuid::new();
+    let user = mongodb.update_avatar(*user.id, avatar).await.unwrap();
+    assert_eq!(Some(avatar), user.avatar);
+    let user = mongodb
+        .update_cover_photo(*user.id, cover_photo)
+        .await
+        .unwrap();
+    assert_eq!(Some(avatar), user.avatar);
+    assert_eq!(Some(cover_photo), user.cover_photo);
+
+    let missing = ObjectId::new();
+    assert_eq!(
+        Err(UserDataSourceError::IdNotFound(missing)),
+        mongodb.update_avatar(missing, avatar).await
+    );
+}
@@ -0,0 +1,149 @@
+//! Implements the `BlockDataSource` trait for `MongoDB`.
+
+use std::fmt::Display;
+
+use async_graphql::futures_util::stream::TryStreamExt;
+use mongodb::{
+    bson::{self, oid::ObjectId},
+    options::{FindOptions, UpdateOptions},
+};
+
+use crate::{
+    data_source::BlockDataSource, models::users::Block,
+    services::user_service::error::UserServiceError,
+};
+
+use super::MongoDB;
+
+const USER_BLOCK_COLLECTION: &str = "user_blocks";
+
+/// Error type for `BlockDataSource` operations.
+#[derive(Debug, PartialEq, Clone)]
+pub enum BlockError {
+    // fail to add block
+    AddBlockFail,
+
+    // cannot find block
+    BlockNotFound(ObjectId),
+
+    // fail to do queries
+    QueryFail,
+}
+
+impl Display for BlockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockError::AddBlockFail => {
+                write!(f, "fail to add block!")
+            }
+            BlockError::BlockNotFound(id) => {
+                write!(f, "cannot find block of user {:?}!", id)
+            }
+            BlockError::QueryFail => {
+                write!(f, "fail to do queries!")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BlockError {}
+
+impl From<BlockError> for UserServiceError {
+    fn from(value: BlockError) -> Self {
+        match value {
+            BlockError::AddBlockFail => UserServiceError::DatabaseError,
+            BlockError::BlockNotFound(id) => UserServiceError::BlockNotFound(id),
+            BlockError::QueryFail => UserServiceError::DatabaseError,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl BlockDataSource for MongoDB {
+    type Error = BlockError;
+
+    async fn add_block(
+        &self,
+        blocker_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<(), Self::Error> {
+        let collection = self.db.collection::<Block>(USER_BLOCK_COLLECTION);
+        let block = Block::new(blocker_id, blocked_id);
+        let filter = bson::doc! {
+            "_id.blocker_id": blocker_id,
+            "_id.blocked_id": blocked_id
+        };
+        let block = bson::to_document(&block).map_err(|_| BlockError::AddBlockFail)?;
+        let update = bson::doc! {"$setOnInsert": block};
+        let options = UpdateOptions::builder().upsert(true).build();
+        match collection.update_one(filter, update, options).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(BlockError::AddBlockFail),
+        }
+    }
+
+    async fn delete_block(
+        &self,
+        blocker_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<(), Self::Error> {
+        let collection = self.db.collection::<Block>(USER_BLOCK_COLLECTION);
+        let filter = bson::doc! {
+            "_id.blocker_id": blocker_id,
+            "_id.blocked_id": blocked_id
+        };
+        match collection.delete_one(filter, None).await {
+            Ok(result) if result.deleted_count == 0 => Err(BlockError::BlockNotFound(blocked_id)),
+            Ok(_) => Ok(()),
+            Err(_) => Err(BlockError::QueryFail),
+        }
+    }
+
+    async fn get_block(
+        &self,
+        blocker_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<Option<Block>, Self::Error> {
+        let collection = self.db.collection::<Block>(USER_BLOCK_COLLECTION);
+        let filter = bson::doc! {
+            "_id.blocker_id": blocker_id,
+            "_id.blocked_id": blocked_id
+        };
+        collection
+            .find_one(filter, None)
+            .await
+            .map_err(|_| BlockError::QueryFail)
+    }
+
+    async fn get_blocks_by_user(&self, blocker_id: ObjectId) -> Result<Vec<Block>, Self::Error> {
+        let collection = self.db.collection::<Block>(USER_BLOCK_COLLECTION);
+        let filter = bson::doc! {"_id.blocker_id": blocker_id};
+        let options = FindOptions::builder()
+            .sort(bson::doc! {"created": -1})
+            .build();
+        let cursor = collection
+            .find(filter, options)
+            .await
+            .map_err(|_| BlockError::QueryFail)?;
+        cursor
+            .try_collect()
+            .await
+            .map_err(|_| BlockError::QueryFail)
+    }
+
+    async fn get_blocks_involving(&self, user_id: ObjectId) -> Result<Vec<Block>, Self::Error> {
+        let collection = self.db.collection::<Block>(USER_BLOCK_COLLECTION);
+        let filter = bson::doc! {"$or": [
+            {"_id.blocker_id": user_id},
+            {"_id.blocked_id": user_id}
+        ]};
+        let cursor = collection
+            .find(filter, None)
+            .await
+            .map_err(|_| BlockError::QueryFail)?;
+        cursor
+            .try_collect()
+            .await
+            .map_err(|_| BlockError::QueryFail)
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    models::reaction::{Reaction, ReactionCount, ReactionKind},
+    services::reaction_service::error::ReactionServiceError,
+};
+
+#[async_trait]
+pub trait ReactionDataSource {
+    type Error: std::error::Error + Send + Sync + Into<ReactionServiceError>;
+
+    /// Store the reaction, replacing the reaction the user already had on the
+    /// same target.
+    async fn set_reaction(&self, reaction: Reaction) -> Result<(), Self::Error>;
+
+    /// Remove the reaction of the user on the target, return the removed
+    /// reaction if there was one.
+    async fn delete_reaction(
+        &self,
+        user_id: ObjectId,
+        target_id: ObjectId,
+    ) -> Result<Option<Reaction>, Self::Error>;
+
+    async fn get_reaction(
+        &self,
+        user_id: ObjectId,
+        target_id: ObjectId,
+    ) -> Result<Option<Reaction>, Self::Error>;
+
+    /// Return the reactions on the target, newest first, only those of `kind`
+    /// if given.
+    async fn get_reactions(
+        &self,
+        target_id: ObjectId,
+        kind: Option<ReactionKind>,
+    ) -> Result<Vec<Reaction>, Self::Error>;
+
+    /// Count the reactions on the target by kind, kinds without reactions are
+    /// left out.
+    /// The default implementation might not be efficient, reimplementation is recommended.
+    async fn count_reactions(
+        &self,
+        target_id: ObjectId,
+    ) -> Result<Vec<ReactionCount>, Self::Error> {
+        let reactions = self.get_reactions(target_id, None).await?;
+        Ok(ReactionKind::ALL
+            .into_iter()
+            .map(|kind| ReactionCount {
+                kind,
+                count: reactions
+                    .iter()
+                    .filter(|reaction| reaction.kind == kind)
+                    .count() as u64,
+            })
+            .filter(|count| count.count > 0)
+            .collect())
+    }
+
+    /// Count the reactions of `kind` on each of the targets, targets without
+    /// such reactions are left out.
+    /// The default implementation might not be efficient, reimplementation is recommended.
+    async fn count_reactions_of_targets(
+        &self,
+        target_ids: Vec<ObjectId>,
+        kind: ReactionKind,
+    ) -> Result<HashMap<ObjectId, u64>, Self::Error> {
+        let mut counts = HashMap::new();
+        for target_id in target_ids {
+            let count = self
+                .get_reactions(target_id, Some(kind))
+                .await?
+                .len() as u64;
+            if count > 0 {
+                counts.insert(target_id, count);
+            }
+        }
+        Ok(counts)
+    }
+}
@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    common::DateTime,
+    models::report::{ModerationDecision, Report, ReportStatus},
+    services::moderation_service::error::ModerationServiceError,
+};
+
+#[async_trait]
+pub trait ReportDataSource {
+    type Error: std::error::Error + Send + Sync + Into<ModerationServiceError>;
+
+    async fn add_report(&self, report: Report) -> Result<(), Self::Error>;
+
+    async fn get_report(&self, id: ObjectId) -> Result<Option<Report>, Self::Error>;
+
+    /// Return the reports, oldest first, only those with `status` if given.
+    async fn get_reports(&self, status: Option<ReportStatus>) -> Result<Vec<Report>, Self::Error>;
+
+    /// Return the open reports on the target, oldest first.
+    async fn get_open_reports(&self, target_id: ObjectId) -> Result<Vec<Report>, Self::Error>;
+
+    /// Close every open report on the target with `status`, return how many
+    /// were closed.
+    async fn close_reports(
+        &self,
+        target_id: ObjectId,
+        status: ReportStatus,
+        resolved_at: DateTime,
+    ) -> Result<u64, Self::Error>;
+
+    async fn add_decision(&self, decision: ModerationDecision) -> Result<(), Self::Error>;
+
+    /// Return the decisions, newest first, only those on the target if given.
+    async fn get_decisions(
+        &self,
+        target_id: Option<ObjectId>,
+    ) -> Result<Vec<ModerationDecision>, Self::Error>;
+}
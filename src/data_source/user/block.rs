@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+
+use crate::{models::users::Block, services::user_service::error::UserServiceError};
+
+#[async_trait]
+pub trait BlockDataSource {
+    type Error: std::error::Error + Send + Sync + Into<UserServiceError>;
+
+    /// Store a block of `blocked_id` by `blocker_id`, blocking the same user
+    /// twice keeps the first block.
+    async fn add_block(
+        &self,
+        blocker_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<(), Self::Error>;
+
+    async fn delete_block(
+        &self,
+        blocker_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<(), Self::Error>;
+
+    async fn get_block(
+        &self,
+        blocker_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<Option<Block>, Self::Error>;
+
+    /// Return the blocks made by the user, newest first.
+    async fn get_blocks_by_user(&self, blocker_id: ObjectId) -> Result<Vec<Block>, Self::Error>;
+
+    /// Return the blocks made by or against the user.
+    async fn get_blocks_involving(&self, user_id: ObjectId) -> Result<Vec<Block>, Self::Error>;
+}
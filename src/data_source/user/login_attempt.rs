@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+
+use crate::{
+    common::DateTime, models::users::LoginAttempts,
+    services::user_service::error::UserServiceError,
+};
+
+#[async_trait]
+pub trait LoginAttemptDataSource {
+    type Error: std::error::Error + Send + Sync + Into<UserServiceError>;
+
+    async fn get_login_attempts(&self, key: &str) -> Result<Option<LoginAttempts>, Self::Error>;
+
+    /// Store the attempts, replacing those already stored under the same key.
+    async fn set_login_attempts(&self, attempts: LoginAttempts) -> Result<(), Self::Error>;
+
+    /// Count one more failure at `now` against the key, in one atomic update,
+    /// and return the attempts with it. The failures stored under the key are
+    /// forgotten first when neither the last failure nor the lockout ended
+    /// after `forget_before`.
+    async fn add_login_failure(
+        &self,
+        key: &str,
+        now: DateTime,
+        forget_before: DateTime,
+    ) -> Result<LoginAttempts, Self::Error>;
+
+    /// Refuse logins for the key until `until`, unless they already are for
+    /// longer.
+    async fn lock_login_attempts(&self, key: &str, until: DateTime) -> Result<(), Self::Error>;
+
+    /// Forget the attempts stored under the key, if any.
+    async fn delete_login_attempts(&self, key: &str) -> Result<(), Self::Error>;
+}
@@ -1,13 +1,16 @@
+pub mod block;
+pub mod login_attempt;
 mod error;
 
 use async_graphql::futures_util::stream::BoxStream;
 use async_trait::async_trait;
-use mongodb::bson::{self, Uuid};
+use mongodb::bson;
 
 pub use error::UserDataSourceError;
 
+use crate::common::DateTime;
 use crate::models::{
-    users::{CreateUserInput, UpdateUserInput, User},
+    users::{CreateUserInput, Role, UpdateUserInput, User},
     ResourceIdentifier,
 };
 
@@ -59,15 +62,25 @@ pub trait UserDataSource {
         unimplemented!()
     }
 
-    /// Change the user's avatar to the corresponding photo provided by the uuid.
+    /// Change the user's avatar to the corresponding photo provided by the uuid and
+    /// return the updated user.
     /// Note that the job of storing the actual photo is not the responsibility of this trait.
-    async fn update_avatar(&self, _photo_id: ResourceIdentifier) -> Result<(), Self::Error> {
+    async fn update_avatar(
+        &self,
+        _user_id: bson::oid::ObjectId,
+        _photo_id: ResourceIdentifier,
+    ) -> Result<User, Self::Error> {
         unimplemented!()
     }
 
-    /// Change the user's cover photo to the corresponding photo provided by the uuid.
+    /// Change the user's cover photo to the corresponding photo provided by the uuid and
+    /// return the updated user.
     /// Note that the job of storing the actual photo is not the responsibility of this trait.
-    async fn update_cover_photo(&self, _photo_id: Uuid) -> Result<(), Self::Error> {
+    async fn update_cover_photo(
+        &self,
+        _user_id: bson::oid::ObjectId,
+        _photo_id: ResourceIdentifier,
+    ) -> Result<User, Self::Error> {
         unimplemented!()
     }
 
@@ -87,4 +100,39 @@ pub trait UserDataSource {
     ) -> BoxStream<Result<User, Self::Error>> {
         unimplemented!()
     }
+
+    /// Suspend the user until `until`, or lift the suspension when it is
+    /// `None`, and return the updated user.
+    async fn set_suspended_until(
+        &self,
+        _user_id: bson::oid::ObjectId,
+        _until: Option<DateTime>,
+    ) -> Result<User, Self::Error> {
+        unimplemented!()
+    }
+
+    /// Give the user `role` and return the updated user.
+    async fn set_role(
+        &self,
+        _user_id: bson::oid::ObjectId,
+        _role: Role,
+    ) -> Result<User, Self::Error> {
+        unimplemented!()
+    }
+
+    /// Return the users, ordered by username, only those whose username,
+    /// name or primary email contains `search`, ignoring case, if given.
+    async fn search_users(&self, _search: Option<String>) -> Result<Vec<User>, Self::Error> {
+        unimplemented!()
+    }
+
+    /// Return at most `limit` users other than `user` who share a school, a
+    /// major, a company, the city or a skill with them.
+    async fn get_users_with_shared_attributes(
+        &self,
+        _user: &User,
+        _limit: i64,
+    ) -> Result<Vec<User>, Self::Error> {
+        unimplemented!()
+    }
 }
@@ -7,6 +7,8 @@ use warp::reject::Reject;
 pub enum ServerError {
     NotFound,
     Unauthorized,
+    /// The user is logged in but their role does not allow the request.
+    Forbidden,
     InvalidToken,
     InternalServerError,
     InvalidAuthorizationHeader,
@@ -17,6 +19,7 @@ impl ToString for ServerError {
         match self {
             ServerError::NotFound => "Not found".to_string(),
             ServerError::Unauthorized => "Unauthorized".to_string(),
+            ServerError::Forbidden => "Forbidden".to_string(),
             ServerError::InternalServerError => "Internal server error".to_string(),
             ServerError::InvalidToken => "Invalid token".to_string(),
             ServerError::InvalidAuthorizationHeader => "Invalid authorization header".to_string(),
@@ -29,6 +32,7 @@ impl ErrorExtensions for ServerError {
         async_graphql::Error::new(self.to_string()).extend_with(|_, e| match self {
             ServerError::NotFound => e.set("code", "NOT_FOUND"),
             ServerError::Unauthorized => e.set("code", "UNAUTHORIZED"),
+            ServerError::Forbidden => e.set("code", "FORBIDDEN"),
             ServerError::InternalServerError => e.set("code", "INTERNAL_SERVER_ERROR"),
             ServerError::InvalidToken => e.set("code", "INVALID_TOKEN"),
             ServerError::InvalidAuthorizationHeader => unreachable!(),
@@ -1,12 +1,34 @@
-use std::convert::Infallible;
-
-use async_graphql::{
-    http::GraphiQLSource, EmptySubscription, Schema,
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
 };
+
+use async_graphql::{http::GraphiQLSource, EmptySubscription, Schema};
 use async_graphql_warp::GraphQLResponse;
-use warp::{http, reject::Reject, Filter, Rejection};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use warp::{http, hyper::StatusCode, path::Tail, reject::Reject, Filter, Rejection, Reply};
 
-use crate::graphql::{mutation::Mutation, query::Query};
+use crate::{
+    data_source::mongo::MongoDB,
+    graphql::{mutation::Mutation, query::Query},
+    services::{
+        auth_service::AuthService,
+        cv_service::file_service::MAX_CV_FILE_SIZE,
+        health_service::HealthService,
+        rate_limit_service::{
+            backend::RateLimiter, error::RateLimitError, RateLimit, RateLimitService,
+        },
+        storage_service::{
+            backend::{Storage, StorageBackend, StorageError},
+            local_storage::LocalStorage,
+        },
+        user_service::{
+            error::UserServiceError, json_resume_service::JsonResumeService, UserService,
+        },
+    },
+};
 
 #[derive(Debug)]
 enum AuthorizationError {
@@ -39,19 +61,228 @@ pub fn with_auth_header() -> impl Filter<Extract = (Option<String>,), Error = Re
     })
 }
 
+/// Address of the client making the request, given to the GraphQL requests
+/// as data. `None` when the connection does not tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+/// Addresses of the proxies in front of the server, whose `X-Forwarded-For`
+/// header is trusted, from the comma separated `TRUSTED_PROXIES` variable.
+/// Invalid addresses are ignored.
+pub fn trusted_proxies() -> Vec<IpAddr> {
+    std::env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|proxy| proxy.trim().parse().ok())
+        .collect()
+}
+
+/// Return the address of the client, given the address of the connection and
+/// the `X-Forwarded-For` header. The header is only read when the connection
+/// comes from a trusted proxy, from the right, and the first address not
+/// trusted is the client. An invalid address in the header stops there.
+pub fn client_ip(
+    remote: Option<IpAddr>,
+    forwarded_for: Option<&str>,
+    trusted: &[IpAddr],
+) -> Option<IpAddr> {
+    let mut client = remote?;
+    let Some(forwarded_for) = forwarded_for else {
+        return Some(client);
+    };
+    for forwarded in forwarded_for.rsplit(',') {
+        if !trusted.contains(&client) {
+            break;
+        }
+        match forwarded.trim().parse() {
+            Ok(ip) => client = ip,
+            Err(_) => break,
+        }
+    }
+    Some(client)
+}
+
+/// This filter get the address of the client from the connection, or from
+/// the `X-Forwarded-For` header when the connection comes from one of the
+/// `trusted_proxies`. Without trusted proxies, the server must not sit behind
+/// a proxy, or every client gets the address of the proxy.
+pub fn with_client_ip() -> impl Filter<Extract = (ClientIp,), Error = Infallible> + Clone {
+    let trusted: Arc<[IpAddr]> = trusted_proxies().into();
+    warp::addr::remote()
+        .and(warp::header::headers_cloned())
+        .map(move |addr: Option<SocketAddr>, headers: http::HeaderMap| {
+            // several headers make one list, in order
+            let forwarded_for = headers
+                .get_all("X-Forwarded-For")
+                .iter()
+                .filter_map(|header| header.to_str().ok())
+                .collect::<Vec<_>>();
+            let forwarded_for = (!forwarded_for.is_empty()).then(|| forwarded_for.join(","));
+            ClientIp(client_ip(
+                addr.map(|addr| addr.ip()),
+                forwarded_for.as_deref(),
+                &trusted,
+            ))
+        })
+}
+
+/// This filter get the Authorization Header and the address of the client,
+/// and take a token from the bucket of the client, see
+/// `RateLimitService::client_key`.
+/// Reject with `RateLimitError::RateLimited` once the bucket is empty.
+pub fn with_rate_limit(
+    limiter: RateLimiter,
+    limit: RateLimit,
+) -> impl Filter<Extract = (Option<String>, ClientIp), Error = Rejection> + Clone {
+    with_auth_header()
+        .and(with_client_ip())
+        .and_then(move |header: Option<String>, client_ip: ClientIp| {
+            let limiter = limiter.clone();
+            async move {
+                if let Some(key) = RateLimitService::client_key(header.as_deref(), client_ip.0) {
+                    RateLimitService::check(limiter.as_ref(), &key, limit)
+                        .await
+                        .map_err(warp::reject::custom)?;
+                }
+                Ok::<_, Rejection>((header, client_ip))
+            }
+        })
+        .untuple_one()
+}
+
+/// Rejection of requests whose body is larger than the limit, in bytes.
+#[derive(Debug)]
+pub struct BodyTooLarge(pub u64);
+
+impl Reject for BodyTooLarge {}
+
+/// This filter reject with `BodyTooLarge` the requests whose Content-Length
+/// is larger than `max_size` bytes. Multipart requests can be larger by the
+/// size of an uploaded file, whose size is checked when it is read.
+/// Reject with `LengthRequired` the requests other than GET and HEAD without
+/// Content-Length, as chunked requests.
+pub fn with_body_limit(max_size: u64) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::method()
+        .and(warp::header::optional::<u64>("content-length"))
+        .and(warp::header::optional::<String>("content-type"))
+        .and_then(
+            move |method: http::Method,
+                  length: Option<u64>,
+                  content_type: Option<String>| async move {
+                let multipart = content_type.is_some_and(|t| t.starts_with("multipart/"));
+                let limit = if multipart {
+                    max_size + MAX_CV_FILE_SIZE as u64
+                } else {
+                    max_size
+                };
+                match length {
+                    Some(length) if length > limit => {
+                        Err(warp::reject::custom(BodyTooLarge(limit)))
+                    }
+                    // the body is never read past the Content-Length, so a
+                    // body without one, chunked, could be of any size
+                    None if method != http::Method::GET && method != http::Method::HEAD => {
+                        Err(warp::reject::custom(LengthRequired))
+                    }
+                    _ => Ok(()),
+                }
+            },
+        )
+        .untuple_one()
+}
+
+/// Rejection of requests that may have a body but no Content-Length.
+#[derive(Debug)]
+pub struct LengthRequired;
+
+impl Reject for LengthRequired {}
+
+/// This filter let requests through only when `enabled`, and reject them as
+/// not found otherwise. Used for routes disabled in production.
+pub fn enabled(enabled: bool) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::any()
+        .and_then(move || async move {
+            if enabled {
+                Ok(())
+            } else {
+                Err(warp::reject::not_found())
+            }
+        })
+        .untuple_one()
+}
+
+/// Reply to a rate limited request with `429 Too Many Requests`, a
+/// `Retry-After` header and a GraphQL error.
+pub fn rate_limited_reply(err: &RateLimitError) -> warp::reply::Response {
+    let retry_after = err.retry_after_secs().unwrap_or_default();
+    let body = serde_json::json!({
+        "errors": [{
+            "message": err.to_string(),
+            "extensions": {"code": "RATE_LIMITED", "retryAfter": retry_after}
+        }]
+    });
+    let reply = warp::reply::with_status(warp::reply::json(&body), StatusCode::TOO_MANY_REQUESTS);
+    warp::reply::with_header(reply, "Retry-After", retry_after.to_string()).into_response()
+}
+
 /// The filter which receive the GraphQL request and execute it.
 pub fn graphql_handler(schema: Schema<Query, Mutation, EmptySubscription>) -> impl Filter + Clone {
     with_auth_header()
+        .and(with_client_ip())
         .and(async_graphql_warp::graphql(schema))
         .and_then(
-            |header, (schema, request): (Schema<_, _, _>, async_graphql::Request)| async move {
+            |header,
+             client_ip,
+             (schema, request): (Schema<_, _, _>, async_graphql::Request)| async move {
                 Ok::<_, Rejection>(GraphQLResponse::from(
-                    schema.execute(request.data(header)).await,
+                    schema.execute(request.data(header).data(client_ip)).await,
                 ))
             },
         )
 }
 
+/// The filter which serve `/healthz`, answering as long as the process runs.
+pub fn healthz() -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("healthz")
+        .and(warp::get())
+        .map(|| warp::reply::json(&serde_json::json!({"status": "ok"})))
+}
+
+/// The filter which serve `/readyz`, the status of each dependency, with
+/// `503 Service Unavailable` when one of them is down.
+pub fn readyz(
+    mongo: MongoDB,
+    storage: Option<Storage>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("readyz").and(warp::get()).and_then(move || {
+        let mongo = mongo.clone();
+        let storage = storage.clone();
+        async move {
+            let readiness = HealthService::readiness(&mongo, storage.as_ref()).await;
+            let status = if readiness.ready {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            Ok::<_, Rejection>(warp::reply::with_status(
+                warp::reply::json(&readiness),
+                status,
+            ))
+        }
+    })
+}
+
+/// The filter which serve `/version`, the build information of the server
+/// whose GraphQL schema is `schema_sdl`.
+pub fn version(
+    schema_sdl: &str,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    let version = HealthService::version(schema_sdl);
+    warp::path!("version")
+        .and(warp::get())
+        .map(move || warp::reply::json(&version))
+}
+
 /// The filter which serve the GraphQL Playground.
 pub fn graphql_playground(
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
@@ -71,3 +302,130 @@ pub fn graphql_sdl(
             .body(schema.sdl())
     })
 }
+
+#[derive(Debug, Deserialize)]
+struct SignedFileQuery {
+    expires: u64,
+    signature: String,
+}
+
+/// The filter which serve files of the local storage backend under `/files`.
+/// Only urls signed by `LocalStorage::signed_url` are accepted, the route is
+/// not found when another backend is configured.
+pub fn local_files(
+    storage: Option<Arc<LocalStorage>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path("files")
+        .and(warp::get())
+        .and(warp::path::tail())
+        .and(warp::query::<SignedFileQuery>())
+        .and_then(move |tail: Tail, query: SignedFileQuery| {
+            let storage = storage.clone();
+            async move {
+                let storage = storage.ok_or_else(warp::reject::not_found)?;
+                let key = tail.as_str();
+                if !storage.verify_signature(key, query.expires, &query.signature) {
+                    return Ok::<_, Rejection>(
+                        http::Response::builder()
+                            .status(StatusCode::FORBIDDEN)
+                            .body(Vec::new()),
+                    );
+                }
+                let response = match storage.get(key).await {
+                    Ok(object) => http::Response::builder()
+                        .header("content-type", object.content_type)
+                        .body(object.data),
+                    Err(StorageError::NotFound(_)) | Err(StorageError::InvalidKey(_)) => {
+                        http::Response::builder()
+                            .status(StatusCode::NOT_FOUND)
+                            .body(Vec::new())
+                    }
+                    Err(_) => http::Response::builder()
+                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .body(Vec::new()),
+                };
+                Ok(response)
+            }
+        })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonResumeQuery {
+    cv_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+fn error_reply(
+    status: StatusCode,
+    error: impl ToString,
+) -> warp::reply::WithStatus<warp::reply::Json> {
+    let body = ErrorBody {
+        error: error.to_string(),
+    };
+    warp::reply::with_status(warp::reply::json(&body), status)
+}
+
+/// The filter which serve the profile of a user in the JSON Resume format
+/// under `/users/<id>/resume.json`. The metadata of a CV is included with
+/// `?cvId=<id>`. Like GraphQL requests, the viewer is identified by the
+/// Authorization header and anonymous requests are allowed, the contact
+/// details of the user are only included for the user and their friends.
+pub fn json_resume(
+    db: MongoDB,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("users" / String / "resume.json")
+        .and(warp::get())
+        .and(warp::query::<JsonResumeQuery>())
+        .and(with_auth_header())
+        .and_then(
+            move |user_id: String, query: JsonResumeQuery, token: Option<String>| {
+                let db = db.clone();
+                async move {
+                    let user_id = match ObjectId::parse_str(&user_id) {
+                        Ok(user_id) => user_id,
+                        Err(_) => return Err(warp::reject::not_found()),
+                    };
+                    let cv_id = match query.cv_id.map(|cv_id| ObjectId::parse_str(&cv_id)) {
+                        Some(Ok(cv_id)) => Some(cv_id),
+                        Some(Err(_)) => {
+                            return Ok(error_reply(StatusCode::BAD_REQUEST, "Invalid cvId"))
+                        }
+                        None => None,
+                    };
+                    let viewer_id = match token {
+                        Some(token) => match AuthService::decode_token(&token, true) {
+                            Some(claims) => UserService::get_user_by_username(&db, claims.sub)
+                                .await
+                                .ok()
+                                .map(|user| user.id.into()),
+                            None => {
+                                return Ok(error_reply(
+                                    StatusCode::UNAUTHORIZED,
+                                    UserServiceError::InvalidToken,
+                                ))
+                            }
+                        },
+                        None => None,
+                    };
+                    let reply = match JsonResumeService::export(&db, viewer_id, user_id, cv_id)
+                        .await
+                    {
+                        Ok(resume) => {
+                            warp::reply::with_status(warp::reply::json(&resume), StatusCode::OK)
+                        }
+                        Err(err @ UserServiceError::IdNotFound(_))
+                        | Err(err @ UserServiceError::CVNotFound(_)) => {
+                            error_reply(StatusCode::NOT_FOUND, err)
+                        }
+                        Err(err) => error_reply(StatusCode::INTERNAL_SERVER_ERROR, err),
+                    };
+                    Ok(reply)
+                }
+            },
+        )
+}
@@ -0,0 +1,58 @@
+//! Refuse every request made with the access token of a suspended user, the
+//! token stays valid until it expires but cannot be used meanwhile.
+
+use std::sync::Arc;
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute},
+    ErrorExtensions, Pos, Response,
+};
+
+use crate::{
+    data_source::mongo::{MongoDB, MongoForTesting},
+    services::{
+        auth_service::AuthService,
+        user_service::{error::UserServiceError, UserService},
+    },
+};
+
+/// Extension checking that the user of the access token, if any, is not
+/// suspended before executing the request. Anonymous requests and invalid
+/// tokens are left to the resolvers, which only act for the user of the
+/// token, so a suspended user cannot act by leaving the token out.
+pub struct AccountStatusCheck;
+
+impl ExtensionFactory for AccountStatusCheck {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(AccountStatusCheckExtension)
+    }
+}
+
+struct AccountStatusCheckExtension;
+
+#[async_graphql::async_trait::async_trait]
+impl Extension for AccountStatusCheckExtension {
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let claims = ctx
+            .data_opt::<Option<String>>()
+            .and_then(|token| token.as_deref())
+            .and_then(|token| AuthService::decode_token(token, true));
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .or_else(|| ctx.data_opt::<MongoForTesting>().map(|db| &**db));
+        if let (Some(claims), Some(db)) = (claims, db) {
+            if let Ok(user) = UserService::get_user_by_username(db, claims.sub).await {
+                if let Some(until) = user.suspended_until.filter(|_| user.is_suspended()) {
+                    let error = UserServiceError::AccountSuspended(until).extend();
+                    return Response::from_errors(vec![error.into_server_error(Pos::default())]);
+                }
+            }
+        }
+        next.run(ctx, operation_name).await
+    }
+}
@@ -0,0 +1,171 @@
+//! The `admin` namespaces of queries and mutations, only available to admins.
+
+use async_graphql as gql;
+use async_graphql::{connection, Context, ErrorExtensions, Object};
+
+use crate::{
+    common::DateTime,
+    data_source::mongo::{MongoDB, MongoForTesting},
+    error::ServerError,
+    models::{
+        comment::Comment,
+        users::{Role, User},
+    },
+    object_id::ScalarObjectId,
+    services::admin_service::AdminService,
+};
+
+use super::{connection_complexity, viewer_id, DEFAULT_CONNECTION_PAGE};
+
+pub struct AdminQuery;
+
+#[Object]
+impl AdminQuery {
+    /// Users ordered by username, only those whose username, name or primary
+    /// email contains `search` if given.
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
+    async fn users(
+        &self,
+        ctx: &Context<'_>,
+        search: Option<String>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<
+        connection::Connection<
+            ScalarObjectId,
+            User,
+            connection::EmptyFields,
+            connection::EmptyFields,
+        >,
+    > {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let users = AdminService::users(db, search)
+            .await
+            .map_err(|e| e.extend())?;
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let users = if let Some(after) = after {
+                    users
+                        .into_iter()
+                        .skip_while(|user| user.id != after)
+                        .skip(1)
+                        .collect::<Vec<_>>()
+                } else if let Some(before) = before {
+                    users
+                        .into_iter()
+                        .take_while(|user| user.id != before)
+                        .collect::<Vec<_>>()
+                } else {
+                    users
+                };
+                let users = if let Some(first) = first {
+                    users.into_iter().take(first).collect::<Vec<_>>()
+                } else if let Some(last) = last {
+                    let size = users.len();
+                    users
+                        .into_iter()
+                        .skip(size.saturating_sub(last))
+                        .collect::<Vec<_>>()
+                } else {
+                    users.into_iter().take(DEFAULT_CONNECTION_PAGE).collect::<Vec<_>>()
+                };
+                let mut connection = connection::Connection::new(true, false);
+                connection.edges.extend(
+                    users
+                        .into_iter()
+                        .map(|user| connection::Edge::new(user.id, user)),
+                );
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+}
+
+pub struct AdminMutation;
+
+#[Object]
+impl AdminMutation {
+    /// Suspend the user until `until`, for a week if not given.
+    async fn suspend_user(
+        &self,
+        ctx: &Context<'_>,
+        user_id: ScalarObjectId,
+        until: Option<DateTime>,
+    ) -> gql::Result<User> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let admin_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        AdminService::suspend_user(db, admin_id, user_id.into(), until)
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    async fn unsuspend_user(
+        &self,
+        ctx: &Context<'_>,
+        user_id: ScalarObjectId,
+    ) -> gql::Result<User> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        AdminService::unsuspend_user(db, user_id.into())
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    /// Give the user `role`, it applies once they log in or refresh their
+    /// access token.
+    async fn set_user_role(
+        &self,
+        ctx: &Context<'_>,
+        user_id: ScalarObjectId,
+        role: Role,
+    ) -> gql::Result<User> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let admin_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        AdminService::set_role(db, admin_id, user_id.into(), role)
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    /// Delete a CV whoever its author is.
+    async fn delete_cv(&self, ctx: &Context<'_>, cv_id: ScalarObjectId) -> gql::Result<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        AdminService::delete_cv(db, cv_id.into())
+            .await
+            .map(|_| true)
+            .map_err(|e| e.extend())
+    }
+
+    /// Delete a comment whoever its author is, its replies are kept.
+    async fn delete_comment(
+        &self,
+        ctx: &Context<'_>,
+        comment_id: ScalarObjectId,
+    ) -> gql::Result<Comment> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        AdminService::delete_comment(db, comment_id.into())
+            .await
+            .map_err(|e| e.extend())
+    }
+}
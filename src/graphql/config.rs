@@ -0,0 +1,145 @@
+use async_graphql::{EmptySubscription, SchemaBuilder};
+
+use super::{
+    mutation::Mutation,
+    persisted_queries::{PersistedQueries, PersistedQueryMode},
+    query::Query,
+};
+
+/// Environment the server runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    Development,
+    Production,
+}
+
+/// Default of `GRAPHQL_MAX_DEPTH`.
+pub const DEFAULT_MAX_DEPTH: usize = 15;
+
+/// Default of `GRAPHQL_MAX_COMPLEXITY`.
+pub const DEFAULT_MAX_COMPLEXITY: usize = 5000;
+
+/// Default of `GRAPHQL_MAX_BODY_SIZE`, in bytes.
+pub const DEFAULT_MAX_BODY_SIZE: u64 = 64 * 1024;
+
+/// Default of `PERSISTED_QUERIES_CACHE_SIZE`.
+pub const DEFAULT_PERSISTED_QUERY_CACHE_SIZE: usize = 1000;
+
+/// Default of `PERSISTED_QUERIES_FILE`.
+pub const DEFAULT_PERSISTED_QUERY_FILE: &str = "persisted-queries.json";
+
+/// GraphQL endpoint configuration, read from the environment:
+///
+/// - `APP_ENV`: `development` (default) or `production`. Introspection and
+///   the playground are disabled in production.
+/// - `GRAPHQL_MAX_DEPTH`: deepest selection a query can have.
+/// - `GRAPHQL_MAX_COMPLEXITY`: highest complexity a query can have, each
+///   field costs 1 and connections cost their page items times the page size.
+/// - `GRAPHQL_MAX_BODY_SIZE`: largest request body in bytes, uploads can be
+///   larger by the size of the uploaded file.
+/// - `PERSISTED_QUERIES`: `automatic`, `allow-list` or `disabled`, see
+///   `persisted_queries`. Defaults to `allow-list` in production and
+///   `automatic` otherwise.
+/// - `PERSISTED_QUERIES_CACHE_SIZE`: number of queries cached in automatic
+///   mode.
+/// - `PERSISTED_QUERIES_FILE`: persisted query manifest read in allow-list
+///   mode, as generated by the `persisted-queries` command. The server does
+///   not start in allow-list mode without it.
+#[derive(Debug, Clone)]
+pub struct GraphQLConfig {
+    pub environment: Environment,
+    pub max_depth: usize,
+    pub max_complexity: usize,
+    pub max_body_size: u64,
+    pub persisted_queries: PersistedQueryMode,
+}
+
+impl Default for GraphQLConfig {
+    fn default() -> Self {
+        Self {
+            environment: Environment::Development,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_complexity: DEFAULT_MAX_COMPLEXITY,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            persisted_queries: PersistedQueryMode::Automatic {
+                cache_size: DEFAULT_PERSISTED_QUERY_CACHE_SIZE,
+            },
+        }
+    }
+}
+
+impl GraphQLConfig {
+    pub fn from_env() -> Result<Self, String> {
+        fn parse<T: std::str::FromStr>(name: &str, default: T) -> Result<T, String> {
+            match std::env::var(name) {
+                Ok(value) => value
+                    .parse::<T>()
+                    .map_err(|_| format!("{} is not a valid number: {:?}", name, value)),
+                Err(_) => Ok(default),
+            }
+        }
+
+        let environment = match std::env::var("APP_ENV")
+            .unwrap_or_else(|_| "development".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "development" => Environment::Development,
+            "production" => Environment::Production,
+            other => return Err(format!("Unknown environment {:?}", other)),
+        };
+        let default_mode = match environment {
+            Environment::Development => "automatic",
+            Environment::Production => "allow-list",
+        };
+        let persisted_queries = match std::env::var("PERSISTED_QUERIES")
+            .unwrap_or_else(|_| default_mode.to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "automatic" => PersistedQueryMode::Automatic {
+                cache_size: parse(
+                    "PERSISTED_QUERIES_CACHE_SIZE",
+                    DEFAULT_PERSISTED_QUERY_CACHE_SIZE,
+                )?,
+            },
+            "allow-list" => {
+                let path = std::env::var("PERSISTED_QUERIES_FILE")
+                    .unwrap_or_else(|_| DEFAULT_PERSISTED_QUERY_FILE.to_string());
+                PersistedQueryMode::allow_list(&path)?
+            }
+            "disabled" => PersistedQueryMode::Disabled,
+            other => return Err(format!("Unknown persisted queries mode {:?}", other)),
+        };
+        Ok(Self {
+            environment,
+            max_depth: parse("GRAPHQL_MAX_DEPTH", DEFAULT_MAX_DEPTH)?,
+            max_complexity: parse("GRAPHQL_MAX_COMPLEXITY", DEFAULT_MAX_COMPLEXITY)?,
+            max_body_size: parse("GRAPHQL_MAX_BODY_SIZE", DEFAULT_MAX_BODY_SIZE)?,
+            persisted_queries,
+        })
+    }
+
+    pub fn is_production(&self) -> bool {
+        self.environment == Environment::Production
+    }
+
+    /// Apply the depth and complexity limits and the persisted queries to the
+    /// schema, and disable introspection in production.
+    pub fn apply(
+        &self,
+        schema: SchemaBuilder<Query, Mutation, EmptySubscription>,
+    ) -> SchemaBuilder<Query, Mutation, EmptySubscription> {
+        let mut schema = schema
+            .limit_depth(self.max_depth)
+            .limit_complexity(self.max_complexity);
+        if let Some(persisted_queries) = PersistedQueries::new(self.persisted_queries.clone()) {
+            schema = schema.extension(persisted_queries);
+        }
+        if self.is_production() {
+            schema.disable_introspection()
+        } else {
+            schema
+        }
+    }
+}
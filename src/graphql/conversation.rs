@@ -0,0 +1,64 @@
+//! Implement graphql-specific fields for Conversation and Message
+
+use async_graphql as gql;
+use async_graphql::{Context, ErrorExtensions};
+
+use crate::data_source::mongo::{MongoDB, MongoForTesting};
+use crate::error::ServerError;
+use crate::models::conversation::{Conversation, Message};
+use crate::models::users::User;
+use crate::services::conversation_service::ConversationService;
+use crate::services::user_service::UserService;
+
+use super::viewer_id;
+
+#[async_graphql::ComplexObject]
+impl Conversation {
+    async fn participants(&self, ctx: &Context<'_>) -> gql::Result<Vec<User>> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let mut participants = Vec::with_capacity(self.participant_ids.len());
+        for id in &self.participant_ids {
+            // skip the users deleted since the conversation started
+            if let Ok(user) = UserService::get_user_by_id(db, *id).await {
+                participants.push(user);
+            }
+        }
+        Ok(participants)
+    }
+
+    async fn last_message(&self, ctx: &Context<'_>) -> gql::Result<Option<Message>> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        ConversationService::last_message(db, *self.id)
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    /// Number of messages the logged in user has not read yet.
+    async fn unread_count(&self, ctx: &Context<'_>) -> gql::Result<u64> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        ConversationService::unread_count(db, user_id, *self.id)
+            .await
+            .map_err(|e| e.extend())
+    }
+}
+
+#[async_graphql::ComplexObject]
+impl Message {
+    async fn sender(&self, ctx: &Context<'_>) -> gql::Result<User> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        UserService::get_user_by_id(db, self.sender_id)
+            .await
+            .map_err(|e| e.extend())
+    }
+}
@@ -1,21 +1,35 @@
 //! Implement graphql-specific field for CV
 
 use async_graphql as gql;
-use async_graphql::{connection, futures_util::StreamExt, ComplexObject, Context};
+use async_graphql::{connection, futures_util::StreamExt, ComplexObject, Context, ErrorExtensions};
 
 use crate::models::cv::interactions::Share;
-use crate::models::cv::Like;
+use crate::models::cv::{CVRevision, Like, ShareLink, TextExtractionStatus};
+use crate::models::reaction::{Reaction, ReactionCount, ReactionKind};
 use crate::services::cv_service::like_service::LikeService;
+use crate::services::cv_service::file_service::CVFileService;
+use crate::services::cv_service::revision_service::CVRevisionService;
+use crate::services::cv_service::share_link_service::ShareLinkService;
 use crate::services::cv_service::share_service::ShareService;
+use crate::services::reaction_service::ReactionService;
+use crate::services::storage_service::backend::Storage;
 use crate::{
     data_source::mongo::{MongoDB, MongoForTesting},
-    models::{comment::Comment, cv::CV},
+    models::{
+        comment::{Comment, CommentSort},
+        cv::CV,
+    },
+    error::ServerError,
     object_id::ScalarObjectId,
     services::cv_service::comment_service::CommentService,
 };
 
+use super::reaction::reactors;
+use super::{authorization, connection_complexity, viewer_id};
+
 #[ComplexObject]
 impl CV {
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
     async fn likes(
         &self,
         ctx: &Context<'_>,
@@ -90,9 +104,12 @@ impl CV {
         .await
     }
 
+    /// Comments on this CV, replies are in the `replies` of each comment.
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
     async fn comments(
         &self,
         ctx: &Context<'_>,
+        #[graphql(default)] sort: CommentSort,
         after: Option<String>,
         before: Option<String>,
         first: Option<i32>,
@@ -111,7 +128,13 @@ impl CV {
         let comments_list = CommentService::get_comments_list_by_cv_id(db, self.id.into())
             .await
             .collect::<Vec<_>>()
-            .await;
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.extend())?;
+        let comments_list = CommentService::sort_comments(db, comments_list, sort)
+            .await
+            .map_err(|e| e.extend())?;
         connection::query(
             after,
             before,
@@ -121,14 +144,14 @@ impl CV {
                 let comments_list = if let Some(after) = after {
                     comments_list
                         .into_iter()
-                        .skip_while(|comment| comment.as_ref().unwrap().id != after)
+                        .skip_while(|comment| comment.id != after)
                         .skip(1)
                         .map(|comment| comment)
                         .collect::<Vec<_>>()
                 } else if let Some(before) = before {
                     comments_list
                         .into_iter()
-                        .take_while(|comment| comment.as_ref().unwrap().id != before)
+                        .take_while(|comment| comment.id != before)
                         .map(|comment| comment)
                         .collect::<Vec<_>>()
                 } else {
@@ -152,7 +175,7 @@ impl CV {
                 connection
                     .edges
                     .extend(comments_list.into_iter().map(|comment| {
-                        connection::Edge::new(comment.as_ref().unwrap().id, comment.unwrap())
+                        connection::Edge::new(comment.id, comment)
                     }));
                 Ok::<_, async_graphql::Error>(connection)
             },
@@ -160,6 +183,7 @@ impl CV {
         .await
     }
 
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
     async fn shares(
         &self,
         ctx: &Context<'_>,
@@ -244,6 +268,37 @@ impl CV {
             .map_err(|err| err.into())
     }
 
+    /// Number of reactions on this CV for every reaction kind.
+    async fn reaction_counts(&self, ctx: &Context<'_>) -> gql::Result<Vec<ReactionCount>> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        ReactionService::reaction_counts(db, self.id.into())
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    /// Reactions on this CV, newest first, only those of `kind` if given.
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
+    async fn reactors(
+        &self,
+        ctx: &Context<'_>,
+        kind: Option<ReactionKind>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<
+        connection::Connection<
+            ScalarObjectId,
+            Reaction,
+            connection::EmptyFields,
+            connection::EmptyFields,
+        >,
+    > {
+        reactors(ctx, self.id.into(), kind, after, before, first, last).await
+    }
+
     async fn shares_count(&self, ctx: &Context<'_>) -> gql::Result<u64> {
         let db = ctx
             .data_opt::<MongoDB>()
@@ -253,4 +308,104 @@ impl CV {
             .map(|count| count as u64)
             .map_err(|err| err.into())
     }
+
+    /// Progress of the text extraction of the uploaded file, used by the CV
+    /// search. `null` if no file has been uploaded.
+    async fn text_extraction_status(&self) -> Option<TextExtractionStatus> {
+        self.extraction_status()
+    }
+
+    /// A short-lived url to download the file of this CV, `null` if no file
+    /// has been uploaded or file storage is not available.
+    async fn file_url(&self, ctx: &Context<'_>) -> gql::Result<Option<String>> {
+        let storage = match ctx.data_opt::<Storage>() {
+            Some(storage) => storage,
+            None => return Ok(None),
+        };
+        CVFileService::get_cv_file_url(storage.as_ref(), self)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Share links of this CV that can still be used, along with their view
+    /// counts. Only visible to the author.
+    async fn share_links(&self, ctx: &Context<'_>) -> gql::Result<Vec<ShareLink>> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        ShareLinkService::get_active_share_links_of_cv(db, user_id, self.id.into())
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// The revision history of this CV, oldest first.
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
+    async fn revisions(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<
+        connection::Connection<
+            ScalarObjectId,
+            CVRevision,
+            connection::EmptyFields,
+            connection::EmptyFields,
+        >,
+    > {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let viewer_id = viewer_id(ctx).await;
+        let revisions = CVRevisionService::get_revisions_of_cv(db, viewer_id, self.id.into())
+            .await
+            .map_err(|err| -> gql::Error { err.into() })?;
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let revisions = if let Some(after) = after {
+                    revisions
+                        .into_iter()
+                        .skip_while(|revision| revision.id != after)
+                        .skip(1)
+                        .collect::<Vec<_>>()
+                } else if let Some(before) = before {
+                    revisions
+                        .into_iter()
+                        .take_while(|revision| revision.id != before)
+                        .collect::<Vec<_>>()
+                } else {
+                    revisions
+                };
+                let revisions = if let Some(first) = first {
+                    revisions.into_iter().take(first).collect::<Vec<_>>()
+                } else if let Some(last) = last {
+                    let size = revisions.len();
+                    revisions
+                        .into_iter()
+                        .skip(size.saturating_sub(last))
+                        .collect::<Vec<_>>()
+                } else {
+                    revisions
+                };
+                let mut connection = connection::Connection::new(true, false);
+                connection.edges.extend(
+                    revisions
+                        .into_iter()
+                        .map(|revision| connection::Edge::new(revision.id, revision)),
+                );
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
 }
@@ -0,0 +1,42 @@
+//! Implement graphql-specific field for FriendRequest
+
+use async_graphql as gql;
+use async_graphql::{Context, ErrorExtensions};
+
+use crate::common::DateTime;
+use crate::data_source::mongo::{MongoDB, MongoForTesting};
+use crate::models::friend_request::FriendRequest;
+use crate::models::users::User;
+use crate::services::user_service::UserService;
+
+#[async_graphql::ComplexObject]
+impl FriendRequest {
+    /// The user who sent the request.
+    async fn from(&self, ctx: &Context<'_>) -> gql::Result<User> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        UserService::get_user_by_id(db, self.id.from)
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    /// The user the request was sent to.
+    async fn to(&self, ctx: &Context<'_>) -> gql::Result<User> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        UserService::get_user_by_id(db, self.id.to)
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    async fn created_at(&self) -> DateTime {
+        self.created_at.into()
+    }
+
+    /// When the request was last accepted or rejected.
+    async fn updated_at(&self) -> DateTime {
+        self.updated_at.into()
+    }
+}
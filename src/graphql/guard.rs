@@ -0,0 +1,72 @@
+//! Guards restricting fields and mutations to some roles, or limiting how
+//! often they can be used.
+
+use async_graphql::{Context, ErrorExtensions, Guard};
+
+use crate::{
+    error::ServerError,
+    filters::ClientIp,
+    models::users::Role,
+    services::rate_limit_service::{backend::RateLimiter, RateLimit, RateLimitService},
+};
+
+use super::{authorization, GqlResult};
+
+/// Only let through users logged in with at least `role`, e.g.
+/// `#[graphql(guard = "RoleGuard::new(Role::Moderator)")]`. The role is read
+/// from the access token.
+pub struct RoleGuard {
+    role: Role,
+}
+
+impl RoleGuard {
+    pub fn new(role: Role) -> Self {
+        Self { role }
+    }
+}
+
+#[async_graphql::async_trait::async_trait]
+impl Guard for RoleGuard {
+    async fn check(&self, ctx: &Context<'_>) -> GqlResult<()> {
+        let claims = authorization(ctx)?;
+        if claims.role >= self.role {
+            Ok(())
+        } else {
+            Err(ServerError::Forbidden.extend())
+        }
+    }
+}
+
+/// Limit how often a client can use the field, e.g.
+/// `#[graphql(guard = "RateLimitGuard::new(RateLimit::per_minute(10))")]`.
+/// Each field has its own buckets, on top of the limit of the endpoint.
+/// Nothing is limited when there is no `RateLimiter` in the context.
+pub struct RateLimitGuard {
+    limit: RateLimit,
+}
+
+impl RateLimitGuard {
+    pub fn new(limit: RateLimit) -> Self {
+        Self { limit }
+    }
+}
+
+#[async_graphql::async_trait::async_trait]
+impl Guard for RateLimitGuard {
+    async fn check(&self, ctx: &Context<'_>) -> GqlResult<()> {
+        let Some(limiter) = ctx.data_opt::<RateLimiter>() else {
+            return Ok(());
+        };
+        let token = ctx
+            .data_opt::<Option<String>>()
+            .and_then(|token| token.as_deref());
+        let client_ip = ctx.data_opt::<ClientIp>().and_then(|client_ip| client_ip.0);
+        let Some(key) = RateLimitService::client_key(token, client_ip) else {
+            return Ok(());
+        };
+        let key = format!("{}:{}", ctx.item.node.name.node, key);
+        RateLimitService::check(limiter.as_ref(), &key, self.limit)
+            .await
+            .map_err(|e| e.extend())
+    }
+}
@@ -1,20 +1,65 @@
 use async_graphql::ErrorExtensions;
+use mongodb::bson::oid::ObjectId;
 
 use crate::{
+    data_source::mongo::{MongoDB, MongoForTesting},
     error::ServerError,
-    services::auth_service::{AuthService, Claims},
+    services::{
+        auth_service::{AuthService, Claims},
+        user_service::UserService,
+    },
 };
 
+pub mod account_status;
+pub mod admin;
+pub mod config;
 pub mod mutation;
+pub mod persisted_queries;
 pub mod query;
+mod conversation;
 mod cv;
+mod friend_request;
+pub mod guard;
+pub(crate) mod reaction;
 mod user;
 
+#[cfg(test)]
+mod tests;
+
 pub type GqlResult<T> = Result<T, async_graphql::Error>;
 
+/// Page size of connections when neither `first` nor `last` is given.
+const DEFAULT_CONNECTION_PAGE: usize = 20;
+
+/// Complexity of a connection field, the complexity of one page item times
+/// the number of items requested.
+pub(crate) fn connection_complexity(
+    child_complexity: usize,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> usize {
+    let page = first
+        .or(last)
+        .map_or(DEFAULT_CONNECTION_PAGE, |page| page.max(1) as usize);
+    child_complexity.saturating_mul(page)
+}
+
 fn authorization(ctx: &async_graphql::Context<'_>) -> GqlResult<Claims> {
     let token = ctx.data_unchecked::<Option<String>>();
     let token = token.as_ref().ok_or_else(|| ServerError::Unauthorized.extend())?;
     let rs = AuthService::decode_token(token, true);
     rs.ok_or_else(|| ServerError::InvalidToken.extend())
 }
+
+/// Resolve the id of the user making the request, if any.
+/// Anonymous requests and invalid tokens yield `None`.
+pub(crate) async fn viewer_id(ctx: &async_graphql::Context<'_>) -> Option<ObjectId> {
+    let claims = authorization(ctx).ok()?;
+    let db = ctx
+        .data_opt::<MongoDB>()
+        .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+    UserService::get_user_by_username(db, claims.sub)
+        .await
+        .ok()
+        .map(|user| user.id.into())
+}
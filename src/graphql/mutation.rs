@@ -1,26 +1,57 @@
-use async_graphql::{Context, ErrorExtensions, Object};
+use std::io::Read;
+
+use async_graphql::{Context, ErrorExtensions, Object, Upload};
 
 use crate::{
+    error::ServerError,
     data_source::mongo::{MongoDB, MongoForTesting},
-    models::{users::{CreateUserInput, User}, cv::CV, comment::Comment},
+    models::{
+        users::{
+            profile_import::{ProfileImport, ProfileImportFormat, ProfileImportMode},
+            CreateUserInput, Role, User,
+        },
+        conversation::Message,
+        cv::{CVTemplate, ShareLink, Visibility, CV},
+        comment::Comment,
+        reaction::{Reaction, ReactionKind, ReactionTarget},
+        report::{ModerationAction, Report, ReportTarget},
+    },
     object_id::ScalarObjectId,
     services::{
         auth_service::AuthService,
+        conversation_service::ConversationService,
+        reaction_service::ReactionService,
+        moderation_service::ModerationService,
         cv_service::{
             bookmark_service::BookmarkService, comment_service::CommentService,
-            cv_service::CVService, like_service::LikeService as CVLikeService,
-            share_service::ShareService,
+            cv_service::CVService, error::CVServiceError, file_service::CVFileService,
+            like_service::LikeService as CVLikeService, render_service::CVRenderService, revision_service::CVRevisionService, share_link_service::ShareLinkService,
+            share_service::ShareService, text_service::CVTextService,
+        },
+        rate_limit_service::RateLimit,
+        storage_service::backend::Storage,
+        user_service::{
+            block_service::BlockService,
+            error::UserServiceError,
+            import_service::ProfileImportService,
+            photo_service::{PhotoKind, PhotoService},
+            UserService,
         },
-        user_service::UserService,
     },
 };
 
-use super::{authorization, GqlResult};
+use super::{
+    admin::AdminMutation,
+    authorization,
+    guard::{RateLimitGuard, RoleGuard},
+    viewer_id, GqlResult,
+};
 
 pub struct Mutation;
 
 #[Object]
 impl Mutation {
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_hour(5))")]
     async fn user_register(&self, ctx: &Context<'_>, new_user: CreateUserInput) -> GqlResult<User> {
         let rs = AuthService::register(
             ctx.data_opt::<MongoDB>()
@@ -34,6 +65,7 @@ impl Mutation {
         }
     }
 
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_minute(20))")]
     async fn send_friend_request(
         &self,
         ctx: &Context<'_>,
@@ -52,7 +84,7 @@ impl Mutation {
         authorization(ctx)?;
         match rs {
             Ok(_) => Ok(true),
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e.extend()),
         }
     }
 
@@ -72,7 +104,7 @@ impl Mutation {
         authorization(ctx)?;
         match rs {
             Ok(_) => Ok(true),
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e.extend()),
         }
     }
 
@@ -92,10 +124,114 @@ impl Mutation {
         authorization(ctx)?;
         match rs {
             Ok(_) => Ok(true),
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e.extend()),
         }
     }
 
+    /// Withdraw a pending friend request sent by the logged in user.
+    async fn cancel_friend_request(
+        &self,
+        ctx: &Context<'_>,
+        friend_id: ScalarObjectId,
+    ) -> GqlResult<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        UserService::cancel_friend_request(db, user_id, friend_id.into())
+            .await
+            .map(|_| true)
+            .map_err(|e| e.extend())
+    }
+
+    /// Remove a friend of the logged in user.
+    async fn unfriend(&self, ctx: &Context<'_>, friend_id: ScalarObjectId) -> GqlResult<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        UserService::unfriend(db, user_id, friend_id.into())
+            .await
+            .map(|_| true)
+            .map_err(|e| e.extend())
+    }
+
+    /// Block a user, which also removes any friendship or pending friend
+    /// request between the logged in user and them.
+    async fn block_user(&self, ctx: &Context<'_>, user_id: ScalarObjectId) -> GqlResult<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let viewer_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        BlockService::block_user(db, viewer_id, user_id.into())
+            .await
+            .map(|_| true)
+            .map_err(|e| e.extend())
+    }
+
+    async fn unblock_user(&self, ctx: &Context<'_>, user_id: ScalarObjectId) -> GqlResult<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let viewer_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        BlockService::unblock_user(db, viewer_id, user_id.into())
+            .await
+            .map(|_| true)
+            .map_err(|e| e.extend())
+    }
+
+    /// Send a message to a user. Only friends can start a conversation.
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_minute(30))")]
+    async fn send_message(
+        &self,
+        ctx: &Context<'_>,
+        recipient_id: ScalarObjectId,
+        content: String,
+    ) -> GqlResult<Message> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let viewer_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        ConversationService::send_message(db, viewer_id, recipient_id.into(), content)
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    /// Mark the messages received in a conversation as read, return how many
+    /// were unread.
+    async fn mark_conversation_read(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: ScalarObjectId,
+    ) -> GqlResult<u64> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let viewer_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        ConversationService::mark_conversation_read(db, viewer_id, conversation_id.into())
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_minute(10))")]
     async fn create_cv(
         &self,
         ctx: &Context<'_>,
@@ -142,34 +278,179 @@ impl Mutation {
         cv_id: ScalarObjectId,
         title: String,
     ) -> GqlResult<CV> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CVService::change_title(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
+            user_id,
             cv_id.into(),
             title,
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(cv) => Ok(cv),
             Err(e) => Err(e.into()),
         }
     }
 
+    /// Change who is allowed to see a CV, only the author can do this
+    async fn change_cv_visibility(
+        &self,
+        ctx: &Context<'_>,
+        cv_id: ScalarObjectId,
+        visibility: Visibility,
+    ) -> GqlResult<CV> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let rs = CVService::change_visibility(db, user_id, cv_id.into(), visibility).await;
+        rs.map_err(|e| e.into())
+    }
+
+    /// Bring the CV back to the state of one of its revisions, only the author can do this
+    async fn restore_cv_revision(
+        &self,
+        ctx: &Context<'_>,
+        revision_id: ScalarObjectId,
+    ) -> GqlResult<CV> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let cv = CVRevisionService::restore_revision(db, user_id, revision_id.into()).await?;
+        if let Some(storage) = ctx.data_opt::<Storage>() {
+            CVTextService::spawn_extraction(db.clone(), storage.clone(), &cv);
+        }
+        Ok(cv)
+    }
+
+    /// Upload the file of a CV using the GraphQL multipart request spec,
+    /// only the author can do this. PDF, Word and plain text files are accepted.
+    /// The text of the file is extracted in the background for the CV search.
+    async fn upload_cv_file(
+        &self,
+        ctx: &Context<'_>,
+        cv_id: ScalarObjectId,
+        file: Upload,
+    ) -> GqlResult<CV> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let storage = ctx
+            .data_opt::<Storage>()
+            .ok_or(CVServiceError::StorageError)?;
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let file = file.value(ctx)?;
+        let content_type = file.content_type.clone();
+        let mut data = Vec::new();
+        file.into_read().read_to_end(&mut data)?;
+        let cv = CVFileService::upload_cv_file(
+            db,
+            storage.as_ref(),
+            user_id,
+            cv_id.into(),
+            content_type.as_deref(),
+            data,
+        )
+        .await?;
+        CVTextService::spawn_extraction(db.clone(), storage.clone(), &cv);
+        Ok(cv)
+    }
+
+    /// Render a PDF from the profile of the current user with the template and
+    /// attach it to the CV in place of its file.
+    async fn render_cv_pdf(
+        &self,
+        ctx: &Context<'_>,
+        cv_id: ScalarObjectId,
+        #[graphql(default)] template: CVTemplate,
+    ) -> GqlResult<CV> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let storage = ctx
+            .data_opt::<Storage>()
+            .ok_or(CVServiceError::StorageError)?;
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let cv =
+            CVRenderService::render_cv_pdf(db, storage.as_ref(), user_id, cv_id.into(), template)
+                .await?;
+        CVTextService::spawn_extraction(db.clone(), storage.clone(), &cv);
+        Ok(cv)
+    }
+
+    /// Upload a new avatar for the current user using the GraphQL multipart
+    /// request spec. PNG, JPEG, GIF and WebP images are accepted.
+    async fn upload_avatar(&self, ctx: &Context<'_>, file: Upload) -> GqlResult<User> {
+        upload_photo(ctx, PhotoKind::Avatar, file).await
+    }
+
+    /// Upload a new cover photo for the current user using the GraphQL
+    /// multipart request spec. PNG, JPEG, GIF and WebP images are accepted.
+    async fn upload_cover_photo(&self, ctx: &Context<'_>, file: Upload) -> GqlResult<User> {
+        upload_photo(ctx, PhotoKind::CoverPhoto, file).await
+    }
+
+    /// Import educations and experiences into the profile of the current
+    /// user from a JSON Resume file or a LinkedIn data export archive. Nothing
+    /// is saved unless `confirm` is true, so the returned diff can be shown as
+    /// a preview and the same file sent again to apply it.
+    async fn import_profile(
+        &self,
+        ctx: &Context<'_>,
+        file: Upload,
+        format: ProfileImportFormat,
+        #[graphql(default)] mode: ProfileImportMode,
+        #[graphql(default)] confirm: bool,
+    ) -> GqlResult<ProfileImport> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let mut data = Vec::new();
+        file.value(ctx)?.into_read().read_to_end(&mut data)?;
+        let rs =
+            ProfileImportService::import_profile(db, user_id, format, mode, &data, confirm).await;
+        rs.map_err(|e| e.extend())
+    }
+
     async fn change_cv_description(
         &self,
         ctx: &Context<'_>,
         cv_id: ScalarObjectId,
         description: String,
     ) -> GqlResult<CV> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CVService::change_description(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
+            user_id,
             cv_id.into(),
             description,
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(cv) => Ok(cv),
             Err(e) => Err(e.into()),
@@ -182,14 +463,18 @@ impl Mutation {
         cv_id: ScalarObjectId,
         tag: String,
     ) -> GqlResult<CV> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CVService::add_tag(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
+            user_id,
             cv_id.into(),
             tag,
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(cv) => Ok(cv),
             Err(e) => Err(e.into()),
@@ -202,79 +487,97 @@ impl Mutation {
         cv_id: ScalarObjectId,
         tag: String,
     ) -> GqlResult<CV> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CVService::remove_tag(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
+            user_id,
             cv_id.into(),
             tag,
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(cv) => Ok(cv),
             Err(e) => Err(e.into()),
         }
     }
 
+    /// Comment on a CV as the logged in user.
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_minute(10))")]
     async fn add_comment_to_cv(
         &self,
         ctx: &Context<'_>,
         cv_id: ScalarObjectId,
-        author_id: ScalarObjectId,
         content: String,
     ) -> GqlResult<CV> {
-        let rs = CVService::add_comment(
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let rs = CommentService::add_comment_to_cv(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
             cv_id.into(),
-            author_id.into(),
+            user_id,
             content,
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(cv) => Ok(cv),
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e.extend()),
         }
     }
 
+    /// Delete a comment of the CV. Only its author or a moderator can.
     async fn remove_comment_from_cv(
         &self,
         ctx: &Context<'_>,
         cv_id: ScalarObjectId,
         comment_id: ScalarObjectId,
     ) -> GqlResult<CV> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CVService::remove_comment(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
+            user_id,
             cv_id.into(),
             comment_id.into(),
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(cv) => Ok(cv),
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e.extend()),
         }
     }
 
+    /// Edit a comment. Only its author can.
     async fn update_content_comment(
         &self,
         ctx: &Context<'_>,
         comment_id: ScalarObjectId,
         content: String,
     ) -> GqlResult<Comment> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CommentService::update_content_comment(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
+            user_id,
             comment_id.into(),
             content,
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(comment) => Ok(comment),
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e.extend()),
         }
     }
 
@@ -282,16 +585,18 @@ impl Mutation {
         &self,
         ctx: &Context<'_>,
         comment_id: ScalarObjectId,
-        author_id: ScalarObjectId,
     ) -> GqlResult<bool> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CommentService::add_bookmark(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
-            author_id.into(),
+            user_id,
             comment_id.into(),
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(_) => Ok(true),
             Err(e) => Err(e.into()),
@@ -302,81 +607,96 @@ impl Mutation {
         &self,
         ctx: &Context<'_>,
         comment_id: ScalarObjectId,
-        author_id: ScalarObjectId,
     ) -> GqlResult<bool> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CommentService::remove_bookmark(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
-            author_id.into(),
+            user_id,
             comment_id.into(),
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(_) => Ok(true),
             Err(e) => Err(e.into()),
         }
     }
 
+    /// Reply to a comment as the logged in user.
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_minute(10))")]
     async fn add_reply_to_comment(
         &self,
         ctx: &Context<'_>,
         comment_id: ScalarObjectId,
-        author_id: ScalarObjectId,
         content: String,
     ) -> GqlResult<Comment> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CommentService::add_reply_comment(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
             comment_id.into(),
-            author_id.into(),
+            user_id,
             content,
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(comment) => Ok(comment),
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e.extend()),
         }
     }
 
+    /// Delete a reply, leaving a tombstone in its place. Only its author or
+    /// a moderator can.
     async fn remove_reply_from_comment(
         &self,
         ctx: &Context<'_>,
         comment_id: ScalarObjectId,
         reply_id: ScalarObjectId,
     ) -> GqlResult<Comment> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CommentService::remove_reply_comment(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
+            user_id,
             comment_id.into(),
             reply_id.into(),
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(comment) => Ok(comment),
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e.extend()),
         }
     }
 
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_minute(30))")]
     async fn like_comment(
         &self,
         ctx: &Context<'_>,
         comment_id: ScalarObjectId,
-        user_id: ScalarObjectId,
     ) -> GqlResult<bool> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CommentService::add_like_comment(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
-            user_id.into(),
+            user_id,
             comment_id.into(),
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(_) => Ok(true),
-            Err(e) => Err(e.into()),
+            Err(e) => Err(e.extend()),
         }
     }
 
@@ -384,103 +704,242 @@ impl Mutation {
         &self,
         ctx: &Context<'_>,
         comment_id: ScalarObjectId,
-        user_id: ScalarObjectId,
     ) -> GqlResult<bool> {
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
         let rs = CommentService::remove_like_comment(
             ctx.data_opt::<MongoDB>()
                 .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>()),
-            user_id.into(),
+            user_id,
             comment_id.into(),
         )
         .await;
-        authorization(ctx)?;
         match rs {
             Ok(_) => Ok(true),
             Err(e) => Err(e.into()),
         }
     }
 
-    async fn share_cv(
-        &self,
-        ctx: &Context<'_>,
-        user_id: ScalarObjectId,
-        cv_id: ScalarObjectId,
-    ) -> GqlResult<bool> {
+    async fn share_cv(&self, ctx: &Context<'_>, cv_id: ScalarObjectId) -> GqlResult<bool> {
         let db = ctx
             .data_opt::<MongoDB>()
             .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
         authorization(ctx)?;
-        let rs = ShareService::share_cv(db, user_id.into(), cv_id.into()).await;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let rs = ShareService::share_cv(db, user_id, cv_id.into()).await;
         rs.map_err(|e| e.into()).map(|_| true)
     }
 
-    async fn unshare_cv(
+    /// Create a link that gives anyone holding it read access to the CV.
+    /// `expires_in` is in seconds, omit it or `max_views` for no limit.
+    async fn create_cv_share_link(
         &self,
         ctx: &Context<'_>,
-        user_id: ScalarObjectId,
         cv_id: ScalarObjectId,
-    ) -> GqlResult<bool> {
+        expires_in: Option<i64>,
+        max_views: Option<u32>,
+    ) -> GqlResult<ShareLink> {
         let db = ctx
             .data_opt::<MongoDB>()
             .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
         authorization(ctx)?;
-        let rs = ShareService::unshare_cv(db, user_id.into(), cv_id.into()).await;
-        rs.map_err(|e| e.into()).map(|_| true)
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let rs = ShareLinkService::create_share_link(
+            db,
+            user_id,
+            cv_id.into(),
+            expires_in,
+            max_views,
+        )
+        .await;
+        rs.map_err(|e| e.into())
     }
 
-    async fn like_cv(
+    /// Revoke a share link so it can no longer be used.
+    async fn revoke_cv_share_link(
         &self,
         ctx: &Context<'_>,
-        user_id: ScalarObjectId,
-        cv_id: ScalarObjectId,
+        link_id: ScalarObjectId,
     ) -> GqlResult<bool> {
         let db = ctx
             .data_opt::<MongoDB>()
             .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
         authorization(ctx)?;
-        let rs = CVLikeService::like_cv(db, user_id.into(), cv_id.into()).await;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let rs = ShareLinkService::revoke_share_link(db, user_id, link_id.into()).await;
         rs.map_err(|e| e.into()).map(|_| true)
     }
 
-    async fn unlike_cv(
+    async fn unshare_cv(&self, ctx: &Context<'_>, cv_id: ScalarObjectId) -> GqlResult<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let rs = ShareService::unshare_cv(db, user_id, cv_id.into()).await;
+        rs.map_err(|e| e.into()).map(|_| true)
+    }
+
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_minute(30))")]
+    async fn like_cv(&self, ctx: &Context<'_>, cv_id: ScalarObjectId) -> GqlResult<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let rs = CVLikeService::like_cv(db, user_id, cv_id.into()).await;
+        rs.map_err(|e| e.into()).map(|_| true)
+    }
+
+    async fn unlike_cv(&self, ctx: &Context<'_>, cv_id: ScalarObjectId) -> GqlResult<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let rs = CVLikeService::unlike_cv(db, user_id, cv_id.into()).await;
+        rs.map_err(|e| e.into()).map(|_| true)
+    }
+
+    /// React to a CV or a comment, replacing the previous reaction of the
+    /// logged in user on it. Liking is reacting with `LIKE`.
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_minute(30))")]
+    async fn react(
         &self,
         ctx: &Context<'_>,
-        user_id: ScalarObjectId,
-        cv_id: ScalarObjectId,
-    ) -> GqlResult<bool> {
+        target_type: ReactionTarget,
+        target_id: ScalarObjectId,
+        kind: ReactionKind,
+    ) -> GqlResult<Reaction> {
         let db = ctx
             .data_opt::<MongoDB>()
             .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
         authorization(ctx)?;
-        let rs = CVLikeService::unlike_cv(db, user_id.into(), cv_id.into()).await;
-        rs.map_err(|e| e.into()).map(|_| true)
+        let viewer_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        ReactionService::react(db, viewer_id, target_type, target_id.into(), kind)
+            .await
+            .map_err(|e| e.extend())
     }
 
-    async fn bookmark_cv(
+    /// Remove the reaction of the logged in user on a CV or a comment, return
+    /// whether there was one.
+    async fn unreact(&self, ctx: &Context<'_>, target_id: ScalarObjectId) -> GqlResult<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let viewer_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        ReactionService::unreact(db, viewer_id, target_id.into(), None)
+            .await
+            .map(|reaction| reaction.is_some())
+            .map_err(|e| e.extend())
+    }
+
+    /// Mutations only available to admins.
+    #[graphql(guard = "RoleGuard::new(Role::Admin)")]
+    async fn admin(&self) -> AdminMutation {
+        AdminMutation
+    }
+
+    /// Report a CV, a comment or a user to the moderators.
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_hour(20))")]
+    async fn report_content(
         &self,
         ctx: &Context<'_>,
-        user_id: ScalarObjectId,
-        cv_id: ScalarObjectId,
-    ) -> GqlResult<bool> {
+        target_type: ReportTarget,
+        target_id: ScalarObjectId,
+        reason: String,
+    ) -> GqlResult<Report> {
         let db = ctx
             .data_opt::<MongoDB>()
             .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
         authorization(ctx)?;
-        let rs = BookmarkService::bookmark_cv(db, user_id.into(), cv_id.into()).await;
-        rs.map_err(|e| e.into()).map(|_| true)
+        let viewer_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        ModerationService::report_content(db, viewer_id, target_type, target_id.into(), reason)
+            .await
+            .map_err(|e| e.extend())
     }
 
-    async fn unbookmark_cv(
+    /// Act on a report as the logged in moderator, closing every open report
+    /// on the same target.
+    #[graphql(guard = "RoleGuard::new(Role::Moderator)")]
+    async fn resolve_report(
         &self,
         ctx: &Context<'_>,
-        user_id: ScalarObjectId,
-        cv_id: ScalarObjectId,
-    ) -> GqlResult<bool> {
+        report_id: ScalarObjectId,
+        action: ModerationAction,
+        note: Option<String>,
+    ) -> GqlResult<Report> {
         let db = ctx
             .data_opt::<MongoDB>()
             .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
         authorization(ctx)?;
-        let rs = BookmarkService::unbookmark_cv(db, user_id.into(), cv_id.into()).await;
+        let viewer_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        ModerationService::resolve_report(db, viewer_id, report_id.into(), action, note)
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    async fn bookmark_cv(&self, ctx: &Context<'_>, cv_id: ScalarObjectId) -> GqlResult<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let rs = BookmarkService::bookmark_cv(db, user_id, cv_id.into()).await;
         rs.map_err(|e| e.into()).map(|_| true)
     }
+
+    async fn unbookmark_cv(&self, ctx: &Context<'_>, cv_id: ScalarObjectId) -> GqlResult<bool> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let rs = BookmarkService::unbookmark_cv(db, user_id, cv_id.into()).await;
+        rs.map_err(|e| e.into()).map(|_| true)
+    }
+}
+
+async fn upload_photo(ctx: &Context<'_>, kind: PhotoKind, file: Upload) -> GqlResult<User> {
+    let db = ctx
+        .data_opt::<MongoDB>()
+        .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+    let storage = ctx
+        .data_opt::<Storage>()
+        .ok_or_else(|| UserServiceError::StorageError.extend())?;
+    authorization(ctx)?;
+    let user_id = viewer_id(ctx)
+        .await
+        .ok_or_else(|| ServerError::Unauthorized.extend())?;
+    let mut data = Vec::new();
+    file.value(ctx)?.into_read().read_to_end(&mut data)?;
+    let rs = PhotoService::upload_photo(db, storage.as_ref(), user_id, kind, data).await;
+    rs.map_err(|e| e.extend())
 }
@@ -0,0 +1,349 @@
+//! Persisted queries: clients send the sha256 hash of a query instead of the
+//! query itself, following Apollo's `persistedQuery` request extension.
+//!
+//! In automatic mode, a client sending an unknown hash gets a
+//! `PERSISTED_QUERY_NOT_FOUND` error and retries with the query and its
+//! hash, the query is then cached. In allow-list mode, only the queries of
+//! an allow-list are accepted, by hash or in full. The allow-list is
+//! generated from the documents of the client with the `persisted-queries`
+//! command when the client is built, and deployed along with the server.
+
+use std::{collections::HashMap, fmt, sync::Arc, sync::Mutex};
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest},
+    parser::{self, types::OperationType},
+    ErrorExtensions, Pos, Request, ServerResult, Value,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PersistedQueryError {
+    /// Error when the `persistedQuery` extension cannot be read, or is not
+    /// of version 1.
+    InvalidExtension,
+
+    /// Error when no query is known for the hash, the client should send the
+    /// query along with the hash.
+    NotFound,
+
+    /// Error when the hash is not the sha256 hash of the query.
+    HashMismatch,
+
+    /// Error when the query is not in the allow-list.
+    NotAllowed,
+}
+
+impl fmt::Display for PersistedQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PersistedQueryError::InvalidExtension => {
+                write!(
+                    f,
+                    "Invalid persistedQuery extension, only version 1 is supported"
+                )
+            }
+
+            // Apollo clients look for this exact message
+            PersistedQueryError::NotFound => {
+                write!(f, "PersistedQueryNotFound")
+            }
+
+            PersistedQueryError::HashMismatch => {
+                write!(f, "Provided sha256Hash does not match query")
+            }
+
+            PersistedQueryError::NotAllowed => {
+                write!(f, "Only persisted queries are allowed")
+            }
+        }
+    }
+}
+
+impl ErrorExtensions for PersistedQueryError {
+    fn extend(&self) -> async_graphql::Error {
+        let code = match self {
+            PersistedQueryError::InvalidExtension => "INVALID_PERSISTED_QUERY",
+            PersistedQueryError::NotFound => "PERSISTED_QUERY_NOT_FOUND",
+            PersistedQueryError::HashMismatch => "PERSISTED_QUERY_HASH_MISMATCH",
+            PersistedQueryError::NotAllowed => "PERSISTED_QUERY_NOT_ALLOWED",
+        };
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| e.set("code", code))
+    }
+}
+
+/// Return the hex encoded sha256 hash of the query.
+pub fn query_hash(query: &str) -> String {
+    hex::encode(Sha256::digest(query.as_bytes()))
+}
+
+/// An operation of an Apollo persisted query manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestOperation {
+    /// sha256 hash of `body`.
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub body: String,
+}
+
+/// Apollo persisted query manifest, the format of the allow-list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    pub operations: Vec<ManifestOperation>,
+}
+
+impl Manifest {
+    /// Build the manifest of the documents, each given as its source and
+    /// holding a single operation. Used by the `persisted-queries` command to
+    /// generate the allow-list.
+    pub fn from_documents(documents: &[String]) -> Result<Self, String> {
+        let mut operations = Vec::new();
+        for document in documents {
+            let parsed = parser::parse_query(document).map_err(|err| err.to_string())?;
+            let mut document_operations = parsed.operations.iter();
+            let (Some((name, operation)), None) =
+                (document_operations.next(), document_operations.next())
+            else {
+                return Err(format!("Expected a single operation in {:?}", document));
+            };
+            let kind = match operation.node.ty {
+                OperationType::Query => "query",
+                OperationType::Mutation => "mutation",
+                OperationType::Subscription => "subscription",
+            };
+            operations.push(ManifestOperation {
+                id: query_hash(document),
+                name: name.map(|name| name.to_string()).unwrap_or_default(),
+                kind: kind.to_string(),
+                body: document.clone(),
+            });
+        }
+        Ok(Self {
+            format: "apollo-persisted-query-manifest".to_string(),
+            version: 1,
+            operations,
+        })
+    }
+}
+
+/// Queries accepted in allow-list mode, by hash.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllowList(HashMap<String, String>);
+
+impl AllowList {
+    /// Read the allow-list from an Apollo persisted query manifest. The hash
+    /// of each operation is checked against its body.
+    pub fn from_manifest(manifest: &str) -> Result<Self, String> {
+        let manifest = serde_json::from_str::<Manifest>(manifest)
+            .map_err(|err| format!("Invalid persisted query manifest: {}", err))?;
+        let mut queries = HashMap::new();
+        for operation in manifest.operations {
+            if query_hash(&operation.body) != operation.id {
+                return Err(format!(
+                    "Persisted query {:?} does not match its hash",
+                    operation.name
+                ));
+            }
+            queries.insert(operation.id, operation.body);
+        }
+        Ok(Self(queries))
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let manifest = std::fs::read_to_string(path)
+            .map_err(|err| format!("Cannot read persisted queries {:?}: {}", path, err))?;
+        Self::from_manifest(&manifest)
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&String> {
+        self.0.get(hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Least recently used cache of the queries registered by clients, by hash.
+struct LruCache {
+    capacity: usize,
+    /// The query and when it was last used.
+    queries: HashMap<String, (String, u64)>,
+    clock: u64,
+}
+
+impl LruCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            queries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, hash: &str) -> Option<String> {
+        self.clock += 1;
+        let (query, used) = self.queries.get_mut(hash)?;
+        *used = self.clock;
+        Some(query.clone())
+    }
+
+    fn put(&mut self, hash: String, query: String) {
+        self.clock += 1;
+        if self.queries.len() >= self.capacity && !self.queries.contains_key(&hash) {
+            let oldest = self
+                .queries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(hash, _)| hash.clone());
+            if let Some(oldest) = oldest {
+                self.queries.remove(&oldest);
+            }
+        }
+        self.queries.insert(hash, (query, self.clock));
+    }
+}
+
+/// Which queries are accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PersistedQueryMode {
+    /// Persisted queries are not supported, the `persistedQuery` extension is
+    /// ignored.
+    Disabled,
+    /// Any query is accepted, and the last `cache_size` queries sent with
+    /// their hash can then be sent by hash only.
+    Automatic { cache_size: usize },
+    /// Only the queries of the allow-list are accepted.
+    AllowList(AllowList),
+}
+
+impl PersistedQueryMode {
+    /// Accept only the queries of the manifest at `path`. A missing or
+    /// invalid manifest is an error, strict mode is never turned off.
+    pub fn allow_list(path: &str) -> Result<Self, String> {
+        Ok(Self::AllowList(AllowList::from_file(path)?))
+    }
+}
+
+#[derive(Deserialize)]
+struct PersistedQuery {
+    version: i32,
+    #[serde(rename = "sha256Hash")]
+    sha256_hash: String,
+}
+
+/// Extension resolving persisted queries, see the module documentation.
+pub struct PersistedQueries {
+    mode: PersistedQueryMode,
+    cache: Arc<Mutex<LruCache>>,
+}
+
+impl PersistedQueries {
+    /// Return the extension for the mode, if any.
+    pub fn new(mode: PersistedQueryMode) -> Option<Self> {
+        let cache_size = match mode {
+            PersistedQueryMode::Disabled => return None,
+            PersistedQueryMode::Automatic { cache_size } => cache_size,
+            PersistedQueryMode::AllowList(_) => 0,
+        };
+        Some(Self {
+            mode,
+            cache: Arc::new(Mutex::new(LruCache::new(cache_size))),
+        })
+    }
+}
+
+impl ExtensionFactory for PersistedQueries {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(PersistedQueriesExtension {
+            mode: self.mode.clone(),
+            cache: self.cache.clone(),
+        })
+    }
+}
+
+struct PersistedQueriesExtension {
+    mode: PersistedQueryMode,
+    cache: Arc<Mutex<LruCache>>,
+}
+
+impl PersistedQueriesExtension {
+    /// Return the query to execute.
+    fn resolve(
+        &self,
+        query: String,
+        extension: Option<Value>,
+    ) -> Result<String, PersistedQueryError> {
+        let hash = match extension {
+            Some(value) => {
+                let persisted_query = async_graphql::from_value::<PersistedQuery>(value)
+                    .map_err(|_| PersistedQueryError::InvalidExtension)?;
+                if persisted_query.version != 1 {
+                    return Err(PersistedQueryError::InvalidExtension);
+                }
+                Some(persisted_query.sha256_hash)
+            }
+            None => None,
+        };
+        if let Some(hash) = &hash {
+            if !query.is_empty() && query_hash(&query) != *hash {
+                return Err(PersistedQueryError::HashMismatch);
+            }
+        }
+
+        match &self.mode {
+            PersistedQueryMode::Disabled => Ok(query),
+            PersistedQueryMode::Automatic { .. } => match hash {
+                Some(hash) if query.is_empty() => self
+                    .cache
+                    .lock()
+                    .unwrap()
+                    .get(&hash)
+                    .ok_or(PersistedQueryError::NotFound),
+                Some(hash) => {
+                    self.cache.lock().unwrap().put(hash, query.clone());
+                    Ok(query)
+                }
+                None => Ok(query),
+            },
+            PersistedQueryMode::AllowList(allow_list) => {
+                let hash = match hash {
+                    Some(hash) => hash,
+                    None => query_hash(&query),
+                };
+                match allow_list.get(&hash) {
+                    Some(query) => Ok(query.clone()),
+                    None if query.is_empty() => Err(PersistedQueryError::NotFound),
+                    None => Err(PersistedQueryError::NotAllowed),
+                }
+            }
+        }
+    }
+}
+
+#[async_graphql::async_trait::async_trait]
+impl Extension for PersistedQueriesExtension {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        mut request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        let extension = request.extensions.remove("persistedQuery");
+        let query = std::mem::take(&mut request.query);
+        request.query = self
+            .resolve(query, extension)
+            .map_err(|err| err.extend().into_server_error(Pos::default()))?;
+        next.run(ctx, request).await
+    }
+}
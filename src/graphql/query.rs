@@ -1,22 +1,106 @@
 use std::pin::Pin;
 
-use crate::models::cv::CV;
+use crate::models::comment::{Comment, CommentSort};
+use crate::models::conversation::{Conversation, Message};
+use crate::models::cv::{CVRevisionDiff, CV};
 use crate::models::cv_details::CVDetails;
+use crate::models::friend_request::FriendRequest;
+use crate::models::report::{ModerationDecision, Report, ReportStatus};
 use crate::object_id::ScalarObjectId;
+use crate::services::cv_service::comment_service::{thread_limit, CommentService};
 use crate::services::cv_service::cv_service::CVService;
+use crate::services::cv_service::revision_service::CVRevisionService;
+use crate::services::cv_service::share_link_service::ShareLinkService;
 use crate::{
     data_source::mongo::{MongoDB, MongoForTesting},
-    models::users::User,
-    services::{auth_service::AuthService, user_service::UserService},
+    error::ServerError,
+    filters::ClientIp,
+    models::users::{FriendSuggestion, Role, User},
+    services::{
+        auth_service::AuthService,
+        conversation_service::ConversationService,
+        moderation_service::ModerationService,
+        rate_limit_service::RateLimit,
+        user_service::{
+            block_service::BlockService, suggestion_service::FriendSuggestionService, UserService,
+        },
+    },
 };
 use async_graphql as gql;
 use async_graphql::{futures_util::StreamExt, Context, InputObject, Object};
 use gql::{connection, ErrorExtensions};
 
-use super::authorization;
+use super::{
+    admin::AdminQuery,
+    authorization, connection_complexity,
+    guard::{RateLimitGuard, RoleGuard},
+    DEFAULT_CONNECTION_PAGE,
+    viewer_id,
+};
 
 pub struct Query;
 
+type FriendRequestConnection = connection::Connection<
+    ScalarObjectId,
+    FriendRequest,
+    connection::EmptyFields,
+    connection::EmptyFields,
+>;
+
+/// Paginate friend requests, the cursor of a request is the id of the other
+/// user returned by `other`.
+async fn friend_requests_connection(
+    friend_requests: Vec<FriendRequest>,
+    other: fn(&FriendRequest) -> ScalarObjectId,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> gql::Result<FriendRequestConnection> {
+    connection::query(
+        after,
+        before,
+        first,
+        last,
+        |after, before, first, last| async move {
+            let friend_requests = if let Some(after) = after {
+                friend_requests
+                    .into_iter()
+                    .skip_while(|friend_request| other(friend_request) != after)
+                    .skip(1)
+                    .collect::<Vec<_>>()
+            } else if let Some(before) = before {
+                friend_requests
+                    .into_iter()
+                    .take_while(|friend_request| other(friend_request) != before)
+                    .collect::<Vec<_>>()
+            } else {
+                friend_requests
+            };
+            let friend_requests = if let Some(first) = first {
+                friend_requests
+                    .into_iter()
+                    .take(first)
+                    .collect::<Vec<_>>()
+            } else if let Some(last) = last {
+                let size = friend_requests.len();
+                friend_requests
+                    .into_iter()
+                    .skip(size.saturating_sub(last))
+                    .collect::<Vec<_>>()
+            } else {
+                friend_requests.into_iter().take(DEFAULT_CONNECTION_PAGE).collect::<Vec<_>>()
+            };
+            let mut connection = connection::Connection::new(true, false);
+            connection.edges.extend(friend_requests.into_iter().map(|friend_request| {
+                connection::Edge::new(other(&friend_request), friend_request)
+            }));
+            Ok::<_, async_graphql::Error>(connection)
+        },
+    )
+    .await
+}
+
 #[derive(InputObject)]
 struct LoginInfo {
     username: String,
@@ -33,13 +117,20 @@ struct LoginResult {
 impl Query {
     /// Login, access token can be used to access protected data,
     /// refresh token can be used to generate new access token
+    #[graphql(guard = "RateLimitGuard::new(RateLimit::per_minute(10))")]
     async fn login(&self, ctx: &Context<'_>, login_info: LoginInfo) -> gql::Result<LoginResult> {
         let db = ctx
             .data_opt::<MongoDB>()
             .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
-        let rs =
-            AuthService::authenticate(db, Some(login_info.username), None, login_info.password)
-                .await;
+        let client_ip = ctx.data_opt::<ClientIp>().and_then(|client_ip| client_ip.0);
+        let rs = AuthService::authenticate(
+            db,
+            Some(login_info.username),
+            None,
+            login_info.password,
+            client_ip,
+        )
+        .await;
         match rs {
             Ok(token) => Ok(LoginResult {
                 access_token: token.0,
@@ -74,7 +165,51 @@ impl Query {
         }
     }
 
+    /// Get a CV by id, if it is visible to the requesting user
+    async fn cv_by_id(&self, ctx: &Context<'_>, cv_id: ScalarObjectId) -> gql::Result<CV> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let viewer_id = viewer_id(ctx).await;
+        CVService::get_cv_by_id(db, viewer_id, cv_id.into())
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// Open a CV through a share link, no login required.
+    /// Each successful call counts as one view of the link.
+    async fn cv_by_share_token(&self, ctx: &Context<'_>, token: String) -> gql::Result<CV> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        ShareLinkService::get_cv_by_share_token(db, &token)
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// Compare the metadata of two revisions of the same CV
+    async fn cv_revision_diff(
+        &self,
+        ctx: &Context<'_>,
+        from_revision_id: ScalarObjectId,
+        to_revision_id: ScalarObjectId,
+    ) -> gql::Result<CVRevisionDiff> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let viewer_id = viewer_id(ctx).await;
+        CVRevisionService::diff_revisions(
+            db,
+            viewer_id,
+            from_revision_id.into(),
+            to_revision_id.into(),
+        )
+        .await
+        .map_err(|e| e.into())
+    }
+
     /// Get CVs using specified filter
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
     async fn cvs_list(
         &self,
         ctx: &Context<'_>,
@@ -95,7 +230,8 @@ impl Query {
             .data_opt::<MongoDB>()
             .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
         authorization(ctx)?;
-        let stream = CVService::find_suggested_cvs(db, filter).await?;
+        let viewer_id = viewer_id(ctx).await;
+        let stream = CVService::find_suggested_cvs(db, viewer_id, filter).await?;
         let stream = Pin::from(Box::new(stream)).collect::<Vec<_>>().await;
         connection::query(
             after,
@@ -145,6 +281,347 @@ impl Query {
         .await
     }
 
+    /// Pending friend requests sent to the logged in user, newest first
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
+    async fn incoming_friend_requests(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<FriendRequestConnection> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let friend_requests = UserService::incoming_friend_requests(db, user_id)
+            .await
+            .map_err(|e| e.extend())?;
+        friend_requests_connection(
+            friend_requests,
+            |friend_request| friend_request.id.from.into(),
+            after,
+            before,
+            first,
+            last,
+        )
+        .await
+    }
+
+    /// Pending friend requests sent by the logged in user, newest first
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
+    async fn outgoing_friend_requests(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<FriendRequestConnection> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let friend_requests = UserService::outgoing_friend_requests(db, user_id)
+            .await
+            .map_err(|e| e.extend())?;
+        friend_requests_connection(
+            friend_requests,
+            |friend_request| friend_request.id.to.into(),
+            after,
+            before,
+            first,
+            last,
+        )
+        .await
+    }
+
+    /// Users the logged in user may know, best suggestions first
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
+    async fn suggested_friends(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<
+        connection::Connection<
+            ScalarObjectId,
+            FriendSuggestion,
+            connection::EmptyFields,
+            connection::EmptyFields,
+        >,
+    > {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let suggestions = FriendSuggestionService::suggested_friends(db, user_id)
+            .await
+            .map_err(|e| e.extend())?;
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let suggestions = if let Some(after) = after {
+                    suggestions
+                        .into_iter()
+                        .skip_while(|suggestion| suggestion.user.id != after)
+                        .skip(1)
+                        .collect::<Vec<_>>()
+                } else if let Some(before) = before {
+                    suggestions
+                        .into_iter()
+                        .take_while(|suggestion| suggestion.user.id != before)
+                        .collect::<Vec<_>>()
+                } else {
+                    suggestions
+                };
+                let suggestions = if let Some(first) = first {
+                    suggestions.into_iter().take(first).collect::<Vec<_>>()
+                } else if let Some(last) = last {
+                    let size = suggestions.len();
+                    suggestions
+                        .into_iter()
+                        .skip(size.saturating_sub(last))
+                        .collect::<Vec<_>>()
+                } else {
+                    suggestions.into_iter().take(DEFAULT_CONNECTION_PAGE).collect::<Vec<_>>()
+                };
+                let mut connection = connection::Connection::new(true, false);
+                connection.edges.extend(
+                    suggestions
+                        .into_iter()
+                        .map(|suggestion| connection::Edge::new(suggestion.user.id, suggestion)),
+                );
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Users blocked by the logged in user, most recently blocked first
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
+    async fn blocked_users(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<
+        connection::Connection<
+            ScalarObjectId,
+            User,
+            connection::EmptyFields,
+            connection::EmptyFields,
+        >,
+    > {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let users = BlockService::blocked_users(db, user_id)
+            .await
+            .map_err(|e| e.extend())?;
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let users = if let Some(after) = after {
+                    users
+                        .into_iter()
+                        .skip_while(|user| user.id != after)
+                        .skip(1)
+                        .collect::<Vec<_>>()
+                } else if let Some(before) = before {
+                    users
+                        .into_iter()
+                        .take_while(|user| user.id != before)
+                        .collect::<Vec<_>>()
+                } else {
+                    users
+                };
+                let users = if let Some(first) = first {
+                    users.into_iter().take(first).collect::<Vec<_>>()
+                } else if let Some(last) = last {
+                    let size = users.len();
+                    users
+                        .into_iter()
+                        .skip(size.saturating_sub(last))
+                        .collect::<Vec<_>>()
+                } else {
+                    users.into_iter().take(DEFAULT_CONNECTION_PAGE).collect::<Vec<_>>()
+                };
+                let mut connection = connection::Connection::new(true, false);
+                connection
+                    .edges
+                    .extend(users.into_iter().map(|user| connection::Edge::new(user.id, user)));
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Conversations of the logged in user, most recently active first
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
+    async fn conversations(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<
+        connection::Connection<
+            ScalarObjectId,
+            Conversation,
+            connection::EmptyFields,
+            connection::EmptyFields,
+        >,
+    > {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let conversations = ConversationService::conversations(db, user_id)
+            .await
+            .map_err(|e| e.extend())?;
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let conversations = if let Some(after) = after {
+                    conversations
+                        .into_iter()
+                        .skip_while(|conversation| conversation.id != after)
+                        .skip(1)
+                        .collect::<Vec<_>>()
+                } else if let Some(before) = before {
+                    conversations
+                        .into_iter()
+                        .take_while(|conversation| conversation.id != before)
+                        .collect::<Vec<_>>()
+                } else {
+                    conversations
+                };
+                let conversations = if let Some(first) = first {
+                    conversations.into_iter().take(first).collect::<Vec<_>>()
+                } else if let Some(last) = last {
+                    let size = conversations.len();
+                    conversations
+                        .into_iter()
+                        .skip(size.saturating_sub(last))
+                        .collect::<Vec<_>>()
+                } else {
+                    conversations.into_iter().take(DEFAULT_CONNECTION_PAGE).collect::<Vec<_>>()
+                };
+                let mut connection = connection::Connection::new(true, false);
+                connection.edges.extend(
+                    conversations
+                        .into_iter()
+                        .map(|conversation| connection::Edge::new(conversation.id, conversation)),
+                );
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Messages of a conversation of the logged in user, newest first
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
+    async fn messages(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: ScalarObjectId,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<
+        connection::Connection<
+            ScalarObjectId,
+            Message,
+            connection::EmptyFields,
+            connection::EmptyFields,
+        >,
+    > {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let messages = ConversationService::messages(db, user_id, conversation_id.into())
+            .await
+            .map_err(|e| e.extend())?;
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let messages = if let Some(after) = after {
+                    messages
+                        .into_iter()
+                        .skip_while(|message| message.id != after)
+                        .skip(1)
+                        .collect::<Vec<_>>()
+                } else if let Some(before) = before {
+                    messages
+                        .into_iter()
+                        .take_while(|message| message.id != before)
+                        .collect::<Vec<_>>()
+                } else {
+                    messages
+                };
+                let messages = if let Some(first) = first {
+                    messages.into_iter().take(first).collect::<Vec<_>>()
+                } else if let Some(last) = last {
+                    let size = messages.len();
+                    messages
+                        .into_iter()
+                        .skip(size.saturating_sub(last))
+                        .collect::<Vec<_>>()
+                } else {
+                    messages.into_iter().take(DEFAULT_CONNECTION_PAGE).collect::<Vec<_>>()
+                };
+                let mut connection = connection::Connection::new(true, false);
+                connection.edges.extend(
+                    messages
+                        .into_iter()
+                        .map(|message| connection::Edge::new(message.id, message)),
+                );
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
     async fn get_comment_by_id(
         &self,
         ctx: &Context<'_>,
@@ -159,4 +636,127 @@ impl Query {
             Err(e) => Err(e.extend()),
         }
     }
+
+    /// The thread the comment belongs to: the comment on the CV first, then
+    /// every reply right after the comment it replies to, replies to the same
+    /// comment in `sort` order. Use `parentId` and `depth` to nest them.
+    /// Only the oldest `limit` comments of the thread are returned, 100 by
+    /// default and 500 at most.
+    #[graphql(complexity = "child_complexity.saturating_mul(thread_limit(limit))")]
+    async fn comment_thread(
+        &self,
+        ctx: &Context<'_>,
+        comment_id: ScalarObjectId,
+        #[graphql(default)] sort: CommentSort,
+        limit: Option<i32>,
+    ) -> gql::Result<Vec<Comment>> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let viewer_id = viewer_id(ctx).await;
+        CommentService::get_thread(db, viewer_id, comment_id.into(), sort, thread_limit(limit))
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    /// Queries only available to admins.
+    #[graphql(guard = "RoleGuard::new(Role::Admin)")]
+    async fn admin(&self) -> AdminQuery {
+        AdminQuery
+    }
+
+    /// Reports for the logged in moderator to review, oldest first, only
+    /// those with `status` if given.
+    #[graphql(
+        guard = "RoleGuard::new(Role::Moderator)",
+        complexity = "connection_complexity(child_complexity, first, last)"
+    )]
+    async fn moderation_queue(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<ReportStatus>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<
+        connection::Connection<
+            ScalarObjectId,
+            Report,
+            connection::EmptyFields,
+            connection::EmptyFields,
+        >,
+    > {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        let reports = ModerationService::moderation_queue(db, user_id, status)
+            .await
+            .map_err(|e| e.extend())?;
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let reports = if let Some(after) = after {
+                    reports
+                        .into_iter()
+                        .skip_while(|report| report.id != after)
+                        .skip(1)
+                        .collect::<Vec<_>>()
+                } else if let Some(before) = before {
+                    reports
+                        .into_iter()
+                        .take_while(|report| report.id != before)
+                        .collect::<Vec<_>>()
+                } else {
+                    reports
+                };
+                let reports = if let Some(first) = first {
+                    reports.into_iter().take(first).collect::<Vec<_>>()
+                } else if let Some(last) = last {
+                    let size = reports.len();
+                    reports
+                        .into_iter()
+                        .skip(size.saturating_sub(last))
+                        .collect::<Vec<_>>()
+                } else {
+                    reports.into_iter().take(DEFAULT_CONNECTION_PAGE).collect::<Vec<_>>()
+                };
+                let mut connection = connection::Connection::new(true, false);
+                connection.edges.extend(
+                    reports
+                        .into_iter()
+                        .map(|report| connection::Edge::new(report.id, report)),
+                );
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Decisions taken on reported content, newest first, only those on the
+    /// target if given. Only visible to moderators.
+    #[graphql(guard = "RoleGuard::new(Role::Moderator)")]
+    async fn moderation_log(
+        &self,
+        ctx: &Context<'_>,
+        target_id: Option<ScalarObjectId>,
+    ) -> gql::Result<Vec<ModerationDecision>> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        authorization(ctx)?;
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        ModerationService::decisions(db, user_id, target_id.map(|id| id.into()))
+            .await
+            .map_err(|e| e.extend())
+    }
 }
@@ -0,0 +1,98 @@
+//! Implement graphql-specific fields for Reaction
+
+use async_graphql as gql;
+use async_graphql::{connection, Context, ErrorExtensions};
+use mongodb::bson::oid::ObjectId;
+
+use crate::data_source::mongo::{MongoDB, MongoForTesting};
+use crate::models::reaction::{Reaction, ReactionKind};
+use crate::models::users::User;
+use crate::object_id::ScalarObjectId;
+use crate::services::reaction_service::ReactionService;
+use crate::services::user_service::UserService;
+
+use super::DEFAULT_CONNECTION_PAGE;
+
+#[async_graphql::ComplexObject]
+impl Reaction {
+    async fn user(&self, ctx: &Context<'_>) -> gql::Result<User> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        UserService::get_user_by_id(db, self.user_id())
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    #[graphql(name = "targetId")]
+    async fn graphql_target_id(&self) -> ScalarObjectId {
+        self.target_id().into()
+    }
+}
+
+/// Reactions on a CV or a comment, newest first, only those of `kind` if
+/// given. The cursor is the id of the user who reacted.
+pub(crate) async fn reactors(
+    ctx: &Context<'_>,
+    target_id: ObjectId,
+    kind: Option<ReactionKind>,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> gql::Result<
+    connection::Connection<
+        ScalarObjectId,
+        Reaction,
+        connection::EmptyFields,
+        connection::EmptyFields,
+    >,
+> {
+    let db = ctx
+        .data_opt::<MongoDB>()
+        .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+    let reactions = ReactionService::reactions(db, target_id, kind)
+        .await
+        .map_err(|e| e.extend())?;
+    connection::query(
+        after,
+        before,
+        first,
+        last,
+        |after: Option<ScalarObjectId>, before: Option<ScalarObjectId>, first, last| async move {
+            let reactions = if let Some(after) = after {
+                reactions
+                    .into_iter()
+                    .skip_while(|reaction| reaction.user_id() != *after)
+                    .skip(1)
+                    .collect::<Vec<_>>()
+            } else if let Some(before) = before {
+                reactions
+                    .into_iter()
+                    .take_while(|reaction| reaction.user_id() != *before)
+                    .collect::<Vec<_>>()
+            } else {
+                reactions
+            };
+            let reactions = if let Some(first) = first {
+                reactions.into_iter().take(first).collect::<Vec<_>>()
+            } else if let Some(last) = last {
+                let size = reactions.len();
+                reactions
+                    .into_iter()
+                    .skip(size.saturating_sub(last))
+                    .collect::<Vec<_>>()
+            } else {
+                reactions.into_iter().take(DEFAULT_CONNECTION_PAGE).collect::<Vec<_>>()
+            };
+            let mut connection = connection::Connection::new(true, false);
+            connection.edges.extend(
+                reactions
+                    .into_iter()
+                    .map(|reaction| connection::Edge::new(reaction.user_id().into(), reaction)),
+            );
+            Ok::<_, async_graphql::Error>(connection)
+        },
+    )
+    .await
+}
@@ -0,0 +1,308 @@
+use async_graphql::{value, EmptySubscription, Request, Schema};
+
+use super::{
+    config::{Environment, GraphQLConfig},
+    connection_complexity,
+    mutation::Mutation,
+    persisted_queries::{query_hash, AllowList, Manifest, PersistedQueryMode},
+    query::Query,
+};
+use crate::{
+    filters::{enabled, with_body_limit, BodyTooLarge, LengthRequired},
+    services::cv_service::comment_service::{
+        thread_limit, DEFAULT_THREAD_LIMIT, MAX_THREAD_LIMIT,
+    },
+};
+
+fn schema(config: &GraphQLConfig) -> Schema<Query, Mutation, EmptySubscription> {
+    config
+        .apply(Schema::build(Query, Mutation, EmptySubscription))
+        .finish()
+}
+
+/// Users with `friends` nested `depth` times, through the `admin` namespace
+/// whose guard refuses anonymous requests before anything is resolved.
+fn nested_friends(depth: usize, first: i32) -> String {
+    let mut selection = "id".to_string();
+    for _ in 0..depth {
+        selection = format!(
+            "friends(first: {}) {{ edges {{ node {{ {} }} }} }}",
+            first, selection
+        );
+    }
+    format!(
+        "{{ admin {{ users(first: {}) {{ edges {{ node {{ {} }} }} }} }} }}",
+        first, selection
+    )
+}
+
+async fn error_messages(
+    schema: &Schema<Query, Mutation, EmptySubscription>,
+    query: String,
+) -> Vec<String> {
+    // anonymous request
+    let request = Request::new(query).data(None::<String>);
+    schema
+        .execute(request)
+        .await
+        .errors
+        .into_iter()
+        .map(|error| error.message)
+        .collect()
+}
+
+#[test]
+fn connections_cost_their_page_size() {
+    assert_eq!(30, connection_complexity(3, Some(10), None));
+    assert_eq!(15, connection_complexity(3, None, Some(5)));
+    assert_eq!(60, connection_complexity(3, None, None));
+    assert_eq!(3, connection_complexity(3, Some(-1), None));
+    assert_eq!(usize::MAX, connection_complexity(usize::MAX, Some(2), None));
+}
+
+#[tokio::test]
+async fn deep_queries_are_refused() {
+    let config = GraphQLConfig {
+        max_depth: 8,
+        ..GraphQLConfig::default()
+    };
+    let schema = schema(&config);
+    let errors = error_messages(&schema, nested_friends(2, 1)).await;
+    assert_eq!(vec!["Query is nested too deep.".to_string()], errors);
+}
+
+#[tokio::test]
+async fn complex_queries_are_refused() {
+    let config = GraphQLConfig {
+        max_complexity: 1000,
+        ..GraphQLConfig::default()
+    };
+    let schema = schema(&config);
+    // within the limits, the query fails later for lack of a token
+    let errors = error_messages(&schema, nested_friends(1, 10)).await;
+    assert_eq!(vec!["Unauthorized".to_string()], errors);
+    let errors = error_messages(&schema, nested_friends(1, 100)).await;
+    assert_eq!(vec!["Query is too complex.".to_string()], errors);
+}
+
+#[tokio::test]
+async fn introspection_is_disabled_in_production() {
+    let query = "{ __schema { queryType { name } } }";
+    let development = schema(&GraphQLConfig::default());
+    let data = development.execute(query).await.data.into_json().unwrap();
+    assert_eq!("Query", data["__schema"]["queryType"]["name"]);
+
+    let production = schema(&GraphQLConfig {
+        environment: Environment::Production,
+        ..GraphQLConfig::default()
+    });
+    let data = production.execute(query).await.data.into_json().unwrap();
+    assert!(data["__schema"].is_null());
+}
+
+#[tokio::test]
+async fn large_bodies_are_refused() {
+    let filter = with_body_limit(10);
+    warp::test::request()
+        .header("content-length", "10")
+        .filter(&filter)
+        .await
+        .unwrap();
+    let rejection = warp::test::request()
+        .header("content-length", "11")
+        .filter(&filter)
+        .await
+        .unwrap_err();
+    assert!(matches!(rejection.find(), Some(BodyTooLarge(10))));
+    // uploads can be as large as a file on top of the limit
+    warp::test::request()
+        .header("content-length", "11")
+        .header("content-type", "multipart/form-data; boundary=x")
+        .filter(&filter)
+        .await
+        .unwrap();
+    // a chunked body could be of any size
+    let rejection = warp::test::request()
+        .method("POST")
+        .header("transfer-encoding", "chunked")
+        .filter(&filter)
+        .await
+        .unwrap_err();
+    assert!(matches!(rejection.find(), Some(LengthRequired)));
+    // GET requests have no body
+    warp::test::request().filter(&filter).await.unwrap();
+}
+
+#[tokio::test]
+async fn comment_threads_cost_their_limit() {
+    let config = GraphQLConfig {
+        max_complexity: 1000,
+        ..GraphQLConfig::default()
+    };
+    let schema = schema(&config);
+    let thread = |limit: i32| {
+        format!(
+            "{{ commentThread(commentId: \"{}\", limit: {}) {{ id content depth }} }}",
+            mongodb::bson::oid::ObjectId::new(),
+            limit
+        )
+    };
+    let errors = error_messages(&schema, thread(500)).await;
+    assert_eq!(vec!["Query is too complex.".to_string()], errors);
+    // larger limits cost no more than the largest one
+    assert_eq!(MAX_THREAD_LIMIT, thread_limit(Some(i32::MAX)));
+    assert_eq!(DEFAULT_THREAD_LIMIT, thread_limit(None));
+}
+
+#[tokio::test]
+async fn disabled_routes_are_not_found() {
+    assert!(warp::test::request().filter(&enabled(true)).await.is_ok());
+    let rejection = warp::test::request()
+        .filter(&enabled(false))
+        .await
+        .unwrap_err();
+    assert!(rejection.is_not_found());
+}
+
+/// Execute `query`, sent along with `hash` as a persisted query if given,
+/// return the error codes or the data.
+async fn persisted_query(
+    schema: &Schema<Query, Mutation, EmptySubscription>,
+    query: &str,
+    hash: Option<String>,
+) -> Result<serde_json::Value, Vec<String>> {
+    let mut request = Request::new(query);
+    if let Some(hash) = hash {
+        request.extensions.insert(
+            "persistedQuery".to_string(),
+            value!({"version": 1, "sha256Hash": hash}),
+        );
+    }
+    let response = schema.execute(request).await;
+    if response.errors.is_empty() {
+        Ok(response.data.into_json().unwrap())
+    } else {
+        Err(response
+            .errors
+            .into_iter()
+            .map(|error| {
+                let error = serde_json::to_value(error).unwrap();
+                error["extensions"]["code"].as_str().unwrap().to_string()
+            })
+            .collect())
+    }
+}
+
+#[tokio::test]
+async fn automatic_persisted_queries() {
+    let schema = schema(&GraphQLConfig {
+        persisted_queries: PersistedQueryMode::Automatic { cache_size: 1 },
+        ..GraphQLConfig::default()
+    });
+    let query = "{ __typename }";
+    let hash = query_hash(query);
+
+    assert_eq!(
+        Err(vec!["PERSISTED_QUERY_NOT_FOUND".to_string()]),
+        persisted_query(&schema, "", Some(hash.clone())).await
+    );
+    assert_eq!(
+        Err(vec!["PERSISTED_QUERY_HASH_MISMATCH".to_string()]),
+        persisted_query(&schema, query, Some(query_hash("{ other }"))).await
+    );
+    let data = serde_json::json!({"__typename": "Query"});
+    assert_eq!(
+        Ok(data.clone()),
+        persisted_query(&schema, query, Some(hash.clone())).await
+    );
+    assert_eq!(
+        Ok(data.clone()),
+        persisted_query(&schema, "", Some(hash.clone())).await
+    );
+    // plain queries are still accepted
+    assert_eq!(Ok(data), persisted_query(&schema, query, None).await);
+
+    // the least recently used query is evicted
+    let other = "query Other { __typename }";
+    persisted_query(&schema, other, Some(query_hash(other)))
+        .await
+        .unwrap();
+    assert!(persisted_query(&schema, "", Some(hash)).await.is_err());
+}
+
+#[tokio::test]
+async fn allow_listed_queries_only() {
+    let query = "query Typename { __typename }".to_string();
+    let manifest = Manifest::from_documents(std::slice::from_ref(&query)).unwrap();
+    assert_eq!("Typename", manifest.operations[0].name);
+    let manifest = serde_json::to_string(&manifest).unwrap();
+    let allow_list = AllowList::from_manifest(&manifest).unwrap();
+    assert_eq!(1, allow_list.len());
+    let schema = schema(&GraphQLConfig {
+        persisted_queries: PersistedQueryMode::AllowList(allow_list),
+        ..GraphQLConfig::default()
+    });
+
+    let data = serde_json::json!({"__typename": "Query"});
+    assert_eq!(
+        Ok(data.clone()),
+        persisted_query(&schema, "", Some(query_hash(&query))).await
+    );
+    assert_eq!(Ok(data), persisted_query(&schema, &query, None).await);
+    assert_eq!(
+        Err(vec!["PERSISTED_QUERY_NOT_ALLOWED".to_string()]),
+        persisted_query(&schema, "{ __typename }", None).await
+    );
+    let other = "{ __typename }";
+    assert_eq!(
+        Err(vec!["PERSISTED_QUERY_NOT_FOUND".to_string()]),
+        persisted_query(&schema, "", Some(query_hash(other))).await
+    );
+
+    // the manifest cannot be edited without updating the hashes
+    let tampered = manifest.replace("__typename", "userDetail { id }");
+    assert!(AllowList::from_manifest(&tampered).is_err());
+}
+
+#[test]
+fn allow_list_is_read_from_its_file() {
+    let path = std::env::temp_dir().join(format!("seevi-queries-{}", mongodb::bson::Uuid::new()));
+    let path = path.to_str().unwrap();
+    // a missing manifest does not turn the allow-list off
+    assert!(PersistedQueryMode::allow_list(path).is_err());
+
+    let query = "query Typename { __typename }".to_string();
+    let manifest = Manifest::from_documents(std::slice::from_ref(&query)).unwrap();
+    std::fs::write(path, serde_json::to_string(&manifest).unwrap()).unwrap();
+    let mode = PersistedQueryMode::allow_list(path);
+    std::fs::write(path, "invalid").unwrap();
+    let invalid = PersistedQueryMode::allow_list(path);
+    std::fs::remove_file(path).unwrap();
+    assert!(matches!(mode, Ok(PersistedQueryMode::AllowList(allow_list)) if allow_list.len() == 1));
+    // an invalid manifest is still an error
+    assert!(invalid.is_err());
+}
+
+#[tokio::test]
+async fn users_are_only_acted_for_with_their_token() {
+    let schema = schema(&GraphQLConfig::default());
+    let id = mongodb::bson::oid::ObjectId::new();
+    // without a token, a suspended user gets nothing done, and nothing is
+    // written before the token is checked
+    let mutations = [
+        format!("addCommentToCv(cvId: \"{}\", content: \"hi\") {{ id }}", id),
+        format!("addReplyToComment(commentId: \"{}\", content: \"hi\") {{ id }}", id),
+        format!("likeComment(commentId: \"{}\")", id),
+    ];
+    for mutation in &mutations {
+        let errors = error_messages(&schema, format!("mutation {{ {} }}", mutation)).await;
+        assert_eq!(vec!["Unauthorized".to_string()], errors);
+    }
+    // nor can they name the user to act for
+    let mutation = format!(
+        "mutation {{ addCommentToCv(cvId: \"{}\", authorId: \"{}\", content: \"hi\") {{ id }} }}",
+        id, id
+    );
+    let errors = error_messages(&schema, mutation).await;
+    assert!(errors[0].contains("Unknown argument \"authorId\""), "{:?}", errors);
+}
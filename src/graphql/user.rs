@@ -1,19 +1,72 @@
 //! Implement graphql-specific field for User
 
 use async_graphql as gql;
-use async_graphql::{connection, Context};
+use async_graphql::{connection, Context, ErrorExtensions, Json};
 use gql::futures_util::StreamExt;
 
 use crate::data_source::mongo::{MongoDB, MongoForTesting};
 use crate::models::cv::CV;
-use crate::models::users::User;
+use crate::common::DateTime;
+use crate::models::users::{JsonResume, PhotoSize, Role, User};
 use crate::object_id::ScalarObjectId;
 use crate::services::cv_service::bookmark_service::BookmarkService;
 use crate::services::cv_service::cv_service::CVService;
+use crate::services::storage_service::backend::Storage;
+use crate::services::user_service::json_resume_service::JsonResumeService;
+use crate::services::user_service::photo_service::{PhotoKind, PhotoService};
 use crate::services::user_service::UserService;
 
+use super::{connection_complexity, guard::RoleGuard, viewer_id};
+
 #[async_graphql::ComplexObject]
 impl User {
+    /// Until when the user is suspended, `null` if they never were. Only
+    /// visible to moderators.
+    #[graphql(guard = "RoleGuard::new(Role::Moderator)")]
+    async fn suspended_until(&self) -> Option<DateTime> {
+        self.suspended_until
+    }
+
+    /// A short-lived url to download the avatar in the requested size, `null`
+    /// if no avatar has been uploaded or photo storage is not available.
+    async fn avatar_url(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "PhotoSize::Medium")] size: PhotoSize,
+    ) -> gql::Result<Option<String>> {
+        self.photo_url(ctx, PhotoKind::Avatar, size).await
+    }
+
+    /// A short-lived url to download the cover photo in the requested size,
+    /// `null` if no cover photo has been uploaded or photo storage is not available.
+    async fn cover_photo_url(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "PhotoSize::Large")] size: PhotoSize,
+    ) -> gql::Result<Option<String>> {
+        self.photo_url(ctx, PhotoKind::CoverPhoto, size).await
+    }
+
+    /// The profile in the JSON Resume format, see https://jsonresume.org/schema.
+    /// The metadata of the CV `cvId` is included if provided, it must be a
+    /// CV of this user that the viewer is allowed to see. Contact details are
+    /// only included for the user and their friends.
+    async fn as_json_resume(
+        &self,
+        ctx: &Context<'_>,
+        cv_id: Option<ScalarObjectId>,
+    ) -> gql::Result<Json<JsonResume>> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let viewer_id = viewer_id(ctx).await;
+        JsonResumeService::export(db, viewer_id, self.id.into(), cv_id.map(|id| id.into()))
+            .await
+            .map(Json)
+            .map_err(|e| e.extend())
+    }
+
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
     async fn friends(
         &self,
         ctx: &Context<'_>,
@@ -32,7 +85,8 @@ impl User {
         let db = ctx
             .data_opt::<MongoDB>()
             .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
-        let friends_list = UserService::friend_lists(db, self.id.into())
+        let viewer_id = viewer_id(ctx).await;
+        let friends_list = UserService::friends_visible_to(db, viewer_id, self.id.into())
             .await
             .collect::<Vec<_>>()
             .await;
@@ -84,6 +138,7 @@ impl User {
         .await
     }
 
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
     async fn cvs(
         &self,
         ctx: &Context<'_>,
@@ -102,7 +157,8 @@ impl User {
         let db = ctx
             .data_opt::<MongoDB>()
             .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
-        let cvs = CVService::get_cvs_by_user_id(db, self.id.into())
+        let viewer_id = viewer_id(ctx).await;
+        let cvs = CVService::get_cvs_by_user_id(db, viewer_id, self.id.into())
             .await?
             .collect::<Vec<_>>()
             .await;
@@ -144,6 +200,7 @@ impl User {
         .await
     }
 
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
     async fn bookmarked_cvs(
         &self,
         ctx: &Context<'_>,
@@ -209,3 +266,20 @@ impl User {
         .await
     }
 }
+
+impl User {
+    async fn photo_url(
+        &self,
+        ctx: &Context<'_>,
+        kind: PhotoKind,
+        size: PhotoSize,
+    ) -> gql::Result<Option<String>> {
+        let storage = match ctx.data_opt::<Storage>() {
+            Some(storage) => storage,
+            None => return Ok(None),
+        };
+        PhotoService::get_photo_url(storage.as_ref(), self, kind, size)
+            .await
+            .map_err(|e| e.extend())
+    }
+}
@@ -3,13 +3,29 @@ extern crate derive_builder;
 
 use std::convert::Infallible;
 
-use async_graphql::{EmptySubscription, Schema};
+use async_graphql::{http::MultipartOptions, EmptySubscription, Schema};
 use async_graphql_warp::{GraphQLBadRequest, GraphQLResponse};
 use data_source::mongo::{self, MongoDB, MongoForTesting};
-use filters::{graphql_sdl, with_auth_header};
-use graphql::{mutation::Mutation, query::Query};
+use filters::{
+    enabled, graphql_sdl, healthz, json_resume, local_files, rate_limited_reply, readyz, version,
+    with_body_limit, with_rate_limit, BodyTooLarge, LengthRequired,
+};
+use graphql::{
+    account_status::AccountStatusCheck, config::GraphQLConfig, mutation::Mutation, query::Query,
+};
 use mock_data::populate_mocked_data;
-use warp::{hyper::StatusCode, Filter, Rejection};
+use models::{sex::Sex, users::CreateUserInput};
+use services::{
+    admin_service::{error::AdminServiceError, AdminService},
+    cv_service::file_service::MAX_CV_FILE_SIZE,
+    rate_limit_service::{
+        backend::RateLimiter, default_rate_limit, error::RateLimitError,
+        memory_store::MemoryRateLimitStore,
+    },
+    storage_service::{backend::Storage, config::StorageConfig, memory_storage::MemoryStorage},
+};
+use std::sync::Arc;
+use warp::{hyper::StatusCode, Filter, Rejection, Reply};
 
 pub mod data_source;
 pub mod error;
@@ -31,94 +47,231 @@ pub async fn run_server_for_test() {
 
     let mongo_ds = MongoDB::init_with_database_name("seevi_test").await;
     populate_mocked_data(mongo_ds.clone()).await;
+    let resume_db = mongo_ds.clone();
+    let health_db = mongo_ds.clone();
 
     let mongo_ds = MongoForTesting::from(mongo_ds);
+    let storage: Storage = Arc::new(MemoryStorage::new());
+    let health_storage = Some(storage.clone());
+    let limiter: RateLimiter = Arc::new(MemoryRateLimitStore::new());
+    let config = GraphQLConfig::from_env().expect("Invalid GraphQL configuration");
 
-    let schema = Schema::build(Query, Mutation, EmptySubscription)
+    let schema = config
+        .apply(Schema::build(Query, Mutation, EmptySubscription))
         .data(mongo_ds)
+        .data(storage)
+        .data(limiter.clone())
+        .extension(AccountStatusCheck)
         .finish();
+    let schema_sdl = schema.sdl();
 
-    let graphql_post = with_auth_header()
-        .and(async_graphql_warp::graphql(schema.clone()))
+    let graphql_post = with_body_limit(config.max_body_size)
+        .and(with_rate_limit(limiter, default_rate_limit()))
+        .and(async_graphql_warp::graphql_opts(
+            schema.clone(),
+            MultipartOptions::default().max_file_size(MAX_CV_FILE_SIZE),
+        ))
         .and_then(
             |header,
+             client_ip,
              (schema, request): (
                 Schema<Query, Mutation, EmptySubscription>,
                 async_graphql::Request,
             )| async move {
                 Ok::<_, Rejection>(GraphQLResponse::from(
-                    schema.execute(request.data(header)).await,
+                    schema.execute(request.data(header).data(client_ip)).await,
                 ))
             },
         );
     let logger = warp::log("seevi_backend");
 
     let routes = warp::path!("graphql" / "playground")
+        .and(enabled(!config.is_production()))
         .and(filters::graphql_playground())
         .with(logger)
-        .or(warp::path!("graphql" / "schema").and(graphql_sdl(schema)))
+        .or(warp::path!("graphql" / "schema")
+            .and(enabled(!config.is_production()))
+            .and(graphql_sdl(schema)))
         .with(logger)
         .or(warp::path!("graphql").and(graphql_post))
         .with(logger)
+        .or(local_files(None))
+        .with(logger)
+        .or(json_resume(resume_db))
+        .with(logger)
+        .or(healthz())
+        .or(readyz(health_db, health_storage))
+        .or(version(&schema_sdl))
         .recover(|err: Rejection| async move {
             if let Some(GraphQLBadRequest(err)) = err.find() {
-                return Ok::<_, Infallible>(warp::reply::with_status(
-                    err.to_string(),
-                    StatusCode::BAD_REQUEST,
-                ));
+                return Ok::<_, Infallible>(
+                    warp::reply::with_status(err.to_string(), StatusCode::BAD_REQUEST)
+                        .into_response(),
+                );
+            }
+            if let Some(err) = err.find::<RateLimitError>() {
+                return Ok(rate_limited_reply(err));
+            }
+            if let Some(BodyTooLarge(limit)) = err.find() {
+                return Ok(warp::reply::with_status(
+                    format!("Request body is larger than {} bytes", limit),
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                )
+                .into_response());
+            }
+            if let Some(LengthRequired) = err.find() {
+                return Ok(warp::reply::with_status(
+                    "Content-Length is required".to_string(),
+                    StatusCode::LENGTH_REQUIRED,
+                )
+                .into_response());
+            }
+            if err.is_not_found() {
+                return Ok(
+                    warp::reply::with_status("NOT_FOUND".to_string(), StatusCode::NOT_FOUND)
+                        .into_response(),
+                );
             }
 
             Ok(warp::reply::with_status(
                 "INTERNAL_SERVER_ERROR".to_string(),
                 StatusCode::INTERNAL_SERVER_ERROR,
-            ))
+            )
+            .into_response())
         });
     warp::serve(routes).run(([127, 0, 0, 1], 8000)).await;
 }
 
+/// Make the user `username` an admin, registering them with `email` and
+/// `password` if they do not exist yet. Used to bootstrap the first admin,
+/// who can then give roles to other users.
+pub async fn create_admin(
+    username: String,
+    email: String,
+    password: String,
+) -> Result<models::users::User, AdminServiceError> {
+    let mongo_ds = mongo::MongoDB::init().await;
+    let input = CreateUserInput::builder()
+        .with_username(username)
+        .with_password(password)
+        .with_primary_email(email)
+        .with_sex(Sex::Others)
+        .build()
+        .map_err(|e| AdminServiceError::CreateAdminFailed(e.to_string()))?;
+    AdminService::create_admin(&mongo_ds, input).await
+}
+
 pub async fn run_server() {
     pretty_env_logger::init();
 
     let mongo_ds = mongo::MongoDB::init().await;
+    if let Err(e) = mongo_ds.create_indexes().await {
+        log::warn!("Database indexes could not be created: {}", e);
+    }
+    if let Err(e) = mongo_ds.migrate_legacy_likes().await {
+        log::warn!("Likes could not be migrated to reactions: {}", e);
+    }
+    if let Err(e) = mongo_ds.migrate_comment_threads().await {
+        log::warn!("Comment threads could not be migrated: {}", e);
+    }
+    let limiter: RateLimiter = Arc::new(MemoryRateLimitStore::new());
+    let config = GraphQLConfig::from_env().expect("Invalid GraphQL configuration");
 
-    let schema = Schema::build(Query, Mutation, EmptySubscription)
-        .data(mongo_ds)
-        .finish();
+    let mut schema = config
+        .apply(Schema::build(Query, Mutation, EmptySubscription))
+        .data(mongo_ds.clone())
+        .data(limiter.clone())
+        .extension(AccountStatusCheck);
+    let storage = match StorageConfig::from_env() {
+        Ok(config) => config.init_storage().await,
+        Err(e) => Err(e),
+    };
+    let (health_storage, local_storage) = match storage {
+        Ok((storage, local_storage)) => {
+            schema = schema.data(storage.clone());
+            (Some(storage), local_storage)
+        }
+        Err(e) => {
+            log::warn!("File storage is not available: {}", e);
+            (None, None)
+        }
+    };
+    let schema = schema.finish();
+    let schema_sdl = schema.sdl();
 
-    let graphql_post = with_auth_header()
-        .and(async_graphql_warp::graphql(schema.clone()))
+    let graphql_post = with_body_limit(config.max_body_size)
+        .and(with_rate_limit(limiter, default_rate_limit()))
+        .and(async_graphql_warp::graphql_opts(
+            schema.clone(),
+            MultipartOptions::default().max_file_size(MAX_CV_FILE_SIZE),
+        ))
         .and_then(
             |header,
+             client_ip,
              (schema, request): (
                 Schema<Query, Mutation, EmptySubscription>,
                 async_graphql::Request,
             )| async move {
                 Ok::<_, Rejection>(GraphQLResponse::from(
-                    schema.execute(request.data(header)).await,
+                    schema.execute(request.data(header).data(client_ip)).await,
                 ))
             },
         );
     let logger = warp::log("seevi_backend");
 
     let routes = warp::path!("graphql" / "playground")
+        .and(enabled(!config.is_production()))
         .and(filters::graphql_playground())
         .with(logger)
-        .or(warp::path!("graphql" / "schema").and(graphql_sdl(schema)))
+        .or(warp::path!("graphql" / "schema")
+            .and(enabled(!config.is_production()))
+            .and(graphql_sdl(schema)))
         .with(logger)
         .or(warp::path!("graphql").and(graphql_post))
         .with(logger)
+        .or(local_files(local_storage))
+        .with(logger)
+        .or(json_resume(mongo_ds.clone()))
+        .with(logger)
+        .or(healthz())
+        .or(readyz(mongo_ds, health_storage))
+        .or(version(&schema_sdl))
         .recover(|err: Rejection| async move {
             if let Some(GraphQLBadRequest(err)) = err.find() {
-                return Ok::<_, Infallible>(warp::reply::with_status(
-                    err.to_string(),
-                    StatusCode::BAD_REQUEST,
-                ));
+                return Ok::<_, Infallible>(
+                    warp::reply::with_status(err.to_string(), StatusCode::BAD_REQUEST)
+                        .into_response(),
+                );
+            }
+            if let Some(err) = err.find::<RateLimitError>() {
+                return Ok(rate_limited_reply(err));
+            }
+            if let Some(BodyTooLarge(limit)) = err.find() {
+                return Ok(warp::reply::with_status(
+                    format!("Request body is larger than {} bytes", limit),
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                )
+                .into_response());
+            }
+            if let Some(LengthRequired) = err.find() {
+                return Ok(warp::reply::with_status(
+                    "Content-Length is required".to_string(),
+                    StatusCode::LENGTH_REQUIRED,
+                )
+                .into_response());
+            }
+            if err.is_not_found() {
+                return Ok(
+                    warp::reply::with_status("NOT_FOUND".to_string(), StatusCode::NOT_FOUND)
+                        .into_response(),
+                );
             }
 
             Ok(warp::reply::with_status(
                 "INTERNAL_SERVER_ERROR".to_string(),
                 StatusCode::INTERNAL_SERVER_ERROR,
-            ))
+            )
+            .into_response())
         });
     warp::serve(routes).run(([127, 0, 0, 1], 8000)).await;
 }
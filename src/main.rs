@@ -1,14 +1,49 @@
 use std::env;
 
-use seevi_backend::{run_server, run_server_for_test};
+use seevi_backend::{
+    create_admin, graphql::persisted_queries::Manifest, run_server, run_server_for_test,
+};
 
 #[tokio::main]
 async fn main() {
     let args: Vec<String> = env::args().collect();
-    if args[1] == "--test" {
-        println!("Running server for test");
-        run_server_for_test().await;
-    } else {
-        run_server().await;
+    match args.get(1).map(String::as_str) {
+        Some("--test") => {
+            println!("Running server for test");
+            run_server_for_test().await;
+        }
+        Some("create-admin") => {
+            let (Some(username), Some(email)) = (args.get(2), args.get(3)) else {
+                eprintln!("Usage: {} create-admin <username> <email>", args[0]);
+                eprintln!("The password of new accounts is read from ADMIN_PASSWORD");
+                std::process::exit(2);
+            };
+            let password = env::var("ADMIN_PASSWORD").unwrap_or_default();
+            match create_admin(username.clone(), email.clone(), password).await {
+                Ok(user) => println!("{} is now an admin", user.username),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some("persisted-queries") => {
+            // print the allow-list of the given documents, one operation each
+            let documents = args[2..]
+                .iter()
+                .map(|path| {
+                    std::fs::read_to_string(path)
+                        .map_err(|e| format!("Cannot read {:?}: {}", path, e))
+                })
+                .collect::<Result<Vec<_>, _>>();
+            match documents.and_then(|documents| Manifest::from_documents(&documents)) {
+                Ok(manifest) => println!("{}", serde_json::to_string_pretty(&manifest).unwrap()),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => run_server().await,
     }
 }
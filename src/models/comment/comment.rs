@@ -8,12 +8,21 @@ use serde::{Deserialize, Serialize};
 use crate::common::DateTime;
 use crate::{
     data_source::mongo::{MongoDB, MongoForTesting},
+    error::ServerError,
+    graphql::{connection_complexity, reaction::reactors, viewer_id},
+    models::{
+        reaction::{Reaction, ReactionCount, ReactionKind},
+        users::User,
+    },
     object_id::ScalarObjectId,
-    services::cv_service::comment_service::CommentService,
+    services::{
+        cv_service::comment_service::CommentService, reaction_service::ReactionService,
+        user_service::UserService,
+    },
 };
 
 use super::create_comment_input::CreateCommentInput;
-use super::Like;
+use super::{CommentSort, CommentVersion, Like, Mention};
 
 #[derive(Debug, Serialize, Deserialize, Clone, SimpleObject, Builder)]
 #[graphql(complex)]
@@ -22,20 +31,68 @@ pub struct Comment {
     pub id: ScalarObjectId,
     pub author: ScalarObjectId,
 
+    #[graphql(skip)]
     pub content: String,
 
     pub created: DateTime,
 
     #[graphql(skip)]
     pub replies: Vec<ScalarObjectId>,
+
+    /// The comment this one replies to, `None` for comments on a CV.
+    #[serde(default)]
+    #[builder(default)]
+    pub parent_id: Option<ScalarObjectId>,
+
+    /// The comment on a CV that starts the thread, `None` for comments on a
+    /// CV.
+    #[serde(default)]
+    #[builder(default)]
+    pub root_id: Option<ScalarObjectId>,
+
+    /// How deep the comment is nested in its thread, 0 for comments on a CV.
+    #[serde(default)]
+    #[builder(default)]
+    pub depth: u32,
+
+    /// When the content was last edited, `None` if it never was.
+    #[serde(default)]
+    #[builder(default)]
+    pub edited_at: Option<DateTime>,
+
+    /// When the comment was deleted. Deleted comments keep their replies but
+    /// their content is cleared.
+    #[serde(default)]
+    #[builder(default)]
+    pub deleted_at: Option<DateTime>,
+
+    /// When the comment was hidden by moderation. The content of hidden
+    /// comments is not shown.
+    #[serde(default)]
+    #[builder(default)]
+    pub hidden_at: Option<DateTime>,
+
+    /// Users mentioned in the content, in order of appearance.
+    #[serde(default)]
+    #[builder(default)]
+    #[graphql(skip)]
+    pub mentions: Vec<Mention>,
+
+    /// Previous contents of the comment, oldest first.
+    #[serde(default)]
+    #[builder(default)]
+    #[graphql(skip)]
+    pub history: Vec<CommentVersion>,
 }
 
 #[ComplexObject]
 impl Comment {
     /// Get replies of this comment.
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
     async fn replies(
         &self,
         ctx: &Context<'_>,
+        #[graphql(default)] sort: CommentSort,
         after: Option<String>,
         before: Option<String>,
         first: Option<i32>,
@@ -55,7 +112,15 @@ impl Comment {
             Ok(comments_list) => comments_list,
             Err(e) => return Err(e.extend()),
         };
-        let comments_list = comments_list.collect::<Vec<_>>().await;
+        let comments_list = comments_list
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.extend())?;
+        let comments_list = CommentService::sort_comments(db, comments_list, sort)
+            .await
+            .map_err(|e| e.extend())?;
         connection::query(
             after,
             before,
@@ -65,14 +130,14 @@ impl Comment {
                 let comments_list = if let Some(after) = after {
                     comments_list
                         .into_iter()
-                        .skip_while(|comment| comment.as_ref().unwrap().id != after)
+                        .skip_while(|comment| comment.id != after)
                         .skip(1)
                         .map(|comment| comment)
                         .collect::<Vec<_>>()
                 } else if let Some(before) = before {
                     comments_list
                         .into_iter()
-                        .take_while(|comment| comment.as_ref().unwrap().id != before)
+                        .take_while(|comment| comment.id != before)
                         .map(|comment| comment)
                         .collect::<Vec<_>>()
                 } else {
@@ -96,7 +161,7 @@ impl Comment {
                 connection
                     .edges
                     .extend(comments_list.into_iter().map(|comment| {
-                        connection::Edge::new(comment.as_ref().unwrap().id, comment.unwrap())
+                        connection::Edge::new(comment.id, comment)
                     }));
                 Ok::<_, async_graphql::Error>(connection)
             },
@@ -104,7 +169,13 @@ impl Comment {
         .await
     }
 
+    /// Number of direct replies to this comment, deleted ones included.
+    async fn reply_count(&self) -> u64 {
+        self.replies.len() as u64
+    }
+
     /// Get likes of this comment
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
     async fn likes(
         &self,
         ctx: &Context<'_>,
@@ -175,6 +246,99 @@ impl Comment {
         .await
     }
 
+    /// Number of reactions on this comment for every reaction kind.
+    async fn reaction_counts(&self, ctx: &Context<'_>) -> gql::Result<Vec<ReactionCount>> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        ReactionService::reaction_counts(db, self.id.into())
+            .await
+            .map_err(|e| e.extend())
+    }
+
+    /// Reactions on this comment, newest first, only those of `kind` if given.
+    #[graphql(complexity = "connection_complexity(child_complexity, first, last)")]
+    async fn reactors(
+        &self,
+        ctx: &Context<'_>,
+        kind: Option<ReactionKind>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> gql::Result<
+        connection::Connection<
+            ScalarObjectId,
+            Reaction,
+            connection::EmptyFields,
+            connection::EmptyFields,
+        >,
+    > {
+        reactors(ctx, self.id.into(), kind, after, before, first, last).await
+    }
+
+    /// The content of the comment, empty if it was deleted or hidden by
+    /// moderation.
+    async fn content(&self) -> &str {
+        if self.hidden_at.is_some() {
+            return "";
+        }
+        &self.content
+    }
+
+    /// Users mentioned in the content, each one once.
+    async fn mentions(&self, ctx: &Context<'_>) -> gql::Result<Vec<User>> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let mut users: Vec<User> = Vec::new();
+        for mention in self.visible_mentions() {
+            if users.iter().any(|user| *user.id == mention.user_id) {
+                continue;
+            }
+            // skip the users deleted since they were mentioned
+            if let Ok(user) = UserService::get_user_by_id(db, mention.user_id).await {
+                users.push(user);
+            }
+        }
+        Ok(users)
+    }
+
+    /// Position of the mentions in the content, to turn them into links.
+    async fn mention_spans(&self) -> Vec<Mention> {
+        self.visible_mentions().to_vec()
+    }
+
+    /// Whether the content was edited since the comment was posted.
+    async fn edited(&self) -> bool {
+        self.edited_at.is_some()
+    }
+
+    /// Whether the comment was deleted, its content is then empty.
+    async fn deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// Whether the comment was hidden by moderation, its content is then
+    /// empty.
+    async fn hidden(&self) -> bool {
+        self.hidden_at.is_some()
+    }
+
+    /// Previous contents of the comment, oldest first. Only visible to the
+    /// author of the comment.
+    async fn edit_history(&self, ctx: &Context<'_>) -> gql::Result<Vec<CommentVersion>> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        let user_id = viewer_id(ctx)
+            .await
+            .ok_or_else(|| ServerError::Unauthorized.extend())?;
+        CommentService::edit_history(db, user_id, self.id.into())
+            .await
+            .map_err(|e| e.extend())
+    }
+
     /// Get the number of likes of this comment.
     async fn likes_count(&self, ctx: &Context<'_>, comment_id: ScalarObjectId) -> gql::Result<i32> {
         let db = ctx
@@ -196,8 +360,39 @@ impl Comment {
             content,
             created: DateTime::now(),
             replies: vec![],
+            edited_at: None,
+            deleted_at: None,
+            hidden_at: None,
+            mentions: vec![],
+            history: vec![],
+            parent_id: None,
+            root_id: None,
+            depth: 0,
+        }
+    }
+
+    /// Create a reply to `parent`, in the same thread.
+    pub fn reply_to(parent: &Comment, content: String, author: bson::oid::ObjectId) -> Self {
+        Self {
+            parent_id: Some(parent.id),
+            root_id: Some(parent.root_id.unwrap_or(parent.id)),
+            depth: parent.depth + 1,
+            ..Self::new(content, author)
+        }
+    }
+
+    /// The mentions that can be shown, none if the comment is hidden.
+    fn visible_mentions(&self) -> &[Mention] {
+        match self.hidden_at {
+            Some(_) => &[],
+            None => &self.mentions,
         }
     }
+
+    /// The id of the comment starting the thread of this comment.
+    pub fn thread_id(&self) -> ScalarObjectId {
+        self.root_id.unwrap_or(self.id)
+    }
 }
 
 impl From<CreateCommentInput> for Comment {
@@ -1,7 +1,7 @@
 use async_graphql::{ComplexObject, SimpleObject};
 use serde::{Deserialize, Serialize};
 
-use crate::{object_id::ScalarObjectId, common::DateTime};
+use crate::{models::reaction::Reaction, object_id::ScalarObjectId, common::DateTime};
 
 use super::Key;
 
@@ -24,3 +24,16 @@ impl Like {
         }
     }
 }
+
+/// Likes are the `Like` reactions on the comment.
+impl From<Reaction> for Like {
+    fn from(reaction: Reaction) -> Self {
+        Self {
+            key: Key {
+                user_id: reaction.user_id().into(),
+                comment_id: reaction.target_id().into(),
+            },
+            created: reaction.created,
+        }
+    }
+}
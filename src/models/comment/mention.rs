@@ -0,0 +1,37 @@
+use async_graphql as gql;
+use async_graphql::{ComplexObject, Context, ErrorExtensions, SimpleObject};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data_source::mongo::{MongoDB, MongoForTesting},
+    models::users::User,
+    services::user_service::UserService,
+};
+
+/// A `@username` mention of a user in the content of a comment. Offsets are
+/// counted in UTF-16 code units, like the indices of JavaScript strings.
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject, PartialEq)]
+#[graphql(complex, name = "CommentMention")]
+pub struct Mention {
+    #[graphql(skip)]
+    pub user_id: ObjectId,
+    pub username: String,
+    /// Offset of the `@` of the mention.
+    pub start: u32,
+    /// Offset just after the end of the mention.
+    pub end: u32,
+}
+
+#[ComplexObject]
+impl Mention {
+    /// The mentioned user.
+    async fn user(&self, ctx: &Context<'_>) -> gql::Result<User> {
+        let db = ctx
+            .data_opt::<MongoDB>()
+            .unwrap_or_else(|| ctx.data_unchecked::<MongoForTesting>());
+        UserService::get_user_by_id(db, self.user_id)
+            .await
+            .map_err(|e| e.extend())
+    }
+}
@@ -2,7 +2,10 @@ pub mod bookmark;
 pub mod comment;
 mod create_comment_input;
 pub mod like;
+pub mod mention;
+mod sort;
 pub mod update_comment_input;
+pub mod version;
 
 use async_graphql::SimpleObject;
 pub use bookmark::Bookmark;
@@ -11,9 +14,12 @@ pub use create_comment_input::{
     CreateCommentInput, CreateCommentInputBuilder, CreateCommentInputBuilderError,
 };
 pub use like::Like;
+pub use mention::Mention;
+pub use sort::CommentSort;
 use serde::Deserialize;
 use serde::Serialize;
 pub use update_comment_input::UpdateCommentInput;
+pub use version::CommentVersion;
 
 use crate::object_id::ScalarObjectId;
 
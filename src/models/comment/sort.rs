@@ -0,0 +1,14 @@
+use async_graphql::Enum;
+
+/// Order of comments and replies.
+#[derive(Debug, Clone, Copy, Enum, PartialEq, Eq, Default)]
+#[graphql(name = "CommentSort")]
+pub enum CommentSort {
+    /// Oldest first, the order they were posted in.
+    #[default]
+    Oldest,
+    /// Newest first.
+    Newest,
+    /// Most liked first, then oldest first.
+    Top,
+}
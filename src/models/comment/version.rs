@@ -0,0 +1,14 @@
+use async_graphql::SimpleObject;
+use serde::{Deserialize, Serialize};
+
+use crate::common::DateTime;
+
+/// A previous content of an edited comment.
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject, PartialEq)]
+#[graphql(name = "CommentVersion")]
+pub struct CommentVersion {
+    pub content: String,
+    /// When this content was written, either when the comment was created or
+    /// when it was last edited before.
+    pub created: DateTime,
+}
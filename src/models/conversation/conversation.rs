@@ -0,0 +1,50 @@
+use async_graphql::SimpleObject;
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::{common::DateTime, object_id::ScalarObjectId};
+
+/// A private conversation between two users.
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject, PartialEq)]
+#[graphql(complex)]
+pub struct Conversation {
+    #[serde(rename = "_id")]
+    pub id: ScalarObjectId,
+    /// Ids of the users in the conversation, sorted so that the conversation
+    /// between the same users can be found whoever started it.
+    #[graphql(skip)]
+    pub participant_ids: Vec<ObjectId>,
+    /// The sorted participant ids joined in one string. A unique index on it
+    /// keeps two users from ending up with several conversations. Missing on
+    /// conversations created before it was added.
+    #[graphql(skip)]
+    #[serde(default)]
+    pub participants_key: String,
+    pub created: DateTime,
+    /// When the last message was sent, or when the conversation was created.
+    pub updated: DateTime,
+}
+
+impl Conversation {
+    pub fn new(mut participant_ids: Vec<ObjectId>) -> Self {
+        participant_ids.sort();
+        participant_ids.dedup();
+        let participants_key = participant_ids
+            .iter()
+            .map(|id| id.to_hex())
+            .collect::<Vec<_>>()
+            .join(":");
+        let now = DateTime::now();
+        Self {
+            id: ScalarObjectId::new(),
+            participant_ids,
+            participants_key,
+            created: now,
+            updated: now,
+        }
+    }
+
+    pub fn has_participant(&self, user_id: ObjectId) -> bool {
+        self.participant_ids.contains(&user_id)
+    }
+}
@@ -0,0 +1,32 @@
+use async_graphql::SimpleObject;
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::{common::DateTime, object_id::ScalarObjectId};
+
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject, PartialEq)]
+#[graphql(complex)]
+pub struct Message {
+    #[serde(rename = "_id")]
+    pub id: ScalarObjectId,
+    pub conversation_id: ScalarObjectId,
+    #[graphql(skip)]
+    pub sender_id: ObjectId,
+    pub content: String,
+    pub created: DateTime,
+    /// When the recipient read the message, `None` while it is unread.
+    pub read_at: Option<DateTime>,
+}
+
+impl Message {
+    pub fn new(conversation_id: ObjectId, sender_id: ObjectId, content: impl Into<String>) -> Self {
+        Self {
+            id: ScalarObjectId::new(),
+            conversation_id: conversation_id.into(),
+            sender_id,
+            content: content.into(),
+            created: DateTime::now(),
+            read_at: None,
+        }
+    }
+}
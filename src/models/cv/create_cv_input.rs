@@ -2,6 +2,8 @@ use async_graphql::InputObject;
 use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 
+use super::Visibility;
+
 /// CV InputObject for CV creation query in GraphQL.
 #[derive(Serialize, Deserialize, Clone, InputObject, Builder)]
 #[builder(pattern = "owned", setter(into, prefix = "with", strip_option))]
@@ -12,6 +14,8 @@ pub struct CreateCVInput {
     pub description: Option<String>,
     #[builder(setter(custom), field(type = "Vec<String>"))]
     pub tags: Vec<String>,
+    #[builder(default)]
+    pub visibility: Option<Visibility>,
 }
 
 impl Clone for CreateCVInputBuilder {
@@ -21,6 +25,7 @@ impl Clone for CreateCVInputBuilder {
             title: self.title.clone(),
             description: self.description.clone(),
             tags: self.tags.clone(),
+            visibility: self.visibility,
         }
     }
 }
@@ -1,5 +1,5 @@
 use async_graphql::SimpleObject;
-use mongodb::bson::{self, Uuid};
+use mongodb::bson;
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -7,7 +7,7 @@ use crate::{
     object_id::ScalarObjectId, common::DateTime,
 };
 
-use super::CreateCVInput;
+use super::{CreateCVInput, TextExtraction, TextExtractionStatus, Visibility};
 
 /// Struct represents CV defined in the Diagram. Note that this struct only
 /// represents the metadata of a CV.
@@ -24,7 +24,19 @@ pub struct CV {
     pub comments: Vec<bson::oid::ObjectId>,
     pub created: DateTime,
     /// The resource identifier of the CV, can be used to query the actual CV data on the storage.
+    /// `None` until a file is uploaded.
     pub cv: Option<ResourceIdentifier>,
+    /// Who is allowed to see this CV. CVs stored before this field existed are public.
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Text extracted from the uploaded file, see `extraction_status`.
+    #[graphql(skip)]
+    #[serde(default)]
+    pub text_extraction: Option<TextExtraction>,
+    /// When the CV was hidden by moderation, hidden CVs are only shown to
+    /// their author.
+    #[serde(default)]
+    pub hidden_at: Option<DateTime>,
 }
 
 impl From<CreateCVInput> for CV {
@@ -37,7 +49,30 @@ impl From<CreateCVInput> for CV {
             tags: input.tags,
             comments: Vec::default(),
             created: DateTime::now(),
-            cv: Uuid::new().into(),
+            cv: None,
+            visibility: input.visibility.unwrap_or_default(),
+            text_extraction: None,
+            hidden_at: None,
+        }
+    }
+}
+
+impl CV {
+    /// Status of the text extraction of the current file, `None` if no file
+    /// has been uploaded.
+    pub fn extraction_status(&self) -> Option<TextExtractionStatus> {
+        let file_id = self.cv?;
+        match &self.text_extraction {
+            Some(extraction) if extraction.file_id == file_id => Some(extraction.status),
+            _ => Some(TextExtractionStatus::Pending),
+        }
+    }
+
+    /// Text extracted from the current file, if the extraction has completed.
+    pub fn extracted_text(&self) -> Option<&str> {
+        match &self.text_extraction {
+            Some(extraction) if Some(extraction.file_id) == self.cv => extraction.text.as_deref(),
+            _ => None,
         }
     }
 }
@@ -2,7 +2,7 @@ use async_graphql::SimpleObject;
 use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 
-use crate::common::DateTime;
+use crate::{common::DateTime, models::reaction::Reaction};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, SimpleObject)]
 #[graphql(name = "CvInteractionKey")]
@@ -37,6 +37,19 @@ impl Like {
     }
 }
 
+/// Likes are the `Like` reactions on the CV.
+impl From<Reaction> for Like {
+    fn from(reaction: Reaction) -> Self {
+        Self {
+            key: Key {
+                user_id: reaction.user_id(),
+                cv_id: reaction.target_id(),
+            },
+            created: reaction.created,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, SimpleObject, PartialEq)]
 #[graphql(name = "CvBookmark")]
 pub struct Bookmark {
@@ -5,6 +5,11 @@ pub use update_cv_input::UpdateCVInput;
 pub use create_cv_input::CreateCVInput;
 pub use cv::CV;
 pub mod interactions;
+pub mod revision;
+pub mod share_link;
+pub mod template;
+pub mod text_extraction;
+pub mod visibility;
 #[cfg(test)]
 mod tests;
 
@@ -12,3 +17,8 @@ pub use interactions::Key;
 pub use interactions::Like;
 pub use interactions::Bookmark;
 pub use interactions::Share;
+pub use revision::{CVRevision, CVRevisionDiff, FieldChange};
+pub use share_link::ShareLink;
+pub use template::CVTemplate;
+pub use text_extraction::{TextExtraction, TextExtractionStatus};
+pub use visibility::Visibility;
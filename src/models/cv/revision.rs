@@ -0,0 +1,109 @@
+use async_graphql::SimpleObject;
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::DateTime,
+    models::ResourceIdentifier,
+    object_id::ScalarObjectId,
+};
+
+use super::{Visibility, CV};
+
+/// An immutable snapshot of the metadata of a CV, taken every time the CV is
+/// created or changed.
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject, PartialEq)]
+#[graphql(name = "CvRevision")]
+pub struct CVRevision {
+    #[serde(rename = "_id")]
+    pub id: ScalarObjectId,
+    pub cv_id: ScalarObjectId,
+    /// Position of the revision in the history of the CV, starting from 1.
+    pub number: u32,
+    /// The user who made the change.
+    pub author_id: ScalarObjectId,
+    pub created: DateTime,
+    /// The revision this one was restored from, if any.
+    pub restored_from: Option<ScalarObjectId>,
+    pub title: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub cv: Option<ResourceIdentifier>,
+    pub visibility: Visibility,
+}
+
+/// A metadata field that differs between two revisions. Values are rendered
+/// as strings, tags are joined with a comma.
+#[derive(Debug, Clone, SimpleObject, PartialEq)]
+#[graphql(name = "CvFieldChange")]
+pub struct FieldChange {
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// The differences between the metadata of two revisions of the same CV.
+#[derive(Debug, Clone, SimpleObject, PartialEq)]
+#[graphql(name = "CvRevisionDiff")]
+pub struct CVRevisionDiff {
+    pub from: ScalarObjectId,
+    pub to: ScalarObjectId,
+    pub changes: Vec<FieldChange>,
+}
+
+impl CVRevision {
+    /// Take a snapshot of the current state of the CV.
+    pub fn new(
+        cv: &CV,
+        number: u32,
+        author_id: ObjectId,
+        restored_from: Option<ObjectId>,
+    ) -> Self {
+        Self {
+            id: ObjectId::new().into(),
+            cv_id: cv.id,
+            number,
+            author_id: author_id.into(),
+            created: DateTime::now(),
+            restored_from: restored_from.map(|id| id.into()),
+            title: cv.title.clone(),
+            description: cv.description.clone(),
+            tags: cv.tags.clone(),
+            cv: cv.cv,
+            visibility: cv.visibility,
+        }
+    }
+
+    /// Compare the metadata of this revision with a later one.
+    pub fn diff(&self, to: &CVRevision) -> CVRevisionDiff {
+        let fields = [
+            ("title", Some(self.title.clone()), Some(to.title.clone())),
+            ("description", self.description.clone(), to.description.clone()),
+            ("tags", Some(self.tags.join(", ")), Some(to.tags.join(", "))),
+            (
+                "cv",
+                self.cv.map(|cv| cv.to_string()),
+                to.cv.map(|cv| cv.to_string()),
+            ),
+            (
+                "visibility",
+                Some(format!("{:?}", self.visibility)),
+                Some(format!("{:?}", to.visibility)),
+            ),
+        ];
+        let changes = fields
+            .into_iter()
+            .filter(|(_, old_value, new_value)| old_value != new_value)
+            .map(|(field, old_value, new_value)| FieldChange {
+                field: field.to_string(),
+                old_value,
+                new_value,
+            })
+            .collect();
+        CVRevisionDiff {
+            from: self.id,
+            to: to.id,
+            changes,
+        }
+    }
+}
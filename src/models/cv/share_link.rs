@@ -0,0 +1,92 @@
+use async_graphql::SimpleObject;
+use mongodb::bson;
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{common::DateTime, object_id::ScalarObjectId};
+
+/// Length of the generated share link tokens.
+const TOKEN_LENGTH: usize = 32;
+
+/// Longest lifetime a share link can be given, in seconds (one year).
+pub const MAX_SHARE_LINK_LIFETIME: i64 = 365 * 24 * 60 * 60;
+
+/// A link that gives anyone holding its token read access to a CV, until it
+/// expires, runs out of views or is revoked by the author.
+#[derive(Debug, Serialize, Deserialize, Clone, SimpleObject, PartialEq)]
+#[graphql(name = "CvShareLink")]
+pub struct ShareLink {
+    #[serde(rename = "_id")]
+    pub id: ScalarObjectId,
+    /// The unguessable token used to open the CV.
+    pub token: String,
+    pub cv_id: ScalarObjectId,
+    #[graphql(skip)]
+    pub created_by: ScalarObjectId,
+    pub created: DateTime,
+    /// The link cannot be used after this moment, `None` means it never expires.
+    pub expires_at: Option<DateTime>,
+    /// Maximum number of times the link can be opened, `None` means unlimited.
+    pub max_views: Option<u32>,
+    /// Number of times the link has been opened.
+    pub view_count: u32,
+    #[graphql(skip)]
+    pub revoked: bool,
+}
+
+impl ShareLink {
+    /// Create a new link with a freshly generated token.
+    /// `expires_in` is the lifetime of the link in seconds, a lifetime too large
+    /// to be represented means the link never expires.
+    pub fn new(
+        cv_id: bson::oid::ObjectId,
+        created_by: bson::oid::ObjectId,
+        expires_in: Option<i64>,
+        max_views: Option<u32>,
+    ) -> Self {
+        let created = DateTime::now();
+        let expires_at = expires_in.and_then(|secs| {
+            secs.checked_mul(1000)
+                .and_then(|millis| created.timestamp_millis().checked_add(millis))
+                .map(|millis| bson::DateTime::from_millis(millis).into())
+        });
+        Self {
+            id: bson::oid::ObjectId::new().into(),
+            token: Self::generate_token(),
+            cv_id: cv_id.into(),
+            created_by: created_by.into(),
+            created,
+            expires_at,
+            max_views,
+            view_count: 0,
+            revoked: false,
+        }
+    }
+
+    /// Return whether the link has passed its expiry time.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|expires_at| expires_at <= DateTime::now())
+            .unwrap_or(false)
+    }
+
+    /// Return whether the link has been opened as many times as allowed.
+    pub fn is_exhausted(&self) -> bool {
+        self.max_views
+            .map(|max_views| self.view_count >= max_views)
+            .unwrap_or(false)
+    }
+
+    /// Return whether the link can still be used to open the CV.
+    pub fn is_active(&self) -> bool {
+        !self.revoked && !self.is_expired() && !self.is_exhausted()
+    }
+
+    fn generate_token() -> String {
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(TOKEN_LENGTH)
+            .map(char::from)
+            .collect()
+    }
+}
@@ -0,0 +1,15 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+/// Layouts a PDF CV can be rendered with from the profile of its author.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Enum, PartialEq, Eq, Default)]
+#[graphql(name = "CvTemplate")]
+pub enum CVTemplate {
+    /// Serif fonts in black and white, sections separated by rules.
+    #[default]
+    Classic,
+    /// A colored header band with the name, sans-serif fonts.
+    Modern,
+    /// Smaller fonts and margins to fit long profiles on fewer pages.
+    Compact,
+}
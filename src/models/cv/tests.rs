@@ -20,7 +20,7 @@ fn test_cv_from_input() {
     assert_eq!(test_cv.tags, vec!["tag".to_string()]);
     assert_eq!(test_cv.description, None);
     assert_eq!(test_cv.comments, Vec::default());
-    assert!(test_cv.cv.is_some());
+    assert!(test_cv.cv.is_none(), "No file is uploaded yet");
 }
 
 #[test]
@@ -45,3 +45,31 @@ fn test_update_cv() {
     );
     assert_eq!(test_cv_update.title, Some("title".to_string()));
 }
+
+#[test]
+fn test_cv_revision_diff() {
+    use crate::models::cv::{cv::CV, CVRevision};
+    let author_id = ObjectId::new();
+    let mut cv = CV::from(
+        CreateCVInputBuilder::default()
+            .with_author_id(author_id)
+            .with_title("title")
+            .with_tag("tag".to_string())
+            .build()
+            .unwrap(),
+    );
+    let old = CVRevision::new(&cv, 1, author_id, None);
+    assert!(old.diff(&old).changes.is_empty());
+
+    cv.title = "new title".to_string();
+    cv.tags.push("tag2".to_string());
+    let new = CVRevision::new(&cv, 2, author_id, None);
+    let diff = old.diff(&new);
+    assert_eq!(diff.from, old.id);
+    assert_eq!(diff.to, new.id);
+    assert_eq!(diff.changes.len(), 2);
+    assert_eq!(diff.changes[0].field, "title");
+    assert_eq!(diff.changes[1].field, "tags");
+    assert_eq!(diff.changes[1].old_value, Some("tag".to_string()));
+    assert_eq!(diff.changes[1].new_value, Some("tag, tag2".to_string()));
+}
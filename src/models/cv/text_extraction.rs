@@ -0,0 +1,45 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+use crate::models::ResourceIdentifier;
+
+/// Progress of the text extraction of the file attached to a CV.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Enum, PartialEq, Eq)]
+#[graphql(name = "CvTextExtractionStatus")]
+pub enum TextExtractionStatus {
+    /// The file has been uploaded, extraction has not finished yet.
+    Pending,
+    /// The text has been extracted and is used by the CV search.
+    Completed,
+    /// The file could not be read, e.g. it is corrupted or encrypted.
+    Failed,
+    /// Text cannot be extracted from this type of file.
+    Unsupported,
+}
+
+/// Outcome of the text extraction of a file. The id of the file is kept so
+/// that the outcome is ignored once another file is uploaded.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TextExtraction {
+    pub file_id: ResourceIdentifier,
+    pub status: TextExtractionStatus,
+    pub text: Option<String>,
+}
+
+impl TextExtraction {
+    pub fn new(file_id: ResourceIdentifier, status: TextExtractionStatus) -> Self {
+        Self {
+            file_id,
+            status,
+            text: None,
+        }
+    }
+
+    pub fn completed(file_id: ResourceIdentifier, text: String) -> Self {
+        Self {
+            file_id,
+            status: TextExtractionStatus::Completed,
+            text: Some(text),
+        }
+    }
+}
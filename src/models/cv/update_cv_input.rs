@@ -2,7 +2,9 @@ use async_graphql::InputObject;
 use serde::{Deserialize, Serialize};
 
 
-use crate::object_id::ScalarObjectId;
+use crate::{models::ResourceIdentifier, object_id::ScalarObjectId};
+
+use super::Visibility;
 
 #[derive(Serialize, Deserialize, Clone, InputObject, Builder, Default)]
 #[builder(
@@ -21,6 +23,10 @@ pub struct UpdateCVInput {
     pub description: Option<String>,
     #[builder(default)]
     pub tags: Option<Vec<String>>,
+    #[builder(default)]
+    pub visibility: Option<Visibility>,
+    #[builder(default)]
+    pub cv: Option<ResourceIdentifier>,
 }
 
 impl UpdateCVInput {
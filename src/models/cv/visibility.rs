@@ -0,0 +1,24 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+/// Who is allowed to see a CV.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Enum, PartialEq, Eq, Default)]
+#[graphql(name = "CvVisibility")]
+pub enum Visibility {
+    /// Visible to everyone and listed in searches.
+    #[default]
+    Public,
+    /// Visible only to the author and users with an accepted friend request with the author.
+    FriendsOnly,
+    /// Visible only to the author.
+    Private,
+    /// Visible to anyone who has the link, but never listed in searches or profiles.
+    Unlisted,
+}
+
+impl Visibility {
+    /// Return whether CVs with this visibility may appear in listings.
+    pub fn is_listed(&self) -> bool {
+        matches!(self, Visibility::Public | Visibility::FriendsOnly)
+    }
+}
@@ -7,18 +7,18 @@ use serde::{Deserialize, Serialize};
 #[graphql(input_name = "ExperienceInput")]
 #[graphql(complex)]
 pub struct Experience {
-    title: String,
-    company: String,
-    employment_type: String,
-    location: String,
-    description: String,
+    pub title: String,
+    pub company: String,
+    pub employment_type: String,
+    pub location: String,
+    pub description: String,
 
     #[graphql(skip)]
     #[builder(default)]
-    start_date: Option<DateTime>,
+    pub start_date: Option<DateTime>,
     #[graphql(skip)]
     #[builder(default)]
-    end_date: Option<DateTime>,
+    pub end_date: Option<DateTime>,
 }
 
 #[ComplexObject]
@@ -1,8 +1,9 @@
+use async_graphql::{Enum, SimpleObject};
 use mongodb::bson::{self, oid::ObjectId};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Enum)]
 pub enum FriendRequestStatus {
     Pending,
     Accepted,
@@ -15,13 +16,17 @@ pub struct FriendRequestID {
     pub to: ObjectId,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, SimpleObject)]
+#[graphql(complex)]
 pub struct FriendRequest {
     #[serde(rename = "_id")]
+    #[graphql(skip)]
     pub id: FriendRequestID,
     pub message: Option<String>,
     pub status: FriendRequestStatus,
+    #[graphql(skip)]
     pub created_at: bson::DateTime,
+    #[graphql(skip)]
     pub updated_at: bson::DateTime,
 }
 
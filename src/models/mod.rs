@@ -2,12 +2,15 @@ use mongodb::bson::Uuid;
 
 pub mod experience;
 pub mod comment;
+pub mod conversation;
 pub mod users;
 pub mod cv;
 pub mod education;
 pub mod friend_request;
 pub mod cv_details;
 pub mod range_values;
+pub mod reaction;
+pub mod report;
 pub mod sex;
 
 pub type ResourceIdentifier = Uuid;
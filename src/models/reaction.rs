@@ -0,0 +1,85 @@
+use async_graphql::{Enum, SimpleObject};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::common::DateTime;
+
+/// The reactions users can leave on CVs and comments. Liking a CV or a
+/// comment is reacting with `Like`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Enum, PartialEq, Eq)]
+pub enum ReactionKind {
+    Like,
+    Love,
+    Haha,
+    Wow,
+    Sad,
+    Angry,
+}
+
+impl ReactionKind {
+    pub const ALL: [ReactionKind; 6] = [
+        ReactionKind::Like,
+        ReactionKind::Love,
+        ReactionKind::Haha,
+        ReactionKind::Wow,
+        ReactionKind::Sad,
+        ReactionKind::Angry,
+    ];
+}
+
+/// What a reaction is left on.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Enum, PartialEq, Eq)]
+#[graphql(name = "ReactionTargetType")]
+pub enum ReactionTarget {
+    Cv,
+    Comment,
+}
+
+/// A user has at most one reaction on a target, so the pair is the id.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReactionKey {
+    pub user_id: ObjectId,
+    pub target_id: ObjectId,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, SimpleObject)]
+#[graphql(complex)]
+pub struct Reaction {
+    #[serde(rename = "_id")]
+    #[graphql(skip)]
+    pub key: ReactionKey,
+    pub target_type: ReactionTarget,
+    pub kind: ReactionKind,
+    pub created: DateTime,
+}
+
+impl Reaction {
+    pub fn new(
+        user_id: ObjectId,
+        target_type: ReactionTarget,
+        target_id: ObjectId,
+        kind: ReactionKind,
+    ) -> Self {
+        Self {
+            key: ReactionKey { user_id, target_id },
+            target_type,
+            kind,
+            created: DateTime::now(),
+        }
+    }
+
+    pub fn user_id(&self) -> ObjectId {
+        self.key.user_id
+    }
+
+    pub fn target_id(&self) -> ObjectId {
+        self.key.target_id
+    }
+}
+
+/// Number of reactions of one kind on a target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, SimpleObject)]
+pub struct ReactionCount {
+    pub kind: ReactionKind,
+    pub count: u64,
+}
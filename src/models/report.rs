@@ -0,0 +1,119 @@
+use async_graphql::{Enum, SimpleObject};
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::{common::DateTime, object_id::ScalarObjectId};
+
+/// What can be reported to the moderators.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Enum, PartialEq, Eq)]
+#[graphql(name = "ReportTargetType")]
+pub enum ReportTarget {
+    Cv,
+    Comment,
+    User,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Enum, PartialEq, Eq)]
+pub enum ReportStatus {
+    /// Waiting for a moderator.
+    Open,
+    /// A moderator found nothing wrong with the content.
+    Dismissed,
+    /// A moderator hid the content.
+    ContentHidden,
+    /// A moderator suspended the author of the content, or the reported user.
+    UserSuspended,
+}
+
+/// What a moderator can do about a report.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Enum, PartialEq, Eq)]
+pub enum ModerationAction {
+    /// Close the report, showing the content again if it was hidden.
+    Dismiss,
+    /// Hide the reported CV or comment.
+    HideContent,
+    /// Suspend the author of the reported content, or the reported user.
+    SuspendUser,
+}
+
+impl From<ModerationAction> for ReportStatus {
+    fn from(action: ModerationAction) -> Self {
+        match action {
+            ModerationAction::Dismiss => ReportStatus::Dismissed,
+            ModerationAction::HideContent => ReportStatus::ContentHidden,
+            ModerationAction::SuspendUser => ReportStatus::UserSuspended,
+        }
+    }
+}
+
+/// A user flagging a CV, a comment or another user as abusive.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, SimpleObject)]
+pub struct Report {
+    #[serde(rename = "_id")]
+    pub id: ScalarObjectId,
+    pub reporter_id: ScalarObjectId,
+    pub target_type: ReportTarget,
+    pub target_id: ScalarObjectId,
+    pub reason: String,
+    pub status: ReportStatus,
+    pub created: DateTime,
+    /// When a moderator closed the report, `None` while it is open.
+    pub resolved_at: Option<DateTime>,
+}
+
+impl Report {
+    pub fn new(
+        reporter_id: ObjectId,
+        target_type: ReportTarget,
+        target_id: ObjectId,
+        reason: String,
+    ) -> Self {
+        Self {
+            id: ObjectId::new().into(),
+            reporter_id: reporter_id.into(),
+            target_type,
+            target_id: target_id.into(),
+            reason,
+            status: ReportStatus::Open,
+            created: DateTime::now(),
+            resolved_at: None,
+        }
+    }
+}
+
+/// Audit record of a decision taken on reported content.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, SimpleObject)]
+pub struct ModerationDecision {
+    #[serde(rename = "_id")]
+    pub id: ScalarObjectId,
+    /// The report the decision was taken on.
+    pub report_id: ScalarObjectId,
+    /// The moderator who took the decision, `None` when the content was
+    /// hidden automatically.
+    pub moderator_id: Option<ScalarObjectId>,
+    pub target_type: ReportTarget,
+    pub target_id: ScalarObjectId,
+    pub action: ModerationAction,
+    pub note: Option<String>,
+    pub created: DateTime,
+}
+
+impl ModerationDecision {
+    pub fn new(
+        report: &Report,
+        moderator_id: Option<ObjectId>,
+        action: ModerationAction,
+        note: Option<String>,
+    ) -> Self {
+        Self {
+            id: ObjectId::new().into(),
+            report_id: report.id,
+            moderator_id: moderator_id.map(|id| id.into()),
+            target_type: report.target_type,
+            target_id: report.target_id,
+            action,
+            note,
+            created: DateTime::now(),
+        }
+    }
+}
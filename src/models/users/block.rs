@@ -0,0 +1,40 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::common::DateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BlockKey {
+    pub blocker_id: ObjectId,
+    pub blocked_id: ObjectId,
+}
+
+/// A user blocking another one. Blocks are one-sided but keep both users from
+/// interacting with each other.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Block {
+    #[serde(rename = "_id")]
+    pub key: BlockKey,
+    pub created: DateTime,
+}
+
+impl Block {
+    pub fn new(blocker_id: ObjectId, blocked_id: ObjectId) -> Self {
+        Self {
+            key: BlockKey {
+                blocker_id,
+                blocked_id,
+            },
+            created: DateTime::now(),
+        }
+    }
+
+    /// Return the other user of the block, seen from `user_id`.
+    pub fn other(&self, user_id: ObjectId) -> ObjectId {
+        if self.key.blocker_id == user_id {
+            self.key.blocked_id
+        } else {
+            self.key.blocker_id
+        }
+    }
+}
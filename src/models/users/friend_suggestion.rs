@@ -0,0 +1,29 @@
+use async_graphql::{Enum, SimpleObject};
+
+use super::User;
+
+/// What a suggested user has in common with the viewer.
+#[derive(Debug, Clone, Copy, Enum, PartialEq, Eq)]
+pub enum FriendSuggestionReason {
+    MutualFriends,
+    SameSchool,
+    SameMajor,
+    SameCompany,
+    SameCity,
+    SharedSkills,
+}
+
+/// A user the viewer may know, with what they have in common.
+#[derive(Debug, Clone, SimpleObject, PartialEq)]
+pub struct FriendSuggestion {
+    pub user: User,
+    /// Number of friends the viewer and the user have in common.
+    pub mutual_friends: i32,
+    /// Everything the viewer and the user have in common, strongest first.
+    pub reasons: Vec<FriendSuggestionReason>,
+    /// A short description of the strongest reason, e.g. "3 mutual friends".
+    pub reason: String,
+    /// Rank of the suggestion, higher is better.
+    #[graphql(skip)]
+    pub score: u32,
+}
@@ -0,0 +1,226 @@
+//! Export of a user profile in the JSON Resume format, see <https://jsonresume.org/schema>.
+
+use mongodb::bson;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{cv::CV, education::Education, experience::Experience};
+
+use super::User;
+
+/// Version of the JSON Resume schema the export follows.
+pub const JSON_RESUME_VERSION: &str = "v1.0.0";
+
+/// A resume in the JSON Resume format. Sections without data are left out.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonResume {
+    pub basics: JsonResumeBasics,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub work: Vec<JsonResumeWork>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub education: Vec<JsonResumeEducation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skills: Vec<JsonResumeSkill>,
+    pub meta: JsonResumeMeta,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonResumeBasics {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Contact details, `email`, `otherEmails` and `location`, are only
+    /// exported to the user and their friends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Emails of the user other than the primary one. The schema has a
+    /// single email, extra properties are allowed in `basics`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub other_emails: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<JsonResumeLocation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonResumeLocation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    /// ISO-3166-1 ALPHA-2 code of the country.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub country_code: Option<String>,
+    /// Used for countries that are not stored as a code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonResumeWork {
+    pub name: String,
+    pub position: String,
+    pub location: String,
+    pub employment_type: String,
+    pub summary: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonResumeEducation {
+    pub institution: String,
+    pub area: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minor: Option<String>,
+    pub study_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonResumeSkill {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonResumeMeta {
+    pub version: String,
+    /// The CV chosen for the export, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cv: Option<JsonResumeCV>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonResumeCV {
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub created: String,
+}
+
+/// Format a date as `YYYY-MM-DD`, the format required by the schema.
+fn format_date(date: Option<bson::DateTime>) -> Option<String> {
+    let date = date?.try_to_rfc3339_string().ok()?;
+    date.get(..10).map(|date| date.to_string())
+}
+
+impl JsonResume {
+    /// Render the profile of the user, with the metadata of `cv` if provided.
+    pub fn new(user: &User, cv: Option<&CV>) -> Self {
+        let name = format!("{} {}", user.first_name, user.last_name)
+            .trim()
+            .to_string();
+        let country = user
+            .country
+            .as_ref()
+            .map(|country| country.trim())
+            .filter(|country| !country.is_empty());
+        let (country_code, region) = match country {
+            Some(country)
+                if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) =>
+            {
+                (Some(country.to_ascii_uppercase()), None)
+            }
+            Some(country) => (None, Some(country.to_string())),
+            None => (None, None),
+        };
+        let location = match (&user.city, country_code, region) {
+            (None, None, None) => None,
+            (city, country_code, region) => Some(JsonResumeLocation {
+                city: city.clone(),
+                country_code,
+                region,
+            }),
+        };
+        Self {
+            basics: JsonResumeBasics {
+                name,
+                label: cv.map(|cv| cv.title.clone()),
+                email: Some(user.primary_email.clone()),
+                other_emails: user.other_emails.clone(),
+                summary: user.about.clone(),
+                location,
+            },
+            work: user.experiences.iter().map(JsonResumeWork::from).collect(),
+            education: user
+                .educations
+                .iter()
+                .map(JsonResumeEducation::from)
+                .collect(),
+            skills: user
+                .skills
+                .iter()
+                .map(|skill| JsonResumeSkill {
+                    name: skill.clone(),
+                })
+                .collect(),
+            meta: JsonResumeMeta {
+                version: JSON_RESUME_VERSION.to_string(),
+                cv: cv.map(JsonResumeCV::from),
+            },
+        }
+    }
+}
+
+impl JsonResume {
+    /// Remove the contact details of the user from the resume.
+    pub fn without_contact(mut self) -> Self {
+        self.basics.email = None;
+        self.basics.other_emails.clear();
+        self.basics.location = None;
+        self
+    }
+}
+
+impl From<&Experience> for JsonResumeWork {
+    fn from(experience: &Experience) -> Self {
+        Self {
+            name: experience.company.clone(),
+            position: experience.title.clone(),
+            location: experience.location.clone(),
+            employment_type: experience.employment_type.clone(),
+            summary: experience.description.clone(),
+            start_date: format_date(experience.start_date),
+            end_date: format_date(experience.end_date),
+        }
+    }
+}
+
+impl From<&Education> for JsonResumeEducation {
+    fn from(education: &Education) -> Self {
+        Self {
+            institution: education.school.clone(),
+            area: education.major.clone(),
+            minor: education.minor.clone(),
+            study_type: education.degree.clone(),
+            start_date: format_date(education.start_date),
+            end_date: format_date(education.end_date),
+        }
+    }
+}
+
+impl From<&CV> for JsonResumeCV {
+    fn from(cv: &CV) -> Self {
+        Self {
+            id: cv.id.to_hex(),
+            title: cv.title.clone(),
+            description: cv.description.clone(),
+            tags: cv.tags.clone(),
+            created: cv.created.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    }
+}
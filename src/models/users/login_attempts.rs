@@ -0,0 +1,53 @@
+use std::net::IpAddr;
+
+use mongodb::bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+
+use crate::common::DateTime;
+
+/// Failed logins counted against an account or a client address.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LoginAttempts {
+    /// What the failures are counted against, see `LoginAttempts::account_key`
+    /// and `LoginAttempts::ip_key`.
+    #[serde(rename = "_id")]
+    pub key: String,
+    /// Failures in a row since the last successful login.
+    pub failures: u32,
+    pub last_failure: DateTime,
+    /// Logins are refused until then.
+    pub locked_until: Option<DateTime>,
+}
+
+impl LoginAttempts {
+    pub fn new(key: String) -> Self {
+        Self {
+            key,
+            failures: 0,
+            last_failure: DateTime::now(),
+            locked_until: None,
+        }
+    }
+
+    /// Key of the failures against an existing user.
+    pub fn account_key(user_id: ObjectId) -> String {
+        format!("user:{}", user_id.to_hex())
+    }
+
+    /// Key of the failures against a username or email that does not belong
+    /// to anyone, so probing unknown accounts is throttled as well.
+    pub fn identifier_key(identifier: &str) -> String {
+        format!("login:{}", identifier.to_lowercase())
+    }
+
+    /// Key of the failures coming from a client address.
+    pub fn ip_key(ip: IpAddr) -> String {
+        format!("ip:{}", ip)
+    }
+
+    /// Return whether logins are refused at the moment.
+    pub fn is_locked(&self) -> bool {
+        self.locked_until
+            .is_some_and(|until| until > DateTime::now())
+    }
+}
@@ -1,10 +1,23 @@
+pub mod block;
 pub mod create_user_input;
+pub mod friend_suggestion;
+pub mod json_resume;
+pub mod login_attempts;
+pub mod photo_size;
+pub mod profile_import;
+pub mod role;
 pub mod update_user_input;
 pub mod user;
 
 #[cfg(test)]
 pub mod tests;
 
+pub use block::Block;
 pub use create_user_input::CreateUserInput;
+pub use friend_suggestion::{FriendSuggestion, FriendSuggestionReason};
+pub use json_resume::JsonResume;
+pub use login_attempts::LoginAttempts;
+pub use photo_size::PhotoSize;
+pub use role::Role;
 pub use update_user_input::UpdateUserInput;
 pub use user::User;
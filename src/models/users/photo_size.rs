@@ -0,0 +1,23 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+/// Resized variants generated for avatars and cover photos.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Enum, PartialEq, Eq, Hash)]
+pub enum PhotoSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl PhotoSize {
+    pub const ALL: [PhotoSize; 3] = [PhotoSize::Small, PhotoSize::Medium, PhotoSize::Large];
+
+    /// Name of the variant, used in the object names of stored photos.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PhotoSize::Small => "small",
+            PhotoSize::Medium => "medium",
+            PhotoSize::Large => "large",
+        }
+    }
+}
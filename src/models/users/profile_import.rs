@@ -0,0 +1,62 @@
+use async_graphql::{Enum, OutputType, SimpleObject};
+
+use crate::models::{education::Education, experience::Experience};
+
+use super::User;
+
+/// Formats of the files a profile can be imported from.
+#[derive(Debug, Clone, Copy, Enum, PartialEq, Eq)]
+pub enum ProfileImportFormat {
+    /// A resume in the JSON Resume format, see https://jsonresume.org/schema.
+    JsonResume,
+    /// The ZIP archive of a LinkedIn data export, `Positions.csv` and
+    /// `Education.csv` are read from it.
+    #[graphql(name = "LINKEDIN")]
+    LinkedIn,
+}
+
+/// How imported entries are combined with the ones already in the profile.
+#[derive(Debug, Clone, Copy, Enum, PartialEq, Eq, Default)]
+pub enum ProfileImportMode {
+    /// Keep the existing entries and add the imported ones that are not in the profile yet.
+    #[default]
+    Merge,
+    /// Replace the entries of the profile with the imported ones.
+    Replace,
+}
+
+/// The changes an import makes to a list of the profile.
+#[derive(Debug, Clone, SimpleObject, PartialEq)]
+#[graphql(concrete(name = "EducationImportDiff", params(Education)))]
+#[graphql(concrete(name = "ExperienceImportDiff", params(Experience)))]
+pub struct ImportDiff<T: OutputType> {
+    /// Imported entries that are not in the profile.
+    pub added: Vec<T>,
+    /// Entries of the profile that the import removes, only when replacing.
+    pub removed: Vec<T>,
+    /// Imported entries that are already in the profile.
+    pub unchanged: Vec<T>,
+}
+
+impl<T: OutputType> Default for ImportDiff<T> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            unchanged: Vec::new(),
+        }
+    }
+}
+
+/// The outcome of `importProfile`, a preview unless the import was confirmed.
+#[derive(Debug, Clone, SimpleObject, PartialEq)]
+pub struct ProfileImport {
+    pub format: ProfileImportFormat,
+    pub mode: ProfileImportMode,
+    /// Whether the changes have been saved.
+    pub applied: bool,
+    pub educations: ImportDiff<Education>,
+    pub experiences: ImportDiff<Experience>,
+    /// The user after the import if it was applied, as it is now otherwise.
+    pub user: User,
+}
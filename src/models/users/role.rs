@@ -0,0 +1,15 @@
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+/// What a user is allowed to do besides using the site. Each role can do
+/// everything the roles before it can.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, Enum, PartialEq, Eq, PartialOrd, Ord, Default,
+)]
+pub enum Role {
+    #[default]
+    User,
+    /// Reviews reported content.
+    Moderator,
+    Admin,
+}
@@ -4,6 +4,7 @@ use gql::futures_util::StreamExt;
 use mongodb::bson::Uuid;
 use serde::{Deserialize, Serialize};
 
+use crate::common::DateTime;
 use crate::models::cv::CV;
 use crate::models::experience::Experience;
 use crate::{
@@ -16,7 +17,7 @@ use crate::{
     object_id::ScalarObjectId,
 };
 
-use super::CreateUserInput;
+use super::{CreateUserInput, Role};
 
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, Enum, PartialEq, Eq)]
@@ -57,6 +58,14 @@ pub struct User {
     pub experiences: Vec<Experience>,
     pub personalities: Vec<String>,
     pub sex: Sex,
+    /// Users stored before roles existed are plain users.
+    #[serde(default)]
+    pub role: Role,
+    /// Until when the user is suspended by a moderator, `None` if they are
+    /// not.
+    #[serde(default)]
+    #[graphql(skip)]
+    pub suspended_until: Option<DateTime>,
 }
 
 impl From<CreateUserInput> for User {
@@ -86,6 +95,21 @@ impl From<CreateUserInput> for User {
             experiences: input.experiences,
             personalities: input.personalities,
             sex: input.sex,
+            role: Role::default(),
+            suspended_until: None,
         }
     }
 }
+
+impl User {
+    /// Whether the user can review reported content.
+    pub fn is_moderator(&self) -> bool {
+        self.role >= Role::Moderator
+    }
+
+    /// Whether the user is currently suspended.
+    pub fn is_suspended(&self) -> bool {
+        self.suspended_until
+            .is_some_and(|until| until > DateTime::now())
+    }
+}
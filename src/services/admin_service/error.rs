@@ -0,0 +1,68 @@
+use async_graphql::ErrorExtensions;
+use mongodb::bson::oid::ObjectId;
+use std::fmt;
+
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AdminServiceError {
+    /// Error when the user does not exist.
+    UserNotFound(ObjectId),
+
+    /// Error when the CV does not exist.
+    CvNotFound(ObjectId),
+
+    /// Error when the comment does not exist.
+    CommentNotFound(ObjectId),
+
+    /// Error when an admin tries to suspend themselves or change their own
+    /// role, which could leave the site without admins.
+    SelfAction,
+
+    /// Error when the admin account cannot be registered.
+    CreateAdminFailed(String),
+
+    /// Database error
+    DatabaseError,
+}
+
+impl fmt::Display for AdminServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdminServiceError::UserNotFound(id) => {
+                write!(f, "User {:?} not found", id)
+            }
+            AdminServiceError::CvNotFound(id) => {
+                write!(f, "CV {:?} not found", id)
+            }
+            AdminServiceError::CommentNotFound(id) => {
+                write!(f, "Comment {:?} not found", id)
+            }
+            AdminServiceError::SelfAction => {
+                write!(
+                    f,
+                    "Admins cannot suspend themselves or change their own role"
+                )
+            }
+            AdminServiceError::CreateAdminFailed(reason) => {
+                write!(f, "Cannot create the admin account: {}", reason)
+            }
+            AdminServiceError::DatabaseError => {
+                write!(f, "Database error")
+            }
+        }
+    }
+}
+
+impl ErrorExtensions for AdminServiceError {
+    fn extend(&self) -> async_graphql::Error {
+        let code = match self {
+            AdminServiceError::UserNotFound(_) => "USER_NOT_FOUND",
+            AdminServiceError::CvNotFound(_) => "CV_NOT_FOUND",
+            AdminServiceError::CommentNotFound(_) => "COMMENT_NOT_FOUND",
+            AdminServiceError::SelfAction => "SELF_ACTION",
+            AdminServiceError::CreateAdminFailed(_) => "CREATE_ADMIN_FAILED",
+            AdminServiceError::DatabaseError => "DATABASE_ERROR",
+        };
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| e.set("code", code))
+    }
+}
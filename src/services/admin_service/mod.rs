@@ -0,0 +1,145 @@
+pub mod error;
+#[cfg(test)]
+mod tests;
+
+use mongodb::bson::{self, oid::ObjectId};
+
+use crate::{
+    common::DateTime,
+    data_source::{CVDataSource, CVDataSourceError, CommentDataSource, UserDataSource},
+    models::{
+        comment::Comment,
+        users::{CreateUserInput, Role, User},
+    },
+};
+
+use self::error::AdminServiceError;
+
+use super::{
+    auth_service::AuthService,
+    cv_service::comment_service::{CommentService, CommentServiceError},
+    moderation_service::SUSPENSION_DURATION,
+    user_service::error::UserServiceError,
+};
+
+pub struct AdminService;
+
+impl AdminService {
+    /// Return the users, ordered by username, only those whose username,
+    /// name or primary email contains `search` if given.
+    pub async fn users(
+        db: &(impl UserDataSource + std::marker::Sync),
+        search: Option<String>,
+    ) -> Result<Vec<User>, AdminServiceError> {
+        let search = search
+            .map(|search| search.trim().to_string())
+            .filter(|search| !search.is_empty());
+        db.search_users(search)
+            .await
+            .map_err(|err| Self::user_error(err.into(), None))
+    }
+
+    /// Suspend the user until `until`, for `SUSPENSION_DURATION` if not
+    /// given.
+    pub async fn suspend_user(
+        db: &(impl UserDataSource + std::marker::Sync),
+        admin_id: ObjectId,
+        user_id: ObjectId,
+        until: Option<DateTime>,
+    ) -> Result<User, AdminServiceError> {
+        if admin_id == user_id {
+            return Err(AdminServiceError::SelfAction);
+        }
+        let until = until.unwrap_or_else(|| {
+            let until = DateTime::now().timestamp_millis() + SUSPENSION_DURATION.as_millis() as i64;
+            bson::DateTime::from_millis(until).into()
+        });
+        db.set_suspended_until(user_id, Some(until))
+            .await
+            .map_err(|err| Self::user_error(err.into(), Some(user_id)))
+    }
+
+    pub async fn unsuspend_user(
+        db: &(impl UserDataSource + std::marker::Sync),
+        user_id: ObjectId,
+    ) -> Result<User, AdminServiceError> {
+        db.set_suspended_until(user_id, None)
+            .await
+            .map_err(|err| Self::user_error(err.into(), Some(user_id)))
+    }
+
+    /// Give the user `role`, it applies to the access tokens issued from then
+    /// on.
+    pub async fn set_role(
+        db: &(impl UserDataSource + std::marker::Sync),
+        admin_id: ObjectId,
+        user_id: ObjectId,
+        role: Role,
+    ) -> Result<User, AdminServiceError> {
+        if admin_id == user_id {
+            return Err(AdminServiceError::SelfAction);
+        }
+        db.set_role(user_id, role)
+            .await
+            .map_err(|err| Self::user_error(err.into(), Some(user_id)))
+    }
+
+    /// Delete the CV whoever its author is.
+    pub async fn delete_cv(
+        db: &(impl CVDataSource + std::marker::Sync),
+        cv_id: ObjectId,
+    ) -> Result<(), AdminServiceError> {
+        match db.get_cv_by_id(cv_id).await {
+            Ok(_) => {}
+            Err(CVDataSourceError::DatabaseError) => return Err(AdminServiceError::DatabaseError),
+            Err(_) => return Err(AdminServiceError::CvNotFound(cv_id)),
+        }
+        db.delete_cv(cv_id).await.map_err(|err| match err {
+            CVDataSourceError::IdNotFound(_) => AdminServiceError::CvNotFound(cv_id),
+            _ => AdminServiceError::DatabaseError,
+        })
+    }
+
+    /// Delete the comment whoever its author is, it becomes a tombstone like
+    /// comments deleted by their author.
+    pub async fn delete_comment(
+        db: &(impl CommentDataSource + std::marker::Sync),
+        comment_id: ObjectId,
+    ) -> Result<Comment, AdminServiceError> {
+        CommentService::delete_comment(db, comment_id)
+            .await
+            .map_err(|err| match err {
+                CommentServiceError::DatabaseError => AdminServiceError::DatabaseError,
+                _ => AdminServiceError::CommentNotFound(comment_id),
+            })
+    }
+
+    /// Make the user with the username of `input` an admin, registering them
+    /// first if they do not exist. Used to bootstrap the first admin.
+    pub async fn create_admin(
+        db: &(impl UserDataSource + std::marker::Sync),
+        input: CreateUserInput,
+    ) -> Result<User, AdminServiceError> {
+        let user = match db.get_user_by_username(&input.username).await {
+            Ok(user) => user,
+            Err(_) if input.password.is_empty() => {
+                return Err(AdminServiceError::CreateAdminFailed(
+                    "a password is required".to_string(),
+                ))
+            }
+            Err(_) => AuthService::register(db, input)
+                .await
+                .map_err(|err| AdminServiceError::CreateAdminFailed(err.to_string()))?,
+        };
+        db.set_role(user.id.into(), Role::Admin)
+            .await
+            .map_err(|err| Self::user_error(err.into(), Some(user.id.into())))
+    }
+
+    fn user_error(err: UserServiceError, user_id: Option<ObjectId>) -> AdminServiceError {
+        match (err, user_id) {
+            (UserServiceError::DatabaseError, _) | (_, None) => AdminServiceError::DatabaseError,
+            (_, Some(user_id)) => AdminServiceError::UserNotFound(user_id),
+        }
+    }
+}
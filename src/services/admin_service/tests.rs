@@ -0,0 +1,131 @@
+use mongodb::bson::oid::ObjectId;
+
+use super::{error::AdminServiceError, AdminService};
+use crate::{
+    data_source::{CVDataSource, UserDataSource},
+    models::{
+        comment::CreateCommentInput,
+        sex::Sex,
+        users::{CreateUserInput, Role},
+    },
+    services::{
+        cv_service::{comment_service::CommentService, cv_service::CVService},
+        tests::MockDatabase,
+        user_service::UserService,
+    },
+};
+
+fn user_input(username: &str) -> CreateUserInput {
+    CreateUserInput::builder()
+        .with_username(username)
+        .with_password("password")
+        .with_primary_email(format!("{}@mail.com", username))
+        .with_sex(Sex::Female)
+        .build()
+        .unwrap()
+}
+
+async fn create_user(db: &MockDatabase, username: &str) -> ObjectId {
+    *UserService::create_user(db, user_input(username))
+        .await
+        .unwrap()
+        .id
+}
+
+#[tokio::test]
+async fn create_admin_registers_or_promotes() {
+    dotenv::dotenv().ok();
+    let db = MockDatabase::new();
+    let mut input = user_input("root");
+    input.password = String::new();
+    assert!(matches!(
+        AdminService::create_admin(&db, input).await,
+        Err(AdminServiceError::CreateAdminFailed(_))
+    ));
+    let admin = AdminService::create_admin(&db, user_input("root"))
+        .await
+        .unwrap();
+    assert_eq!(Role::Admin, admin.role);
+    assert!(bcrypt::verify("password", &admin.password).unwrap());
+
+    let alice = create_user(&db, "alice").await;
+    let promoted = AdminService::create_admin(&db, user_input("alice"))
+        .await
+        .unwrap();
+    assert_eq!(alice, *promoted.id);
+    assert_eq!(Role::Admin, promoted.role);
+    assert_eq!(2, AdminService::users(&db, None).await.unwrap().len());
+}
+
+#[tokio::test]
+async fn admins_manage_users_and_content() {
+    let db = MockDatabase::new();
+    let admin = create_user(&db, "admin").await;
+    let alice = create_user(&db, "alice").await;
+    create_user(&db, "bob").await;
+
+    let users = AdminService::users(&db, Some(" ALI ".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(
+        vec!["alice".to_string()],
+        users.into_iter().map(|u| u.username).collect::<Vec<_>>()
+    );
+    let users = AdminService::users(&db, None).await.unwrap();
+    assert_eq!(
+        vec!["admin", "alice", "bob"],
+        users
+            .iter()
+            .map(|u| u.username.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    // admins cannot lock themselves out
+    assert_eq!(
+        Err(AdminServiceError::SelfAction),
+        AdminService::suspend_user(&db, admin, admin, None).await
+    );
+    assert_eq!(
+        Err(AdminServiceError::SelfAction),
+        AdminService::set_role(&db, admin, admin, Role::User).await
+    );
+
+    let user = AdminService::suspend_user(&db, admin, alice, None)
+        .await
+        .unwrap();
+    assert!(user.is_suspended());
+    let user = AdminService::unsuspend_user(&db, alice).await.unwrap();
+    assert!(!user.is_suspended());
+    let missing = ObjectId::new();
+    assert_eq!(
+        Err(AdminServiceError::UserNotFound(missing)),
+        AdminService::suspend_user(&db, admin, missing, None).await
+    );
+
+    AdminService::set_role(&db, admin, alice, Role::Moderator)
+        .await
+        .unwrap();
+    assert!(db.get_user_by_id(alice).await.unwrap().is_moderator());
+
+    // content is deleted whoever wrote it
+    let cv_id = *CVService::create_cv(&db, alice, "CV".to_string(), "".to_string())
+        .await
+        .unwrap()
+        .id;
+    AdminService::delete_cv(&db, cv_id).await.unwrap();
+    assert!(db.get_cv_by_id(cv_id).await.is_err());
+    assert_eq!(
+        Err(AdminServiceError::CvNotFound(cv_id)),
+        AdminService::delete_cv(&db, cv_id).await
+    );
+    let input = CreateCommentInput {
+        author: alice.into(),
+        content: "comment".to_string(),
+    };
+    let comment = CommentService::create_comment(&db, input).await.unwrap();
+    let comment = AdminService::delete_comment(&db, *comment.id)
+        .await
+        .unwrap();
+    assert!(comment.deleted_at.is_some());
+    assert!(comment.content.is_empty());
+}
@@ -0,0 +1,105 @@
+//! Counting failed logins, per account and per client address, and refusing
+//! logins for a while once there are too many of them.
+
+use std::time::Duration;
+
+use mongodb::bson;
+
+use crate::{
+    common::DateTime, data_source::LoginAttemptDataSource, models::users::LoginAttempts,
+    services::user_service::error::UserServiceError,
+};
+
+/// Failures in a row after which an account is locked.
+pub const ACCOUNT_LOCKOUT_THRESHOLD: u32 = 5;
+
+/// Failures in a row after which a client address is locked, higher than for
+/// accounts since several users can share an address.
+pub const IP_LOCKOUT_THRESHOLD: u32 = 20;
+
+/// Lockout after reaching the threshold, doubled with every further failure.
+pub const BASE_LOCKOUT: Duration = Duration::from_secs(30);
+
+/// Longest lockout.
+pub const MAX_LOCKOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Failures are forgotten once there was none for this long, counted from the
+/// end of the lockout if there was one.
+pub const FAILURE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Return how long logins are refused after `failures` failures in a row, if
+/// at all.
+pub fn lockout_duration(failures: u32, threshold: u32) -> Option<Duration> {
+    if failures < threshold {
+        return None;
+    }
+    let factor = 2u32.checked_pow(failures - threshold).unwrap_or(u32::MAX);
+    Some(
+        BASE_LOCKOUT
+            .checked_mul(factor)
+            .map_or(MAX_LOCKOUT, |duration| duration.min(MAX_LOCKOUT)),
+    )
+}
+
+fn after(time: DateTime, duration: Duration) -> DateTime {
+    bson::DateTime::from_millis(time.timestamp_millis() + duration.as_millis() as i64).into()
+}
+
+fn before(time: DateTime, duration: Duration) -> DateTime {
+    bson::DateTime::from_millis(time.timestamp_millis() - duration.as_millis() as i64).into()
+}
+
+/// Return `AccountLocked` with the latest lockout end if any of the keys is
+/// locked.
+pub(super) async fn check_lockout(
+    database: &(impl LoginAttemptDataSource + std::marker::Sync),
+    keys: &[String],
+) -> Result<(), UserServiceError> {
+    let mut locked_until = None;
+    for key in keys {
+        let attempts = database
+            .get_login_attempts(key)
+            .await
+            .map_err(|err| err.into())?;
+        if let Some(attempts) = attempts.filter(LoginAttempts::is_locked) {
+            locked_until = locked_until.max(attempts.locked_until);
+        }
+    }
+    match locked_until {
+        Some(until) => Err(UserServiceError::AccountLocked(until)),
+        None => Ok(()),
+    }
+}
+
+/// Count a failed login against the key, locking it once there are
+/// `threshold` failures in a row. The failure is counted atomically, so
+/// concurrent failures are all counted.
+pub(super) async fn record_failure(
+    database: &(impl LoginAttemptDataSource + std::marker::Sync),
+    key: String,
+    threshold: u32,
+) -> Result<(), UserServiceError> {
+    let now = DateTime::now();
+    let attempts = database
+        .add_login_failure(&key, now, before(now, FAILURE_WINDOW))
+        .await
+        .map_err(|err| err.into())?;
+    match lockout_duration(attempts.failures, threshold) {
+        Some(duration) => database
+            .lock_login_attempts(&key, after(now, duration))
+            .await
+            .map_err(|err| err.into()),
+        None => Ok(()),
+    }
+}
+
+/// Forget the failures counted against the key.
+pub(super) async fn clear_failures(
+    database: &(impl LoginAttemptDataSource + std::marker::Sync),
+    key: &str,
+) -> Result<(), UserServiceError> {
+    database
+        .delete_login_attempts(key)
+        .await
+        .map_err(|err| err.into())
+}
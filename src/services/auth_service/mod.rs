@@ -1,19 +1,30 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    net::IpAddr,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use jsonwebtoken::{Algorithm, DecodingKey, Validation};
 use mongodb::bson;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    data_source::{UserDataSource, UserDataSourceError},
-    models::users::{CreateUserInput, UpdateUserInput, User},
+    data_source::{LoginAttemptDataSource, UserDataSource},
+    models::users::{CreateUserInput, LoginAttempts, Role, UpdateUserInput, User},
 };
 
 use super::user_service::error::UserServiceError;
 
+pub mod lockout;
+
 #[cfg(test)]
 mod tests;
 
+/// Lifetime of access tokens, in seconds.
+const ACCESS_TOKEN_LIFETIME: u64 = 300;
+
+/// Lifetime of refresh tokens, in seconds.
+const REFRESH_TOKEN_LIFETIME: u64 = 86400;
+
 /// The struct Claims is used to store
 /// the data of the token needed to authenticate services
 #[derive(Debug, Serialize, Deserialize)]
@@ -27,6 +38,10 @@ pub struct Claims {
     /// aud is the audience of the token,
     /// here we choose to use the website url
     pub aud: String,
+    /// role of the user when the token was issued,
+    /// tokens issued before roles existed are plain users
+    #[serde(default)]
+    pub role: Role,
 }
 
 pub struct AuthService;
@@ -153,132 +168,89 @@ impl AuthService {
         user.map(|user| user).map_err(|err| err.into())
     }
 
+    /// Encode a token for the user, an access token lasts
+    /// `ACCESS_TOKEN_LIFETIME` and a refresh token `REFRESH_TOKEN_LIFETIME`
+    fn encode_token(user: &User, is_access: bool) -> jsonwebtoken::errors::Result<String> {
+        let header = jsonwebtoken::Header::new(Algorithm::HS256);
+        let time_now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let lifetime = if is_access {
+            ACCESS_TOKEN_LIFETIME
+        } else {
+            REFRESH_TOKEN_LIFETIME
+        };
+        let claims = Claims {
+            sub: user.username.to_owned(),
+            // TODO: change audience address
+            exp: (time_now + lifetime) as usize,
+            aud: "www.example.com".to_string(),
+            role: user.role,
+        };
+        let binding = AuthService::fetch_secret_key(is_access);
+        let secret_key = binding.as_bytes();
+        jsonwebtoken::encode(
+            &header,
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(secret_key),
+        )
+    }
+
     /// Authenticate a user
     /// Return an access token + refresh token as a string if the authentication is successful
     /// otherwise return an error
+    ///
+    /// Failed logins are counted against the account and against `client_ip`,
+    /// both are locked for a while after too many failures in a row, see
+    /// `lockout`. Suspended users cannot log in.
     pub async fn authenticate(
-        database: &(impl UserDataSource + std::marker::Sync),
+        database: &(impl UserDataSource + LoginAttemptDataSource + std::marker::Sync),
         username: Option<String>,
         email: Option<String>,
         password: String,
+        client_ip: Option<IpAddr>,
     ) -> Result<(String, String), UserServiceError> {
-        if username.is_none() && email.is_none() {
+        let (user, identifier) = if let Some(username) = username {
+            (database.get_user_by_username(&username).await.ok(), username)
+        } else if let Some(email) = email {
+            (database.get_user_by_email(&email).await.ok(), email)
+        } else {
             return Err(UserServiceError::WrongEmailUsernameOrPassword);
-        }
-        if let Some(username) = username.clone() {
-            let user = database.get_user_by_username(&username).await;
-            if user.is_err() {
-                return Err(UserServiceError::WrongEmailUsernameOrPassword);
-            }
-            let user = user.unwrap();
-            let correct =
-                bcrypt::verify(password, &user.password).expect("Error verifying password");
-            if !correct {
-                return Err(UserServiceError::WrongEmailUsernameOrPassword);
-            }
-            let header = jsonwebtoken::Header::new(Algorithm::HS256);
-            let time_now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            let expiration_time_access = time_now + 300;
-            let claims = Claims {
-                sub: user.username.to_owned(),
-                // TODO: change audience address
-                exp: expiration_time_access as usize,
-                aud: "www.example.com".to_string(),
-            };
-            let binding = AuthService::fetch_secret_key(true);
-            let secret_key_access = binding.as_bytes();
-            let access_token = jsonwebtoken::encode(
-                &header,
-                &claims,
-                &jsonwebtoken::EncodingKey::from_secret(secret_key_access.as_ref()),
-            );
-            let binding_refresh = AuthService::fetch_secret_key(false);
-            let secret_key_refresh = binding_refresh.as_bytes();
-            let expiration_time_refresh = time_now + 86400;
-            let refresh_claims = Claims {
-                sub: user.username.to_owned(),
-                // TODO: change audience address
-                exp: expiration_time_refresh as usize,
-                aud: "www.example.com".to_string(),
-            };
-            let refresh_token = jsonwebtoken::encode(
-                &header,
-                &refresh_claims,
-                &jsonwebtoken::EncodingKey::from_secret(secret_key_refresh.as_ref()),
-            );
-            match access_token {
-                Ok(access_token) => match refresh_token {
-                    Ok(refresh_token) => {
-                        return Ok((access_token, refresh_token));
-                    }
-                    Err(_) => {
-                        return Err(UserServiceError::WrongEmailUsernameOrPassword);
-                    }
-                },
-                Err(_) => {
-                    return Err(UserServiceError::WrongEmailUsernameOrPassword);
+        };
+        let account_key = match &user {
+            Some(user) => LoginAttempts::account_key(*user.id),
+            None => LoginAttempts::identifier_key(&identifier),
+        };
+        let ip_key = client_ip.map(LoginAttempts::ip_key);
+        let keys = std::iter::once(account_key.clone())
+            .chain(ip_key.clone())
+            .collect::<Vec<_>>();
+        lockout::check_lockout(database, &keys).await?;
+
+        let user = match user {
+            Some(user) if bcrypt::verify(&password, &user.password).unwrap_or(false) => user,
+            _ => {
+                lockout::record_failure(database, account_key, lockout::ACCOUNT_LOCKOUT_THRESHOLD)
+                    .await?;
+                if let Some(ip_key) = ip_key {
+                    lockout::record_failure(database, ip_key, lockout::IP_LOCKOUT_THRESHOLD)
+                        .await?;
                 }
-            }
-        } else if let Some(email) = email.clone() {
-            let user = database.get_user_by_email(&email).await;
-            if user.is_err() {
-                return Err(UserServiceError::WrongEmailUsernameOrPassword);
-            }
-            let user = user.unwrap();
-            if let Err(_) = bcrypt::verify(password, user.password.as_str()) {
                 return Err(UserServiceError::WrongEmailUsernameOrPassword);
             }
-            let time_now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
-            let expiration_time_access = time_now + 300;
-            let header = jsonwebtoken::Header::new(Algorithm::HS256);
-            let claims = Claims {
-                sub: user.username.to_owned(),
-                // TODO: change audience address
-                exp: expiration_time_access as usize,
-                aud: "www.example.com".to_string(),
-            };
-            let binding = AuthService::fetch_secret_key(true);
-            let secret_key_access = binding.as_bytes();
-            let access_token = jsonwebtoken::encode(
-                &header,
-                &claims,
-                &jsonwebtoken::EncodingKey::from_secret(secret_key_access.as_ref()),
-            );
-            let binding_refresh = AuthService::fetch_secret_key(false);
-            let secret_key_refresh = binding_refresh.as_bytes();
-            let expiration_time_refresh = time_now + 86400;
-            let refresh_claims = Claims {
-                sub: user.username.to_owned(),
-                // TODO: change audience address
-                exp: expiration_time_refresh as usize,
-                aud: "www.example.com".to_string(),
-            };
-            let refresh_token = jsonwebtoken::encode(
-                &header,
-                &refresh_claims,
-                &jsonwebtoken::EncodingKey::from_secret(secret_key_refresh.as_ref()),
-            );
-            match access_token {
-                Ok(access_token) => match refresh_token {
-                    Ok(refresh_token) => {
-                        return Ok((access_token, refresh_token));
-                    }
-                    Err(_) => {
-                        return Err(UserServiceError::WrongEmailUsernameOrPassword);
-                    }
-                },
-                Err(_) => {
-                    return Err(UserServiceError::WrongEmailUsernameOrPassword);
-                }
-            }
+        };
+        // the address keeps its failures, it may be guessing other accounts
+        lockout::clear_failures(database, &account_key).await?;
+        if let Some(until) = user.suspended_until.filter(|_| user.is_suspended()) {
+            return Err(UserServiceError::AccountSuspended(until));
         }
-        return Err(UserServiceError::WrongEmailUsernameOrPassword);
+
+        let access_token = AuthService::encode_token(&user, true)
+            .map_err(|_| UserServiceError::WrongEmailUsernameOrPassword)?;
+        let refresh_token = AuthService::encode_token(&user, false)
+            .map_err(|_| UserServiceError::WrongEmailUsernameOrPassword)?;
+        Ok((access_token, refresh_token))
     }
 
     /// Change the password of the user with the given id
@@ -302,41 +274,22 @@ impl AuthService {
         user.map(|user| user).map_err(|err| err.into())
     }
 
-    /// Function to generate a new access token from a refresh token
+    /// Function to generate a new access token from a refresh token,
+    /// suspended users cannot get new access tokens
     pub async fn generate_new_access_token(
         database: &(impl UserDataSource + std::marker::Sync),
         refresh_token: String,
-    ) -> Result<String, UserDataSourceError> {
-        let token_data = AuthService::decode_token(&refresh_token, false);
-        if token_data.is_none() {
-            return Err(UserDataSourceError::InvalidToken);
-        }
-        let token_data = token_data.unwrap();
-        let binding = AuthService::fetch_secret_key(true);
-        let secret_key_access = binding.as_bytes();
-        let header = jsonwebtoken::Header::new(Algorithm::HS256);
-        let time_now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let expiration_time_access = time_now + 300;
-        let claims = Claims {
-            sub: token_data.sub,
-            exp: expiration_time_access as usize,
-            aud: "www.example.com".to_string(),
-        };
-        let access_token = jsonwebtoken::encode(
-            &header,
-            &claims,
-            &jsonwebtoken::EncodingKey::from_secret(secret_key_access.as_ref()),
-        );
-        match access_token {
-            Ok(access_token) => {
-                return Ok(access_token);
-            }
-            Err(_) => {
-                return Err(UserDataSourceError::InvalidToken);
-            }
+    ) -> Result<String, UserServiceError> {
+        let token_data = AuthService::decode_token(&refresh_token, false)
+            .ok_or(UserServiceError::InvalidToken)?;
+        // read the role again so that role changes apply to new access tokens
+        let user = database
+            .get_user_by_username(&token_data.sub)
+            .await
+            .map_err(|_| UserServiceError::InvalidToken)?;
+        if let Some(until) = user.suspended_until.filter(|_| user.is_suspended()) {
+            return Err(UserServiceError::AccountSuspended(until));
         }
+        AuthService::encode_token(&user, true).map_err(|_| UserServiceError::InvalidToken)
     }
 }
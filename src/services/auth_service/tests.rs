@@ -1,10 +1,16 @@
 use super::super::tests::MockDatabase;
+use crate::common::DateTime;
+use crate::filters::client_ip;
+use crate::data_source::{LoginAttemptDataSource, UserDataSource};
 use crate::models::education::Education;
 use crate::models::sex::Sex;
 use crate::models::users::create_user_input::CreateUserInputBuilder;
-use crate::models::users::CreateUserInput;
-use crate::services::auth_service::{AuthService, Claims};
-use mongodb::bson::Uuid;
+use crate::models::users::{CreateUserInput, LoginAttempts, Role};
+use crate::services::auth_service::{lockout, AuthService, Claims};
+use crate::services::user_service::error::UserServiceError;
+use mongodb::bson::{self, Uuid};
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
 
 pub fn create_demo_user_input(test_uuid: Uuid) -> CreateUserInput {
     let demo = CreateUserInputBuilder::default()
@@ -119,6 +125,7 @@ async fn authenticate_user_test() {
         Some(user2.username),
         None,
         "test_password".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -129,6 +136,7 @@ async fn authenticate_user_test() {
     )
     .unwrap();
     assert_eq!(token_data.claims.sub, "test_user");
+    assert_eq!(token_data.claims.role, Role::User);
 }
 
 #[tokio::test]
@@ -144,7 +152,223 @@ async fn invalid_authenticate() {
         None,
         // Introduce wrong password
         "test_password123".to_string(),
+        None,
     )
     .await
     .expect_err("Should return error due to wrong password");
 }
+
+#[tokio::test]
+async fn refreshed_token_carries_current_role() {
+    dotenv::dotenv().ok();
+    let db = MockDatabase::new();
+    let user = AuthService::register(&db, create_demo_user_input(Uuid::new()))
+        .await
+        .unwrap();
+    let (_, refresh_token) = AuthService::authenticate(
+        &db,
+        Some(user.username),
+        None,
+        "test_password".to_string(),
+        None,
+    )
+    .await
+    .unwrap();
+    db.set_role(*user.id, Role::Moderator).await.unwrap();
+    let access_token = AuthService::generate_new_access_token(&db, refresh_token)
+        .await
+        .unwrap();
+    let claims = AuthService::decode_token(&access_token, true).unwrap();
+    assert_eq!(Role::Moderator, claims.role);
+}
+
+async fn login(
+    db: &MockDatabase,
+    password: &str,
+    ip: Option<IpAddr>,
+) -> Result<(), UserServiceError> {
+    AuthService::authenticate(
+        db,
+        Some("test_user".to_string()),
+        None,
+        password.to_string(),
+        ip,
+    )
+    .await
+    .map(|_| ())
+}
+
+#[test]
+fn lockout_backs_off_exponentially() {
+    let threshold = lockout::ACCOUNT_LOCKOUT_THRESHOLD;
+    assert_eq!(None, lockout::lockout_duration(threshold - 1, threshold));
+    assert_eq!(
+        Some(lockout::BASE_LOCKOUT),
+        lockout::lockout_duration(threshold, threshold)
+    );
+    assert_eq!(
+        Some(lockout::BASE_LOCKOUT * 4),
+        lockout::lockout_duration(threshold + 2, threshold)
+    );
+    assert_eq!(
+        Some(lockout::MAX_LOCKOUT),
+        lockout::lockout_duration(u32::MAX, threshold)
+    );
+}
+
+#[tokio::test]
+async fn account_is_locked_after_failed_logins() {
+    dotenv::dotenv().ok();
+    let db = MockDatabase::new();
+    let user = AuthService::register(&db, create_demo_user_input(Uuid::new()))
+        .await
+        .unwrap();
+    let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    for _ in 1..lockout::ACCOUNT_LOCKOUT_THRESHOLD {
+        assert_eq!(
+            Err(UserServiceError::WrongEmailUsernameOrPassword),
+            login(&db, "wrong", Some(ip)).await
+        );
+    }
+    // a success before the threshold resets the count
+    login(&db, "test_password", Some(ip)).await.unwrap();
+    for _ in 0..lockout::ACCOUNT_LOCKOUT_THRESHOLD {
+        assert_eq!(
+            Err(UserServiceError::WrongEmailUsernameOrPassword),
+            login(&db, "wrong", Some(ip)).await
+        );
+    }
+    // even the right password is refused while locked
+    assert!(matches!(
+        login(&db, "test_password", Some(ip)).await,
+        Err(UserServiceError::AccountLocked(_))
+    ));
+    let key = LoginAttempts::account_key(*user.id);
+    let attempts = db.get_login_attempts(&key).await.unwrap().unwrap();
+    assert_eq!(lockout::ACCOUNT_LOCKOUT_THRESHOLD, attempts.failures);
+
+    // once the lockout is over, the next failure locks for twice as long
+    let mut expired = attempts.clone();
+    expired.locked_until = Some(DateTime::now());
+    db.set_login_attempts(expired).await.unwrap();
+    login(&db, "wrong", Some(ip)).await.unwrap_err();
+    let attempts = db.get_login_attempts(&key).await.unwrap().unwrap();
+    let locked_for = attempts.locked_until.unwrap().timestamp_millis()
+        - attempts.last_failure.timestamp_millis();
+    assert_eq!(lockout::BASE_LOCKOUT.as_millis() as i64 * 2, locked_for);
+
+    // the address keeps every failure, refused logins aside, and is not
+    // locked yet
+    let ip_key = LoginAttempts::ip_key(ip);
+    let ip_attempts = db.get_login_attempts(&ip_key).await.unwrap().unwrap();
+    assert_eq!(lockout::ACCOUNT_LOCKOUT_THRESHOLD * 2, ip_attempts.failures);
+    assert!(!ip_attempts.is_locked());
+}
+
+#[tokio::test]
+async fn address_is_locked_across_accounts() {
+    dotenv::dotenv().ok();
+    let db = MockDatabase::new();
+    let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    for i in 0..lockout::IP_LOCKOUT_THRESHOLD {
+        let rs = AuthService::authenticate(
+            &db,
+            Some(format!("unknown_{}", i)),
+            None,
+            "password".to_string(),
+            Some(ip),
+        )
+        .await;
+        assert_eq!(Err(UserServiceError::WrongEmailUsernameOrPassword), rs);
+    }
+    AuthService::register(&db, create_demo_user_input(Uuid::new()))
+        .await
+        .unwrap();
+    assert!(matches!(
+        login(&db, "test_password", Some(ip)).await,
+        Err(UserServiceError::AccountLocked(_))
+    ));
+    // other addresses can still log in
+    login(&db, "test_password", None).await.unwrap();
+}
+
+#[tokio::test]
+async fn concurrent_failures_are_all_counted() {
+    dotenv::dotenv().ok();
+    let db = MockDatabase::new();
+    let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let logins = (0..lockout::ACCOUNT_LOCKOUT_THRESHOLD).map(|i| {
+        AuthService::authenticate(
+            &db,
+            Some(format!("unknown_{}", i % 2)),
+            None,
+            "password".to_string(),
+            Some(ip),
+        )
+    });
+    async_graphql::futures_util::future::join_all(logins).await;
+    let ip_key = LoginAttempts::ip_key(ip);
+    let ip_attempts = db.get_login_attempts(&ip_key).await.unwrap().unwrap();
+    assert_eq!(lockout::ACCOUNT_LOCKOUT_THRESHOLD, ip_attempts.failures);
+}
+
+#[test]
+fn client_ip_is_forwarded_by_trusted_proxies_only() {
+    let client = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7));
+    let proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let spoofed = "198.51.100.1, 203.0.113.7";
+    assert_eq!(Some(proxy), client_ip(Some(proxy), Some(spoofed), &[]));
+    assert_eq!(Some(client), client_ip(Some(proxy), Some(spoofed), &[proxy]));
+    // the client cannot pretend to be another one by adding to the header
+    assert_eq!(Some(client), client_ip(Some(client), Some(spoofed), &[proxy]));
+    // proxies in a row are skipped, and an invalid entry stops the walk
+    let chain = "203.0.113.7, 10.0.0.1";
+    assert_eq!(Some(client), client_ip(Some(proxy), Some(chain), &[proxy]));
+    assert_eq!(Some(proxy), client_ip(Some(proxy), Some("unknown"), &[proxy]));
+    assert_eq!(None, client_ip(None, Some(spoofed), &[proxy]));
+}
+
+#[tokio::test]
+async fn suspended_user_cannot_login_or_refresh() {
+    dotenv::dotenv().ok();
+    let db = MockDatabase::new();
+    let user = AuthService::register(&db, create_demo_user_input(Uuid::new()))
+        .await
+        .unwrap();
+    let (_, refresh_token) = AuthService::authenticate(
+        &db,
+        Some(user.username.clone()),
+        None,
+        "test_password".to_string(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let until = DateTime::now().timestamp_millis() + Duration::from_secs(3600).as_millis() as i64;
+    let until: DateTime = bson::DateTime::from_millis(until).into();
+    db.set_suspended_until(*user.id, Some(until)).await.unwrap();
+    assert_eq!(
+        Err(UserServiceError::AccountSuspended(until)),
+        login(&db, "test_password", None).await
+    );
+    // a wrong password does not tell whether the user is suspended
+    assert_eq!(
+        Err(UserServiceError::WrongEmailUsernameOrPassword),
+        login(&db, "wrong", None).await
+    );
+    assert_eq!(
+        Err(UserServiceError::AccountSuspended(until)),
+        AuthService::generate_new_access_token(&db, refresh_token.clone()).await
+    );
+
+    // an elapsed suspension no longer applies
+    db.set_suspended_until(*user.id, Some(DateTime::now()))
+        .await
+        .unwrap();
+    login(&db, "test_password", None).await.unwrap();
+    AuthService::generate_new_access_token(&db, refresh_token)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,79 @@
+use async_graphql::ErrorExtensions;
+use mongodb::bson::oid::ObjectId;
+use std::fmt;
+
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ConversationServiceError {
+    /// Error when the recipient of a message does not exist.
+    UserNotFound(ObjectId),
+
+    /// Error when a user sends a message to themselves.
+    CannotMessageSelf,
+
+    /// Error when starting a conversation with a user who is not a friend.
+    NotFriends(ObjectId),
+
+    /// Error when one of the users has blocked the other.
+    UserBlocked(ObjectId),
+
+    /// Error when the conversation does not exist or the user is not part of
+    /// it.
+    ConversationNotFound(ObjectId),
+
+    /// Error when the message is empty.
+    EmptyMessage,
+
+    /// Error when the message is longer than the limit, in characters.
+    MessageTooLong(usize),
+
+    /// Database error
+    DatabaseError,
+}
+
+impl fmt::Display for ConversationServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversationServiceError::UserNotFound(id) => {
+                write!(f, "User {:?} not found", id)
+            }
+            ConversationServiceError::CannotMessageSelf => {
+                write!(f, "Users cannot send messages to themselves")
+            }
+            ConversationServiceError::NotFriends(id) => {
+                write!(f, "User {:?} is not a friend", id)
+            }
+            ConversationServiceError::UserBlocked(id) => {
+                write!(f, "User {:?} is blocked", id)
+            }
+            ConversationServiceError::ConversationNotFound(id) => {
+                write!(f, "Conversation {:?} not found", id)
+            }
+            ConversationServiceError::EmptyMessage => {
+                write!(f, "Message cannot be empty")
+            }
+            ConversationServiceError::MessageTooLong(limit) => {
+                write!(f, "Message is longer than {} characters", limit)
+            }
+            ConversationServiceError::DatabaseError => {
+                write!(f, "Database error")
+            }
+        }
+    }
+}
+
+impl ErrorExtensions for ConversationServiceError {
+    fn extend(&self) -> async_graphql::Error {
+        let code = match self {
+            ConversationServiceError::UserNotFound(_) => "USER_NOT_FOUND",
+            ConversationServiceError::CannotMessageSelf => "CANNOT_MESSAGE_SELF",
+            ConversationServiceError::NotFriends(_) => "NOT_FRIENDS",
+            ConversationServiceError::UserBlocked(_) => "USER_BLOCKED",
+            ConversationServiceError::ConversationNotFound(_) => "CONVERSATION_NOT_FOUND",
+            ConversationServiceError::EmptyMessage => "EMPTY_MESSAGE",
+            ConversationServiceError::MessageTooLong(_) => "MESSAGE_TOO_LONG",
+            ConversationServiceError::DatabaseError => "DATABASE_ERROR",
+        };
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| e.set("code", code))
+    }
+}
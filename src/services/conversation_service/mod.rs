@@ -0,0 +1,156 @@
+pub mod error;
+#[cfg(test)]
+mod tests;
+
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    common::DateTime,
+    data_source::{BlockDataSource, ConversationDataSource, FriendsListDataSource, UserDataSource},
+    models::conversation::{Conversation, Message},
+};
+
+use self::error::ConversationServiceError;
+
+use super::user_service::{block_service::BlockService, UserService};
+
+/// Longest message that can be sent, in characters.
+pub const MAX_MESSAGE_LENGTH: usize = 2000;
+
+pub struct ConversationService;
+
+impl ConversationService {
+    /// Send a message to the recipient. The first message between two users
+    /// starts their conversation, which is only allowed between friends.
+    /// Blocked users cannot message each other, even in an existing
+    /// conversation.
+    pub async fn send_message(
+        db: &(impl UserDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + ConversationDataSource
+              + std::marker::Sync),
+        sender_id: ObjectId,
+        recipient_id: ObjectId,
+        content: impl Into<String>,
+    ) -> Result<Message, ConversationServiceError> {
+        let content = content.into().trim().to_string();
+        if content.is_empty() {
+            return Err(ConversationServiceError::EmptyMessage);
+        }
+        if content.chars().count() > MAX_MESSAGE_LENGTH {
+            return Err(ConversationServiceError::MessageTooLong(MAX_MESSAGE_LENGTH));
+        }
+        if sender_id == recipient_id {
+            return Err(ConversationServiceError::CannotMessageSelf);
+        }
+        UserService::get_user_by_id(db, recipient_id)
+            .await
+            .map_err(|_| ConversationServiceError::UserNotFound(recipient_id))?;
+        let blocked = BlockService::is_blocked(db, sender_id, recipient_id)
+            .await
+            .map_err(|_| ConversationServiceError::DatabaseError)?;
+        if blocked {
+            return Err(ConversationServiceError::UserBlocked(recipient_id));
+        }
+
+        let mut participant_ids = vec![sender_id, recipient_id];
+        participant_ids.sort();
+        let conversation = db
+            .find_conversation(participant_ids.clone())
+            .await
+            .map_err(|err| err.into())?;
+        let conversation = match conversation {
+            Some(conversation) => conversation,
+            None => {
+                if !UserService::are_friends(db, sender_id, recipient_id).await {
+                    return Err(ConversationServiceError::NotFriends(recipient_id));
+                }
+                // another first message may have created it in the meantime
+                db.add_conversation(Conversation::new(participant_ids))
+                    .await
+                    .map_err(|err| err.into())?
+            }
+        };
+
+        let message = Message::new(*conversation.id, sender_id, content);
+        db.add_message(message.clone())
+            .await
+            .map_err(|err| err.into())?;
+        Ok(message)
+    }
+
+    /// Return the conversation if the user is part of it.
+    pub async fn get_conversation(
+        db: &(impl ConversationDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        conversation_id: ObjectId,
+    ) -> Result<Conversation, ConversationServiceError> {
+        let conversation = db
+            .get_conversation(conversation_id)
+            .await
+            .map_err(|err| err.into())?;
+        match conversation {
+            Some(conversation) if conversation.has_participant(user_id) => Ok(conversation),
+            _ => Err(ConversationServiceError::ConversationNotFound(
+                conversation_id,
+            )),
+        }
+    }
+
+    /// Return the conversations of the user, most recently active first.
+    pub async fn conversations(
+        db: &(impl ConversationDataSource + std::marker::Sync),
+        user_id: ObjectId,
+    ) -> Result<Vec<Conversation>, ConversationServiceError> {
+        db.get_conversations_by_user(user_id)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Return the messages of a conversation of the user, newest first.
+    pub async fn messages(
+        db: &(impl ConversationDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        conversation_id: ObjectId,
+    ) -> Result<Vec<Message>, ConversationServiceError> {
+        Self::get_conversation(db, user_id, conversation_id).await?;
+        db.get_messages(conversation_id)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    pub async fn last_message(
+        db: &(impl ConversationDataSource + std::marker::Sync),
+        conversation_id: ObjectId,
+    ) -> Result<Option<Message>, ConversationServiceError> {
+        db.get_last_message(conversation_id)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Mark the messages the user received in the conversation as read,
+    /// return how many were unread.
+    pub async fn mark_conversation_read(
+        db: &(impl ConversationDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        conversation_id: ObjectId,
+    ) -> Result<u64, ConversationServiceError> {
+        Self::get_conversation(db, user_id, conversation_id).await?;
+        db.mark_messages_read(conversation_id, user_id, DateTime::now())
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Count the messages the user has not read yet in the conversation.
+    pub async fn unread_count(
+        db: &(impl ConversationDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        conversation_id: ObjectId,
+    ) -> Result<u64, ConversationServiceError> {
+        Self::get_conversation(db, user_id, conversation_id).await?;
+        db.count_unread_messages(conversation_id, user_id)
+            .await
+            .map_err(|err| err.into())
+    }
+}
@@ -0,0 +1,171 @@
+use mongodb::bson::oid::ObjectId;
+
+use super::{error::ConversationServiceError, ConversationService, MAX_MESSAGE_LENGTH};
+use crate::services::{
+    tests::{create_user, make_friends, MockDatabase},
+    user_service::{block_service::BlockService, UserService},
+};
+
+#[tokio::test]
+async fn only_friends_can_start_a_conversation() {
+    let db = MockDatabase::new();
+    let user = create_user(&db, "user").await;
+    let friend = create_user(&db, "friend").await;
+    let stranger = create_user(&db, "stranger").await;
+    make_friends(&db, user, friend).await;
+
+    assert_eq!(
+        Err(ConversationServiceError::NotFriends(stranger)),
+        ConversationService::send_message(&db, user, stranger, "hi").await
+    );
+    assert_eq!(
+        Err(ConversationServiceError::CannotMessageSelf),
+        ConversationService::send_message(&db, user, user, "hi").await
+    );
+    let missing = ObjectId::new();
+    assert_eq!(
+        Err(ConversationServiceError::UserNotFound(missing)),
+        ConversationService::send_message(&db, user, missing, "hi").await
+    );
+    assert_eq!(
+        Err(ConversationServiceError::EmptyMessage),
+        ConversationService::send_message(&db, user, friend, "  ").await
+    );
+    let long = "a".repeat(MAX_MESSAGE_LENGTH + 1);
+    assert_eq!(
+        Err(ConversationServiceError::MessageTooLong(MAX_MESSAGE_LENGTH)),
+        ConversationService::send_message(&db, user, friend, long).await
+    );
+
+    let first = ConversationService::send_message(&db, user, friend, " hi ")
+        .await
+        .unwrap();
+    assert_eq!("hi", first.content);
+    assert_eq!(user, first.sender_id);
+    let reply = ConversationService::send_message(&db, friend, user, "hello")
+        .await
+        .unwrap();
+    // both messages belong to the same conversation, whoever sent them
+    assert_eq!(first.conversation_id, reply.conversation_id);
+
+    // the conversation goes on after they are no longer friends
+    UserService::unfriend(&db, user, friend).await.unwrap();
+    ConversationService::send_message(&db, user, friend, "still there?")
+        .await
+        .unwrap();
+    let messages = ConversationService::messages(&db, friend, *first.conversation_id)
+        .await
+        .unwrap();
+    let contents = messages
+        .iter()
+        .map(|message| message.content.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["still there?", "hello", "hi"], contents);
+}
+
+#[tokio::test]
+async fn blocked_users_cannot_message() {
+    let db = MockDatabase::new();
+    let user = create_user(&db, "user").await;
+    let friend = create_user(&db, "friend").await;
+    make_friends(&db, user, friend).await;
+    ConversationService::send_message(&db, user, friend, "hi")
+        .await
+        .unwrap();
+
+    BlockService::block_user(&db, friend, user).await.unwrap();
+    assert_eq!(
+        Err(ConversationServiceError::UserBlocked(friend)),
+        ConversationService::send_message(&db, user, friend, "hi").await
+    );
+    assert_eq!(
+        Err(ConversationServiceError::UserBlocked(user)),
+        ConversationService::send_message(&db, friend, user, "hi").await
+    );
+}
+
+#[tokio::test]
+async fn conversations_and_read_receipts() {
+    let db = MockDatabase::new();
+    let user = create_user(&db, "user").await;
+    let friend = create_user(&db, "friend").await;
+    let other = create_user(&db, "other").await;
+    let outsider = create_user(&db, "outsider").await;
+    make_friends(&db, user, friend).await;
+    make_friends(&db, user, other).await;
+
+    let with_friend = ConversationService::send_message(&db, friend, user, "one")
+        .await
+        .unwrap()
+        .conversation_id;
+    ConversationService::send_message(&db, friend, user, "two")
+        .await
+        .unwrap();
+    let with_other = ConversationService::send_message(&db, user, other, "hey")
+        .await
+        .unwrap()
+        .conversation_id;
+
+    let conversations = ConversationService::conversations(&db, user).await.unwrap();
+    let ids = conversations
+        .iter()
+        .map(|conversation| conversation.id)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![with_other, with_friend], ids);
+    assert_eq!(
+        1,
+        ConversationService::conversations(&db, friend)
+            .await
+            .unwrap()
+            .len()
+    );
+
+    // outsiders cannot see or read the conversation
+    assert_eq!(
+        Err(ConversationServiceError::ConversationNotFound(*with_friend)),
+        ConversationService::messages(&db, outsider, *with_friend).await
+    );
+    assert_eq!(
+        Err(ConversationServiceError::ConversationNotFound(*with_friend)),
+        ConversationService::mark_conversation_read(&db, outsider, *with_friend).await
+    );
+
+    assert_eq!(
+        Ok(2),
+        ConversationService::unread_count(&db, user, *with_friend).await
+    );
+    assert_eq!(
+        Ok(0),
+        ConversationService::unread_count(&db, friend, *with_friend).await
+    );
+    assert_eq!(
+        Ok(2),
+        ConversationService::mark_conversation_read(&db, user, *with_friend).await
+    );
+    assert_eq!(
+        Ok(0),
+        ConversationService::unread_count(&db, user, *with_friend).await
+    );
+    let messages = ConversationService::messages(&db, friend, *with_friend)
+        .await
+        .unwrap();
+    assert!(messages.iter().all(|message| message.read_at.is_some()));
+    let last = ConversationService::last_message(&db, *with_friend)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!("two", last.content);
+
+    // the reply is unread until the friend reads it
+    ConversationService::send_message(&db, user, friend, "three")
+        .await
+        .unwrap();
+    assert_eq!(
+        Ok(1),
+        ConversationService::unread_count(&db, friend, *with_friend).await
+    );
+    assert_eq!(
+        Ok(0),
+        ConversationService::mark_conversation_read(&db, user, *with_friend).await
+    );
+}
@@ -1,22 +1,30 @@
 #[cfg(test)]
 mod tests;
-use async_graphql::futures_util::StreamExt;
+use async_graphql::futures_util::{self, StreamExt};
 use futures_core::stream::BoxStream;
 use mongodb::bson::oid::ObjectId;
 
 use crate::{
-    data_source::{cv::bookmark::BookmarkDataSource, CVDataSource, UserDataSource},
+    data_source::{
+        cv::bookmark::BookmarkDataSource, BlockDataSource, CVDataSource, FriendsListDataSource,
+        UserDataSource,
+    },
     models::cv::CV,
     services::user_service::error::UserServiceError,
 };
 
-use super::error::CVServiceError;
+use super::{cv_service::CVService, error::CVServiceError};
 
 pub struct BookmarkService;
 
 impl BookmarkService {
     pub async fn bookmark_cv(
-        db: &(impl BookmarkDataSource + UserDataSource + CVDataSource + std::marker::Sync),
+        db: &(impl BookmarkDataSource
+              + UserDataSource
+              + CVDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
         user_id: ObjectId,
         cv_id: ObjectId,
     ) -> Result<(), CVServiceError> {
@@ -33,8 +41,9 @@ impl BookmarkService {
                 }
             }
         }
-        if let Err(e) = db.get_cv_by_id(cv_id).await {
-            match e {
+        let cv = match db.get_cv_by_id(cv_id).await {
+            Ok(cv) => cv,
+            Err(e) => match e {
                 crate::data_source::CVDataSourceError::IdNotFound(_) => {
                     return Err(CVServiceError::LikeFailed("CV not found".to_string()));
                 }
@@ -46,7 +55,10 @@ impl BookmarkService {
                         "Something went wrong".to_string(),
                     ));
                 }
-            }
+            },
+        };
+        if !CVService::can_view_cv(db, Some(user_id), &cv).await? {
+            return Err(CVServiceError::CVNotVisible(cv_id));
         }
         db.add_bookmark(user_id, cv_id).await.map_err(|e| e.into())
     }
@@ -89,8 +101,13 @@ impl BookmarkService {
             .map_err(|e| e.into())
     }
 
+    /// Return the CVs bookmarked by the user, leaving out those the user can no longer see.
     pub async fn get_bookmarked_cvs_of_user(
-        db: &(impl BookmarkDataSource + UserDataSource + std::marker::Sync),
+        db: &(impl BookmarkDataSource
+              + UserDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
         user_id: ObjectId,
     ) -> Result<BoxStream<Result<CV, CVServiceError>>, CVServiceError> {
         if let Err(e) = db.get_user_by_id(user_id).await.map_err(|e| e.into()) {
@@ -106,10 +123,24 @@ impl BookmarkService {
                 }
             }
         }
-        db.get_bookmarked_cvs_of_user(user_id)
+        let cvs = db
+            .get_bookmarked_cvs_of_user(user_id)
             .await
-            .map_err(|e| e.into())
-            .map(|stream| stream.map(|item| item.map_err(|e| e.into())).boxed())
+            .map_err(|e| e.into())?
+            .collect::<Vec<_>>()
+            .await;
+        let mut visible_cvs = Vec::with_capacity(cvs.len());
+        for cv in cvs {
+            match cv {
+                Ok(cv) => {
+                    if CVService::can_view_cv(db, Some(user_id), &cv).await? {
+                        visible_cvs.push(Ok(cv));
+                    }
+                }
+                Err(e) => visible_cvs.push(Err(e.into())),
+            }
+        }
+        Ok(futures_util::stream::iter(visible_cvs).boxed())
     }
 
     pub async fn get_bookmark_count_of_cv(
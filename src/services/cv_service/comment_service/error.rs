@@ -22,6 +22,13 @@ pub enum CommentServiceError {
     DeleteCommentFailed,
     /// Database error
     DatabaseError,
+    /// Error when the author of the comment or CV has blocked the user, or
+    /// the other way around
+    UserBlocked(bson::oid::ObjectId),
+    /// Error when editing or replying to a deleted comment
+    CommentDeleted(bson::oid::ObjectId),
+    /// Error when the user is not the author of the comment
+    NotAuthor,
 }
 
 impl fmt::Display for CommentServiceError {
@@ -51,6 +58,15 @@ impl fmt::Display for CommentServiceError {
             CommentServiceError::DatabaseError => {
                 write!(f, "Database error")
             }
+            CommentServiceError::UserBlocked(id) => {
+                write!(f, "User {} is blocked", id)
+            }
+            CommentServiceError::CommentDeleted(id) => {
+                write!(f, "Comment {} is deleted", id)
+            }
+            CommentServiceError::NotAuthor => {
+                write!(f, "Only the author of the comment can do this")
+            }
         }
     }
 }
@@ -63,6 +79,9 @@ static CREATE_COMMENT_FAILED: &str = "CREATE_COMMENT_FAILED";
 static UPDATE_COMMENT_FAILED: &str = "UPDATE_COMMENT_FAILED";
 static DELETE_COMMENT_FAILED: &str = "DELETE_COMMENT_FAILED";
 static DATABASE_ERROR: &str = "DATABASE_ERROR";
+static USER_BLOCKED: &str = "USER_BLOCKED";
+static COMMENT_DELETED: &str = "COMMENT_DELETED";
+static NOT_AUTHOR: &str = "NOT_AUTHOR";
 
 impl ErrorExtensions for CommentServiceError {
     fn extend(&self) -> async_graphql::Error {
@@ -91,6 +110,15 @@ impl ErrorExtensions for CommentServiceError {
             CommentServiceError::DatabaseError => {
                 async_graphql::Error::new(&self.to_string()).extend_with(|_, e| e.set("code", DATABASE_ERROR))
             }
+            CommentServiceError::UserBlocked(_) => {
+                async_graphql::Error::new(self.to_string()).extend_with(|_, e| e.set("code", USER_BLOCKED))
+            }
+            CommentServiceError::CommentDeleted(_) => {
+                async_graphql::Error::new(self.to_string()).extend_with(|_, e| e.set("code", COMMENT_DELETED))
+            }
+            CommentServiceError::NotAuthor => {
+                async_graphql::Error::new(self.to_string()).extend_with(|_, e| e.set("code", NOT_AUTHOR))
+            }
         }
     }
 }
@@ -0,0 +1,56 @@
+//! Find the `@username` mentions in the content of comments.
+
+/// A mention found in a content, before the username is checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedMention {
+    pub username: String,
+    /// Offset of the `@`, in UTF-16 code units as in JavaScript strings.
+    pub start: usize,
+    /// Offset just after the username, in UTF-16 code units.
+    pub end: usize,
+}
+
+fn is_username_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
+/// Return the `@username` mentions of the content in order of appearance.
+/// An `@` right after a word, as in an email address, is not a mention, and
+/// a dot or dash ending a username is taken as punctuation.
+pub fn parse_mentions(content: &str) -> Vec<ParsedMention> {
+    let chars = content.chars().collect::<Vec<_>>();
+    // UTF-16 offset of each character, and of the end of the content
+    let mut offsets = Vec::with_capacity(chars.len() + 1);
+    let mut offset = 0;
+    for c in &chars {
+        offsets.push(offset);
+        offset += c.len_utf16();
+    }
+    offsets.push(offset);
+    let mut mentions = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let starts_mention =
+            chars[i] == '@' && (i == 0 || !(is_username_char(chars[i - 1]) || chars[i - 1] == '@'));
+        if starts_mention {
+            let mut end = i + 1;
+            while end < chars.len() && is_username_char(chars[end]) {
+                end += 1;
+            }
+            while end > i + 1 && matches!(chars[end - 1], '.' | '-') {
+                end -= 1;
+            }
+            if end > i + 1 {
+                mentions.push(ParsedMention {
+                    username: chars[i + 1..end].iter().collect(),
+                    start: offsets[i],
+                    end: offsets[end],
+                });
+                i = end;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    mentions
+}
@@ -1,35 +1,217 @@
+use std::collections::HashMap;
+
 use async_graphql::futures_util::FutureExt;
 use async_graphql::futures_util::{stream::BoxStream, StreamExt, TryStreamExt};
 use mongodb::bson::oid::ObjectId;
 
 mod error;
+pub mod mention;
 
 pub use error::CommentServiceError;
 
+use crate::data_source::BlockDataSource;
 use crate::data_source::BookmarkDataSource;
-use crate::data_source::LikeDataSource;
+use crate::data_source::FriendsListDataSource;
+use crate::data_source::MentionNotifier;
+use crate::data_source::ReactionDataSource;
+use crate::data_source::UserDataSource;
 use crate::data_source::{CVDataSource, CVDataSourceError, CommentDataSource};
-use crate::models::comment::{Comment, CreateCommentInput, Like, UpdateCommentInput};
+use crate::common::DateTime;
+use crate::models::comment::{
+    Comment, CommentSort, CommentVersion, CreateCommentInput, Like, Mention,
+};
+use crate::models::cv::CV;
+use crate::models::reaction::{ReactionKind, ReactionTarget};
+use crate::services::cv_service::cv_service::CVService;
+use crate::services::reaction_service::ReactionService;
+use crate::services::user_service::{block_service::BlockService, UserService};
+
+use self::mention::parse_mentions;
+
+/// Most distinct usernames looked up for the mentions of one comment. Further
+/// usernames are left as plain text.
+pub const MAX_MENTIONED_USERNAMES: usize = 10;
+
+/// Number of comments of a thread returned when no limit is given.
+pub const DEFAULT_THREAD_LIMIT: usize = 100;
+
+/// Most comments of a thread returned at once.
+pub const MAX_THREAD_LIMIT: usize = 500;
+
+/// The number of comments of a thread to return for the requested `limit`.
+pub fn thread_limit(limit: Option<i32>) -> usize {
+    limit.map_or(DEFAULT_THREAD_LIMIT, |limit| {
+        (limit.max(1) as usize).min(MAX_THREAD_LIMIT)
+    })
+}
+
+/// Deepest level replies can be nested at when `MAX_COMMENT_DEPTH` is not
+/// set, comments on a CV being at level 0.
+pub const DEFAULT_MAX_COMMENT_DEPTH: u32 = 3;
+
+/// Deepest level replies can be nested at. Replies to comments at this level
+/// are added next to them instead.
+pub fn max_comment_depth() -> u32 {
+    std::env::var("MAX_COMMENT_DEPTH")
+        .ok()
+        .and_then(|depth| depth.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_MAX_COMMENT_DEPTH)
+        .max(1)
+}
 
 pub struct CommentService {}
 
 impl CommentService {
     pub async fn create_comment(
-        cmt_database: &(impl CommentDataSource + std::marker::Sync),
+        cmt_database: &(impl CommentDataSource
+              + UserDataSource
+              + BlockDataSource
+              + MentionNotifier
+              + std::marker::Sync),
         input: CreateCommentInput,
     ) -> Result<Comment, CommentServiceError> {
-        let rs = cmt_database.create_comment(input).await;
-        match rs {
-            Ok(rs) => {
-                return Ok(rs);
+        Self::store_comment(cmt_database, Comment::from(input)).await
+    }
+
+    /// Store the new comment and notify the users it mentions.
+    async fn store_comment(
+        cmt_database: &(impl CommentDataSource
+              + UserDataSource
+              + BlockDataSource
+              + MentionNotifier
+              + std::marker::Sync),
+        mut comment: Comment,
+    ) -> Result<Comment, CommentServiceError> {
+        comment.mentions = Self::resolve_mentions(cmt_database, &comment.content).await;
+        cmt_database
+            .add_comment(comment.clone())
+            .await
+            .map_err(|err| err.into())?;
+        Self::notify_mentions(cmt_database, &comment, &[]).await;
+        Ok(comment)
+    }
+
+    /// Find the mentions of existing users in the content. Mentions of
+    /// unknown usernames, and of usernames past the first
+    /// `MAX_MENTIONED_USERNAMES`, are left as plain text.
+    async fn resolve_mentions(
+        database: &(impl UserDataSource + std::marker::Sync),
+        content: &str,
+    ) -> Vec<Mention> {
+        let mut mentions = Vec::new();
+        let mut looked_up: Vec<(String, Option<ObjectId>)> = Vec::new();
+        for parsed in parse_mentions(content) {
+            let known = looked_up
+                .iter()
+                .find(|(username, _)| *username == parsed.username)
+                .map(|(_, user_id)| *user_id);
+            let user_id = match known {
+                Some(user_id) => user_id,
+                None if looked_up.len() >= MAX_MENTIONED_USERNAMES => None,
+                None => {
+                    let user =
+                        UserService::get_user_by_username(database, parsed.username.clone()).await;
+                    let user_id = user.ok().map(|user| *user.id);
+                    looked_up.push((parsed.username.clone(), user_id));
+                    user_id
+                }
+            };
+            let Some(user_id) = user_id else {
+                continue;
+            };
+            mentions.push(Mention {
+                user_id,
+                username: parsed.username,
+                start: parsed.start as u32,
+                end: parsed.end as u32,
+            });
+        }
+        mentions
+    }
+
+    /// Notify the users mentioned in the comment, except the author, the
+    /// users already mentioned in `previous` and the users blocked by or
+    /// blocking the author.
+    async fn notify_mentions(
+        database: &(impl BlockDataSource + MentionNotifier + std::marker::Sync),
+        comment: &Comment,
+        previous: &[Mention],
+    ) {
+        let author = comment.author.into();
+        let mut notified = Vec::new();
+        for mention in &comment.mentions {
+            let user_id = mention.user_id;
+            if user_id == author
+                || notified.contains(&user_id)
+                || previous.iter().any(|mention| mention.user_id == user_id)
+            {
+                continue;
             }
-            Err(err) => {
-                let err: CommentServiceError = err.into();
-                return Err(err);
+            notified.push(user_id);
+            // when blocks cannot be checked, the mention is not notified
+            if let Ok(false) = BlockService::is_blocked(database, author, user_id).await {
+                database.notify_mention(user_id, comment).await;
             }
         }
     }
 
+    /// Check that the user can comment on the CV: return `UserBlocked` if the
+    /// author of the CV has blocked the user or the other way around, and
+    /// `IdNotFound(not_found)` if the user cannot see the CV.
+    async fn check_can_comment(
+        database: &(impl FriendsListDataSource + BlockDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        cv: &CV,
+        not_found: ObjectId,
+    ) -> Result<(), CommentServiceError> {
+        let author = cv.author_id.into();
+        let blocked = BlockService::is_blocked(database, user_id, author)
+            .await
+            .map_err(|_| CommentServiceError::DatabaseError)?;
+        if blocked {
+            return Err(CommentServiceError::UserBlocked(author));
+        }
+        let visible = CVService::can_view_cv(database, Some(user_id), cv)
+            .await
+            .map_err(|_| CommentServiceError::DatabaseError)?;
+        if !visible {
+            return Err(CommentServiceError::IdNotFound(not_found));
+        }
+        Ok(())
+    }
+
+    /// Comment on the CV, unless the author of the CV has blocked the user
+    /// or the other way around. CVs the user cannot see are not found.
+    pub async fn add_comment_to_cv(
+        database: &(impl CVDataSource
+              + CommentDataSource
+              + UserDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + MentionNotifier
+              + std::marker::Sync),
+        cv_id: ObjectId,
+        author_id: ObjectId,
+        content: String,
+    ) -> Result<CV, CommentServiceError> {
+        let cv = match database.get_cv_by_id(cv_id).await {
+            Ok(cv) => cv,
+            Err(CVDataSourceError::IdNotFound(id)) => {
+                return Err(CommentServiceError::IdNotFound(id))
+            }
+            Err(_) => return Err(CommentServiceError::DatabaseError),
+        };
+        Self::check_can_comment(database, author_id, &cv, cv_id).await?;
+        let mut comment = Comment::new(content, author_id);
+        comment.mentions = Self::resolve_mentions(database, &comment.content).await;
+        let cv = database
+            .add_comment_to_cv(cv_id, comment.clone())
+            .await
+            .map_err(|_| CommentServiceError::CreateCommentFailed)?;
+        Self::notify_mentions(database, &comment, &[]).await;
+        Ok(cv)
+    }
+
     pub async fn get_comment_by_id(
         cmt_database: &(impl CommentDataSource + std::marker::Sync),
         comment_id: ObjectId,
@@ -71,57 +253,128 @@ impl CommentService {
         Ok(rs.boxed())
     }
 
+    /// Replace the content of the comment, keeping the previous content in
+    /// its edit history. Only the author of the comment can edit it.
     pub async fn update_content_comment(
-        cmt_database: &(impl CommentDataSource + std::marker::Sync),
+        cmt_database: &(impl CommentDataSource
+              + UserDataSource
+              + BlockDataSource
+              + MentionNotifier
+              + std::marker::Sync),
+        user_id: ObjectId,
         comment_id: ObjectId,
         content: String,
     ) -> Result<Comment, CommentServiceError> {
-        let input = UpdateCommentInput::builder()
-            .with_content(content)
-            .build()
-            .unwrap();
-        let rs = cmt_database
-            .find_and_update_comment(comment_id, input)
-            .await;
-        rs.map(|rs| rs).map_err(|err| err.into())
+        let comment = Self::get_comment_by_id(cmt_database, comment_id).await?;
+        if comment.author != user_id.into() {
+            return Err(CommentServiceError::NotAuthor);
+        }
+        if comment.deleted_at.is_some() {
+            return Err(CommentServiceError::CommentDeleted(comment_id));
+        }
+        if content.trim().is_empty() {
+            return Err(CommentServiceError::EmptyContent);
+        }
+        if content == comment.content {
+            return Ok(comment);
+        }
+        let previous = CommentVersion {
+            content: comment.content,
+            created: comment.edited_at.unwrap_or(comment.created),
+        };
+        let mentions = Self::resolve_mentions(cmt_database, &content).await;
+        let edited = cmt_database
+            .edit_comment(comment_id, content, mentions, DateTime::now(), previous)
+            .await
+            .map_err(|err| err.into())?;
+        Self::notify_mentions(cmt_database, &edited, &comment.mentions).await;
+        Ok(edited)
     }
 
-    pub async fn add_like_comment(
-        cmt_database: &(impl CommentDataSource + LikeDataSource + std::marker::Sync),
+    /// Delete the comment, leaving a tombstone in place so that its replies
+    /// are kept. Deleting a comment twice keeps the first deletion.
+    pub async fn delete_comment(
+        cmt_database: &(impl CommentDataSource + std::marker::Sync),
+        comment_id: ObjectId,
+    ) -> Result<Comment, CommentServiceError> {
+        let comment = Self::get_comment_by_id(cmt_database, comment_id).await?;
+        if comment.deleted_at.is_some() {
+            return Ok(comment);
+        }
+        cmt_database
+            .delete_comment(comment_id, DateTime::now())
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Delete the comment on behalf of the user, who must be its author or a
+    /// moderator.
+    pub async fn delete_comment_of_user(
+        cmt_database: &(impl CommentDataSource + UserDataSource + std::marker::Sync),
         user_id: ObjectId,
         comment_id: ObjectId,
     ) -> Result<Comment, CommentServiceError> {
-        let comment = cmt_database.get_comment_by_id(comment_id).await;
-        match comment {
-            Ok(_) => {
-                let rs = cmt_database.add_like(user_id, comment_id).await;
-                match rs {
-                    Ok(_) => {
-                        return Ok(comment.unwrap());
-                    }
-                    Err(err) => Err(err.into()),
-                }
+        let comment = Self::get_comment_by_id(cmt_database, comment_id).await?;
+        if comment.author != user_id.into() {
+            let moderator = UserService::get_user_by_id(cmt_database, user_id)
+                .await
+                .is_ok_and(|user| user.is_moderator());
+            if !moderator {
+                return Err(CommentServiceError::NotAuthor);
             }
-            Err(err) => Err(err.into()),
         }
+        Self::delete_comment(cmt_database, comment_id).await
+    }
+
+    /// Return the previous contents of the comment, oldest first. Only the
+    /// author of the comment can see them.
+    pub async fn edit_history(
+        cmt_database: &(impl CommentDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        comment_id: ObjectId,
+    ) -> Result<Vec<CommentVersion>, CommentServiceError> {
+        let comment = Self::get_comment_by_id(cmt_database, comment_id).await?;
+        if comment.author != user_id.into() {
+            return Err(CommentServiceError::NotAuthor);
+        }
+        Ok(comment.history)
+    }
+
+    pub async fn add_like_comment(
+        cmt_database: &(impl CommentDataSource
+              + CVDataSource
+              + UserDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + ReactionDataSource
+              + std::marker::Sync),
+        user_id: ObjectId,
+        comment_id: ObjectId,
+    ) -> Result<Comment, CommentServiceError> {
+        ReactionService::react(
+            cmt_database,
+            user_id,
+            ReactionTarget::Comment,
+            comment_id,
+            ReactionKind::Like,
+        )
+        .await
+        .map_err(CommentServiceError::from)?;
+        Self::get_comment_by_id(cmt_database, comment_id).await
     }
     pub async fn remove_like_comment(
-        cmt_database: &(impl CommentDataSource + LikeDataSource + std::marker::Sync),
+        cmt_database: &(impl CommentDataSource + ReactionDataSource + std::marker::Sync),
         user_id: ObjectId,
         comment_id: ObjectId,
     ) -> Result<Comment, CommentServiceError> {
-        let comment = cmt_database.get_comment_by_id(comment_id).await;
-        match comment {
-            Ok(_) => {
-                let rs = cmt_database.delete_like(user_id, comment_id).await;
-                match rs {
-                    Ok(_) => {
-                        return Ok(comment.unwrap());
-                    }
-                    Err(err) => Err(err.into()),
-                }
-            }
-            Err(err) => Err(err.into()),
+        let comment = Self::get_comment_by_id(cmt_database, comment_id).await?;
+        let like =
+            ReactionService::unreact(cmt_database, user_id, comment_id, Some(ReactionKind::Like))
+                .await
+                .map_err(CommentServiceError::from)?;
+        match like {
+            Some(_) => Ok(comment),
+            None => Err(CommentServiceError::NoLikes),
         }
     }
 
@@ -173,8 +426,17 @@ impl CommentService {
         rs.map(|rs| rs).map_err(|err| err.into())
     }
 
+    /// Reply to the comment, unless its author or the author of the CV has
+    /// blocked the user or the other way around. Comments on CVs the user
+    /// cannot see are not found.
     pub async fn add_reply_comment(
-        cmt_database: &(impl CommentDataSource + std::marker::Sync),
+        cmt_database: &(impl CommentDataSource
+              + CVDataSource
+              + UserDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + MentionNotifier
+              + std::marker::Sync),
         comment_id: ObjectId,
         user_id: ObjectId,
         content: String,
@@ -182,21 +444,40 @@ impl CommentService {
         let cmt = cmt_database.get_comment_by_id(comment_id).await;
         match cmt {
             Ok(cmt) => {
-                let create_input = CreateCommentInput {
-                    author: user_id.into(),
-                    content,
+                if cmt.deleted_at.is_some() {
+                    return Err(CommentServiceError::CommentDeleted(comment_id));
+                }
+                let author = cmt.author.into();
+                let blocked = BlockService::is_blocked(cmt_database, user_id, author)
+                    .await
+                    .map_err(|_| CommentServiceError::DatabaseError)?;
+                if blocked {
+                    return Err(CommentServiceError::UserBlocked(author));
+                }
+                let cv = match cmt_database.get_cv_by_comment_id(*cmt.thread_id()).await {
+                    Ok(cv) => cv,
+                    Err(CVDataSourceError::IdNotFound(_)) => {
+                        return Err(CommentServiceError::IdNotFound(comment_id))
+                    }
+                    Err(_) => return Err(CommentServiceError::DatabaseError),
                 };
-                let new_cmt = cmt_database.create_comment(create_input).await;
-                match new_cmt {
-                    Ok(new_cmt) => {
-                        let rs = cmt_database
-                            .add_reply_to_comment(cmt.id.into(), new_cmt.id.into())
-                            .await;
-                        match rs {
-                            Ok(rs) => Ok(rs),
-                            Err(err) => Err(err.into()),
-                        }
+                Self::check_can_comment(cmt_database, user_id, &cv, comment_id).await?;
+                // replies nested deeper than the limit go to the deepest
+                // allowed comment of the thread instead
+                let mut parent = cmt;
+                while parent.depth >= max_comment_depth() {
+                    match parent.parent_id {
+                        Some(id) => parent = Self::get_comment_by_id(cmt_database, *id).await?,
+                        None => break,
                     }
+                }
+                let reply = Comment::reply_to(&parent, content, user_id);
+                let new_cmt = Self::store_comment(cmt_database, reply).await?;
+                let rs = cmt_database
+                    .add_reply_to_comment(parent.id.into(), new_cmt.id.into())
+                    .await;
+                match rs {
+                    Ok(rs) => Ok(rs),
                     Err(err) => Err(err.into()),
                 }
             }
@@ -204,34 +485,127 @@ impl CommentService {
         }
     }
 
+    /// Delete a reply of the comment on behalf of the user, like
+    /// `delete_comment_of_user`. The reply stays as a tombstone so that its
+    /// own replies are kept. Return the comment replied to.
     pub async fn remove_reply_comment(
-        cmt_database: &(impl CommentDataSource + std::marker::Sync),
+        cmt_database: &(impl CommentDataSource + UserDataSource + std::marker::Sync),
+        user_id: ObjectId,
         comment_id: ObjectId,
         reply_id: ObjectId,
     ) -> Result<Comment, CommentServiceError> {
-        let rs = cmt_database
-            .find_and_remove_reply(comment_id, reply_id)
-            .await;
-        rs.map(|rs| rs).map_err(|err| err.into())
+        let comment = Self::get_comment_by_id(cmt_database, comment_id).await?;
+        if !comment.replies.contains(&reply_id.into()) {
+            return Err(CommentServiceError::IdNotFound(reply_id));
+        }
+        Self::delete_comment_of_user(cmt_database, user_id, reply_id).await?;
+        Ok(comment)
+    }
+
+    /// Order the comments, the most liked first for `CommentSort::Top`.
+    pub async fn sort_comments(
+        cmt_database: &(impl ReactionDataSource + std::marker::Sync),
+        mut comments: Vec<Comment>,
+        sort: CommentSort,
+    ) -> Result<Vec<Comment>, CommentServiceError> {
+        comments.sort_by_key(|comment| comment.created);
+        match sort {
+            CommentSort::Oldest => {}
+            CommentSort::Newest => comments.reverse(),
+            CommentSort::Top => {
+                let ids = comments.iter().map(|comment| *comment.id).collect();
+                let likes = ReactionService::reaction_count_of_targets(
+                    cmt_database,
+                    ids,
+                    ReactionKind::Like,
+                )
+                .await
+                .map_err(CommentServiceError::from)?;
+                comments.sort_by_key(|comment| {
+                    std::cmp::Reverse(likes.get(&*comment.id).copied().unwrap_or(0))
+                });
+            }
+        }
+        Ok(comments)
+    }
+
+    /// Return the thread of the comment, starting with the comment on the
+    /// CV, each comment followed by its replies in `sort` order. Only the
+    /// oldest `limit` comments of the thread are returned. Threads on CVs the
+    /// viewer cannot see are not found.
+    pub async fn get_thread(
+        cmt_database: &(impl CommentDataSource
+              + CVDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + ReactionDataSource
+              + std::marker::Sync),
+        viewer_id: Option<ObjectId>,
+        comment_id: ObjectId,
+        sort: CommentSort,
+        limit: usize,
+    ) -> Result<Vec<Comment>, CommentServiceError> {
+        let comment = Self::get_comment_by_id(cmt_database, comment_id).await?;
+        let root_id = comment.thread_id();
+        let cv = match cmt_database.get_cv_by_comment_id(*root_id).await {
+            Ok(cv) => cv,
+            Err(CVDataSourceError::IdNotFound(_)) => {
+                return Err(CommentServiceError::IdNotFound(comment_id))
+            }
+            Err(_) => return Err(CommentServiceError::DatabaseError),
+        };
+        let visible = CVService::can_view_cv(cmt_database, viewer_id, &cv)
+            .await
+            .map_err(|_| CommentServiceError::DatabaseError)?;
+        if !visible {
+            return Err(CommentServiceError::IdNotFound(comment_id));
+        }
+
+        let comments = cmt_database
+            .get_thread(*root_id, limit)
+            .await
+            .map_err(|err| err.into())?;
+        let (root, replies): (Vec<_>, Vec<_>) = comments
+            .into_iter()
+            .partition(|comment| comment.id == root_id);
+        let replies = Self::sort_comments(cmt_database, replies, sort).await?;
+        let mut children: HashMap<ObjectId, Vec<Comment>> = HashMap::new();
+        for reply in replies {
+            if let Some(parent_id) = reply.parent_id {
+                children.entry(*parent_id).or_default().push(reply);
+            }
+        }
+
+        let mut thread = Vec::new();
+        let mut pending = root;
+        while let Some(comment) = pending.pop() {
+            // push the replies in reverse so that the first one is next
+            if let Some(replies) = children.remove(&*comment.id) {
+                pending.extend(replies.into_iter().rev());
+            }
+            thread.push(comment);
+        }
+        Ok(thread)
     }
 
     pub async fn get_likes_count(
-        cmt_database: &(impl CommentDataSource + LikeDataSource + std::marker::Sync),
+        cmt_database: &(impl CommentDataSource + ReactionDataSource + std::marker::Sync),
         comment_id: ObjectId,
     ) -> Result<i32, CommentServiceError> {
-        let rs = cmt_database.get_likes_count_of_comment(comment_id).await;
-        rs.map(|rs| rs).map_err(|err| err.into())
+        let rs = ReactionService::reaction_count(cmt_database, comment_id, ReactionKind::Like);
+        rs.await.map(|rs| rs as i32).map_err(|err| err.into())
     }
 
     pub async fn get_likes(
-        cmt_database: &(impl CommentDataSource + LikeDataSource + std::marker::Sync),
+        cmt_database: &(impl CommentDataSource + ReactionDataSource + std::marker::Sync),
         comment_id: ObjectId,
     ) -> Result<BoxStream<Result<Like, CommentServiceError>>, CommentServiceError> {
-        let likes = match cmt_database.get_likes(comment_id).await {
-            Ok(likes) => likes,
-            Err(err) => return Err(err.into()),
-        };
-        let rs = likes.map(|item| Ok(item)).boxed();
+        let likes = ReactionService::reactions(cmt_database, comment_id, Some(ReactionKind::Like))
+            .await
+            .map_err(CommentServiceError::from)?;
+        let rs = async_graphql::futures_util::stream::iter(likes)
+            .map(|item| Ok(Like::from(item)))
+            .boxed();
         Ok(rs)
     }
 }
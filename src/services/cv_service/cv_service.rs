@@ -1,19 +1,27 @@
-use async_graphql::futures_util::stream::BoxStream;
+use async_graphql::futures_util::{self, stream::BoxStream, StreamExt};
 use mongodb::bson::oid::ObjectId;
 
-use crate::data_source::{CVDataSource, CVDataSourceError, CVDetailsDataSource, CommentDataSource};
-use crate::models::comment::CreateCommentInput;
-use crate::models::cv::{CreateCVInput, UpdateCVInput, CV};
+use crate::data_source::{
+    cv::revision::CVRevisionDataSource, BlockDataSource, CVDataSource, CVDataSourceError,
+    CVDetailsDataSource, CommentDataSource, FriendsListDataSource, UserDataSource,
+};
+use crate::models::comment::Comment;
+use crate::models::cv::{CreateCVInput, UpdateCVInput, Visibility, CV};
 
 use crate::models::cv_details::CVDetails;
+use crate::services::user_service::{block_service::BlockService, UserService};
 
-use super::error::CVServiceError;
+use super::{
+    comment_service::{CommentService, CommentServiceError},
+    error::CVServiceError,
+    revision_service::CVRevisionService,
+};
 
 pub struct CVService {}
 
 impl CVService {
     pub async fn create_cv(
-        database: &(impl CVDataSource + std::marker::Sync),
+        database: &(impl CVRevisionDataSource + std::marker::Sync),
         user_id: ObjectId,
         title: String,
         description: String,
@@ -24,8 +32,9 @@ impl CVService {
             .with_description(description)
             .build()
             .unwrap();
-        let rs = database.create_cv(input).await;
-        rs.map_err(|err| err.into())
+        let cv = database.create_cv(input).await?;
+        CVRevisionService::record_revision(database, user_id, &cv, None).await?;
+        Ok(cv)
     }
 
     pub async fn delete_cv(
@@ -36,73 +45,94 @@ impl CVService {
         rs.map_err(|err| err.into())
     }
 
-    pub async fn change_title(
+    /// Apply the update to the CV and record the result as a new revision
+    /// made by `user_id`. The caller is responsible for checking that
+    /// `user_id` is the author of the CV.
+    pub async fn update_cv(
+        database: &(impl CVRevisionDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        cv_id: ObjectId,
+        input: UpdateCVInput,
+    ) -> Result<CV, CVDataSourceError> {
+        let cv = database.find_and_update_cv(cv_id, input).await?;
+        CVRevisionService::record_revision(database, user_id, &cv, None).await?;
+        Ok(cv)
+    }
+
+    /// Return the CV if `user_id` is its author.
+    async fn get_own_cv(
         database: &(impl CVDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        cv_id: ObjectId,
+    ) -> Result<CV, CVServiceError> {
+        let cv = database.get_cv_by_id(cv_id).await?;
+        if cv.author_id != user_id.into() {
+            return Err(CVServiceError::PermissionDenied);
+        }
+        Ok(cv)
+    }
+
+    /// Change the title of the CV, only the author of the CV is allowed to do so.
+    pub async fn change_title(
+        database: &(impl CVRevisionDataSource + std::marker::Sync),
+        user_id: ObjectId,
         cv_id: ObjectId,
         title: String,
-    ) -> Result<CV, CVDataSourceError> {
+    ) -> Result<CV, CVServiceError> {
+        Self::get_own_cv(database, user_id, cv_id).await?;
         let input = UpdateCVInput::builder().with_title(title).build().unwrap();
-        let rs = database.find_and_update_cv(cv_id, input).await;
+        let rs = Self::update_cv(database, user_id, cv_id, input).await;
         rs.map_err(|err| err.into())
     }
 
+    /// Change the description of the CV, only the author of the CV is allowed to do so.
     pub async fn change_description(
-        database: &(impl CVDataSource + std::marker::Sync),
+        database: &(impl CVRevisionDataSource + std::marker::Sync),
+        user_id: ObjectId,
         cv_id: ObjectId,
         description: String,
-    ) -> Result<CV, CVDataSourceError> {
+    ) -> Result<CV, CVServiceError> {
+        Self::get_own_cv(database, user_id, cv_id).await?;
         let input = UpdateCVInput::builder()
             .with_description(description)
             .build()
             .unwrap();
-        let rs = database.find_and_update_cv(cv_id, input).await;
+        let rs = Self::update_cv(database, user_id, cv_id, input).await;
         rs.map_err(|err| err.into())
     }
 
+    /// Add a tag to the CV, only the author of the CV is allowed to do so.
     pub async fn add_tag(
-        database: &(impl CVDataSource + std::marker::Sync),
+        database: &(impl CVRevisionDataSource + std::marker::Sync),
+        user_id: ObjectId,
         cv_id: ObjectId,
         tag: String,
-    ) -> Result<CV, CVDataSourceError> {
-        let cv = database.get_cv_by_id(cv_id).await;
-        match cv {
-            Ok(cv) => {
-                let mut tags = cv.clone().tags;
-                if tags.iter().position(|x| *x == tag).is_none() {
-                    tags.push(tag);
-                }
-                let input: UpdateCVInput =
-                    UpdateCVInput::builder().with_tags(tags).build().unwrap();
-                let rs = database.find_and_update_cv(cv_id.into(), input).await;
-                rs.map_err(|err| err.into())
-            }
-            Err(err) => {
-                return Err(err.into());
-            }
+    ) -> Result<CV, CVServiceError> {
+        let cv = Self::get_own_cv(database, user_id, cv_id).await?;
+        let mut tags = cv.tags;
+        if !tags.contains(&tag) {
+            tags.push(tag);
         }
+        let input: UpdateCVInput = UpdateCVInput::builder().with_tags(tags).build().unwrap();
+        let rs = Self::update_cv(database, user_id, cv_id, input).await;
+        rs.map_err(|err| err.into())
     }
 
+    /// Remove a tag from the CV, only the author of the CV is allowed to do so.
     pub async fn remove_tag(
-        database: &(impl CVDataSource + std::marker::Sync),
+        database: &(impl CVRevisionDataSource + std::marker::Sync),
+        user_id: ObjectId,
         cv_id: ObjectId,
         tag: String,
-    ) -> Result<CV, CVDataSourceError> {
-        let cv = database.get_cv_by_id(cv_id).await;
-        match cv {
-            Ok(cv) => {
-                let mut tags = cv.clone().tags;
-                if let Some(index) = tags.iter().position(|x| *x == tag) {
-                    tags.remove(index);
-                }
-                let input: UpdateCVInput =
-                    UpdateCVInput::builder().with_tags(tags).build().unwrap();
-                let rs = database.find_and_update_cv(cv_id.into(), input).await;
-                rs.map_err(|err| err.into())
-            }
-            Err(err) => {
-                return Err(err.into());
-            }
+    ) -> Result<CV, CVServiceError> {
+        let cv = Self::get_own_cv(database, user_id, cv_id).await?;
+        let mut tags = cv.tags;
+        if let Some(index) = tags.iter().position(|x| *x == tag) {
+            tags.remove(index);
         }
+        let input: UpdateCVInput = UpdateCVInput::builder().with_tags(tags).build().unwrap();
+        let rs = Self::update_cv(database, user_id, cv_id, input).await;
+        rs.map_err(|err| err.into())
     }
 
     pub async fn add_comment(
@@ -111,45 +141,179 @@ impl CVService {
         author_id: ObjectId,
         content: String,
     ) -> Result<CV, CVDataSourceError> {
-        let input = CreateCommentInput {
-            author: author_id.into(),
-            content,
-        };
-        let rs = database.create_comment(input).await;
-        match rs {
-            Ok(comment) => {
-                let rs = database.add_comment_to_cv(cv_id, comment).await;
-                match rs {
-                    Ok(cv) => Ok(cv),
-                    Err(err) => Err(err.into()),
-                }
-            }
-            Err(_err) => Err(CVDataSourceError::AddCommentFailed),
-        }
+        let comment = Comment::new(content, author_id);
+        database.add_comment_to_cv(cv_id, comment).await
     }
 
+    /// Delete a comment of the CV on behalf of the user, who must be the
+    /// author of the comment or a moderator. The comment stays in the CV as a
+    /// tombstone so that its replies are kept.
     pub async fn remove_comment(
-        database: &(impl CVDataSource + CommentDataSource + std::marker::Sync),
+        database: &(impl CVDataSource + CommentDataSource + UserDataSource + std::marker::Sync),
+        user_id: ObjectId,
         cv_id: ObjectId,
         comment_id: ObjectId,
-    ) -> Result<CV, CVDataSourceError> {
-        let rs = database.remove_comment_from_cv(cv_id, comment_id).await;
-        rs.map_err(|err| err.into())
+    ) -> Result<CV, CommentServiceError> {
+        let cv = match database.get_cv_by_id(cv_id).await {
+            Ok(cv) => cv,
+            Err(CVDataSourceError::IdNotFound(id)) => {
+                return Err(CommentServiceError::IdNotFound(id))
+            }
+            Err(_) => return Err(CommentServiceError::DatabaseError),
+        };
+        if !cv.comments.contains(&comment_id) {
+            return Err(CommentServiceError::IdNotFound(comment_id));
+        }
+        CommentService::delete_comment_of_user(database, user_id, comment_id).await?;
+        Ok(cv)
     }
 
+    /// Return the CVs matching the filter that `viewer_id` may see in a
+    /// listing, leaving out the CVs of users blocked by or blocking the viewer.
     pub async fn find_suggested_cvs(
-        database: &(impl CVDetailsDataSource + std::marker::Sync),
+        database: &(impl CVDetailsDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
+        viewer_id: Option<ObjectId>,
         cv_details: CVDetails,
     ) -> Result<BoxStream<CV>, CVServiceError> {
-        let stream = database.get_cvs_by_filter(cv_details).await;
-        stream.map_err(|err| err.into())
+        let cvs = database
+            .get_cvs_by_filter(cv_details)
+            .await
+            .map_err(|err| err.into())?
+            .collect::<Vec<_>>()
+            .await;
+        let hidden = match viewer_id {
+            Some(viewer_id) => BlockService::hidden_user_ids(database, viewer_id).await,
+            None => Vec::new(),
+        };
+        let mut listed_cvs = Vec::with_capacity(cvs.len());
+        for cv in cvs {
+            if hidden.contains(&cv.author_id.into()) {
+                continue;
+            }
+            if Self::can_list_cv(database, viewer_id, &cv).await? {
+                listed_cvs.push(cv);
+            }
+        }
+        Ok(futures_util::stream::iter(listed_cvs).boxed())
     }
 
+    /// Return the CVs of `user_id` that `viewer_id` is allowed to see in a listing.
     pub async fn get_cvs_by_user_id(
-        database: &(impl CVDataSource + std::marker::Sync),
+        database: &(impl CVDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
+        viewer_id: Option<ObjectId>,
         user_id: ObjectId,
     ) -> Result<BoxStream<Result<CV, CVDataSourceError>>, CVDataSourceError> {
-        let rs = database.get_cvs_by_user_id(user_id).await;
-        rs
+        let cvs = database
+            .get_cvs_by_user_id(user_id)
+            .await?
+            .collect::<Vec<_>>()
+            .await;
+        let mut listed_cvs = Vec::with_capacity(cvs.len());
+        for cv in cvs {
+            match cv {
+                Ok(cv) => {
+                    let listed = Self::can_list_cv(database, viewer_id, &cv)
+                        .await
+                        .map_err(|_| CVDataSourceError::DatabaseError)?;
+                    if listed {
+                        listed_cvs.push(Ok(cv));
+                    }
+                }
+                cv => listed_cvs.push(cv),
+            }
+        }
+        Ok(futures_util::stream::iter(listed_cvs).boxed())
+    }
+
+    /// Return the CV with the provided id if `viewer_id` is allowed to see it.
+    pub async fn get_cv_by_id(
+        database: &(impl CVDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
+        viewer_id: Option<ObjectId>,
+        cv_id: ObjectId,
+    ) -> Result<CV, CVServiceError> {
+        let cv = database.get_cv_by_id(cv_id).await?;
+        if !Self::can_view_cv(database, viewer_id, &cv).await? {
+            return Err(CVServiceError::CVNotVisible(cv_id));
+        }
+        Ok(cv)
+    }
+
+    /// Change the visibility of the CV, only the author of the CV is allowed to do so.
+    pub async fn change_visibility(
+        database: &(impl CVRevisionDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        cv_id: ObjectId,
+        visibility: Visibility,
+    ) -> Result<CV, CVServiceError> {
+        Self::get_own_cv(database, user_id, cv_id).await?;
+        let input = UpdateCVInput::builder()
+            .with_visibility(visibility)
+            .build()
+            .unwrap();
+        let rs = Self::update_cv(database, user_id, cv_id, input).await;
+        rs.map_err(|err| err.into())
+    }
+
+    /// Return whether `viewer_id` is allowed to open the CV directly, e.g. by its id.
+    /// Anonymous viewers are represented by `None`. CVs hidden by moderation
+    /// are only shown to their author, and users who blocked each other
+    /// cannot see each other's CVs.
+    pub async fn can_view_cv(
+        database: &(impl FriendsListDataSource + BlockDataSource + std::marker::Sync),
+        viewer_id: Option<ObjectId>,
+        cv: &CV,
+    ) -> Result<bool, CVServiceError> {
+        if viewer_id == Some(cv.author_id.into()) {
+            return Ok(true);
+        }
+        if cv.hidden_at.is_some() {
+            return Ok(false);
+        }
+        if let Some(viewer_id) = viewer_id {
+            let blocked = BlockService::is_blocked(database, viewer_id, cv.author_id.into())
+                .await
+                .map_err(|_| CVServiceError::DatabaseError)?;
+            if blocked {
+                return Ok(false);
+            }
+        }
+        let visible = match cv.visibility {
+            Visibility::Public | Visibility::Unlisted => true,
+            Visibility::Private => false,
+            Visibility::FriendsOnly => match viewer_id {
+                Some(viewer_id) => {
+                    UserService::are_friends(database, viewer_id, cv.author_id.into()).await
+                }
+                None => false,
+            },
+        };
+        Ok(visible)
+    }
+
+    /// Return whether the CV may be shown to `viewer_id` in listings and searches.
+    /// Unlike `can_view_cv`, unlisted CVs are only listed to their author.
+    /// Like `can_view_cv`, CVs hidden by moderation are only listed to their
+    /// author.
+    pub async fn can_list_cv(
+        database: &(impl FriendsListDataSource + BlockDataSource + std::marker::Sync),
+        viewer_id: Option<ObjectId>,
+        cv: &CV,
+    ) -> Result<bool, CVServiceError> {
+        if viewer_id == Some(cv.author_id.into()) {
+            return Ok(true);
+        }
+        if cv.hidden_at.is_some() || !cv.visibility.is_listed() {
+            return Ok(false);
+        }
+        Self::can_view_cv(database, viewer_id, cv).await
     }
 }
@@ -60,7 +60,40 @@ pub enum CVServiceError{
     UpdateBookmarkFailed,
 
     /// update share failed
-    UpdateShareFailed
+    UpdateShareFailed,
+
+    /// the CV is not visible to the requesting user
+    CVNotVisible(ObjectId),
+
+    /// the user is not allowed to modify the CV
+    PermissionDenied,
+
+    /// share link not found or revoked
+    ShareLinkNotFound,
+
+    /// share link expired or has no views left
+    ShareLinkExpired,
+
+    /// share link options are invalid
+    InvalidShareLink(String),
+
+    /// update share link failed
+    UpdateShareLinkFailed,
+
+    /// the compared revisions belong to different CVs
+    RevisionMismatch,
+
+    /// the uploaded file is rejected
+    InvalidFile(String),
+
+    /// the uploaded file is larger than the limit, in bytes
+    FileTooLarge(usize),
+
+    /// the file storage failed
+    StorageError,
+
+    /// the author of the CV has blocked the user, or the other way around
+    UserBlocked(ObjectId),
 }
 
 impl fmt::Display for CVServiceError{
@@ -123,6 +156,39 @@ impl fmt::Display for CVServiceError{
             CVServiceError::UpdateLikeFailed => {
                 write!(f, "Cannot update like")
             }
+            CVServiceError::CVNotVisible(objectid) => {
+                write!(f, "CV {:?} is not visible to this user", objectid)
+            }
+            CVServiceError::PermissionDenied => {
+                write!(f, "Only the author can modify this CV")
+            }
+            CVServiceError::ShareLinkNotFound => {
+                write!(f, "Cannot find share link")
+            }
+            CVServiceError::ShareLinkExpired => {
+                write!(f, "Share link has expired")
+            }
+            CVServiceError::InvalidShareLink(s) => {
+                write!(f, "Invalid share link: {}", s)
+            }
+            CVServiceError::UpdateShareLinkFailed => {
+                write!(f, "Cannot update share link")
+            }
+            CVServiceError::RevisionMismatch => {
+                write!(f, "Revisions belong to different CVs")
+            }
+            CVServiceError::InvalidFile(s) => {
+                write!(f, "Invalid file: {}", s)
+            }
+            CVServiceError::FileTooLarge(max) => {
+                write!(f, "File is larger than {} bytes", max)
+            }
+            CVServiceError::StorageError => {
+                write!(f, "File storage error")
+            }
+            CVServiceError::UserBlocked(id) => {
+                write!(f, "User {:?} is blocked", id)
+            }
         }
     }
 }
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests;
+
+use std::time::Duration;
+
+use mongodb::bson::{oid::ObjectId, Uuid};
+
+use crate::{
+    data_source::cv::revision::CVRevisionDataSource,
+    models::cv::{UpdateCVInput, CV},
+    services::storage_service::backend::StorageBackend,
+};
+
+use super::{cv_service::CVService, error::CVServiceError};
+
+/// Largest CV file accepted, in bytes.
+pub const MAX_CV_FILE_SIZE: usize = 10 * 1024 * 1024;
+
+/// How long a download url returned by `get_cv_file_url` stays valid.
+pub const CV_FILE_URL_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Content types accepted for CV files, along with the leading bytes every
+/// file of that type starts with. Plain text has no signature and is checked
+/// for valid UTF-8 instead.
+const ALLOWED_CV_FILE_TYPES: [(&str, &[u8]); 4] = [
+    ("application/pdf", b"%PDF-"),
+    (
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        b"PK\x03\x04",
+    ),
+    ("application/msword", b"\xD0\xCF\x11\xE0"),
+    ("text/plain", b""),
+];
+
+pub struct CVFileService;
+
+impl CVFileService {
+    /// Check that the uploaded file is a CV document of an accepted type and
+    /// size, and that its content matches the declared content type. Return
+    /// the normalized content type.
+    pub fn validate_cv_file(
+        content_type: Option<&str>,
+        data: &[u8],
+    ) -> Result<&'static str, CVServiceError> {
+        if data.is_empty() {
+            return Err(CVServiceError::InvalidFile("File is empty".to_string()));
+        }
+        if data.len() > MAX_CV_FILE_SIZE {
+            return Err(CVServiceError::FileTooLarge(MAX_CV_FILE_SIZE));
+        }
+        let content_type = content_type
+            .map(|content_type| {
+                // drop parameters such as `; charset=utf-8`
+                content_type.split(';').next().unwrap_or_default().trim().to_lowercase()
+            })
+            .ok_or_else(|| CVServiceError::InvalidFile("Missing content type".to_string()))?;
+        let (content_type, signature) = ALLOWED_CV_FILE_TYPES
+            .iter()
+            .find(|(allowed, _)| *allowed == content_type)
+            .ok_or_else(|| {
+                CVServiceError::InvalidFile(format!("Unsupported content type {}", content_type))
+            })?;
+        let matches = match *content_type {
+            "text/plain" => std::str::from_utf8(data).is_ok(),
+            _ => data.starts_with(signature),
+        };
+        if !matches {
+            return Err(CVServiceError::InvalidFile(format!(
+                "File content is not {}",
+                content_type
+            )));
+        }
+        Ok(content_type)
+    }
+
+    /// Name of the object storing a file, files are grouped under the id of their author.
+    pub fn object_name(author_id: ObjectId, file_id: Uuid) -> String {
+        format!("{}/{}", author_id.to_hex(), file_id)
+    }
+
+    /// Store the file and attach it to the CV, only the author of the CV is
+    /// allowed to do so. The change is recorded as a new revision, so the
+    /// previous file stays reachable through the history.
+    pub async fn upload_cv_file(
+        db: &(impl CVRevisionDataSource + std::marker::Sync),
+        storage: &dyn StorageBackend,
+        user_id: ObjectId,
+        cv_id: ObjectId,
+        content_type: Option<&str>,
+        data: Vec<u8>,
+    ) -> Result<CV, CVServiceError> {
+        let cv = db.get_cv_by_id(cv_id).await?;
+        if cv.author_id != user_id.into() {
+            return Err(CVServiceError::PermissionDenied);
+        }
+        let content_type = Self::validate_cv_file(content_type, &data)?;
+        let file_id = Uuid::new();
+        storage
+            .put(&Self::object_name(user_id, file_id), content_type, data)
+            .await
+            .map_err(|_| CVServiceError::StorageError)?;
+        let input = UpdateCVInput::builder().with_cv(file_id).build().unwrap();
+        CVService::update_cv(db, user_id, cv_id, input)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Return a short-lived url to download the file of the CV, or `None` if
+    /// no file has been uploaded.
+    pub async fn get_cv_file_url(
+        storage: &dyn StorageBackend,
+        cv: &CV,
+    ) -> Result<Option<String>, CVServiceError> {
+        let file_id = match cv.cv {
+            Some(file_id) => file_id,
+            None => return Ok(None),
+        };
+        let object = Self::object_name(cv.author_id.into(), file_id);
+        storage
+            .signed_url(&object, CV_FILE_URL_TTL)
+            .await
+            .map(Some)
+            .map_err(|_| CVServiceError::StorageError)
+    }
+}
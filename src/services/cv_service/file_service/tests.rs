@@ -0,0 +1,116 @@
+use mongodb::bson::{oid::ObjectId, Uuid};
+
+use super::{CVFileService, MAX_CV_FILE_SIZE};
+use crate::services::{
+    cv_service::{cv_service::CVService, error::CVServiceError},
+    storage_service::{backend::StorageBackend, memory_storage::MemoryStorage},
+    tests::MockDatabase,
+};
+
+#[test]
+fn accepts_supported_files() {
+    let pdf = b"%PDF-1.7 rest of the file".to_vec();
+    assert_eq!(
+        Ok("application/pdf"),
+        CVFileService::validate_cv_file(Some("application/pdf"), &pdf)
+    );
+    let docx = b"PK\x03\x04 rest of the archive".to_vec();
+    assert_eq!(
+        Ok("application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+        CVFileService::validate_cv_file(
+            Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+            &docx
+        )
+    );
+    assert_eq!(
+        Ok("text/plain"),
+        CVFileService::validate_cv_file(Some("Text/Plain; charset=utf-8"), "Xin chào".as_bytes())
+    );
+}
+
+#[test]
+fn rejects_invalid_files() {
+    let pdf = b"%PDF-1.7".to_vec();
+    assert!(matches!(
+        CVFileService::validate_cv_file(None, &pdf),
+        Err(CVServiceError::InvalidFile(_))
+    ));
+    assert!(matches!(
+        CVFileService::validate_cv_file(Some("image/png"), b"\x89PNG"),
+        Err(CVServiceError::InvalidFile(_))
+    ));
+    assert!(matches!(
+        CVFileService::validate_cv_file(Some("application/pdf"), b"PK\x03\x04"),
+        Err(CVServiceError::InvalidFile(_))
+    ));
+    assert!(matches!(
+        CVFileService::validate_cv_file(Some("text/plain"), &[0xff, 0xfe, 0xfd]),
+        Err(CVServiceError::InvalidFile(_))
+    ));
+    assert!(matches!(
+        CVFileService::validate_cv_file(Some("application/pdf"), &[]),
+        Err(CVServiceError::InvalidFile(_))
+    ));
+    let mut too_large = b"%PDF-".to_vec();
+    too_large.resize(MAX_CV_FILE_SIZE + 1, 0);
+    assert_eq!(
+        Err(CVServiceError::FileTooLarge(MAX_CV_FILE_SIZE)),
+        CVFileService::validate_cv_file(Some("application/pdf"), &too_large)
+    );
+}
+
+#[test]
+fn files_are_stored_under_author() {
+    let author_id = ObjectId::new();
+    let file_id = Uuid::new();
+    let object = CVFileService::object_name(author_id, file_id);
+    assert_eq!(format!("{}/{}", author_id.to_hex(), file_id), object);
+}
+
+#[tokio::test]
+async fn upload_cv_file() {
+    let db = MockDatabase::new();
+    let storage = MemoryStorage::new();
+    let author = ObjectId::new();
+    let cv = CVService::create_cv(&db, author, "title".to_string(), "description".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        Ok(None),
+        CVFileService::get_cv_file_url(&storage, &cv).await
+    );
+
+    let pdf = b"%PDF-1.7 rest of the file".to_vec();
+    let rs = CVFileService::upload_cv_file(
+        &db,
+        &storage,
+        ObjectId::new(),
+        *cv.id,
+        Some("application/pdf"),
+        pdf.clone(),
+    )
+    .await;
+    assert_eq!(Err(CVServiceError::PermissionDenied), rs.map(|_| ()));
+
+    let cv = CVFileService::upload_cv_file(
+        &db,
+        &storage,
+        author,
+        *cv.id,
+        Some("application/pdf"),
+        pdf.clone(),
+    )
+    .await
+    .unwrap();
+    let file_id = cv.cv.unwrap();
+    let object = storage
+        .get(&CVFileService::object_name(author, file_id))
+        .await
+        .unwrap();
+    assert_eq!("application/pdf", object.content_type);
+    assert_eq!(pdf, object.data);
+    assert!(CVFileService::get_cv_file_url(&storage, &cv)
+        .await
+        .unwrap()
+        .is_some());
+}
@@ -5,9 +5,15 @@ use futures_core::stream::BoxStream;
 use mongodb::bson::oid::ObjectId;
 
 use crate::{
-    data_source::{cv::like::LikeDataSource, CVDataSource, UserDataSource},
-    models::cv::Like,
-    services::user_service::error::UserServiceError,
+    data_source::{
+        BlockDataSource, CVDataSource, CommentDataSource, FriendsListDataSource,
+        ReactionDataSource, UserDataSource,
+    },
+    models::{
+        cv::Like,
+        reaction::{ReactionKind, ReactionTarget},
+    },
+    services::{reaction_service::ReactionService, user_service::error::UserServiceError},
 };
 
 use super::error::CVServiceError;
@@ -16,7 +22,13 @@ pub struct LikeService;
 
 impl LikeService {
     pub async fn like_cv(
-        db: &(impl LikeDataSource + UserDataSource + CVDataSource + std::marker::Sync),
+        db: &(impl ReactionDataSource
+              + UserDataSource
+              + CVDataSource
+              + CommentDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
         user_id: ObjectId,
         cv_id: ObjectId,
     ) -> Result<(), CVServiceError> {
@@ -33,26 +45,14 @@ impl LikeService {
                 }
             }
         }
-        if let Err(e) = db.get_cv_by_id(cv_id).await {
-            match e {
-                crate::data_source::CVDataSourceError::IdNotFound(_) => {
-                    return Err(CVServiceError::LikeFailed("CV not found".to_string()));
-                }
-                crate::data_source::CVDataSourceError::DatabaseError => {
-                    return Err(CVServiceError::DatabaseError)
-                }
-                _ => {
-                    return Err(CVServiceError::LikeFailed(
-                        "Something went wrong".to_string(),
-                    ));
-                }
-            }
-        }
-        db.add_like(user_id, cv_id).await.map_err(|e| e.into())
+        ReactionService::react(db, user_id, ReactionTarget::Cv, cv_id, ReactionKind::Like)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.into())
     }
 
     pub async fn unlike_cv(
-        db: &(impl LikeDataSource + UserDataSource + CVDataSource + std::marker::Sync),
+        db: &(impl ReactionDataSource + UserDataSource + CVDataSource + std::marker::Sync),
         user_id: ObjectId,
         cv_id: ObjectId,
     ) -> Result<(), CVServiceError> {
@@ -84,11 +84,15 @@ impl LikeService {
                 }
             }
         }
-        db.delete_like(user_id, cv_id).await.map_err(|e| e.into())
+        match ReactionService::unreact(db, user_id, cv_id, Some(ReactionKind::Like)).await {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err(CVServiceError::LikeNotFound),
+            Err(e) => Err(e.into()),
+        }
     }
 
     pub async fn get_likes_by_cv(
-        db: &(impl LikeDataSource + UserDataSource + CVDataSource + std::marker::Sync),
+        db: &(impl ReactionDataSource + UserDataSource + CVDataSource + std::marker::Sync),
         cv_id: ObjectId,
     ) -> Result<BoxStream<Result<Like, CVServiceError>>, CVServiceError> {
         if let Err(e) = db.get_cv_by_id(cv_id).await {
@@ -106,14 +110,18 @@ impl LikeService {
                 }
             }
         }
-        db.get_likes(cv_id)
+        ReactionService::reactions(db, cv_id, Some(ReactionKind::Like))
             .await
             .map_err(|e| e.into())
-            .map(|stream| stream.map(|item| Ok(item)).boxed())
+            .map(|reactions| {
+                async_graphql::futures_util::stream::iter(reactions)
+                    .map(|reaction| Ok(Like::from(reaction)))
+                    .boxed()
+            })
     }
 
     pub async fn get_likes_count_of_cv(
-        db: &(impl LikeDataSource + UserDataSource + CVDataSource + std::marker::Sync),
+        db: &(impl ReactionDataSource + UserDataSource + CVDataSource + std::marker::Sync),
         cv_id: ObjectId,
     ) -> Result<i32, CVServiceError> {
         if let Err(e) = db.get_cv_by_id(cv_id).await {
@@ -131,6 +139,9 @@ impl LikeService {
                 }
             }
         }
-        db.get_likes_count(cv_id).await.map_err(|e| e.into())
+        ReactionService::reaction_count(db, cv_id, ReactionKind::Like)
+            .await
+            .map(|count| count as i32)
+            .map_err(|e| e.into())
     }
 }
@@ -4,6 +4,11 @@ pub mod error;
 pub mod bookmark_service;
 pub mod like_service;
 pub mod share_service;
+pub mod share_link_service;
+pub mod revision_service;
+pub mod file_service;
+pub mod text_service;
+pub mod render_service;
 
 #[cfg(test)]
 mod tests;
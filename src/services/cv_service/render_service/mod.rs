@@ -0,0 +1,345 @@
+pub mod pdf;
+#[cfg(test)]
+mod tests;
+
+use mongodb::bson::{self, oid::ObjectId};
+
+use crate::{
+    data_source::{cv::revision::CVRevisionDataSource, UserDataSource},
+    models::{
+        cv::{CVTemplate, CV},
+        users::User,
+    },
+    services::storage_service::backend::StorageBackend,
+};
+
+use self::pdf::{text_width, to_ascii, Color, FontFamily, FontStyle, PdfDocument, PAGE_SIZE};
+
+use super::{error::CVServiceError, file_service::CVFileService};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const BLACK: Color = (0.0, 0.0, 0.0);
+const WHITE: Color = (1.0, 1.0, 1.0);
+const GREY: Color = (0.35, 0.35, 0.35);
+
+/// Fonts, sizes and colors of a template, in points.
+struct TemplateStyle {
+    family: FontFamily,
+    margin: f32,
+    name_size: f32,
+    heading_size: f32,
+    body_size: f32,
+    line_spacing: f32,
+    section_spacing: f32,
+    accent: Color,
+    /// Draw the name in white on a band of the accent color.
+    header_band: bool,
+    /// Draw a rule under the section headings.
+    heading_rule: bool,
+}
+
+impl TemplateStyle {
+    fn of(template: CVTemplate) -> Self {
+        match template {
+            CVTemplate::Classic => Self {
+                family: FontFamily::Times,
+                margin: 56.0,
+                name_size: 24.0,
+                heading_size: 13.0,
+                body_size: 11.0,
+                line_spacing: 1.35,
+                section_spacing: 16.0,
+                accent: BLACK,
+                header_band: false,
+                heading_rule: true,
+            },
+            CVTemplate::Modern => Self {
+                family: FontFamily::Helvetica,
+                margin: 48.0,
+                name_size: 26.0,
+                heading_size: 13.0,
+                body_size: 10.5,
+                line_spacing: 1.4,
+                section_spacing: 18.0,
+                accent: (0.11, 0.37, 0.62),
+                header_band: true,
+                heading_rule: false,
+            },
+            CVTemplate::Compact => Self {
+                family: FontFamily::Helvetica,
+                margin: 36.0,
+                name_size: 18.0,
+                heading_size: 11.0,
+                body_size: 9.0,
+                line_spacing: 1.25,
+                section_spacing: 10.0,
+                accent: (0.2, 0.2, 0.2),
+                header_band: false,
+                heading_rule: true,
+            },
+        }
+    }
+}
+
+/// Writes blocks of text from the top of the page down, starting a new page
+/// when the current one is full.
+struct Layout {
+    doc: PdfDocument,
+    style: TemplateStyle,
+    y: f32,
+}
+
+impl Layout {
+    fn new(style: TemplateStyle, title: &str) -> Self {
+        Self {
+            doc: PdfDocument::new(style.family, title),
+            y: PAGE_SIZE.1 - style.margin,
+            style,
+        }
+    }
+
+    fn content_width(&self) -> f32 {
+        PAGE_SIZE.0 - 2.0 * self.style.margin
+    }
+
+    /// Move down by `height`, on a new page if it does not fit on this one.
+    fn advance(&mut self, height: f32) {
+        if self.y - height < self.style.margin {
+            self.doc.add_page();
+            self.y = PAGE_SIZE.1 - self.style.margin;
+        }
+        self.y -= height;
+    }
+
+    /// Split the text into lines that fit in `width`. Words longer than a
+    /// line are kept whole.
+    fn wrap(text: &str, style: FontStyle, size: f32, width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = match line.is_empty() {
+                    true => word.to_string(),
+                    false => format!("{} {}", line, word),
+                };
+                if !line.is_empty() && text_width(&candidate, style, size) > width {
+                    lines.push(std::mem::replace(&mut line, word.to_string()));
+                } else {
+                    line = candidate;
+                }
+            }
+            if !line.is_empty() {
+                lines.push(line);
+            }
+        }
+        lines
+    }
+
+    fn paragraph(&mut self, text: &str, style: FontStyle, size: f32, color: Color) {
+        let text = to_ascii(text);
+        let x = self.style.margin;
+        for line in Self::wrap(&text, style, size, self.content_width()) {
+            self.advance(size * self.style.line_spacing);
+            self.doc.text(x, self.y, style, size, color, &line);
+        }
+    }
+
+    fn header(&mut self, name: &str, subtitle: Option<&str>, contact: &str) {
+        let style = &self.style;
+        let (name_size, body_size, margin) = (style.name_size, style.body_size, style.margin);
+        if self.style.header_band {
+            let height = name_size * 2.0 + body_size * 3.0;
+            self.doc.fill_rect(
+                0.0,
+                PAGE_SIZE.1 - height,
+                PAGE_SIZE.0,
+                height,
+                self.style.accent,
+            );
+            self.y = PAGE_SIZE.1 - name_size * 1.6;
+            self.doc.text(
+                margin,
+                self.y,
+                FontStyle::Bold,
+                name_size,
+                WHITE,
+                &to_ascii(name),
+            );
+            for line in subtitle.into_iter().chain(Some(contact)) {
+                self.y -= body_size * 1.5;
+                self.doc.text(
+                    margin,
+                    self.y,
+                    FontStyle::Regular,
+                    body_size,
+                    WHITE,
+                    &to_ascii(line),
+                );
+            }
+            self.y = PAGE_SIZE.1 - height - self.style.section_spacing / 2.0;
+        } else {
+            self.paragraph(name, FontStyle::Bold, name_size, self.style.accent);
+            if let Some(subtitle) = subtitle {
+                self.paragraph(subtitle, FontStyle::Italic, body_size * 1.2, GREY);
+            }
+            self.paragraph(contact, FontStyle::Regular, body_size, GREY);
+        }
+    }
+
+    fn section(&mut self, title: &str) {
+        let size = self.style.heading_size;
+        // keep the heading with the first line of the section
+        self.advance(self.style.section_spacing + size * 3.0);
+        self.y += size * 3.0;
+        self.paragraph(
+            &title.to_uppercase(),
+            FontStyle::Bold,
+            size,
+            self.style.accent,
+        );
+        if self.style.heading_rule {
+            let y = self.y - size * 0.4;
+            let (left, right) = (self.style.margin, PAGE_SIZE.0 - self.style.margin);
+            self.doc
+                .line((left, y), (right, y), 0.75, self.style.accent);
+            self.y -= size * 0.4;
+        }
+    }
+
+    fn entry(&mut self, title: &str, details: &str, description: &str) {
+        let size = self.style.body_size;
+        self.advance(size * 0.5);
+        self.paragraph(title, FontStyle::Bold, size, BLACK);
+        if !details.is_empty() {
+            self.paragraph(details, FontStyle::Italic, size, GREY);
+        }
+        if !description.trim().is_empty() {
+            self.paragraph(description, FontStyle::Regular, size, BLACK);
+        }
+    }
+}
+
+/// Format a date as e.g. "Mar 2021".
+fn format_date(date: bson::DateTime) -> Option<String> {
+    let date = date.try_to_rfc3339_string().ok()?;
+    let year = date.get(..4)?;
+    let month = date.get(5..7)?.parse::<usize>().ok()?;
+    Some(format!("{} {}", MONTHS.get(month.checked_sub(1)?)?, year))
+}
+
+fn format_period(start: Option<bson::DateTime>, end: Option<bson::DateTime>) -> String {
+    match (start.and_then(format_date), end.and_then(format_date)) {
+        (Some(start), Some(end)) => format!("{} - {}", start, end),
+        (Some(start), None) => format!("{} - Present", start),
+        (None, Some(end)) => end,
+        (None, None) => String::new(),
+    }
+}
+
+/// Join the non-empty parts with a separator.
+fn join(parts: &[&str], separator: &str) -> String {
+    parts
+        .iter()
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+pub struct CVRenderService;
+
+impl CVRenderService {
+    /// Render a PDF CV from the educations, experiences, skills and
+    /// personalities of the profile of the user, titled after the CV.
+    pub fn render_pdf(user: &User, cv: &CV, template: CVTemplate) -> Vec<u8> {
+        let name = join(&[&user.first_name, &user.last_name], " ");
+        let mut layout = Layout::new(TemplateStyle::of(template), &cv.title);
+        let location = join(
+            &[
+                user.city.as_deref().unwrap_or_default(),
+                user.country.as_deref().unwrap_or_default(),
+            ],
+            ", ",
+        );
+        let contact = join(&[&user.primary_email, &location], " | ");
+        let subtitle = Some(cv.title.as_str()).filter(|title| !title.trim().is_empty());
+        layout.header(&name, subtitle, &contact);
+
+        if let Some(about) = user
+            .about
+            .as_deref()
+            .filter(|about| !about.trim().is_empty())
+        {
+            layout.section("About");
+            layout.paragraph(about, FontStyle::Regular, layout.style.body_size, BLACK);
+        }
+        if !user.experiences.is_empty() {
+            layout.section("Experience");
+            for experience in &user.experiences {
+                let title = join(&[&experience.title, &experience.company], " - ");
+                let period = format_period(experience.start_date, experience.end_date);
+                let details = join(
+                    &[&experience.employment_type, &experience.location, &period],
+                    " | ",
+                );
+                layout.entry(&title, &details, &experience.description);
+            }
+        }
+        if !user.educations.is_empty() {
+            layout.section("Education");
+            for education in &user.educations {
+                let study = match &education.minor {
+                    Some(minor) if !minor.trim().is_empty() => {
+                        format!("{}, minor in {}", education.major, minor)
+                    }
+                    _ => education.major.clone(),
+                };
+                let title = join(&[&education.degree, &study], ", ");
+                let period = format_period(education.start_date, education.end_date);
+                let details = join(&[&education.school, &period], " | ");
+                layout.entry(&title, &details, "");
+            }
+        }
+        for (title, items) in [
+            ("Skills", &user.skills),
+            ("Personalities", &user.personalities),
+        ] {
+            if !items.is_empty() {
+                layout.section(title);
+                let items = items.iter().map(|item| item.as_str()).collect::<Vec<_>>();
+                layout.paragraph(
+                    &join(&items, ", "),
+                    FontStyle::Regular,
+                    layout.style.body_size,
+                    BLACK,
+                );
+            }
+        }
+        layout.doc.finish()
+    }
+
+    /// Render a PDF from the profile of the author of the CV and attach it
+    /// to the CV like an uploaded file, only the author is allowed to do so.
+    pub async fn render_cv_pdf(
+        db: &(impl CVRevisionDataSource + UserDataSource + std::marker::Sync),
+        storage: &dyn StorageBackend,
+        user_id: ObjectId,
+        cv_id: ObjectId,
+        template: CVTemplate,
+    ) -> Result<CV, CVServiceError> {
+        let cv = db.get_cv_by_id(cv_id).await?;
+        if cv.author_id != user_id.into() {
+            return Err(CVServiceError::PermissionDenied);
+        }
+        let user = db
+            .get_user_by_id(user_id)
+            .await
+            .map_err(|_| CVServiceError::AuthorIdNotFound(user_id))?;
+        let data = Self::render_pdf(&user, &cv, template);
+        CVFileService::upload_cv_file(db, storage, user_id, cv_id, Some("application/pdf"), data)
+            .await
+    }
+}
@@ -0,0 +1,228 @@
+//! A minimal PDF writer for generated CVs. Only the standard fonts of PDF
+//! readers are used so that no font has to be embedded, which limits the text
+//! to ASCII, see `to_ascii`.
+
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Width and height of an A4 page, in points.
+pub const PAGE_SIZE: (f32, f32) = (595.0, 842.0);
+
+/// Widths of the printable ASCII characters in Helvetica, in thousandths of
+/// the font size, starting with the space.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Bold glyphs are wider, this is enough to keep wrapped lines in the margins.
+const BOLD_WIDTH_FACTOR: f32 = 1.08;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontFamily {
+    Helvetica,
+    Times,
+}
+
+impl FontFamily {
+    fn base_fonts(&self) -> [&'static str; 3] {
+        match self {
+            FontFamily::Helvetica => ["Helvetica", "Helvetica-Bold", "Helvetica-Oblique"],
+            FontFamily::Times => ["Times-Roman", "Times-Bold", "Times-Italic"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontStyle {
+    Regular,
+    Bold,
+    Italic,
+}
+
+impl FontStyle {
+    fn resource_name(&self) -> &'static str {
+        match self {
+            FontStyle::Regular => "F1",
+            FontStyle::Bold => "F2",
+            FontStyle::Italic => "F3",
+        }
+    }
+}
+
+/// A color with red, green and blue components between 0 and 1.
+pub type Color = (f32, f32, f32);
+
+/// Replace the characters the standard fonts cannot show. Accents are
+/// removed, e.g. "Nguyễn Văn Đức" becomes "Nguyen Van Duc", and other
+/// characters outside of ASCII become `?`.
+pub fn to_ascii(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .map(|c| match c {
+            'đ' => 'd',
+            'Đ' => 'D',
+            '‘' | '’' => '\'',
+            '“' | '”' => '"',
+            '–' | '—' => '-',
+            '•' => '*',
+            c if c.is_ascii_control() || c.is_whitespace() => ' ',
+            c if c.is_ascii() => c,
+            _ => '?',
+        })
+        .collect()
+}
+
+/// Width of the ASCII text in the font, in points.
+pub fn text_width(text: &str, style: FontStyle, size: f32) -> f32 {
+    let width: u32 = text
+        .bytes()
+        .map(|b| match b {
+            32..=126 => HELVETICA_WIDTHS[(b - 32) as usize] as u32,
+            _ => 556,
+        })
+        .sum();
+    let factor = match style {
+        FontStyle::Bold => BOLD_WIDTH_FACTOR,
+        _ => 1.0,
+    };
+    width as f32 * size * factor / 1000.0
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '(' | ')') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A document built page by page with absolute positions, the origin is the
+/// bottom left corner of the page.
+pub struct PdfDocument {
+    family: FontFamily,
+    title: String,
+    pages: Vec<String>,
+}
+
+impl PdfDocument {
+    pub fn new(family: FontFamily, title: &str) -> Self {
+        Self {
+            family,
+            title: to_ascii(title),
+            pages: vec![String::new()],
+        }
+    }
+
+    pub fn add_page(&mut self) {
+        self.pages.push(String::new());
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    fn content(&mut self) -> &mut String {
+        self.pages.last_mut().unwrap()
+    }
+
+    /// Draw the text on the current page, `text` must already be ASCII.
+    pub fn text(&mut self, x: f32, y: f32, style: FontStyle, size: f32, color: Color, text: &str) {
+        let op = format!(
+            "BT /{} {:.1} Tf {:.3} {:.3} {:.3} rg {:.2} {:.2} Td ({}) Tj ET\n",
+            style.resource_name(),
+            size,
+            color.0,
+            color.1,
+            color.2,
+            x,
+            y,
+            escape(text)
+        );
+        self.content().push_str(&op);
+    }
+
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        let op = format!(
+            "{:.3} {:.3} {:.3} rg {:.2} {:.2} {:.2} {:.2} re f\n",
+            color.0, color.1, color.2, x, y, width, height
+        );
+        self.content().push_str(&op);
+    }
+
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, color: Color) {
+        let op = format!(
+            "{:.3} {:.3} {:.3} RG {:.2} w {:.2} {:.2} m {:.2} {:.2} l S\n",
+            color.0, color.1, color.2, width, from.0, from.1, to.0, to.1
+        );
+        self.content().push_str(&op);
+    }
+
+    /// Serialize the document.
+    pub fn finish(self) -> Vec<u8> {
+        let fonts = self.family.base_fonts();
+        let first_page = 7;
+        let kids = (0..self.pages.len())
+            .map(|i| format!("{} 0 R", first_page + 2 * i))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids,
+                self.pages.len()
+            ),
+        ];
+        for font in fonts {
+            objects.push(format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                font
+            ));
+        }
+        objects.push(format!(
+            "<< /Title ({}) /Producer (SeeVi) >>",
+            escape(&self.title)
+        ));
+        for (i, content) in self.pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Contents {} 0 R \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> >>",
+                PAGE_SIZE.0,
+                PAGE_SIZE.1,
+                first_page + 2 * i + 1
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            ));
+        }
+
+        let mut data = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(data.len());
+            data.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).bytes());
+        }
+        let xref = data.len();
+        data.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+        for offset in offsets {
+            data.extend(format!("{:010} 00000 n \n", offset).bytes());
+        }
+        data.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R /Info 6 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref
+            )
+            .bytes(),
+        );
+        data
+    }
+}
@@ -0,0 +1,188 @@
+use mongodb::bson::{self, oid::ObjectId};
+
+use super::{
+    pdf::{to_ascii, FontStyle},
+    CVRenderService, Layout,
+};
+use crate::{
+    models::{
+        cv::{CVTemplate, CreateCVInput, CV},
+        education::Education,
+        experience::ExperienceBuilder,
+        sex::Sex,
+        users::{CreateUserInput, User},
+    },
+    services::{
+        cv_service::{
+            cv_service::CVService, error::CVServiceError, file_service::CVFileService,
+            text_service::CVTextService,
+        },
+        storage_service::{backend::StorageBackend, memory_storage::MemoryStorage},
+        tests::MockDatabase,
+        user_service::UserService,
+    },
+};
+
+const TEMPLATES: [CVTemplate; 3] = [CVTemplate::Classic, CVTemplate::Modern, CVTemplate::Compact];
+
+fn user_input(username: &str, experiences: usize) -> CreateUserInput {
+    let mut input = CreateUserInput::builder()
+        .with_username(username)
+        .with_password("password")
+        .with_first_name("Văn Đức")
+        .with_last_name("Nguyễn")
+        .with_primary_email("duc@mail.com")
+        .with_city("Ho Chi Minh City")
+        .with_country("Vietnam")
+        .with_about("Backend developer (Rust) who likes \\ and parentheses.")
+        .with_skill("Rust")
+        .with_skill("MongoDB")
+        .with_personalities("Curious")
+        .with_sex(Sex::Male)
+        .with_education(Education {
+            school: "HCMUS".to_string(),
+            major: "Computer Science".to_string(),
+            minor: None,
+            degree: "Bachelor".to_string(),
+            start_date: Some(bson::DateTime::parse_rfc3339_str("2017-09-01T00:00:00Z").unwrap()),
+            end_date: None,
+        });
+    for i in 0..experiences {
+        input = input.with_experience(
+            ExperienceBuilder::default()
+                .with_title(format!("Developer {}", i))
+                .with_company("SeeVi")
+                .with_employment_type("Full-time")
+                .with_location("Ho Chi Minh City")
+                .with_description("Built GraphQL APIs ".repeat(20))
+                .with_start_date(bson::DateTime::parse_rfc3339_str("2021-03-01T00:00:00Z").unwrap())
+                .build()
+                .unwrap(),
+        );
+    }
+    input.build().unwrap()
+}
+
+async fn create_user(db: &MockDatabase, username: &str, experiences: usize) -> User {
+    UserService::create_user(db, user_input(username, experiences))
+        .await
+        .unwrap()
+}
+
+fn page_count(pdf: &[u8]) -> usize {
+    pdf.windows(12).filter(|w| w == b"/Type /Page ").count()
+}
+
+#[test]
+fn converts_to_ascii() {
+    assert_eq!("Nguyen Van Duc", to_ascii("Nguyễn Văn Đức"));
+    assert_eq!("\"Rust\" - 'GraphQL' ?", to_ascii("“Rust” – ‘GraphQL’ 日"));
+}
+
+#[test]
+fn wraps_lines() {
+    let lines = Layout::wrap(
+        "one two three four five six seven",
+        FontStyle::Regular,
+        10.0,
+        60.0,
+    );
+    assert!(lines.len() > 1);
+    assert_eq!("one two three four five six seven", lines.join(" "));
+    let lines = Layout::wrap("averyveryverylongword", FontStyle::Regular, 10.0, 20.0);
+    assert_eq!(vec!["averyveryverylongword"], lines);
+}
+
+#[tokio::test]
+async fn renders_templates() {
+    let db = MockDatabase::new();
+    let user = create_user(&db, "test", 2).await;
+    let cv = CV::from(
+        CreateCVInput::builder()
+            .with_author_id(*user.id)
+            .with_title("Backend CV")
+            .build()
+            .unwrap(),
+    );
+    for template in TEMPLATES {
+        let pdf = CVRenderService::render_pdf(&user, &cv, template);
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert_eq!(1, page_count(&pdf));
+        let text = CVTextService::extract_text("application/pdf", &pdf).unwrap();
+        for expected in [
+            "Van Duc Nguyen",
+            "Backend CV",
+            "duc@mail.com",
+            "Developer 1 - SeeVi",
+            "Mar 2021 - Present",
+            "Bachelor, Computer Science",
+            "Rust, MongoDB",
+            "Curious",
+            "(Rust)",
+        ] {
+            assert!(
+                text.contains(expected),
+                "{:?}: {:?} not in {:?}",
+                template,
+                expected,
+                text
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn renders_long_profiles_on_several_pages() {
+    let db = MockDatabase::new();
+    let user = create_user(&db, "test", 12).await;
+    let cv = CV::from(
+        CreateCVInput::builder()
+            .with_author_id(*user.id)
+            .with_title("Backend CV")
+            .build()
+            .unwrap(),
+    );
+    let classic = CVRenderService::render_pdf(&user, &cv, CVTemplate::Classic);
+    let compact = CVRenderService::render_pdf(&user, &cv, CVTemplate::Compact);
+    assert!(page_count(&classic) > 1);
+    assert!(page_count(&compact) < page_count(&classic));
+    let text = CVTextService::extract_text("application/pdf", &classic).unwrap();
+    assert!(text.contains("Developer 11 - SeeVi"));
+}
+
+#[tokio::test]
+async fn render_cv_pdf() {
+    let db = MockDatabase::new();
+    let storage = MemoryStorage::new();
+    let user = create_user(&db, "test", 1).await;
+    let other = create_user(&db, "other", 0).await;
+    let cv = CVService::create_cv(&db, *user.id, "Backend CV".to_string(), "".to_string())
+        .await
+        .unwrap();
+    assert_eq!(None, cv.cv);
+
+    assert_eq!(
+        Err(CVServiceError::PermissionDenied),
+        CVRenderService::render_cv_pdf(&db, &storage, *other.id, *cv.id, CVTemplate::Modern).await
+    );
+    let rs = CVRenderService::render_cv_pdf(
+        &db,
+        &storage,
+        *user.id,
+        ObjectId::new(),
+        CVTemplate::Modern,
+    )
+    .await;
+    assert!(rs.is_err());
+
+    let cv = CVRenderService::render_cv_pdf(&db, &storage, *user.id, *cv.id, CVTemplate::Modern)
+        .await
+        .unwrap();
+    let file_id = cv.cv.unwrap();
+    let object = storage
+        .get(&CVFileService::object_name(*user.id, file_id))
+        .await
+        .unwrap();
+    assert_eq!("application/pdf", object.content_type);
+    assert!(object.data.starts_with(b"%PDF-"));
+}
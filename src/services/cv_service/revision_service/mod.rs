@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests;
+
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    data_source::{
+        cv::revision::CVRevisionDataSource, BlockDataSource, CVDataSourceError,
+        FriendsListDataSource,
+    },
+    models::cv::{CVRevision, CVRevisionDiff, CV},
+};
+
+use super::{cv_service::CVService, error::CVServiceError};
+
+/// Times a revision is numbered again when another one took its number.
+const MAX_REVISION_ATTEMPTS: usize = 5;
+
+pub struct CVRevisionService;
+
+impl CVRevisionService {
+    /// Record the current state of the CV as its newest revision. Revisions
+    /// recorded at the same time get the next numbers in turn.
+    pub async fn record_revision(
+        db: &(impl CVRevisionDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        cv: &CV,
+        restored_from: Option<ObjectId>,
+    ) -> Result<CVRevision, CVDataSourceError> {
+        for _ in 0..MAX_REVISION_ATTEMPTS {
+            let number = db.last_cv_revision_number(cv.id.into()).await? + 1;
+            let revision = CVRevision::new(cv, number, user_id, restored_from);
+            match db.add_cv_revision(revision.clone()).await {
+                Ok(()) => return Ok(revision),
+                Err(CVDataSourceError::RevisionNumberTaken(_)) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Err(CVDataSourceError::DatabaseError)
+    }
+
+    /// Return the revisions of the CV, oldest first, if `viewer_id` is allowed to see the CV.
+    pub async fn get_revisions_of_cv(
+        db: &(impl CVRevisionDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
+        viewer_id: Option<ObjectId>,
+        cv_id: ObjectId,
+    ) -> Result<Vec<CVRevision>, CVServiceError> {
+        CVService::get_cv_by_id(db, viewer_id, cv_id).await?;
+        db.get_cv_revisions(cv_id).await.map_err(|err| err.into())
+    }
+
+    /// Compare the metadata of two revisions of the same CV.
+    pub async fn diff_revisions(
+        db: &(impl CVRevisionDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
+        viewer_id: Option<ObjectId>,
+        from_id: ObjectId,
+        to_id: ObjectId,
+    ) -> Result<CVRevisionDiff, CVServiceError> {
+        let from = db.get_cv_revision_by_id(from_id).await?;
+        let to = db.get_cv_revision_by_id(to_id).await?;
+        if from.cv_id != to.cv_id {
+            return Err(CVServiceError::RevisionMismatch);
+        }
+        CVService::get_cv_by_id(db, viewer_id, from.cv_id.into()).await?;
+        Ok(from.diff(&to))
+    }
+
+    /// Bring the metadata of the CV back to the state of the revision. The
+    /// restore itself is recorded as a new revision, so history is never lost.
+    /// Only the author of the CV is allowed to do so.
+    pub async fn restore_revision(
+        db: &(impl CVRevisionDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        revision_id: ObjectId,
+    ) -> Result<CV, CVServiceError> {
+        let revision = db.get_cv_revision_by_id(revision_id).await?;
+        let cv = db.get_cv_by_id(revision.cv_id.into()).await?;
+        if cv.author_id != user_id.into() {
+            return Err(CVServiceError::PermissionDenied);
+        }
+        let cv = db.restore_cv_revision(&revision).await?;
+        Self::record_revision(db, user_id, &cv, Some(revision_id)).await?;
+        Ok(cv)
+    }
+}
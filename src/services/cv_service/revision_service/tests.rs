@@ -0,0 +1,225 @@
+use super::CVRevisionService;
+use crate::{
+    data_source::{cv::revision::CVRevisionDataSource, CVDataSourceError},
+    models::cv::{CVRevision, UpdateCVInput, Visibility, CV},
+    services::{
+        cv_service::{cv_service::CVService, error::CVServiceError},
+        tests::MockDatabase,
+    },
+};
+use mongodb::bson::oid::ObjectId;
+
+#[async_trait::async_trait]
+impl CVRevisionDataSource for MockDatabase {
+    async fn add_cv_revision(&self, revision: CVRevision) -> Result<(), CVDataSourceError> {
+        let mut revisions = self.cv_revisions.lock().unwrap();
+        if revisions
+            .iter()
+            .any(|stored| stored.cv_id == revision.cv_id && stored.number == revision.number)
+        {
+            return Err(CVDataSourceError::RevisionNumberTaken(revision.number));
+        }
+        revisions.push(revision);
+        Ok(())
+    }
+
+    async fn last_cv_revision_number(&self, cv_id: ObjectId) -> Result<u32, CVDataSourceError> {
+        let revisions = self.cv_revisions.lock().unwrap();
+        Ok(revisions
+            .iter()
+            .filter(|revision| *revision.cv_id == cv_id)
+            .map(|revision| revision.number)
+            .max()
+            .unwrap_or(0))
+    }
+
+    async fn get_cv_revision_by_id(&self, id: ObjectId) -> Result<CVRevision, CVDataSourceError> {
+        let revisions = self.cv_revisions.lock().unwrap();
+        revisions
+            .iter()
+            .find(|revision| *revision.id == id)
+            .cloned()
+            .ok_or(CVDataSourceError::IdNotFound(id))
+    }
+
+    async fn get_cv_revisions(&self, cv_id: ObjectId) -> Result<Vec<CVRevision>, CVDataSourceError> {
+        let revisions = self.cv_revisions.lock().unwrap();
+        Ok(revisions
+            .iter()
+            .filter(|revision| *revision.cv_id == cv_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn restore_cv_revision(&self, revision: &CVRevision) -> Result<CV, CVDataSourceError> {
+        let mut cvs = self.cvs.lock().unwrap();
+        let cv = cvs
+            .iter_mut()
+            .find(|cv| cv.id == revision.cv_id)
+            .ok_or(CVDataSourceError::IdNotFound(*revision.cv_id))?;
+        cv.title = revision.title.clone();
+        cv.description = revision.description.clone();
+        cv.tags = revision.tags.clone();
+        cv.cv = revision.cv;
+        cv.visibility = revision.visibility;
+        Ok(cv.clone())
+    }
+}
+
+#[tokio::test]
+async fn basic() {
+    let db = MockDatabase::new();
+    let author = ObjectId::new();
+    let stranger = ObjectId::new();
+    let cv = CVService::create_cv(&db, author, "title".to_string(), "description".to_string())
+        .await
+        .unwrap();
+    let cv_id = *cv.id;
+    CVService::change_title(&db, author, cv_id, "new title".to_string())
+        .await
+        .unwrap();
+    CVService::add_tag(&db, author, cv_id, "rust".to_string())
+        .await
+        .unwrap();
+
+    let revisions = CVRevisionService::get_revisions_of_cv(&db, None, cv_id)
+        .await
+        .unwrap();
+    assert_eq!(3, revisions.len());
+    assert_eq!(vec![1, 2, 3], revisions.iter().map(|r| r.number).collect::<Vec<_>>());
+    assert!(revisions.iter().all(|r| *r.author_id == author));
+    assert_eq!("title", revisions[0].title);
+    assert_eq!("new title", revisions[2].title);
+    assert_eq!(vec!["rust".to_string()], revisions[2].tags);
+
+    let diff = CVRevisionService::diff_revisions(&db, None, *revisions[0].id, *revisions[2].id)
+        .await
+        .unwrap();
+    let fields = diff
+        .changes
+        .iter()
+        .map(|change| change.field.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["title", "tags"], fields);
+    assert_eq!(Some("title".to_string()), diff.changes[0].old_value);
+    assert_eq!(Some("new title".to_string()), diff.changes[0].new_value);
+
+    CVRevisionService::restore_revision(&db, stranger, *revisions[0].id)
+        .await
+        .expect_err("Only the author can restore a revision");
+    let restored = CVRevisionService::restore_revision(&db, author, *revisions[0].id)
+        .await
+        .unwrap();
+    assert_eq!("title", restored.title);
+    assert!(restored.tags.is_empty());
+
+    let revisions = CVRevisionService::get_revisions_of_cv(&db, None, cv_id)
+        .await
+        .unwrap();
+    assert_eq!(4, revisions.len());
+    assert_eq!(Some(revisions[0].id), revisions[3].restored_from);
+    assert_eq!(Some(author.into()), revisions.last().map(|r| r.author_id));
+}
+
+#[tokio::test]
+async fn restore_removes_missing_fields() {
+    let db = MockDatabase::new();
+    let author = ObjectId::new();
+    let cv = CVService::create_cv(&db, author, "title".to_string(), "description".to_string())
+        .await
+        .unwrap();
+    let cv_id = *cv.id;
+    let mut first = CVRevisionService::get_revisions_of_cv(&db, None, cv_id)
+        .await
+        .unwrap()
+        .remove(0);
+    first.description = None;
+    db.cv_revisions.lock().unwrap()[0] = first.clone();
+    let input = UpdateCVInput::builder()
+        .with_cv(mongodb::bson::Uuid::new())
+        .build()
+        .unwrap();
+    CVService::update_cv(&db, author, cv_id, input).await.unwrap();
+
+    let restored = CVRevisionService::restore_revision(&db, author, *first.id)
+        .await
+        .unwrap();
+    assert_eq!(None, restored.description);
+    assert_eq!(None, restored.cv);
+}
+
+#[tokio::test]
+async fn only_the_author_can_edit() {
+    let db = MockDatabase::new();
+    let author = ObjectId::new();
+    let stranger = ObjectId::new();
+    let cv = CVService::create_cv(&db, author, "title".to_string(), "description".to_string())
+        .await
+        .unwrap();
+    let cv_id = *cv.id;
+
+    let rs = CVService::change_title(&db, stranger, cv_id, "stolen".to_string()).await;
+    assert_eq!(Err(CVServiceError::PermissionDenied), rs);
+    let rs = CVService::change_description(&db, stranger, cv_id, "stolen".to_string()).await;
+    assert_eq!(Err(CVServiceError::PermissionDenied), rs);
+    let rs = CVService::add_tag(&db, stranger, cv_id, "stolen".to_string()).await;
+    assert_eq!(Err(CVServiceError::PermissionDenied), rs);
+    let rs = CVService::remove_tag(&db, stranger, cv_id, "stolen".to_string()).await;
+    assert_eq!(Err(CVServiceError::PermissionDenied), rs);
+
+    let revisions = CVRevisionService::get_revisions_of_cv(&db, None, cv_id)
+        .await
+        .unwrap();
+    assert_eq!(1, revisions.len());
+}
+
+#[tokio::test]
+async fn revisions_follow_visibility() {
+    let db = MockDatabase::new();
+    let author = ObjectId::new();
+    let cv = CVService::create_cv(&db, author, "title".to_string(), "description".to_string())
+        .await
+        .unwrap();
+    let other = CVService::create_cv(&db, author, "other".to_string(), "other".to_string())
+        .await
+        .unwrap();
+    CVService::change_visibility(&db, author, *cv.id, Visibility::Private)
+        .await
+        .unwrap();
+
+    let rs = CVRevisionService::get_revisions_of_cv(&db, None, *cv.id).await;
+    assert_eq!(Err(CVServiceError::CVNotVisible(*cv.id)), rs);
+    let revisions = CVRevisionService::get_revisions_of_cv(&db, Some(author), *cv.id)
+        .await
+        .unwrap();
+    assert_eq!(2, revisions.len());
+
+    let other_revisions = CVRevisionService::get_revisions_of_cv(&db, None, *other.id)
+        .await
+        .unwrap();
+    let rs = CVRevisionService::diff_revisions(
+        &db,
+        Some(author),
+        *revisions[0].id,
+        *other_revisions[0].id,
+    )
+    .await;
+    assert_eq!(Err(CVServiceError::RevisionMismatch), rs);
+}
+
+#[tokio::test]
+async fn revisions_recorded_together_get_distinct_numbers() {
+    let db = MockDatabase::new();
+    let author = ObjectId::new();
+    let cv = CVService::create_cv(&db, author, "title".to_string(), "description".to_string())
+        .await
+        .unwrap();
+    let records = (0..3).map(|_| CVRevisionService::record_revision(&db, author, &cv, None));
+    let numbers = async_graphql::futures_util::future::join_all(records)
+        .await
+        .into_iter()
+        .map(|revision| revision.unwrap().number)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![2, 3, 4], numbers);
+    assert_eq!(4, db.last_cv_revision_number(*cv.id).await.unwrap());
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests;
+
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    data_source::cv::share_link::ShareLinkDataSource,
+    models::cv::{share_link::MAX_SHARE_LINK_LIFETIME, ShareLink, CV},
+};
+
+use super::error::CVServiceError;
+
+pub struct ShareLinkService;
+
+impl ShareLinkService {
+    /// Create a share link for the CV, only the author of the CV is allowed to do so.
+    /// `expires_in` is the lifetime of the link in seconds.
+    pub async fn create_share_link(
+        db: &(impl ShareLinkDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        cv_id: ObjectId,
+        expires_in: Option<i64>,
+        max_views: Option<u32>,
+    ) -> Result<ShareLink, CVServiceError> {
+        if matches!(expires_in, Some(secs) if secs <= 0) {
+            return Err(CVServiceError::InvalidShareLink(
+                "expiresIn must be positive".to_string(),
+            ));
+        }
+        if matches!(expires_in, Some(secs) if secs > MAX_SHARE_LINK_LIFETIME) {
+            return Err(CVServiceError::InvalidShareLink(format!(
+                "expiresIn must be at most {} seconds",
+                MAX_SHARE_LINK_LIFETIME
+            )));
+        }
+        if max_views == Some(0) {
+            return Err(CVServiceError::InvalidShareLink(
+                "maxViews must be positive".to_string(),
+            ));
+        }
+        let cv = db.get_cv_by_id(cv_id).await?;
+        if cv.author_id != user_id.into() {
+            return Err(CVServiceError::PermissionDenied);
+        }
+        let link = ShareLink::new(cv_id, user_id, expires_in, max_views);
+        db.add_share_link(link.clone())
+            .await
+            .map_err(|e| e.into())?;
+        Ok(link)
+    }
+
+    /// Open the CV behind a share link, counting one view. Links are granted by
+    /// the author, so they work regardless of the visibility of the CV.
+    pub async fn get_cv_by_share_token(
+        db: &(impl ShareLinkDataSource + std::marker::Sync),
+        token: &str,
+    ) -> Result<CV, CVServiceError> {
+        let link = db
+            .get_share_link_by_token(token)
+            .await
+            .map_err(|e| e.into())?;
+        if link.revoked {
+            return Err(CVServiceError::ShareLinkNotFound);
+        }
+        if !link.is_active() {
+            return Err(CVServiceError::ShareLinkExpired);
+        }
+        let link = db
+            .record_share_link_view(token)
+            .await
+            .map_err(|e| e.into())?;
+        db.get_cv_by_id(*link.cv_id).await.map_err(|e| e.into())
+    }
+
+    /// Revoke a share link, only the author of the CV is allowed to do so.
+    pub async fn revoke_share_link(
+        db: &(impl ShareLinkDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        link_id: ObjectId,
+    ) -> Result<(), CVServiceError> {
+        let link = db
+            .get_share_link_by_id(link_id)
+            .await
+            .map_err(|e| e.into())?;
+        let cv = db.get_cv_by_id(*link.cv_id).await?;
+        if cv.author_id != user_id.into() {
+            return Err(CVServiceError::PermissionDenied);
+        }
+        db.revoke_share_link(link_id).await.map_err(|e| e.into())
+    }
+
+    /// Return the share links of the CV that can still be used, only the author
+    /// of the CV is allowed to see them.
+    pub async fn get_active_share_links_of_cv(
+        db: &(impl ShareLinkDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        cv_id: ObjectId,
+    ) -> Result<Vec<ShareLink>, CVServiceError> {
+        let cv = db.get_cv_by_id(cv_id).await?;
+        if cv.author_id != user_id.into() {
+            return Err(CVServiceError::PermissionDenied);
+        }
+        let links = db
+            .get_share_links_of_cv(cv_id)
+            .await
+            .map_err(|e| e.into())?;
+        Ok(links.into_iter().filter(|link| link.is_active()).collect())
+    }
+}
@@ -0,0 +1,168 @@
+use super::ShareLinkService;
+use crate::{
+    common::DateTime,
+    models::cv::{ShareLink, Visibility},
+    services::cv_service::{cv_service::CVService, error::CVServiceError},
+};
+use mongodb::bson::{self, oid::ObjectId};
+
+use crate::{
+    data_source::cv,
+    services::tests::{CVInteractionError, MockDatabase},
+};
+
+#[async_trait::async_trait]
+impl cv::share_link::ShareLinkDataSource for MockDatabase {
+    type Error = CVInteractionError;
+
+    async fn add_share_link(&self, link: ShareLink) -> Result<(), Self::Error> {
+        let mut links = self.cv_share_links.lock().unwrap();
+        if links.iter().any(|l| l.token == link.token) {
+            return Err(CVInteractionError::AlreadyExists);
+        }
+        links.push(link);
+        Ok(())
+    }
+
+    async fn get_share_link_by_id(&self, id: ObjectId) -> Result<ShareLink, Self::Error> {
+        let links = self.cv_share_links.lock().unwrap();
+        links
+            .iter()
+            .find(|link| *link.id == id)
+            .cloned()
+            .ok_or(CVInteractionError::NotFound)
+    }
+
+    async fn get_share_link_by_token(&self, token: &str) -> Result<ShareLink, Self::Error> {
+        let links = self.cv_share_links.lock().unwrap();
+        links
+            .iter()
+            .find(|link| link.token == token)
+            .cloned()
+            .ok_or(CVInteractionError::NotFound)
+    }
+
+    async fn record_share_link_view(&self, token: &str) -> Result<ShareLink, Self::Error> {
+        let mut links = self.cv_share_links.lock().unwrap();
+        let link = links
+            .iter_mut()
+            .find(|link| link.token == token && link.is_active())
+            .ok_or(CVInteractionError::NotFound)?;
+        link.view_count += 1;
+        Ok(link.clone())
+    }
+
+    async fn revoke_share_link(&self, id: ObjectId) -> Result<(), Self::Error> {
+        let mut links = self.cv_share_links.lock().unwrap();
+        let link = links
+            .iter_mut()
+            .find(|link| *link.id == id)
+            .ok_or(CVInteractionError::NotFound)?;
+        link.revoked = true;
+        Ok(())
+    }
+
+    async fn get_share_links_of_cv(&self, cv_id: ObjectId) -> Result<Vec<ShareLink>, Self::Error> {
+        let links = self.cv_share_links.lock().unwrap();
+        Ok(links
+            .iter()
+            .filter(|link| *link.cv_id == cv_id)
+            .cloned()
+            .collect())
+    }
+}
+
+#[tokio::test]
+async fn basic() {
+    let db = MockDatabase::new();
+    let author = ObjectId::new();
+    let stranger = ObjectId::new();
+    let cv = CVService::create_cv(&db, author, "Test CV".to_string(), "Test CV".to_string())
+        .await
+        .unwrap();
+    let cv_id = *cv.id;
+    CVService::change_visibility(&db, author, cv_id, Visibility::Private)
+        .await
+        .unwrap();
+
+    ShareLinkService::create_share_link(&db, stranger, cv_id, None, None)
+        .await
+        .expect_err("Only the author can create share links");
+    ShareLinkService::create_share_link(&db, author, cv_id, Some(0), None)
+        .await
+        .expect_err("Expiry must be positive");
+    ShareLinkService::create_share_link(&db, author, cv_id, Some(i64::MAX), None)
+        .await
+        .expect_err("Expiry must not overflow");
+    ShareLinkService::create_share_link(&db, author, cv_id, None, Some(0))
+        .await
+        .expect_err("View limit must be positive");
+
+    let unlimited = ShareLinkService::create_share_link(&db, author, cv_id, Some(3600), None)
+        .await
+        .unwrap();
+    let limited = ShareLinkService::create_share_link(&db, author, cv_id, None, Some(2))
+        .await
+        .unwrap();
+    assert_ne!(unlimited.token, limited.token);
+
+    // links work even though the CV is private
+    for _ in 0..2 {
+        let shared = ShareLinkService::get_cv_by_share_token(&db, &limited.token)
+            .await
+            .unwrap();
+        assert_eq!(cv_id, *shared.id);
+    }
+    let rs = ShareLinkService::get_cv_by_share_token(&db, &limited.token).await;
+    assert_eq!(Err(CVServiceError::ShareLinkExpired), rs);
+    ShareLinkService::get_cv_by_share_token(&db, "not-a-token")
+        .await
+        .expect_err("Unknown tokens must be rejected");
+
+    let links = ShareLinkService::get_active_share_links_of_cv(&db, author, cv_id)
+        .await
+        .unwrap();
+    assert_eq!(1, links.len());
+    assert_eq!(unlimited.id, links[0].id);
+    ShareLinkService::get_active_share_links_of_cv(&db, stranger, cv_id)
+        .await
+        .expect_err("Only the author can list share links");
+
+    ShareLinkService::revoke_share_link(&db, stranger, *unlimited.id)
+        .await
+        .expect_err("Only the author can revoke share links");
+    ShareLinkService::revoke_share_link(&db, author, *unlimited.id)
+        .await
+        .unwrap();
+    let rs = ShareLinkService::get_cv_by_share_token(&db, &unlimited.token).await;
+    assert_eq!(Err(CVServiceError::ShareLinkNotFound), rs);
+    let links = ShareLinkService::get_active_share_links_of_cv(&db, author, cv_id)
+        .await
+        .unwrap();
+    assert!(links.is_empty());
+}
+
+#[tokio::test]
+async fn expired_link() {
+    let db = MockDatabase::new();
+    let author = ObjectId::new();
+    let cv = CVService::create_cv(&db, author, "Test CV".to_string(), "Test CV".to_string())
+        .await
+        .unwrap();
+    let mut link = ShareLink::new(*cv.id, author, Some(60), None);
+    link.expires_at = Some(bson::DateTime::from_millis(DateTime::now().timestamp_millis() - 1).into());
+    db.cv_share_links.lock().unwrap().push(link.clone());
+
+    let rs = ShareLinkService::get_cv_by_share_token(&db, &link.token).await;
+    assert_eq!(Err(CVServiceError::ShareLinkExpired), rs);
+    // expired links are not counted as viewed
+    let rs = cv::share_link::ShareLinkDataSource::record_share_link_view(&db, &link.token).await;
+    assert!(matches!(rs, Err(CVInteractionError::NotFound)));
+    assert_eq!(0, db.cv_share_links.lock().unwrap()[0].view_count);
+}
+
+#[test]
+fn huge_lifetime_does_not_overflow() {
+    let link = ShareLink::new(ObjectId::new(), ObjectId::new(), Some(i64::MAX), None);
+    assert_eq!(None, link.expires_at);
+}
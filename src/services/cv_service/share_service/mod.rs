@@ -1,23 +1,30 @@
 #[cfg(test)]
 mod tests;
 
-use async_graphql::futures_util::StreamExt;
+use async_graphql::futures_util::{self, StreamExt};
 use futures_core::stream::BoxStream;
 use mongodb::bson::oid::ObjectId;
 
 use crate::{
-    data_source::{cv::share::ShareDataSource, CVDataSource, UserDataSource},
+    data_source::{
+        cv::share::ShareDataSource, BlockDataSource, CVDataSource, FriendsListDataSource,
+        UserDataSource,
+    },
     models::cv::{interactions::Share, CV},
-    services::user_service::error::UserServiceError,
+    services::user_service::{block_service::BlockService, error::UserServiceError},
 };
 
-use super::error::CVServiceError;
+use super::{cv_service::CVService, error::CVServiceError};
 
 pub struct ShareService;
 
 impl ShareService {
     pub async fn share_cv(
-        db: &(impl ShareDataSource + UserDataSource + std::marker::Sync),
+        db: &(impl ShareDataSource
+              + UserDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
         user_id: ObjectId,
         cv_id: ObjectId,
     ) -> Result<(), CVServiceError> {
@@ -55,6 +62,15 @@ impl ShareService {
                 "You can't share your own CV".to_string(),
             ));
         }
+        let blocked = BlockService::is_blocked(db, user_id, cv.author_id.into())
+            .await
+            .map_err(|_| CVServiceError::DatabaseError)?;
+        if blocked {
+            return Err(CVServiceError::UserBlocked(cv.author_id.into()));
+        }
+        if !CVService::can_view_cv(db, Some(user_id), &cv).await? {
+            return Err(CVServiceError::CVNotVisible(cv_id));
+        }
         db.add_share(user_id, cv_id).await.map_err(|e| e.into())
     }
 
@@ -94,8 +110,13 @@ impl ShareService {
         db.delete_share(user_id, cv_id).await.map_err(|e| e.into())
     }
 
+    /// Return the CVs shared by the user, leaving out those the user can no longer see.
     pub async fn get_shared_cvs_of_user(
-        db: &(impl ShareDataSource + UserDataSource + std::marker::Sync),
+        db: &(impl ShareDataSource
+              + UserDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
         user_id: ObjectId,
     ) -> Result<BoxStream<Result<CV, CVServiceError>>, CVServiceError> {
         if let Err(e) = db.get_user_by_id(user_id).await.map_err(|e| e.into()) {
@@ -111,10 +132,24 @@ impl ShareService {
                 }
             }
         }
-        db.get_shared_cvs_by_user_id(user_id)
+        let cvs = db
+            .get_shared_cvs_by_user_id(user_id)
             .await
-            .map_err(|e| e.into())
-            .map(|stream| stream.map(|item| item.map_err(|err| err.into())).boxed())
+            .map_err(|e| e.into())?
+            .collect::<Vec<_>>()
+            .await;
+        let mut visible_cvs = Vec::with_capacity(cvs.len());
+        for cv in cvs {
+            match cv {
+                Ok(cv) => {
+                    if CVService::can_view_cv(db, Some(user_id), &cv).await? {
+                        visible_cvs.push(Ok(cv));
+                    }
+                }
+                Err(err) => visible_cvs.push(Err(err.into())),
+            }
+        }
+        Ok(futures_util::stream::iter(visible_cvs).boxed())
     }
 
     pub async fn get_shares_of_cv(
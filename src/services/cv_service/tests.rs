@@ -1,13 +1,57 @@
-use async_graphql::futures_util::StreamExt;
+use async_graphql::futures_util::{stream::BoxStream, StreamExt};
 use mongodb::bson;
 
 use crate::{
-    models::{comment::CreateCommentInput, cv::CreateCVInput},
-    services::tests::MockDatabase,
+    common::DateTime,
+    data_source::{
+        CVDataSource, CVDataSourceError, CVDetailsDataSource, FriendsListDataSource,
+        UserDataSource,
+    },
+    models::{
+        comment::{Comment, CommentSort, CreateCommentInput},
+        cv::{Visibility, CV},
+        cv_details::CVDetails,
+        friend_request::FriendRequest,
+        users::Role,
+    },
+    services::{
+        tests::{create_user, MockDatabase},
+        user_service::block_service::BlockService,
+    },
 };
 
-use super::comment_service::CommentService;
+use super::comment_service::{
+    mention::parse_mentions, CommentService, CommentServiceError, DEFAULT_MAX_COMMENT_DEPTH,
+    DEFAULT_THREAD_LIMIT, MAX_MENTIONED_USERNAMES,
+};
 use super::cv_service::CVService;
+use super::error::CVServiceError;
+
+#[async_trait::async_trait]
+impl CVDetailsDataSource for MockDatabase {
+    type Error = CVDataSourceError;
+
+    /// Only the search words are used, matched against the title and tags.
+    async fn get_cvs_by_filter<'a>(
+        &'a self,
+        cv_details: CVDetails,
+    ) -> Result<BoxStream<'a, CV>, Self::Error> {
+        let cvs = self
+            .cvs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|cv| {
+                cv_details
+                    .search_words
+                    .iter()
+                    .any(|word| cv.title == *word || cv.tags.contains(word))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        Ok(async_graphql::futures_util::stream::iter(cvs).boxed())
+    }
+}
 
 fn mock_comment_input() -> CreateCommentInput {
     CreateCommentInput {
@@ -42,16 +86,12 @@ async fn test_comment_services() {
     assert_eq!("some_title", test_cv.title);
 
     let user_id = bson::oid::ObjectId::new();
+    let author = bson::oid::ObjectId::new();
 
     // test add comment to cv
-    let cv = CVService::add_comment(
-        &db,
-        *test_cv.id,
-        bson::oid::ObjectId::new(),
-        "test".to_string(),
-    )
-    .await
-    .unwrap();
+    let cv = CVService::add_comment(&db, *test_cv.id, author, "test".to_string())
+        .await
+        .unwrap();
     assert_eq!(1, cv.comments.len());
 
     // test add like to comment
@@ -91,29 +131,37 @@ async fn test_comment_services() {
     assert_eq!(0, total_bookmark);
 
     // test add reply to comment
-    let comment = CommentService::add_reply_comment(
-        &db,
-        *comment.id,
-        bson::oid::ObjectId::new(),
-        "test_reply".to_string(),
-    )
-    .await
-    .unwrap();
+    let comment =
+        CommentService::add_reply_comment(&db, *comment.id, user_id, "test_reply".to_string())
+            .await
+            .unwrap();
     assert_eq!(1, comment.replies.len());
     let reply = CommentService::get_comment_by_id(&db, comment.replies[0].into())
         .await
         .unwrap();
     assert_eq!("test_reply", reply.content);
 
-    // test remove reply from comment
-    let comment = CommentService::remove_reply_comment(&db, *comment.id, comment.replies[0].into())
+    // test remove reply from comment, which leaves a tombstone
+    let reply_id = comment.replies[0].into();
+    let rs = CommentService::remove_reply_comment(&db, author, *comment.id, reply_id).await;
+    assert_eq!(Some(CommentServiceError::NotAuthor), rs.err());
+    let comment = CommentService::remove_reply_comment(&db, user_id, *comment.id, reply_id)
+        .await
+        .unwrap();
+    assert_eq!(1, comment.replies.len());
+    let reply = CommentService::get_comment_by_id(&db, reply_id)
         .await
         .unwrap();
-    assert_eq!(0, comment.replies.len());
+    assert!(reply.deleted_at.is_some());
+    let rs = CommentService::remove_reply_comment(&db, user_id, reply_id, *comment.id).await;
+    assert_eq!(Some(CommentServiceError::IdNotFound(*comment.id)), rs.err());
 
-    // test update comment content
+    // test update comment content, only by its author
+    let rs =
+        CommentService::update_content_comment(&db, user_id, *comment.id, "nope".to_string()).await;
+    assert_eq!(Some(CommentServiceError::NotAuthor), rs.err());
     let comment =
-        CommentService::update_content_comment(&db, *comment.id, "test_update".to_string())
+        CommentService::update_content_comment(&db, author, *comment.id, "test_update".to_string())
             .await
             .unwrap();
     assert_eq!("test_update", comment.content);
@@ -137,11 +185,367 @@ async fn test_comment_services() {
     assert_eq!(2, comments.len());
     assert_eq!("test 2", comments[1].as_ref().unwrap().content);
 
-    // test remove comment from cv
-    let cv = CVService::remove_comment(&db, *cv.id, cv.comments[0])
+    // test remove comment from cv, which leaves a tombstone
+    let rs = CVService::remove_comment(&db, user_id, *cv.id, cv.comments[0]).await;
+    assert_eq!(Some(CommentServiceError::NotAuthor), rs.err());
+    let cv = CVService::remove_comment(&db, author, *cv.id, cv.comments[0])
         .await
         .unwrap();
-    assert_eq!(1, cv.comments.len());
+    assert_eq!(2, cv.comments.len());
+    let comment = CommentService::get_comment_by_id(&db, cv.comments[0])
+        .await
+        .unwrap();
+    assert!(comment.deleted_at.is_some());
+    assert_eq!("", comment.content);
+}
+
+#[tokio::test]
+async fn test_comment_edit_history_and_tombstone() {
+    let db = MockDatabase::new();
+    let author = bson::oid::ObjectId::new();
+    // on a CV, so that it can be replied to
+    let cv = CVService::create_cv(&db, author, "CV".to_string(), "".to_string())
+        .await
+        .unwrap();
+    let cv = CVService::add_comment(&db, *cv.id, author, "test".to_string())
+        .await
+        .unwrap();
+    let comment = CommentService::get_comment_by_id(&db, cv.comments[0])
+        .await
+        .unwrap();
+    assert!(comment.edited_at.is_none());
+
+    // editing to the same content does not add a version
+    let comment =
+        CommentService::update_content_comment(&db, author, *comment.id, "test".to_string())
+            .await
+            .unwrap();
+    assert!(comment.edited_at.is_none());
+    let rs =
+        CommentService::update_content_comment(&db, author, *comment.id, " ".to_string()).await;
+    assert_eq!(Some(CommentServiceError::EmptyContent), rs.err());
+
+    let edited =
+        CommentService::update_content_comment(&db, author, *comment.id, "first".to_string())
+            .await
+            .unwrap();
+    let edited =
+        CommentService::update_content_comment(&db, author, *edited.id, "second".to_string())
+            .await
+            .unwrap();
+    assert_eq!("second", edited.content);
+    assert!(edited.edited_at.is_some());
+
+    let history = CommentService::edit_history(&db, author, *comment.id)
+        .await
+        .unwrap();
+    let contents = history
+        .iter()
+        .map(|version| version.content.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["test", "first"], contents);
+    assert_eq!(comment.created, history[0].created);
+    assert!(history[1].created >= comment.created);
+    let rs = CommentService::edit_history(&db, bson::oid::ObjectId::new(), *comment.id).await;
+    assert_eq!(Some(CommentServiceError::NotAuthor), rs.err());
+
+    // deleted comments keep their replies but not their content
+    let with_reply = CommentService::add_reply_comment(
+        &db,
+        *comment.id,
+        bson::oid::ObjectId::new(),
+        "reply".to_string(),
+    )
+    .await
+    .unwrap();
+    let deleted = CommentService::delete_comment(&db, *comment.id)
+        .await
+        .unwrap();
+    assert_eq!("", deleted.content);
+    assert!(deleted.deleted_at.is_some());
+    assert!(deleted.history.is_empty());
+    assert_eq!(with_reply.replies, deleted.replies);
+    let replies = CommentService::get_replies_of_comment(&db, *comment.id)
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(1, replies.len());
+
+    // deleting twice keeps the first deletion
+    let again = CommentService::delete_comment(&db, *comment.id)
+        .await
+        .unwrap();
+    assert_eq!(deleted.deleted_at, again.deleted_at);
+    let rs =
+        CommentService::update_content_comment(&db, author, *comment.id, "back".to_string()).await;
+    assert_eq!(
+        Some(CommentServiceError::CommentDeleted(*comment.id)),
+        rs.err()
+    );
+    let rs = CommentService::add_reply_comment(
+        &db,
+        *comment.id,
+        bson::oid::ObjectId::new(),
+        "reply".to_string(),
+    )
+    .await;
+    assert_eq!(
+        Some(CommentServiceError::CommentDeleted(*comment.id)),
+        rs.err()
+    );
+}
+
+#[test]
+fn test_parse_mentions() {
+    let mentions = parse_mentions("@alice, ask @bob.smith. mail a@b.com or @@x @ @é_1-");
+    let found = mentions
+        .iter()
+        .map(|mention| (mention.username.as_str(), mention.start, mention.end))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![("alice", 0, 6), ("bob.smith", 12, 22), ("é_1", 46, 50)],
+        found
+    );
+    assert!(parse_mentions("no mentions here").is_empty());
+
+    // offsets are in UTF-16 code units, an emoji taking two
+    let mentions = parse_mentions("😀 @alice");
+    assert_eq!((3, 9), (mentions[0].start, mentions[0].end));
+}
+
+#[tokio::test]
+async fn test_mentioned_usernames_are_capped() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let alice = create_user(&db, "alice").await;
+    let mut content = (0..MAX_MENTIONED_USERNAMES)
+        .map(|i| format!("@nobody{} ", i))
+        .collect::<String>();
+    content.push_str("@alice");
+    let input = CreateCommentInput {
+        author: author.into(),
+        content,
+    };
+    let comment = CommentService::create_comment(&db, input).await.unwrap();
+    assert!(comment.mentions.is_empty());
+
+    // repeated usernames are only looked up once
+    let content = format!("{}@alice", "@alice ".repeat(MAX_MENTIONED_USERNAMES + 1));
+    let input = CreateCommentInput {
+        author: author.into(),
+        content,
+    };
+    let comment = CommentService::create_comment(&db, input).await.unwrap();
+    assert_eq!(MAX_MENTIONED_USERNAMES + 2, comment.mentions.len());
+    assert!(comment.mentions.iter().all(|mention| mention.user_id == alice));
+}
+
+#[tokio::test]
+async fn test_moderators_can_delete_comments() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let moderator = create_user(&db, "moderator").await;
+    let other = create_user(&db, "other").await;
+    db.set_role(moderator, Role::Moderator).await.unwrap();
+    let cv = CVService::create_cv(&db, author, "CV".to_string(), "".to_string())
+        .await
+        .unwrap();
+    let cv = CVService::add_comment(&db, *cv.id, author, "comment".to_string())
+        .await
+        .unwrap();
+
+    let rs = CommentService::delete_comment_of_user(&db, other, cv.comments[0]).await;
+    assert_eq!(Some(CommentServiceError::NotAuthor), rs.err());
+    let rs = CommentService::update_content_comment(&db, moderator, cv.comments[0], "x".into());
+    assert_eq!(Some(CommentServiceError::NotAuthor), rs.await.err());
+    let deleted = CommentService::delete_comment_of_user(&db, moderator, cv.comments[0])
+        .await
+        .unwrap();
+    assert!(deleted.deleted_at.is_some());
+}
+
+/// Reply to the comment, return the reply.
+async fn reply(db: &MockDatabase, comment: &Comment, content: &str) -> Comment {
+    let parent = CommentService::add_reply_comment(
+        db,
+        *comment.id,
+        bson::oid::ObjectId::new(),
+        content.to_string(),
+    )
+    .await
+    .unwrap();
+    let reply_id = *parent.replies.last().unwrap();
+    CommentService::get_comment_by_id(db, *reply_id)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_comment_threads() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let cv = CVService::create_cv(&db, author, "CV".to_string(), "".to_string())
+        .await
+        .unwrap();
+    let cv = CVService::add_comment(&db, *cv.id, author, "test".to_string())
+        .await
+        .unwrap();
+    let root = CommentService::get_comment_by_id(&db, cv.comments[0])
+        .await
+        .unwrap();
+    let first = reply(&db, &root, "first").await;
+    let second = reply(&db, &first, "second").await;
+    let third = reply(&db, &second, "third").await;
+    assert_eq!(DEFAULT_MAX_COMMENT_DEPTH, third.depth);
+    assert_eq!(Some(second.id), third.parent_id);
+    assert_eq!(Some(root.id), third.root_id);
+    assert_eq!(None, root.root_id);
+
+    // replies deeper than the limit are added next to the comment
+    let flattened = reply(&db, &third, "flattened").await;
+    assert_eq!(Some(second.id), flattened.parent_id);
+    assert_eq!(DEFAULT_MAX_COMMENT_DEPTH, flattened.depth);
+    let second = CommentService::get_comment_by_id(&db, *second.id)
+        .await
+        .unwrap();
+    assert_eq!(vec![third.id, flattened.id], second.replies);
+    let other = reply(&db, &root, "other").await;
+
+    let contents = |thread: Vec<Comment>| {
+        thread
+            .into_iter()
+            .map(|comment| comment.content)
+            .collect::<Vec<_>>()
+    };
+    let thread =
+        CommentService::get_thread(&db, None, *third.id, CommentSort::Oldest, DEFAULT_THREAD_LIMIT)
+            .await
+            .unwrap();
+    assert_eq!(
+        vec!["test", "first", "second", "third", "flattened", "other"],
+        contents(thread)
+    );
+    let thread =
+        CommentService::get_thread(&db, None, *root.id, CommentSort::Newest, DEFAULT_THREAD_LIMIT)
+            .await
+            .unwrap();
+    assert_eq!(
+        vec!["test", "other", "first", "second", "flattened", "third"],
+        contents(thread)
+    );
+
+    // the most liked replies come first
+    for _ in 0..2 {
+        CommentService::add_like_comment(&db, bson::oid::ObjectId::new(), *other.id)
+            .await
+            .unwrap();
+    }
+    let thread =
+        CommentService::get_thread(&db, None, *root.id, CommentSort::Top, DEFAULT_THREAD_LIMIT)
+            .await
+            .unwrap();
+    assert_eq!(
+        vec!["test", "other", "first", "second", "third", "flattened"],
+        contents(thread)
+    );
+
+    // only the oldest comments are returned past the limit
+    let thread = CommentService::get_thread(&db, None, *root.id, CommentSort::Newest, 3)
+        .await
+        .unwrap();
+    assert_eq!(vec!["test", "first", "second"], contents(thread));
+
+    // threads on CVs the viewer cannot see are not found
+    CVService::change_visibility(&db, author, *cv.id, Visibility::Private)
+        .await
+        .unwrap();
+    let rs = CommentService::get_thread(&db, None, *third.id, CommentSort::Oldest, 10).await;
+    assert_eq!(Some(CommentServiceError::IdNotFound(*third.id)), rs.err());
+    let thread =
+        CommentService::get_thread(&db, Some(author), *third.id, CommentSort::Oldest, 10)
+            .await
+            .unwrap();
+    assert_eq!(6, thread.len());
+
+    // and cannot be commented on or replied to
+    let stranger = create_user(&db, "stranger").await;
+    let rs = CommentService::add_comment_to_cv(&db, *cv.id, stranger, "hi".to_string()).await;
+    assert_eq!(Some(CommentServiceError::IdNotFound(*cv.id)), rs.err());
+    let rs = CommentService::add_reply_comment(&db, *third.id, stranger, "hi".to_string()).await;
+    assert_eq!(Some(CommentServiceError::IdNotFound(*third.id)), rs.err());
+    CommentService::add_reply_comment(&db, *third.id, author, "mine".to_string())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_comment_mentions() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let alice = create_user(&db, "alice").await;
+    let bob = create_user(&db, "bob").await;
+    let blocked = create_user(&db, "blocked").await;
+    BlockService::block_user(&db, blocked, author).await.unwrap();
+    let input = CreateCommentInput {
+        author: author.into(),
+        content: "@alice and @alice, @nobody, @author and @blocked".to_string(),
+    };
+    let comment = CommentService::create_comment(&db, input).await.unwrap();
+
+    // unknown usernames are not mentions
+    let mentioned = comment
+        .mentions
+        .iter()
+        .map(|mention| (mention.user_id, mention.start, mention.end))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![(alice, 0, 6), (alice, 11, 17), (author, 28, 35), (blocked, 40, 48)],
+        mentioned
+    );
+    let stored = CommentService::get_comment_by_id(&db, *comment.id)
+        .await
+        .unwrap();
+    assert_eq!(comment.mentions, stored.mentions);
+    // the author and users blocking them are not notified, others only once
+    assert_eq!(
+        vec![(alice, *comment.id)],
+        *db.mention_notifications.lock().unwrap()
+    );
+
+    // only the users newly mentioned by an edit are notified
+    let edited =
+        CommentService::update_content_comment(&db, author, *comment.id, "@bob @alice".to_string())
+            .await
+            .unwrap();
+    assert_eq!(2, edited.mentions.len());
+    assert_eq!(
+        vec![(alice, *comment.id), (bob, *comment.id)],
+        *db.mention_notifications.lock().unwrap()
+    );
+
+    // replies and comments on CVs resolve mentions too
+    let cv = CVService::create_cv(&db, author, "CV".to_string(), "".to_string())
+        .await
+        .unwrap();
+    let cv = CommentService::add_comment_to_cv(&db, *cv.id, bob, "hi @author".to_string())
+        .await
+        .unwrap();
+    let cv_comment = CommentService::get_comment_by_id(&db, cv.comments[0])
+        .await
+        .unwrap();
+    assert_eq!(author, cv_comment.mentions[0].user_id);
+    let comment =
+        CommentService::add_reply_comment(&db, *cv_comment.id, bob, "@alice see".to_string())
+            .await
+            .unwrap();
+    let reply = CommentService::get_comment_by_id(&db, comment.replies[0].into())
+        .await
+        .unwrap();
+    assert_eq!(alice, reply.mentions[0].user_id);
+    let notifications = db.mention_notifications.lock().unwrap();
+    assert_eq!((author, cv.comments[0]), notifications[2]);
+    assert_eq!((alice, *reply.id), notifications[3]);
 }
 
 #[tokio::test]
@@ -159,26 +563,142 @@ async fn test_cv_services() {
     assert_eq!("some_title", test_cv.title);
 
     // test update cv title
-    let cv = CVService::change_title(&db, *test_cv.id, "new_title".to_string())
-        .await
-        .unwrap();
+    let cv = CVService::change_title(
+        &db,
+        *test_cv.author_id,
+        *test_cv.id,
+        "new_title".to_string(),
+    )
+    .await
+    .unwrap();
     assert_eq!("new_title", cv.title);
 
     // test update cv description
-    let cv = CVService::change_description(&db, *test_cv.id, "new_description".to_string())
-        .await
-        .unwrap();
+    let cv = CVService::change_description(
+        &db,
+        *test_cv.author_id,
+        *test_cv.id,
+        "new_description".to_string(),
+    )
+    .await
+    .unwrap();
     assert_eq!("new_description", cv.description.unwrap());
 
     // test add tag to cv
-    let cv = CVService::add_tag(&db, *test_cv.id, "tag3".to_string())
+    let cv = CVService::add_tag(&db, *test_cv.author_id, *test_cv.id, "tag3".to_string())
         .await
         .unwrap();
     assert_eq!(1, cv.tags.len());
 
     // test remove tag from cv
-    let cv = CVService::remove_tag(&db, *test_cv.id, "tag3".to_string())
+    let cv = CVService::remove_tag(&db, *test_cv.author_id, *test_cv.id, "tag3".to_string())
         .await
         .unwrap();
     assert_eq!(0, cv.tags.len());
 }
+
+#[tokio::test]
+async fn test_cv_visibility() {
+    let db = MockDatabase::new();
+    let author = bson::oid::ObjectId::new();
+    let friend = bson::oid::ObjectId::new();
+    let stranger = bson::oid::ObjectId::new();
+    db.add_friend_request(FriendRequest::new(author, friend, None::<String>).accept())
+        .await
+        .unwrap();
+
+    let cv = CVService::create_cv(
+        &db,
+        author,
+        "some_title".to_string(),
+        "some_description".to_string(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(Visibility::Public, cv.visibility);
+    let cv_id = *cv.id;
+
+    CVService::change_visibility(&db, stranger, cv_id, Visibility::Private)
+        .await
+        .expect_err("Only the author can change the visibility");
+
+    let cases = [
+        (Visibility::Public, [true, true, true], [true, true, true]),
+        (Visibility::FriendsOnly, [true, true, false], [true, true, false]),
+        (Visibility::Unlisted, [true, true, true], [true, false, false]),
+        (Visibility::Private, [true, false, false], [true, false, false]),
+    ];
+    for (visibility, viewable, listed) in cases {
+        CVService::change_visibility(&db, author, cv_id, visibility)
+            .await
+            .unwrap();
+        for (i, viewer) in [author, friend, stranger].into_iter().enumerate() {
+            let rs = CVService::get_cv_by_id(&db, Some(viewer), cv_id).await;
+            assert_eq!(viewable[i], rs.is_ok(), "{:?} viewer {}", visibility, i);
+            let cvs = CVService::get_cvs_by_user_id(&db, Some(viewer), author)
+                .await
+                .unwrap()
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(listed[i], cvs.len() == 1, "{:?} viewer {}", visibility, i);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_hidden_cvs_are_not_listed() {
+    let db = MockDatabase::new();
+    let author = bson::oid::ObjectId::new();
+    let stranger = bson::oid::ObjectId::new();
+    let cv = CVService::create_cv(&db, author, "hidden".to_string(), "".to_string())
+        .await
+        .unwrap();
+    db.set_cv_hidden(*cv.id, Some(DateTime::now())).await.unwrap();
+    let search = CVDetails {
+        country: None,
+        city: None,
+        personalities: vec![],
+        experiences: vec![],
+        major: None,
+        search_words: vec!["hidden".to_string()],
+        rating: None,
+        sex: None,
+    };
+
+    for (viewer, listed) in [(Some(author), 1), (Some(stranger), 0), (None, 0)] {
+        let cvs = CVService::get_cvs_by_user_id(&db, viewer, author)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(listed, cvs.len(), "user cvs of {:?}", viewer);
+        let cvs = CVService::find_suggested_cvs(&db, viewer, search.clone())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(listed, cvs.len(), "search of {:?}", viewer);
+    }
+}
+
+#[tokio::test]
+async fn test_blocked_users_cannot_see_cvs() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let blocked = create_user(&db, "blocked").await;
+    let cv = CVService::create_cv(&db, author, "title".to_string(), "".to_string())
+        .await
+        .unwrap();
+    BlockService::block_user(&db, author, blocked).await.unwrap();
+
+    let rs = CVService::get_cv_by_id(&db, Some(blocked), *cv.id).await;
+    assert_eq!(Err(CVServiceError::CVNotVisible(*cv.id)), rs);
+    let cvs = CVService::get_cvs_by_user_id(&db, Some(blocked), author)
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert!(cvs.is_empty());
+    // anonymous viewers still see the CV
+    CVService::get_cv_by_id(&db, None, *cv.id).await.unwrap();
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests;
+
+use std::io::{Cursor, Read};
+
+use mongodb::bson::oid::ObjectId;
+use quick_xml::events::Event;
+
+use crate::{
+    data_source::cv::text_extraction::CVTextDataSource,
+    models::{
+        cv::{TextExtraction, TextExtractionStatus, CV},
+        ResourceIdentifier,
+    },
+    services::storage_service::backend::{Storage, StorageBackend},
+};
+
+use super::{error::CVServiceError, file_service::CVFileService};
+
+/// Longest text kept for a CV, in characters. Anything after it is dropped.
+pub const MAX_CV_TEXT_LENGTH: usize = 100_000;
+
+/// Largest `word/document.xml` read from a DOCX file, in bytes. Compressed
+/// entries can be much larger than the file itself.
+const MAX_DOCX_XML_SIZE: u64 = 20 * 1024 * 1024;
+
+pub struct CVTextService;
+
+impl CVTextService {
+    /// Extract the plain text of a CV file. Return `Unsupported` for types
+    /// that text cannot be extracted from and `Failed` for unreadable files.
+    pub fn extract_text(content_type: &str, data: &[u8]) -> Result<String, TextExtractionStatus> {
+        let text = match content_type {
+            "text/plain" => std::str::from_utf8(data)
+                .map(|text| text.to_string())
+                .map_err(|_| TextExtractionStatus::Failed)?,
+            "application/pdf" => pdf_extract::extract_text_from_mem(data)
+                .map_err(|_| TextExtractionStatus::Failed)?,
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+                Self::extract_docx_text(data).ok_or(TextExtractionStatus::Failed)?
+            }
+            _ => return Err(TextExtractionStatus::Unsupported),
+        };
+        Ok(Self::normalize(&text))
+    }
+
+    /// Read the text of the paragraphs in the main document of a DOCX file.
+    fn extract_docx_text(data: &[u8]) -> Option<String> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data)).ok()?;
+        let mut xml = String::new();
+        archive
+            .by_name("word/document.xml")
+            .ok()?
+            .take(MAX_DOCX_XML_SIZE)
+            .read_to_string(&mut xml)
+            .ok()?;
+
+        let mut reader = quick_xml::Reader::from_str(&xml);
+        let mut text = String::new();
+        let mut in_text = false;
+        loop {
+            match reader.read_event().ok()? {
+                Event::Start(e) if e.name().as_ref() == b"w:t" => in_text = true,
+                Event::End(e) if e.name().as_ref() == b"w:t" => in_text = false,
+                Event::End(e) if e.name().as_ref() == b"w:p" => text.push('\n'),
+                Event::Empty(e) if e.name().as_ref() == b"w:tab" => text.push('\t'),
+                Event::Empty(e) if e.name().as_ref() == b"w:br" => text.push('\n'),
+                Event::Text(e) if in_text => text.push_str(&e.unescape().ok()?),
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+        Some(text)
+    }
+
+    /// Trim every line, drop empty lines and cut the text to `MAX_CV_TEXT_LENGTH`.
+    fn normalize(text: &str) -> String {
+        let text = text
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        match text.char_indices().nth(MAX_CV_TEXT_LENGTH) {
+            Some((end, _)) => text[..end].to_string(),
+            None => text,
+        }
+    }
+
+    /// Extract the text of the file of the CV and store it with the CV.
+    /// Return the status the extraction ended with.
+    pub async fn run_extraction(
+        db: &(impl CVTextDataSource + std::marker::Sync),
+        storage: &dyn StorageBackend,
+        cv_id: ObjectId,
+        author_id: ObjectId,
+        file_id: ResourceIdentifier,
+    ) -> Result<TextExtractionStatus, CVServiceError> {
+        let object = storage
+            .get(&CVFileService::object_name(author_id, file_id))
+            .await
+            .map_err(|_| CVServiceError::StorageError)?;
+        // parsers may panic on malformed files, which only fails the blocking task
+        let rs = tokio::task::spawn_blocking(move || {
+            Self::extract_text(&object.content_type, &object.data)
+        })
+        .await
+        .unwrap_or(Err(TextExtractionStatus::Failed));
+        let extraction = match rs {
+            Ok(text) => TextExtraction::completed(file_id, text),
+            Err(status) => TextExtraction::new(file_id, status),
+        };
+        let status = extraction.status;
+        db.update_cv_text_extraction(cv_id, extraction).await?;
+        Ok(status)
+    }
+
+    /// Run the text extraction of the CV in the background if it is pending.
+    pub fn spawn_extraction<D>(db: D, storage: Storage, cv: &CV)
+    where
+        D: CVTextDataSource + Send + Sync + 'static,
+    {
+        let file_id = match (cv.cv, cv.extraction_status()) {
+            (Some(file_id), Some(TextExtractionStatus::Pending)) => file_id,
+            _ => return,
+        };
+        let cv_id = cv.id.into();
+        let author_id = cv.author_id.into();
+        tokio::spawn(async move {
+            let rs = Self::run_extraction(&db, storage.as_ref(), cv_id, author_id, file_id).await;
+            if let Err(err) = rs {
+                log::warn!("Text extraction of CV {} failed: {}", cv_id, err);
+            }
+        });
+    }
+}
@@ -0,0 +1,199 @@
+use std::io::{Cursor, Write};
+
+use mongodb::bson::{oid::ObjectId, Uuid};
+
+use super::{CVTextService, MAX_CV_TEXT_LENGTH};
+use crate::{
+    data_source::{cv::text_extraction::CVTextDataSource, CVDataSourceError},
+    models::cv::{TextExtraction, TextExtractionStatus},
+    services::{
+        cv_service::{cv_service::CVService, file_service::CVFileService},
+        storage_service::memory_storage::MemoryStorage,
+        tests::MockDatabase,
+    },
+};
+
+const DOCX: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
+#[async_trait::async_trait]
+impl CVTextDataSource for MockDatabase {
+    async fn update_cv_text_extraction(
+        &self,
+        cv_id: ObjectId,
+        extraction: TextExtraction,
+    ) -> Result<(), CVDataSourceError> {
+        let mut cvs = self.cvs.lock().unwrap();
+        if let Some(cv) = cvs
+            .iter_mut()
+            .find(|cv| *cv.id == cv_id && cv.cv == Some(extraction.file_id))
+        {
+            cv.text_extraction = Some(extraction);
+        }
+        Ok(())
+    }
+}
+
+fn docx(paragraphs: &[&str]) -> Vec<u8> {
+    let body = paragraphs
+        .iter()
+        .map(|p| format!("<w:p><w:r><w:t>{}</w:t></w:r></w:p>", p))
+        .collect::<String>();
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+        body
+    );
+    let mut data = Vec::new();
+    let mut zip = zip::ZipWriter::new(Cursor::new(&mut data));
+    zip.start_file("word/document.xml", Default::default())
+        .unwrap();
+    zip.write_all(xml.as_bytes()).unwrap();
+    zip.finish().unwrap();
+    drop(zip);
+    data
+}
+
+/// Build a single page PDF showing the text with a standard font.
+fn pdf(text: &str) -> Vec<u8> {
+    let content = format!("BT /F1 12 Tf 72 720 Td ({}) Tj ET", text);
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>".to_string(),
+        format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+    let mut data = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(data.len());
+        data.extend(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).bytes());
+    }
+    let xref = data.len();
+    data.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+    for offset in offsets {
+        data.extend(format!("{:010} 00000 n \n", offset).bytes());
+    }
+    data.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .bytes(),
+    );
+    data
+}
+
+#[test]
+fn extracts_plain_text() {
+    assert_eq!(
+        Ok("Nguyen Van A\nRust developer".to_string()),
+        CVTextService::extract_text(
+            "text/plain",
+            "  Nguyen   Van A \n\n\tRust developer\n".as_bytes()
+        )
+    );
+    assert_eq!(
+        Err(TextExtractionStatus::Failed),
+        CVTextService::extract_text("text/plain", &[0xff, 0xfe])
+    );
+}
+
+#[test]
+fn extracts_docx_text() {
+    let data = docx(&["Nguyen Van A", "Rust &amp; GraphQL"]);
+    assert_eq!(
+        Ok("Nguyen Van A\nRust & GraphQL".to_string()),
+        CVTextService::extract_text(DOCX, &data)
+    );
+    assert_eq!(
+        Err(TextExtractionStatus::Failed),
+        CVTextService::extract_text(DOCX, b"PK\x03\x04 not a zip")
+    );
+}
+
+#[test]
+fn extracts_pdf_text() {
+    let text =
+        CVTextService::extract_text("application/pdf", &pdf("Senior Rust developer")).unwrap();
+    assert_eq!("Senior Rust developer", text);
+    assert_eq!(
+        Err(TextExtractionStatus::Failed),
+        CVTextService::extract_text("application/pdf", b"%PDF-1.4 truncated")
+    );
+}
+
+#[test]
+fn rejects_unsupported_files() {
+    assert_eq!(
+        Err(TextExtractionStatus::Unsupported),
+        CVTextService::extract_text("application/msword", b"\xD0\xCF\x11\xE0")
+    );
+}
+
+#[test]
+fn truncates_long_text() {
+    let long = "ă".repeat(MAX_CV_TEXT_LENGTH + 10);
+    let text = CVTextService::extract_text("text/plain", long.as_bytes()).unwrap();
+    assert_eq!(MAX_CV_TEXT_LENGTH, text.chars().count());
+}
+
+#[tokio::test]
+async fn run_extraction() {
+    let db = MockDatabase::new();
+    let storage = MemoryStorage::new();
+    let author = ObjectId::new();
+    let cv = CVService::create_cv(&db, author, "title".to_string(), "description".to_string())
+        .await
+        .unwrap();
+    assert_eq!(None, cv.extraction_status());
+
+    let cv = CVFileService::upload_cv_file(
+        &db,
+        &storage,
+        author,
+        *cv.id,
+        Some(DOCX),
+        docx(&["Kubernetes", "MongoDB"]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(Some(TextExtractionStatus::Pending), cv.extraction_status());
+    let first_file = cv.cv.unwrap();
+
+    let status = CVTextService::run_extraction(&db, &storage, *cv.id, author, first_file)
+        .await
+        .unwrap();
+    assert_eq!(TextExtractionStatus::Completed, status);
+    let cv = db.cvs.lock().unwrap()[0].clone();
+    assert_eq!(
+        Some(TextExtractionStatus::Completed),
+        cv.extraction_status()
+    );
+    assert_eq!(Some("Kubernetes\nMongoDB"), cv.extracted_text());
+
+    // the outcome for a replaced file is ignored
+    let cv = CVFileService::upload_cv_file(
+        &db,
+        &storage,
+        author,
+        *cv.id,
+        Some("text/plain"),
+        b"Rust".to_vec(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(Some(TextExtractionStatus::Pending), cv.extraction_status());
+    assert_eq!(None, cv.extracted_text());
+    CVTextService::run_extraction(&db, &storage, *cv.id, author, first_file)
+        .await
+        .unwrap();
+    let cv = db.cvs.lock().unwrap()[0].clone();
+    assert_eq!(Some(TextExtractionStatus::Pending), cv.extraction_status());
+
+    assert!(
+        CVTextService::run_extraction(&db, &storage, *cv.id, author, Uuid::new())
+            .await
+            .is_err()
+    );
+}
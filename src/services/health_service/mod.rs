@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests;
+
+use std::{collections::BTreeMap, future::Future, time::Duration};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::data_source::mongo::MongoDB;
+
+use super::storage_service::backend::Storage;
+
+/// How long a dependency has to answer before it is considered down.
+pub const DEPENDENCY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Status of a dependency of the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Up,
+    Down,
+    /// The dependency is not configured, the server runs without it.
+    Disabled,
+}
+
+/// Status of a dependency as returned by `/readyz`. Why a dependency is down
+/// is only logged, since errors can name hosts and buckets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyStatus {
+    pub status: Status,
+}
+
+impl DependencyStatus {
+    fn new(status: Status) -> Self {
+        Self { status }
+    }
+
+    fn from_result<E: std::fmt::Display>(name: &str, result: Option<Result<(), E>>) -> Self {
+        match result {
+            Some(Ok(())) => Self::new(Status::Up),
+            Some(Err(err)) => {
+                log::warn!("{} is down: {}", name, err);
+                Self::new(Status::Down)
+            }
+            None => {
+                log::warn!("{} is down: timed out", name);
+                Self::new(Status::Down)
+            }
+        }
+    }
+}
+
+/// Whether the server can take traffic, with the status of each dependency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub dependencies: BTreeMap<&'static str, DependencyStatus>,
+}
+
+impl Readiness {
+    /// The server is ready when no dependency is down.
+    pub fn new(dependencies: BTreeMap<&'static str, DependencyStatus>) -> Self {
+        let ready = dependencies
+            .values()
+            .all(|dependency| dependency.status != Status::Down);
+        Self {
+            ready,
+            dependencies,
+        }
+    }
+}
+
+/// Build information returned by `/version`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    pub version: String,
+    /// Commit the server was built from, `unknown` when built outside of git.
+    pub git_hash: String,
+    /// sha256 hash of the GraphQL schema in SDL, changes whenever the API
+    /// does.
+    pub schema_hash: String,
+}
+
+pub struct HealthService;
+
+impl HealthService {
+    async fn with_timeout<T>(check: impl Future<Output = T>) -> Option<T> {
+        tokio::time::timeout(DEPENDENCY_TIMEOUT, check).await.ok()
+    }
+
+    pub async fn check_mongo(mongo: &MongoDB) -> DependencyStatus {
+        DependencyStatus::from_result("MongoDB", Self::with_timeout(mongo.ping()).await)
+    }
+
+    /// Check the storage backend, `Disabled` when there is none.
+    pub async fn check_storage(storage: Option<&Storage>) -> DependencyStatus {
+        match storage {
+            Some(storage) => {
+                DependencyStatus::from_result("Storage", Self::with_timeout(storage.ping()).await)
+            }
+            None => DependencyStatus::new(Status::Disabled),
+        }
+    }
+
+    /// Check the dependencies at the same time, so that the check takes at
+    /// most `DEPENDENCY_TIMEOUT`.
+    pub async fn readiness(mongo: &MongoDB, storage: Option<&Storage>) -> Readiness {
+        let (mongo, storage) =
+            tokio::join!(Self::check_mongo(mongo), Self::check_storage(storage));
+        Readiness::new(BTreeMap::from([("mongodb", mongo), ("storage", storage)]))
+    }
+
+    /// Return the build information, `schema_sdl` being the schema of the
+    /// GraphQL endpoint.
+    pub fn version(schema_sdl: &str) -> VersionInfo {
+        VersionInfo {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: env!("GIT_HASH").to_string(),
+            schema_hash: hex::encode(Sha256::digest(schema_sdl.as_bytes())),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use warp::hyper::body::to_bytes;
+use warp::Reply;
+
+use super::{DependencyStatus, HealthService, Readiness, Status};
+use crate::filters::{healthz, version};
+use crate::services::storage_service::{
+    backend::{ObjectMetadata, Storage, StorageBackend, StorageError, StoredObject},
+    memory_storage::MemoryStorage,
+};
+
+/// A backend which cannot be reached.
+struct UnreachableStorage;
+
+#[async_trait]
+impl StorageBackend for UnreachableStorage {
+    async fn put(&self, _: &str, _: &str, _: Vec<u8>) -> Result<(), StorageError> {
+        Err(StorageError::Backend("connection refused".to_string()))
+    }
+
+    async fn get(&self, _: &str) -> Result<StoredObject, StorageError> {
+        Err(StorageError::Backend("connection refused".to_string()))
+    }
+
+    async fn delete(&self, _: &str) -> Result<(), StorageError> {
+        Err(StorageError::Backend("connection refused".to_string()))
+    }
+
+    async fn head(&self, _: &str) -> Result<ObjectMetadata, StorageError> {
+        Err(StorageError::Backend("connection refused".to_string()))
+    }
+
+    async fn signed_url(&self, _: &str, _: Duration) -> Result<String, StorageError> {
+        Err(StorageError::Backend("connection refused".to_string()))
+    }
+}
+
+fn dependency(status: Status) -> DependencyStatus {
+    DependencyStatus { status }
+}
+
+#[test]
+fn ready_unless_a_dependency_is_down() {
+    let readiness = Readiness::new(BTreeMap::from([
+        ("mongodb", dependency(Status::Up)),
+        ("storage", dependency(Status::Disabled)),
+    ]));
+    assert!(readiness.ready);
+
+    let readiness = Readiness::new(BTreeMap::from([
+        ("mongodb", dependency(Status::Down)),
+        ("storage", dependency(Status::Up)),
+    ]));
+    assert!(!readiness.ready);
+    assert_eq!(
+        serde_json::json!({
+            "ready": false,
+            "dependencies": {
+                "mongodb": { "status": "down" },
+                "storage": { "status": "up" },
+            }
+        }),
+        serde_json::to_value(&readiness).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn check_storage() {
+    let storage: Storage = Arc::new(MemoryStorage::new());
+    assert_eq!(
+        dependency(Status::Up),
+        HealthService::check_storage(Some(&storage)).await
+    );
+
+    assert_eq!(
+        dependency(Status::Disabled),
+        HealthService::check_storage(None).await
+    );
+
+    // the error is not returned, it could name the backend
+    let storage: Storage = Arc::new(UnreachableStorage);
+    let status = HealthService::check_storage(Some(&storage)).await;
+    assert_eq!(dependency(Status::Down), status);
+    assert_eq!(
+        serde_json::json!({ "status": "down" }),
+        serde_json::to_value(&status).unwrap()
+    );
+}
+
+#[test]
+fn version_info() {
+    let info = HealthService::version("type Query { a: Int }");
+    assert_eq!(env!("CARGO_PKG_VERSION"), info.version);
+    assert!(!info.git_hash.is_empty());
+    assert_eq!(64, info.schema_hash.len());
+    assert!(info.schema_hash.chars().all(|c| c.is_ascii_hexdigit()));
+
+    // the hash follows the schema
+    assert_eq!(
+        info.schema_hash,
+        HealthService::version("type Query { a: Int }").schema_hash
+    );
+    assert_ne!(
+        info.schema_hash,
+        HealthService::version("type Query { b: Int }").schema_hash
+    );
+}
+
+#[tokio::test]
+async fn health_endpoints() {
+    let reply = warp::test::request()
+        .path("/healthz")
+        .filter(&healthz())
+        .await
+        .unwrap()
+        .into_response();
+    assert_eq!(200, reply.status());
+
+    let reply = warp::test::request()
+        .path("/version")
+        .filter(&version("type Query { a: Int }"))
+        .await
+        .unwrap()
+        .into_response();
+    assert_eq!(200, reply.status());
+    let body: serde_json::Value =
+        serde_json::from_slice(&to_bytes(reply.into_body()).await.unwrap()).unwrap();
+    assert_eq!(env!("CARGO_PKG_VERSION"), body["version"]);
+    assert!(body["gitHash"].is_string());
+    assert!(body["schemaHash"].is_string());
+}
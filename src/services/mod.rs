@@ -4,6 +4,12 @@ pub mod user_service;
 pub mod storage_service;
 pub mod cv_service;
 pub mod auth_service;
+pub mod conversation_service;
+pub mod reaction_service;
+pub mod moderation_service;
+pub mod admin_service;
+pub mod rate_limit_service;
+pub mod health_service;
 
 type ResourceIdentifier = Uuid;
 #[cfg(test)]
@@ -0,0 +1,93 @@
+use async_graphql::ErrorExtensions;
+use mongodb::bson::oid::ObjectId;
+use std::fmt;
+
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ModerationServiceError {
+    /// Error when the reported CV does not exist.
+    CvNotFound(ObjectId),
+
+    /// Error when the reported comment does not exist.
+    CommentNotFound(ObjectId),
+
+    /// Error when the reported user does not exist.
+    UserNotFound(ObjectId),
+
+    /// Error when reporting without a reason.
+    EmptyReason,
+
+    /// Error when the user already has an open report on the target.
+    AlreadyReported(ObjectId),
+
+    /// Error when the report does not exist.
+    ReportNotFound(ObjectId),
+
+    /// Error when resolving a report that was already resolved.
+    ReportClosed(ObjectId),
+
+    /// Error when the action cannot be taken on the reported target, e.g.
+    /// hiding a user.
+    InvalidAction,
+
+    /// Error when a user who is not a moderator tries to moderate.
+    NotModerator,
+
+    /// Database error
+    DatabaseError,
+}
+
+impl fmt::Display for ModerationServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModerationServiceError::CvNotFound(id) => {
+                write!(f, "CV {:?} not found", id)
+            }
+            ModerationServiceError::CommentNotFound(id) => {
+                write!(f, "Comment {:?} not found", id)
+            }
+            ModerationServiceError::UserNotFound(id) => {
+                write!(f, "User {:?} not found", id)
+            }
+            ModerationServiceError::EmptyReason => {
+                write!(f, "A reason is required")
+            }
+            ModerationServiceError::AlreadyReported(id) => {
+                write!(f, "{:?} is already reported", id)
+            }
+            ModerationServiceError::ReportNotFound(id) => {
+                write!(f, "Report {:?} not found", id)
+            }
+            ModerationServiceError::ReportClosed(id) => {
+                write!(f, "Report {:?} is already resolved", id)
+            }
+            ModerationServiceError::InvalidAction => {
+                write!(f, "This action cannot be taken on the reported target")
+            }
+            ModerationServiceError::NotModerator => {
+                write!(f, "Only moderators can do this")
+            }
+            ModerationServiceError::DatabaseError => {
+                write!(f, "Database error")
+            }
+        }
+    }
+}
+
+impl ErrorExtensions for ModerationServiceError {
+    fn extend(&self) -> async_graphql::Error {
+        let code = match self {
+            ModerationServiceError::CvNotFound(_) => "CV_NOT_FOUND",
+            ModerationServiceError::CommentNotFound(_) => "COMMENT_NOT_FOUND",
+            ModerationServiceError::UserNotFound(_) => "USER_NOT_FOUND",
+            ModerationServiceError::EmptyReason => "EMPTY_REASON",
+            ModerationServiceError::AlreadyReported(_) => "ALREADY_REPORTED",
+            ModerationServiceError::ReportNotFound(_) => "REPORT_NOT_FOUND",
+            ModerationServiceError::ReportClosed(_) => "REPORT_CLOSED",
+            ModerationServiceError::InvalidAction => "INVALID_ACTION",
+            ModerationServiceError::NotModerator => "NOT_MODERATOR",
+            ModerationServiceError::DatabaseError => "DATABASE_ERROR",
+        };
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| e.set("code", code))
+    }
+}
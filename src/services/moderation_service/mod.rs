@@ -0,0 +1,252 @@
+pub mod error;
+#[cfg(test)]
+mod tests;
+
+use std::time::Duration;
+
+use mongodb::bson::{self, oid::ObjectId};
+
+use crate::{
+    common::DateTime,
+    data_source::{
+        CVDataSource, CVDataSourceError, CommentDataSource, ReportDataSource, UserDataSource,
+    },
+    models::report::{ModerationAction, ModerationDecision, Report, ReportStatus, ReportTarget},
+};
+
+use self::error::ModerationServiceError;
+
+use super::{
+    cv_service::comment_service::CommentServiceError, user_service::error::UserServiceError,
+};
+
+/// Number of distinct users reporting a CV or a comment after which it is
+/// hidden until a moderator reviews it, when `AUTO_HIDE_REPORT_THRESHOLD` is
+/// not set.
+pub const DEFAULT_AUTO_HIDE_THRESHOLD: usize = 5;
+
+/// How long a user is suspended by a moderator.
+pub const SUSPENSION_DURATION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Number of distinct users reporting a CV or a comment after which it is
+/// hidden until a moderator reviews it.
+pub fn auto_hide_threshold() -> usize {
+    std::env::var("AUTO_HIDE_REPORT_THRESHOLD")
+        .ok()
+        .and_then(|threshold| threshold.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_AUTO_HIDE_THRESHOLD)
+        .max(1)
+}
+
+pub struct ModerationService;
+
+impl ModerationService {
+    /// Report a CV, a comment or a user to the moderators. A user can only
+    /// have one open report per target. CVs and comments reported by enough
+    /// distinct users are hidden until a moderator reviews them.
+    pub async fn report_content(
+        db: &(impl CVDataSource
+              + CommentDataSource
+              + UserDataSource
+              + ReportDataSource
+              + std::marker::Sync),
+        reporter_id: ObjectId,
+        target_type: ReportTarget,
+        target_id: ObjectId,
+        reason: String,
+    ) -> Result<Report, ModerationServiceError> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(ModerationServiceError::EmptyReason);
+        }
+        let (_, hidden) = Self::reported_target(db, target_type, target_id).await?;
+        let open = db
+            .get_open_reports(target_id)
+            .await
+            .map_err(|err| err.into())?;
+        if open.iter().any(|report| *report.reporter_id == reporter_id) {
+            return Err(ModerationServiceError::AlreadyReported(target_id));
+        }
+        let report = Report::new(reporter_id, target_type, target_id, reason.to_string());
+        db.add_report(report.clone())
+            .await
+            .map_err(|err| err.into())?;
+
+        // a user has at most one open report on a target, so every open
+        // report comes from a different user
+        let reporters = open.len() + 1;
+        if target_type != ReportTarget::User && !hidden && reporters >= auto_hide_threshold() {
+            Self::set_hidden(db, target_type, target_id, Some(DateTime::now())).await?;
+            let note = format!("Hidden automatically after {} reports", reporters);
+            let decision =
+                ModerationDecision::new(&report, None, ModerationAction::HideContent, Some(note));
+            db.add_decision(decision).await.map_err(|err| err.into())?;
+        }
+        Ok(report)
+    }
+
+    /// Return the reports for the moderator to review, oldest first, only
+    /// those with `status` if given.
+    pub async fn moderation_queue(
+        db: &(impl UserDataSource + ReportDataSource + std::marker::Sync),
+        moderator_id: ObjectId,
+        status: Option<ReportStatus>,
+    ) -> Result<Vec<Report>, ModerationServiceError> {
+        Self::check_moderator(db, moderator_id).await?;
+        db.get_reports(status).await.map_err(|err| err.into())
+    }
+
+    /// Act on a report. Every open report on the same target is closed with
+    /// it and the decision is recorded.
+    pub async fn resolve_report(
+        db: &(impl CVDataSource
+              + CommentDataSource
+              + UserDataSource
+              + ReportDataSource
+              + std::marker::Sync),
+        moderator_id: ObjectId,
+        report_id: ObjectId,
+        action: ModerationAction,
+        note: Option<String>,
+    ) -> Result<Report, ModerationServiceError> {
+        Self::check_moderator(db, moderator_id).await?;
+        let report = Self::get_report(db, report_id).await?;
+        if report.status != ReportStatus::Open {
+            return Err(ModerationServiceError::ReportClosed(report_id));
+        }
+        let (target_type, target_id) = (report.target_type, *report.target_id);
+        let (author_id, hidden) = Self::reported_target(db, target_type, target_id).await?;
+        match action {
+            ModerationAction::Dismiss => {
+                // only show again what was hidden automatically, not what
+                // a moderator decided to hide
+                let decisions = db
+                    .get_decisions(Some(target_id))
+                    .await
+                    .map_err(|err| err.into())?;
+                let hidden_automatically = decisions
+                    .first()
+                    .is_some_and(|decision| decision.moderator_id.is_none());
+                if hidden && hidden_automatically {
+                    Self::set_hidden(db, target_type, target_id, None).await?;
+                }
+            }
+            ModerationAction::HideContent => {
+                if target_type == ReportTarget::User {
+                    return Err(ModerationServiceError::InvalidAction);
+                }
+                if !hidden {
+                    Self::set_hidden(db, target_type, target_id, Some(DateTime::now())).await?;
+                }
+            }
+            ModerationAction::SuspendUser => {
+                let until =
+                    DateTime::now().timestamp_millis() + SUSPENSION_DURATION.as_millis() as i64;
+                db.set_suspended_until(author_id, Some(bson::DateTime::from_millis(until).into()))
+                    .await
+                    .map_err(|err| match err.into() {
+                        UserServiceError::DatabaseError => ModerationServiceError::DatabaseError,
+                        _ => ModerationServiceError::UserNotFound(author_id),
+                    })?;
+            }
+        }
+        db.close_reports(target_id, action.into(), DateTime::now())
+            .await
+            .map_err(|err| err.into())?;
+        let decision = ModerationDecision::new(&report, Some(moderator_id), action, note);
+        db.add_decision(decision).await.map_err(|err| err.into())?;
+        Self::get_report(db, report_id).await
+    }
+
+    /// Return the moderation decisions, newest first, only those on the
+    /// target if given.
+    pub async fn decisions(
+        db: &(impl UserDataSource + ReportDataSource + std::marker::Sync),
+        moderator_id: ObjectId,
+        target_id: Option<ObjectId>,
+    ) -> Result<Vec<ModerationDecision>, ModerationServiceError> {
+        Self::check_moderator(db, moderator_id).await?;
+        db.get_decisions(target_id).await.map_err(|err| err.into())
+    }
+
+    async fn check_moderator(
+        db: &(impl UserDataSource + std::marker::Sync),
+        user_id: ObjectId,
+    ) -> Result<(), ModerationServiceError> {
+        match db.get_user_by_id(user_id).await {
+            Ok(user) if user.is_moderator() => Ok(()),
+            Ok(_) => Err(ModerationServiceError::NotModerator),
+            Err(err) => match err.into() {
+                UserServiceError::DatabaseError => Err(ModerationServiceError::DatabaseError),
+                _ => Err(ModerationServiceError::NotModerator),
+            },
+        }
+    }
+
+    async fn get_report(
+        db: &(impl ReportDataSource + std::marker::Sync),
+        report_id: ObjectId,
+    ) -> Result<Report, ModerationServiceError> {
+        db.get_report(report_id)
+            .await
+            .map_err(|err| err.into())?
+            .ok_or(ModerationServiceError::ReportNotFound(report_id))
+    }
+
+    /// Return the user responsible for the target and whether the target is
+    /// hidden, checking it exists.
+    async fn reported_target(
+        db: &(impl CVDataSource + CommentDataSource + UserDataSource + std::marker::Sync),
+        target_type: ReportTarget,
+        target_id: ObjectId,
+    ) -> Result<(ObjectId, bool), ModerationServiceError> {
+        match target_type {
+            ReportTarget::Cv => match db.get_cv_by_id(target_id).await {
+                Ok(cv) => Ok((cv.author_id.into(), cv.hidden_at.is_some())),
+                Err(CVDataSourceError::DatabaseError) => Err(ModerationServiceError::DatabaseError),
+                Err(_) => Err(ModerationServiceError::CvNotFound(target_id)),
+            },
+            ReportTarget::Comment => match db.get_comment_by_id(target_id).await {
+                Ok(comment) => Ok((comment.author.into(), comment.hidden_at.is_some())),
+                Err(err) => match err.into() {
+                    CommentServiceError::DatabaseError => {
+                        Err(ModerationServiceError::DatabaseError)
+                    }
+                    _ => Err(ModerationServiceError::CommentNotFound(target_id)),
+                },
+            },
+            ReportTarget::User => match db.get_user_by_id(target_id).await {
+                Ok(_) => Ok((target_id, false)),
+                Err(err) => match err.into() {
+                    UserServiceError::DatabaseError => Err(ModerationServiceError::DatabaseError),
+                    _ => Err(ModerationServiceError::UserNotFound(target_id)),
+                },
+            },
+        }
+    }
+
+    async fn set_hidden(
+        db: &(impl CVDataSource + CommentDataSource + std::marker::Sync),
+        target_type: ReportTarget,
+        target_id: ObjectId,
+        hidden_at: Option<DateTime>,
+    ) -> Result<(), ModerationServiceError> {
+        match target_type {
+            ReportTarget::Cv => match db.set_cv_hidden(target_id, hidden_at).await {
+                Ok(_) => Ok(()),
+                Err(CVDataSourceError::DatabaseError) => Err(ModerationServiceError::DatabaseError),
+                Err(_) => Err(ModerationServiceError::CvNotFound(target_id)),
+            },
+            ReportTarget::Comment => match db.set_comment_hidden(target_id, hidden_at).await {
+                Ok(_) => Ok(()),
+                Err(err) => match err.into() {
+                    CommentServiceError::DatabaseError => {
+                        Err(ModerationServiceError::DatabaseError)
+                    }
+                    _ => Err(ModerationServiceError::CommentNotFound(target_id)),
+                },
+            },
+            ReportTarget::User => Err(ModerationServiceError::InvalidAction),
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use mongodb::bson::oid::ObjectId;
+
+use super::{auto_hide_threshold, error::ModerationServiceError, ModerationService};
+use crate::{
+    data_source::{CVDataSource, CommentDataSource, UserDataSource},
+    models::{
+        comment::CreateCommentInput,
+        report::{ModerationAction, ReportStatus, ReportTarget},
+        users::Role,
+    },
+    services::{
+        cv_service::{comment_service::CommentService, cv_service::CVService},
+        tests::{create_user, MockDatabase},
+    },
+};
+
+async fn create_moderator(db: &MockDatabase, username: &str) -> ObjectId {
+    let id = create_user(db, username).await;
+    let mut users = db.users.lock().unwrap();
+    let user = users.iter_mut().find(|user| *user.id == id).unwrap();
+    user.role = Role::Moderator;
+    id
+}
+
+#[tokio::test]
+async fn reported_content_is_hidden_automatically() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let cv_id = *CVService::create_cv(&db, author, "CV".to_string(), "".to_string())
+        .await
+        .unwrap()
+        .id;
+
+    assert_eq!(
+        Err(ModerationServiceError::EmptyReason),
+        ModerationService::report_content(&db, author, ReportTarget::Cv, cv_id, " ".to_string())
+            .await
+    );
+    let missing = ObjectId::new();
+    assert_eq!(
+        Err(ModerationServiceError::CvNotFound(missing)),
+        ModerationService::report_content(
+            &db,
+            author,
+            ReportTarget::Cv,
+            missing,
+            "spam".to_string()
+        )
+        .await
+    );
+
+    let threshold = auto_hide_threshold();
+    for i in 0..threshold {
+        let reporter = create_user(&db, &format!("reporter{}", i)).await;
+        let cv = db.get_cv_by_id(cv_id).await.unwrap();
+        assert!(cv.hidden_at.is_none());
+        let report = ModerationService::report_content(
+            &db,
+            reporter,
+            ReportTarget::Cv,
+            cv_id,
+            "spam".into(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(ReportStatus::Open, report.status);
+        // reporting again does not count twice
+        assert_eq!(
+            Err(ModerationServiceError::AlreadyReported(cv_id)),
+            ModerationService::report_content(
+                &db,
+                reporter,
+                ReportTarget::Cv,
+                cv_id,
+                "spam".into()
+            )
+            .await
+        );
+    }
+    let cv = db.get_cv_by_id(cv_id).await.unwrap();
+    assert!(cv.hidden_at.is_some());
+    assert!(!CVService::can_view_cv(&db, None, &cv).await.unwrap());
+    assert!(CVService::can_view_cv(&db, Some(author), &cv).await.unwrap());
+
+    let moderator = create_moderator(&db, "moderator").await;
+    let decisions = ModerationService::decisions(&db, moderator, Some(cv_id))
+        .await
+        .unwrap();
+    assert_eq!(1, decisions.len());
+    assert_eq!(ModerationAction::HideContent, decisions[0].action);
+    assert_eq!(None, decisions[0].moderator_id);
+
+    // dismissing the reports shows the CV again
+    let queue = ModerationService::moderation_queue(&db, moderator, Some(ReportStatus::Open))
+        .await
+        .unwrap();
+    assert_eq!(threshold, queue.len());
+    let report = ModerationService::resolve_report(
+        &db,
+        moderator,
+        *queue[0].id,
+        ModerationAction::Dismiss,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(ReportStatus::Dismissed, report.status);
+    assert!(report.resolved_at.is_some());
+    assert!(db.get_cv_by_id(cv_id).await.unwrap().hidden_at.is_none());
+    assert!(
+        ModerationService::moderation_queue(&db, moderator, Some(ReportStatus::Open))
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn moderators_resolve_reports() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let alice = create_user(&db, "alice").await;
+    let bob = create_user(&db, "bob").await;
+    let moderator = create_moderator(&db, "moderator").await;
+    let input = CreateCommentInput {
+        author: author.into(),
+        content: "comment".to_string(),
+    };
+    let comment_id = *CommentService::create_comment(&db, input).await.unwrap().id;
+
+    let report = ModerationService::report_content(
+        &db,
+        alice,
+        ReportTarget::Comment,
+        comment_id,
+        "insult".into(),
+    )
+    .await
+    .unwrap();
+    ModerationService::report_content(&db, bob, ReportTarget::Comment, comment_id, "rude".into())
+        .await
+        .unwrap();
+    let user_report =
+        ModerationService::report_content(&db, alice, ReportTarget::User, author, "troll".into())
+            .await
+            .unwrap();
+
+    // only moderators see the queue and act on it
+    assert_eq!(
+        Err(ModerationServiceError::NotModerator),
+        ModerationService::moderation_queue(&db, alice, None).await
+    );
+    assert_eq!(
+        Err(ModerationServiceError::NotModerator),
+        ModerationService::resolve_report(&db, alice, *report.id, ModerationAction::Dismiss, None)
+            .await
+    );
+    assert_eq!(
+        3,
+        ModerationService::moderation_queue(&db, moderator, None)
+            .await
+            .unwrap()
+            .len()
+    );
+
+    // hiding closes every report on the comment
+    ModerationService::resolve_report(
+        &db,
+        moderator,
+        *report.id,
+        ModerationAction::HideContent,
+        Some("abusive".to_string()),
+    )
+    .await
+    .unwrap();
+    assert!(db
+        .get_comment_by_id(comment_id)
+        .await
+        .unwrap()
+        .hidden_at
+        .is_some());
+    let hidden =
+        ModerationService::moderation_queue(&db, moderator, Some(ReportStatus::ContentHidden))
+            .await
+            .unwrap();
+    assert_eq!(2, hidden.len());
+    assert_eq!(
+        Err(ModerationServiceError::ReportClosed(*report.id)),
+        ModerationService::resolve_report(
+            &db,
+            moderator,
+            *report.id,
+            ModerationAction::Dismiss,
+            None
+        )
+        .await
+    );
+
+    // users cannot be hidden, only suspended
+    assert_eq!(
+        Err(ModerationServiceError::InvalidAction),
+        ModerationService::resolve_report(
+            &db,
+            moderator,
+            *user_report.id,
+            ModerationAction::HideContent,
+            None
+        )
+        .await
+    );
+    let user_report = ModerationService::resolve_report(
+        &db,
+        moderator,
+        *user_report.id,
+        ModerationAction::SuspendUser,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(ReportStatus::UserSuspended, user_report.status);
+    assert!(db.get_user_by_id(author).await.unwrap().is_suspended());
+
+    let decisions = ModerationService::decisions(&db, moderator, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![ModerationAction::SuspendUser, ModerationAction::HideContent],
+        decisions.iter().map(|d| d.action).collect::<Vec<_>>()
+    );
+    assert_eq!(Some(moderator.into()), decisions[1].moderator_id);
+    assert_eq!(Some("abusive".to_string()), decisions[1].note);
+}
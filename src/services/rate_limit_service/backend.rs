@@ -0,0 +1,20 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{error::RateLimitError, RateLimit};
+
+/// Shared handle to the configured rate limit store, this is what gets put
+/// in the GraphQL context.
+pub type RateLimiter = Arc<dyn RateLimitStore>;
+
+/// Primary abstraction for the token buckets of the rate limiter. The
+/// in-memory store only limits a single server, a store shared between
+/// servers should implement this trait.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Take a token from the bucket of the key, creating a full bucket if
+    /// there is none. Fail with `RateLimitError::RateLimited` and the delay
+    /// before a token is available if the bucket is empty.
+    async fn acquire(&self, key: &str, limit: RateLimit) -> Result<(), RateLimitError>;
+}
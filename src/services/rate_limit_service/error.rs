@@ -0,0 +1,64 @@
+use async_graphql::ErrorExtensions;
+use std::{fmt, time::Duration};
+use warp::reject::Reject;
+
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RateLimitError {
+    /// Error when the client made too many requests, they can retry after
+    /// the given delay.
+    RateLimited(Duration),
+
+    /// Error when the store of the buckets failed, e.g. a shared store is
+    /// unreachable.
+    Store(String),
+}
+
+impl RateLimitError {
+    /// Delay before retrying, in whole seconds, as in the `Retry-After`
+    /// header.
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            RateLimitError::RateLimited(delay) => {
+                Some(delay.as_secs() + u64::from(delay.subsec_nanos() > 0))
+            }
+            RateLimitError::Store(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RateLimitError::RateLimited(_) => {
+                write!(
+                    f,
+                    "Too many requests, retry after {} seconds",
+                    self.retry_after_secs().unwrap_or_default()
+                )
+            }
+
+            RateLimitError::Store(reason) => {
+                write!(f, "Rate limit store error: {}", reason)
+            }
+        }
+    }
+}
+
+impl ErrorExtensions for RateLimitError {
+    fn extend(&self) -> async_graphql::Error {
+        let code = match self {
+            RateLimitError::RateLimited(_) => "RATE_LIMITED",
+            RateLimitError::Store(_) => "RATE_LIMIT_STORE_ERROR",
+        };
+        let retry_after = self.retry_after_secs();
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| {
+            e.set("code", code);
+            if let Some(retry_after) = retry_after {
+                e.set("retryAfter", retry_after);
+            }
+        })
+    }
+}
+
+impl Reject for RateLimitError {}
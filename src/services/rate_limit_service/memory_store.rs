@@ -0,0 +1,148 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use super::{backend::RateLimitStore, error::RateLimitError, RateLimit};
+
+/// Most buckets kept, the least recently used one being dropped to make room
+/// for a new one.
+const MAX_BUCKETS: usize = 100_000;
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    period: Duration,
+    /// Tells apart buckets updated at the same instant.
+    id: u64,
+}
+
+impl Bucket {
+    fn full(limit: RateLimit, now: Instant, id: u64) -> Self {
+        Self {
+            tokens: limit.capacity as f64,
+            updated: now,
+            period: limit.period,
+            id,
+        }
+    }
+
+    fn age_key(&self) -> (Instant, u64) {
+        (self.updated, self.id)
+    }
+
+    /// Refill the bucket for the time elapsed since it was last updated, then
+    /// take a token or return how long until one is available.
+    fn take(&mut self, limit: RateLimit, now: Instant) -> Result<(), Duration> {
+        let rate = limit.capacity as f64 / limit.period.as_secs_f64();
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(limit.capacity as f64);
+        self.updated = now;
+        self.period = limit.period;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+
+    /// Whether the bucket has had the time to refill completely.
+    fn is_full(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.updated) >= self.period
+    }
+}
+
+#[derive(Default)]
+struct Buckets {
+    by_key: HashMap<String, Bucket>,
+    /// Keys of the buckets, least recently updated first.
+    by_age: BTreeMap<(Instant, u64), String>,
+    next_id: u64,
+}
+
+impl Buckets {
+    fn remove_oldest(&mut self) {
+        if let Some((_, key)) = self.by_age.pop_first() {
+            self.by_key.remove(&key);
+        }
+    }
+}
+
+/// Keeps the buckets in memory, so limits only apply to this server.
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<Buckets>,
+    max_buckets: usize,
+}
+
+impl Default for MemoryRateLimitStore {
+    fn default() -> Self {
+        Self::with_max_buckets(MAX_BUCKETS)
+    }
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `max_buckets` buckets.
+    pub fn with_max_buckets(max_buckets: usize) -> Self {
+        Self {
+            buckets: Mutex::default(),
+            max_buckets: max_buckets.max(1),
+        }
+    }
+
+    pub(super) fn acquire_at(
+        &self,
+        key: &str,
+        limit: RateLimit,
+        now: Instant,
+    ) -> Result<(), RateLimitError> {
+        let mut buckets = self.buckets.lock().unwrap();
+        // a missing bucket is the same as a full one, drop the oldest buckets
+        // while they are full
+        while let Some((_, oldest)) = buckets.by_age.first_key_value() {
+            if !buckets.by_key[oldest].is_full(now) {
+                break;
+            }
+            buckets.remove_oldest();
+        }
+
+        let mut bucket = match buckets.by_key.remove(key) {
+            Some(bucket) => {
+                buckets.by_age.remove(&bucket.age_key());
+                bucket
+            }
+            None => {
+                while buckets.by_key.len() >= self.max_buckets {
+                    buckets.remove_oldest();
+                }
+                buckets.next_id += 1;
+                Bucket::full(limit, now, buckets.next_id)
+            }
+        };
+        let taken = bucket.take(limit, now);
+        buckets.by_age.insert(bucket.age_key(), key.to_string());
+        buckets.by_key.insert(key.to_string(), bucket);
+        taken.map_err(RateLimitError::RateLimited)
+    }
+
+    /// Number of buckets kept.
+    #[cfg(test)]
+    pub(super) fn len(&self) -> usize {
+        self.buckets.lock().unwrap().by_key.len()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn acquire(&self, key: &str, limit: RateLimit) -> Result<(), RateLimitError> {
+        self.acquire_at(key, limit, Instant::now())
+    }
+}
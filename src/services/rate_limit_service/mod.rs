@@ -0,0 +1,77 @@
+pub mod backend;
+pub mod error;
+pub mod memory_store;
+#[cfg(test)]
+mod tests;
+
+use std::{net::IpAddr, time::Duration};
+
+use self::{backend::RateLimitStore, error::RateLimitError};
+use super::auth_service::AuthService;
+
+/// Requests a client can make per minute on the GraphQL endpoint when
+/// `RATE_LIMIT_PER_MINUTE` is not set.
+pub const DEFAULT_RATE_LIMIT_PER_MINUTE: u32 = 300;
+
+/// A token bucket holding up to `capacity` tokens and refilled with
+/// `capacity` tokens per `period`, every request takes a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub period: Duration,
+}
+
+impl RateLimit {
+    pub const fn new(capacity: u32, period: Duration) -> Self {
+        Self { capacity, period }
+    }
+
+    pub const fn per_minute(capacity: u32) -> Self {
+        Self::new(capacity, Duration::from_secs(60))
+    }
+
+    pub const fn per_hour(capacity: u32) -> Self {
+        Self::new(capacity, Duration::from_secs(60 * 60))
+    }
+}
+
+/// Limit of the requests a client can make on the GraphQL endpoint.
+pub fn default_rate_limit() -> RateLimit {
+    let capacity = std::env::var("RATE_LIMIT_PER_MINUTE")
+        .ok()
+        .and_then(|capacity| capacity.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_RATE_LIMIT_PER_MINUTE)
+        .max(1);
+    RateLimit::per_minute(capacity)
+}
+
+pub struct RateLimitService;
+
+impl RateLimitService {
+    /// Return the key a client is limited by: the subject of the access token
+    /// for logged in users, the address of the client otherwise, see
+    /// `filters::with_client_ip` for the address behind a proxy. Return
+    /// `None` if there is neither.
+    pub fn client_key(token: Option<&str>, client_ip: Option<IpAddr>) -> Option<String> {
+        match token.and_then(|token| AuthService::decode_token(token, true)) {
+            Some(claims) => Some(format!("user:{}", claims.sub)),
+            None => client_ip.map(|ip| format!("ip:{}", ip)),
+        }
+    }
+
+    /// Take a token from the bucket of the key. A failing store lets the
+    /// request through, the rate limiter should not take the server down.
+    pub async fn check(
+        store: &(impl RateLimitStore + ?Sized),
+        key: &str,
+        limit: RateLimit,
+    ) -> Result<(), RateLimitError> {
+        match store.acquire(key, limit).await {
+            Err(RateLimitError::Store(reason)) => {
+                log::warn!("Rate limit store is not available: {}", reason);
+                Ok(())
+            }
+            rs => rs,
+        }
+    }
+}
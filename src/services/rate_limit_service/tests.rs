@@ -0,0 +1,152 @@
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use super::{
+    backend::{RateLimitStore, RateLimiter},
+    error::RateLimitError,
+    memory_store::MemoryRateLimitStore,
+    RateLimit, RateLimitService,
+};
+use crate::filters::{rate_limited_reply, with_rate_limit, ClientIp};
+
+#[test]
+fn bucket_refills_over_time() {
+    let store = MemoryRateLimitStore::new();
+    let limit = RateLimit::new(2, Duration::from_secs(10));
+    let start = Instant::now();
+
+    store.acquire_at("key", limit, start).unwrap();
+    store.acquire_at("key", limit, start).unwrap();
+    // empty, a token comes back every 5 seconds
+    assert_eq!(
+        Err(RateLimitError::RateLimited(Duration::from_secs(5))),
+        store.acquire_at("key", limit, start)
+    );
+    // other keys have their own bucket
+    store.acquire_at("other", limit, start).unwrap();
+
+    let later = start + Duration::from_secs(4);
+    assert_eq!(
+        Err(RateLimitError::RateLimited(Duration::from_secs(1))),
+        store.acquire_at("key", limit, later)
+    );
+    store
+        .acquire_at("key", limit, start + Duration::from_secs(5))
+        .unwrap();
+
+    // a bucket never holds more than its capacity
+    let much_later = start + Duration::from_secs(1000);
+    store.acquire_at("key", limit, much_later).unwrap();
+    store.acquire_at("key", limit, much_later).unwrap();
+    assert!(store.acquire_at("key", limit, much_later).is_err());
+}
+
+#[test]
+fn buckets_are_bounded() {
+    let store = MemoryRateLimitStore::with_max_buckets(2);
+    let limit = RateLimit::new(1, Duration::from_secs(10));
+    let start = Instant::now();
+
+    store.acquire_at("a", limit, start).unwrap();
+    store.acquire_at("b", limit, start).unwrap();
+    // a new key drops the least recently used bucket
+    store.acquire_at("c", limit, start).unwrap();
+    assert_eq!(2, store.len());
+    // so "a" starts again with a full bucket, dropping "b"
+    store.acquire_at("a", limit, start).unwrap();
+    assert!(store.acquire_at("c", limit, start).is_err());
+    assert_eq!(2, store.len());
+
+    // buckets that had the time to refill are dropped
+    let later = start + Duration::from_secs(10);
+    store.acquire_at("d", limit, later).unwrap();
+    assert_eq!(1, store.len());
+}
+
+#[test]
+fn retry_after_is_rounded_up() {
+    let err = RateLimitError::RateLimited(Duration::from_millis(1500));
+    assert_eq!(Some(2), err.retry_after_secs());
+    assert_eq!(
+        Some(3),
+        RateLimitError::RateLimited(Duration::from_secs(3)).retry_after_secs()
+    );
+}
+
+struct FailingStore;
+
+#[async_trait::async_trait]
+impl RateLimitStore for FailingStore {
+    async fn acquire(&self, _: &str, _: RateLimit) -> Result<(), RateLimitError> {
+        Err(RateLimitError::Store("unreachable".to_string()))
+    }
+}
+
+#[tokio::test]
+async fn failing_store_lets_requests_through() {
+    let limit = RateLimit::per_minute(1);
+    assert_eq!(
+        Ok(()),
+        RateLimitService::check(&FailingStore, "key", limit).await
+    );
+}
+
+#[test]
+fn anonymous_clients_are_limited_by_address() {
+    let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    assert_eq!(
+        Some("ip:10.0.0.1".to_string()),
+        RateLimitService::client_key(None, Some(ip))
+    );
+    // invalid tokens do not give a subject
+    assert_eq!(
+        Some("ip:10.0.0.1".to_string()),
+        RateLimitService::client_key(Some("invalid"), Some(ip))
+    );
+    assert_eq!(None, RateLimitService::client_key(None, None));
+}
+
+#[tokio::test]
+async fn filter_rejects_once_the_bucket_is_empty() {
+    let limiter: RateLimiter = Arc::new(MemoryRateLimitStore::new());
+    let filter = with_rate_limit(limiter, RateLimit::per_minute(2));
+    let addr = "10.0.0.1:1234".parse().unwrap();
+
+    for _ in 0..2 {
+        let (header, client_ip) = warp::test::request()
+            .remote_addr(addr)
+            .filter(&filter)
+            .await
+            .unwrap();
+        assert_eq!(None, header);
+        assert_eq!(ClientIp(Some(addr.ip())), client_ip);
+    }
+    let rejection = warp::test::request()
+        .remote_addr(addr)
+        .filter(&filter)
+        .await
+        .unwrap_err();
+    let err = rejection.find::<RateLimitError>().unwrap();
+    assert_eq!(Some(30), err.retry_after_secs());
+    // the client is not a trusted proxy, so its X-Forwarded-For is ignored
+    warp::test::request()
+        .remote_addr(addr)
+        .header("X-Forwarded-For", "10.0.0.3")
+        .filter(&filter)
+        .await
+        .unwrap_err();
+
+    let reply = rate_limited_reply(err);
+    assert_eq!(429, reply.status().as_u16());
+    assert_eq!("30", reply.headers()["Retry-After"]);
+
+    // another client is not limited
+    warp::test::request()
+        .remote_addr("10.0.0.2:1234".parse().unwrap())
+        .filter(&filter)
+        .await
+        .unwrap();
+}
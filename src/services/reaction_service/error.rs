@@ -0,0 +1,96 @@
+use async_graphql::ErrorExtensions;
+use mongodb::bson::oid::ObjectId;
+use std::fmt;
+
+use crate::services::cv_service::{comment_service::CommentServiceError, error::CVServiceError};
+
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ReactionServiceError {
+    /// Error when the CV reacted to does not exist.
+    CvNotFound(ObjectId),
+
+    /// Error when the user cannot see the CV reacted to.
+    CvNotVisible(ObjectId),
+
+    /// Error when the comment reacted to does not exist.
+    CommentNotFound(ObjectId),
+
+    /// Error when reacting to a deleted comment.
+    CommentDeleted(ObjectId),
+
+    /// Error when the author of the target has blocked the user, or the other
+    /// way around.
+    UserBlocked(ObjectId),
+
+    /// Database error
+    DatabaseError,
+}
+
+impl fmt::Display for ReactionServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReactionServiceError::CvNotFound(id) => {
+                write!(f, "CV {:?} not found", id)
+            }
+            ReactionServiceError::CvNotVisible(id) => {
+                write!(f, "CV {:?} is not visible to this user", id)
+            }
+            ReactionServiceError::CommentNotFound(id) => {
+                write!(f, "Comment {:?} not found", id)
+            }
+            ReactionServiceError::CommentDeleted(id) => {
+                write!(f, "Comment {:?} was deleted", id)
+            }
+            ReactionServiceError::UserBlocked(id) => {
+                write!(f, "User {:?} is blocked", id)
+            }
+            ReactionServiceError::DatabaseError => {
+                write!(f, "Database error")
+            }
+        }
+    }
+}
+
+impl ErrorExtensions for ReactionServiceError {
+    fn extend(&self) -> async_graphql::Error {
+        let code = match self {
+            // the same error as when opening the CV
+            ReactionServiceError::CvNotVisible(id) => {
+                return CVServiceError::CVNotVisible(*id).into();
+            }
+            ReactionServiceError::CvNotFound(_) => "CV_NOT_FOUND",
+            ReactionServiceError::CommentNotFound(_) => "COMMENT_NOT_FOUND",
+            ReactionServiceError::CommentDeleted(_) => "COMMENT_DELETED",
+            ReactionServiceError::UserBlocked(_) => "USER_BLOCKED",
+            ReactionServiceError::DatabaseError => "DATABASE_ERROR",
+        };
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| e.set("code", code))
+    }
+}
+
+impl From<ReactionServiceError> for CVServiceError {
+    fn from(value: ReactionServiceError) -> Self {
+        match value {
+            ReactionServiceError::CvNotFound(_) => {
+                CVServiceError::LikeFailed("CV not found".to_string())
+            }
+            ReactionServiceError::CvNotVisible(id) => CVServiceError::CVNotVisible(id),
+            ReactionServiceError::UserBlocked(id) => CVServiceError::UserBlocked(id),
+            ReactionServiceError::DatabaseError => CVServiceError::DatabaseError,
+            _ => CVServiceError::UpdateLikeFailed,
+        }
+    }
+}
+
+impl From<ReactionServiceError> for CommentServiceError {
+    fn from(value: ReactionServiceError) -> Self {
+        match value {
+            ReactionServiceError::CommentNotFound(id) => CommentServiceError::IdNotFound(id),
+            ReactionServiceError::CommentDeleted(id) => CommentServiceError::CommentDeleted(id),
+            ReactionServiceError::UserBlocked(id) => CommentServiceError::UserBlocked(id),
+            ReactionServiceError::DatabaseError => CommentServiceError::DatabaseError,
+            _ => CommentServiceError::UpdateCommentFailed,
+        }
+    }
+}
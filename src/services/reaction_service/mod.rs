@@ -0,0 +1,195 @@
+pub mod error;
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    data_source::{
+        BlockDataSource, CVDataSource, CVDataSourceError, CommentDataSource,
+        FriendsListDataSource, ReactionDataSource,
+    },
+    models::{
+        cv::CV,
+        reaction::{Reaction, ReactionCount, ReactionKind, ReactionTarget},
+    },
+};
+
+use self::error::ReactionServiceError;
+
+use super::{
+    cv_service::{
+        comment_service::{CommentService, CommentServiceError},
+        cv_service::CVService,
+    },
+    user_service::block_service::BlockService,
+};
+
+pub struct ReactionService;
+
+impl ReactionService {
+    /// React to a CV or a comment. A user has one reaction per target, so
+    /// reacting again replaces the previous reaction. CVs the user cannot see
+    /// cannot be reacted to.
+    pub async fn react(
+        db: &(impl CVDataSource
+              + CommentDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + ReactionDataSource
+              + std::marker::Sync),
+        user_id: ObjectId,
+        target_type: ReactionTarget,
+        target_id: ObjectId,
+        kind: ReactionKind,
+    ) -> Result<Reaction, ReactionServiceError> {
+        let (author_id, cv) = Self::target_author(db, target_type, target_id).await?;
+        let blocked = BlockService::is_blocked(db, user_id, author_id)
+            .await
+            .map_err(|_| ReactionServiceError::DatabaseError)?;
+        if blocked {
+            return Err(ReactionServiceError::UserBlocked(author_id));
+        }
+        if let Some(cv) = cv {
+            let visible = CVService::can_view_cv(db, Some(user_id), &cv)
+                .await
+                .map_err(|_| ReactionServiceError::DatabaseError)?;
+            if !visible {
+                return Err(ReactionServiceError::CvNotVisible(target_id));
+            }
+        }
+        let current = db
+            .get_reaction(user_id, target_id)
+            .await
+            .map_err(|err| err.into())?;
+        if let Some(reaction) = current.filter(|reaction| reaction.kind == kind) {
+            return Ok(reaction);
+        }
+        let reaction = Reaction::new(user_id, target_type, target_id, kind);
+        db.set_reaction(reaction.clone())
+            .await
+            .map_err(|err| err.into())?;
+        Ok(reaction)
+    }
+
+    /// Remove the reaction of the user on the target. When `kind` is given the
+    /// reaction is only removed if it is of that kind. Return the removed
+    /// reaction, if any.
+    pub async fn unreact(
+        db: &(impl ReactionDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        target_id: ObjectId,
+        kind: Option<ReactionKind>,
+    ) -> Result<Option<Reaction>, ReactionServiceError> {
+        if let Some(kind) = kind {
+            let current = db
+                .get_reaction(user_id, target_id)
+                .await
+                .map_err(|err| err.into())?;
+            if current.filter(|reaction| reaction.kind == kind).is_none() {
+                return Ok(None);
+            }
+        }
+        db.delete_reaction(user_id, target_id)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    pub async fn get_reaction(
+        db: &(impl ReactionDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        target_id: ObjectId,
+    ) -> Result<Option<Reaction>, ReactionServiceError> {
+        db.get_reaction(user_id, target_id)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Return the reactions on the target, newest first, only those of `kind`
+    /// if given.
+    pub async fn reactions(
+        db: &(impl ReactionDataSource + std::marker::Sync),
+        target_id: ObjectId,
+        kind: Option<ReactionKind>,
+    ) -> Result<Vec<Reaction>, ReactionServiceError> {
+        db.get_reactions(target_id, kind)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Count the reactions on the target for every kind, including the kinds
+    /// nobody reacted with.
+    pub async fn reaction_counts(
+        db: &(impl ReactionDataSource + std::marker::Sync),
+        target_id: ObjectId,
+    ) -> Result<Vec<ReactionCount>, ReactionServiceError> {
+        let counts = db
+            .count_reactions(target_id)
+            .await
+            .map_err(|err| err.into())?;
+        Ok(ReactionKind::ALL
+            .into_iter()
+            .map(|kind| ReactionCount {
+                kind,
+                count: counts
+                    .iter()
+                    .find(|count| count.kind == kind)
+                    .map_or(0, |count| count.count),
+            })
+            .collect())
+    }
+
+    pub async fn reaction_count(
+        db: &(impl ReactionDataSource + std::marker::Sync),
+        target_id: ObjectId,
+        kind: ReactionKind,
+    ) -> Result<u64, ReactionServiceError> {
+        let counts = db
+            .count_reactions(target_id)
+            .await
+            .map_err(|err| err.into())?;
+        Ok(counts
+            .iter()
+            .find(|count| count.kind == kind)
+            .map_or(0, |count| count.count))
+    }
+
+    /// Count the reactions of `kind` on each of the targets, with one query
+    /// where the data source allows it. Targets without reactions are left out.
+    pub async fn reaction_count_of_targets(
+        db: &(impl ReactionDataSource + std::marker::Sync),
+        target_ids: Vec<ObjectId>,
+        kind: ReactionKind,
+    ) -> Result<HashMap<ObjectId, u64>, ReactionServiceError> {
+        db.count_reactions_of_targets(target_ids, kind)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Return the author of the target, checking it can be reacted to, and
+    /// the target itself when it is a CV.
+    async fn target_author(
+        db: &(impl CVDataSource + CommentDataSource + std::marker::Sync),
+        target_type: ReactionTarget,
+        target_id: ObjectId,
+    ) -> Result<(ObjectId, Option<CV>), ReactionServiceError> {
+        match target_type {
+            ReactionTarget::Cv => match db.get_cv_by_id(target_id).await {
+                Ok(cv) => Ok((cv.author_id.into(), Some(cv))),
+                Err(CVDataSourceError::DatabaseError) => Err(ReactionServiceError::DatabaseError),
+                Err(_) => Err(ReactionServiceError::CvNotFound(target_id)),
+            },
+            ReactionTarget::Comment => match CommentService::get_comment_by_id(db, target_id).await
+            {
+                Ok(comment) if comment.deleted_at.is_some() => {
+                    Err(ReactionServiceError::CommentDeleted(target_id))
+                }
+                Ok(comment) => Ok((comment.author.into(), None)),
+                Err(CommentServiceError::DatabaseError) => Err(ReactionServiceError::DatabaseError),
+                Err(_) => Err(ReactionServiceError::CommentNotFound(target_id)),
+            },
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use mongodb::bson::oid::ObjectId;
+
+use super::{error::ReactionServiceError, ReactionService};
+use crate::{
+    models::{
+        comment::CreateCommentInput,
+        cv::Visibility,
+        reaction::{ReactionKind, ReactionTarget},
+    },
+    services::{
+        cv_service::{
+            comment_service::CommentService, cv_service::CVService, error::CVServiceError,
+            like_service::LikeService,
+        },
+        tests::{create_user, MockDatabase},
+        user_service::block_service::BlockService,
+    },
+};
+
+async fn create_cv(db: &MockDatabase, author: ObjectId) -> ObjectId {
+    *CVService::create_cv(db, author, "CV".to_string(), "".to_string())
+        .await
+        .unwrap()
+        .id
+}
+
+async fn counts(db: &MockDatabase, target_id: ObjectId) -> Vec<(ReactionKind, u64)> {
+    ReactionService::reaction_counts(db, target_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|count| count.count > 0)
+        .map(|count| (count.kind, count.count))
+        .collect()
+}
+
+#[tokio::test]
+async fn one_reaction_per_user_and_target() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let alice = create_user(&db, "alice").await;
+    let bob = create_user(&db, "bob").await;
+    let cv_id = create_cv(&db, author).await;
+
+    ReactionService::react(&db, alice, ReactionTarget::Cv, cv_id, ReactionKind::Love)
+        .await
+        .unwrap();
+    ReactionService::react(&db, bob, ReactionTarget::Cv, cv_id, ReactionKind::Love)
+        .await
+        .unwrap();
+    assert_eq!(vec![(ReactionKind::Love, 2)], counts(&db, cv_id).await);
+    // every kind is counted, even without reactions
+    assert_eq!(
+        ReactionKind::ALL.len(),
+        ReactionService::reaction_counts(&db, cv_id)
+            .await
+            .unwrap()
+            .len()
+    );
+
+    // reacting again replaces the previous reaction
+    let reaction =
+        ReactionService::react(&db, alice, ReactionTarget::Cv, cv_id, ReactionKind::Haha)
+            .await
+            .unwrap();
+    assert_eq!(ReactionKind::Haha, reaction.kind);
+    assert_eq!(
+        vec![(ReactionKind::Love, 1), (ReactionKind::Haha, 1)],
+        counts(&db, cv_id).await
+    );
+    let reactors = ReactionService::reactions(&db, cv_id, None).await.unwrap();
+    assert_eq!(
+        vec![alice, bob],
+        reactors.iter().map(|r| r.user_id()).collect::<Vec<_>>()
+    );
+    let loved = ReactionService::reactions(&db, cv_id, Some(ReactionKind::Love))
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![bob],
+        loved.iter().map(|r| r.user_id()).collect::<Vec<_>>()
+    );
+
+    // removing only a given kind leaves other reactions alone
+    assert_eq!(
+        Ok(None),
+        ReactionService::unreact(&db, bob, cv_id, Some(ReactionKind::Like)).await
+    );
+    let removed = ReactionService::unreact(&db, bob, cv_id, None)
+        .await
+        .unwrap();
+    assert_eq!(Some(ReactionKind::Love), removed.map(|r| r.kind));
+    assert_eq!(
+        Ok(None),
+        ReactionService::unreact(&db, bob, cv_id, None).await
+    );
+    assert_eq!(vec![(ReactionKind::Haha, 1)], counts(&db, cv_id).await);
+}
+
+#[tokio::test]
+async fn likes_are_like_reactions() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let alice = create_user(&db, "alice").await;
+    let cv_id = create_cv(&db, author).await;
+
+    LikeService::like_cv(&db, alice, cv_id).await.unwrap();
+    let reaction = ReactionService::get_reaction(&db, alice, cv_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ReactionKind::Like, reaction.kind);
+    assert_eq!(Ok(1), LikeService::get_likes_count_of_cv(&db, cv_id).await);
+
+    // another reaction replaces the like, which can no longer be removed
+    ReactionService::react(&db, alice, ReactionTarget::Cv, cv_id, ReactionKind::Sad)
+        .await
+        .unwrap();
+    assert_eq!(Ok(0), LikeService::get_likes_count_of_cv(&db, cv_id).await);
+    assert_eq!(
+        Err(CVServiceError::LikeNotFound),
+        LikeService::unlike_cv(&db, alice, cv_id).await
+    );
+    assert_eq!(vec![(ReactionKind::Sad, 1)], counts(&db, cv_id).await);
+
+    let input = CreateCommentInput {
+        author: author.into(),
+        content: "comment".to_string(),
+    };
+    let comment = CommentService::create_comment(&db, input).await.unwrap();
+    CommentService::add_like_comment(&db, alice, *comment.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![(ReactionKind::Like, 1)],
+        counts(&db, *comment.id).await
+    );
+    CommentService::remove_like_comment(&db, alice, *comment.id)
+        .await
+        .unwrap();
+    assert!(counts(&db, *comment.id).await.is_empty());
+}
+
+#[tokio::test]
+async fn reactions_check_target_and_blocks() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let alice = create_user(&db, "alice").await;
+    let cv_id = create_cv(&db, author).await;
+    let input = CreateCommentInput {
+        author: author.into(),
+        content: "comment".to_string(),
+    };
+    let comment = CommentService::create_comment(&db, input).await.unwrap();
+
+    let missing = ObjectId::new();
+    assert_eq!(
+        Err(ReactionServiceError::CvNotFound(missing)),
+        ReactionService::react(&db, alice, ReactionTarget::Cv, missing, ReactionKind::Wow).await
+    );
+    // a comment id is not a CV id
+    assert_eq!(
+        Err(ReactionServiceError::CvNotFound(*comment.id)),
+        ReactionService::react(
+            &db,
+            alice,
+            ReactionTarget::Cv,
+            *comment.id,
+            ReactionKind::Wow
+        )
+        .await
+    );
+    assert_eq!(
+        Err(ReactionServiceError::CommentNotFound(missing)),
+        ReactionService::react(
+            &db,
+            alice,
+            ReactionTarget::Comment,
+            missing,
+            ReactionKind::Wow
+        )
+        .await
+    );
+
+    BlockService::block_user(&db, author, alice).await.unwrap();
+    assert_eq!(
+        Err(ReactionServiceError::UserBlocked(author)),
+        ReactionService::react(&db, alice, ReactionTarget::Cv, cv_id, ReactionKind::Wow).await
+    );
+    BlockService::unblock_user(&db, author, alice)
+        .await
+        .unwrap();
+
+    // CVs the user cannot see are as good as missing
+    CVService::change_visibility(&db, author, cv_id, Visibility::Private)
+        .await
+        .unwrap();
+    assert_eq!(
+        Err(ReactionServiceError::CvNotVisible(cv_id)),
+        ReactionService::react(&db, alice, ReactionTarget::Cv, cv_id, ReactionKind::Wow).await
+    );
+
+    CommentService::delete_comment(&db, *comment.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        Err(ReactionServiceError::CommentDeleted(*comment.id)),
+        ReactionService::react(
+            &db,
+            alice,
+            ReactionTarget::Comment,
+            *comment.id,
+            ReactionKind::Wow
+        )
+        .await
+    );
+    assert!(ReactionService::reactions(&db, cv_id, None)
+        .await
+        .unwrap()
+        .is_empty());
+}
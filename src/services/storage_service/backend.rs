@@ -0,0 +1,73 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+
+/// Shared handle to the configured storage backend, this is what gets put in
+/// the GraphQL context.
+pub type Storage = Arc<dyn StorageBackend>;
+
+/// Metadata of a stored object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectMetadata {
+    pub content_type: String,
+    /// Size of the object in bytes.
+    pub size: u64,
+}
+
+/// A stored object along with its content type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredObject {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageError {
+    /// No object is stored under the key.
+    NotFound(String),
+
+    /// The key cannot be used as an object name.
+    InvalidKey(String),
+
+    /// The backend itself failed, e.g. network or filesystem errors.
+    Backend(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::NotFound(key) => write!(f, "Object {} is not found", key),
+            StorageError::InvalidKey(key) => write!(f, "Object key {:?} is invalid", key),
+            StorageError::Backend(s) => write!(f, "Storage backend error: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+/// Primary abstraction for file storage. Objects are addressed by keys of the
+/// form `prefix/name`, where the prefix usually is the id of the owner.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Store the data under the key, overwriting any existing object.
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<(), StorageError>;
+
+    async fn get(&self, key: &str) -> Result<StoredObject, StorageError>;
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+
+    /// Return the metadata of the object without fetching its content.
+    async fn head(&self, key: &str) -> Result<ObjectMetadata, StorageError>;
+
+    /// Return a url anyone can use to download the object until `expires` has passed.
+    async fn signed_url(&self, key: &str, expires: Duration) -> Result<String, StorageError>;
+
+    /// Check that the backend is reachable, by looking up an object that
+    /// does not exist.
+    async fn ping(&self) -> Result<(), StorageError> {
+        match self.head("healthz/ping").await {
+            Ok(_) | Err(StorageError::NotFound(_)) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use rand::{distributions::Alphanumeric, Rng};
+
+use super::{
+    backend::Storage,
+    local_storage::LocalStorage,
+    memory_storage::MemoryStorage,
+    storage_service::{StorageServer, BUCKET_NAME},
+};
+
+/// Which implementation of `StorageBackend` to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageKind {
+    Gcs,
+    Local,
+    Memory,
+}
+
+/// Storage configuration, read from the environment:
+///
+/// - `STORAGE_BACKEND`: `gcs` (default), `local` or `memory`.
+/// - `GCS_BUCKET`: bucket used by the `gcs` backend.
+/// - `STORAGE_LOCAL_ROOT`: directory used by the `local` backend.
+/// - `STORAGE_PUBLIC_URL`: base url of this server, used in urls of the `local` backend.
+/// - `STORAGE_SIGNING_SECRET`: secret used to sign urls of the `local` backend.
+#[derive(Debug, Clone)]
+pub struct StorageConfig {
+    pub kind: StorageKind,
+    pub gcs_bucket: String,
+    pub local_root: String,
+    pub public_url: String,
+    pub signing_secret: Option<String>,
+}
+
+impl StorageConfig {
+    pub fn from_env() -> Result<Self, String> {
+        let kind = match std::env::var("STORAGE_BACKEND")
+            .unwrap_or_else(|_| "gcs".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "gcs" => StorageKind::Gcs,
+            "local" => StorageKind::Local,
+            "memory" => StorageKind::Memory,
+            other => return Err(format!("Unknown storage backend {:?}", other)),
+        };
+        Ok(Self {
+            kind,
+            gcs_bucket: std::env::var("GCS_BUCKET").unwrap_or_else(|_| BUCKET_NAME.to_string()),
+            local_root: std::env::var("STORAGE_LOCAL_ROOT")
+                .unwrap_or_else(|_| "./storage".to_string()),
+            public_url: std::env::var("STORAGE_PUBLIC_URL")
+                .unwrap_or_else(|_| "http://127.0.0.1:8000".to_string()),
+            signing_secret: std::env::var("STORAGE_SIGNING_SECRET").ok(),
+        })
+    }
+
+    /// Create the configured backend. The local backend is also returned on its
+    /// own, since the `/files` route needs it to verify signed urls.
+    pub async fn init_storage(&self) -> Result<(Storage, Option<Arc<LocalStorage>>), String> {
+        match self.kind {
+            StorageKind::Gcs => {
+                let storage = StorageServer::new()
+                    .await
+                    .map_err(|err| err.to_string())?
+                    .with_bucket(self.gcs_bucket.clone());
+                Ok((Arc::new(storage), None))
+            }
+            StorageKind::Local => {
+                let secret = match &self.signing_secret {
+                    Some(secret) => secret.clone(),
+                    None => {
+                        log::warn!(
+                            "STORAGE_SIGNING_SECRET is not set, signed urls will not survive a restart"
+                        );
+                        rand::thread_rng()
+                            .sample_iter(&Alphanumeric)
+                            .take(32)
+                            .map(char::from)
+                            .collect()
+                    }
+                };
+                let storage = Arc::new(LocalStorage::new(
+                    self.local_root.clone(),
+                    self.public_url.clone(),
+                    secret,
+                ));
+                Ok((storage.clone(), Some(storage)))
+            }
+            StorageKind::Memory => Ok((Arc::new(MemoryStorage::new()), None)),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::backend::{ObjectMetadata, StorageBackend, StorageError, StoredObject};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Suffix of the file holding the content type of an object.
+const CONTENT_TYPE_SUFFIX: &str = ".content-type";
+
+/// Stores objects as files under a root directory. Files are served by the
+/// `/files` warp route, which only accepts urls signed by `signed_url`.
+pub struct LocalStorage {
+    root: PathBuf,
+    /// Base url of the server, signed urls are built as `{public_url}/files/{key}`.
+    public_url: String,
+    secret: Vec<u8>,
+}
+
+impl LocalStorage {
+    pub fn new(
+        root: impl Into<PathBuf>,
+        public_url: impl Into<String>,
+        secret: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self {
+            root: root.into(),
+            public_url: public_url.into().trim_end_matches('/').to_string(),
+            secret: secret.into(),
+        }
+    }
+
+    /// Return whether the signature was produced by `signed_url` for this key
+    /// and has not expired yet. `expires` is a unix timestamp in seconds.
+    pub fn verify_signature(&self, key: &str, expires: u64, signature: &str) -> bool {
+        if expires < unix_now() {
+            return false;
+        }
+        let signature = match hex::decode(signature) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+        self.mac(key, expires).verify_slice(&signature).is_ok()
+    }
+
+    fn mac(&self, key: &str, expires: u64) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any size");
+        mac.update(key.as_bytes());
+        mac.update(b"\n");
+        mac.update(expires.to_string().as_bytes());
+        mac
+    }
+
+    /// Map the key to a path under the root directory, rejecting keys that
+    /// could escape it.
+    fn path_of(&self, key: &str) -> Result<PathBuf, StorageError> {
+        let valid_segment = |segment: &str| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        };
+        if key.ends_with(CONTENT_TYPE_SUFFIX) || !key.split('/').all(valid_segment) {
+            return Err(StorageError::InvalidKey(key.to_string()));
+        }
+        Ok(self.root.join(key))
+    }
+
+    fn content_type_path(path: &Path) -> PathBuf {
+        let mut path = path.as_os_str().to_os_string();
+        path.push(CONTENT_TYPE_SUFFIX);
+        path.into()
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+fn map_io_error(key: &str, err: std::io::Error) -> StorageError {
+    match err.kind() {
+        ErrorKind::NotFound => StorageError::NotFound(key.to_string()),
+        _ => StorageError::Backend(err.to_string()),
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorage {
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        let path = self.path_of(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| map_io_error(key, err))?;
+        }
+        tokio::fs::write(&path, data)
+            .await
+            .map_err(|err| map_io_error(key, err))?;
+        tokio::fs::write(Self::content_type_path(&path), content_type)
+            .await
+            .map_err(|err| map_io_error(key, err))
+    }
+
+    async fn get(&self, key: &str) -> Result<StoredObject, StorageError> {
+        let path = self.path_of(key)?;
+        let data = tokio::fs::read(&path)
+            .await
+            .map_err(|err| map_io_error(key, err))?;
+        let content_type = tokio::fs::read_to_string(Self::content_type_path(&path))
+            .await
+            .map_err(|err| map_io_error(key, err))?;
+        Ok(StoredObject { content_type, data })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.path_of(key)?;
+        tokio::fs::remove_file(&path)
+            .await
+            .map_err(|err| map_io_error(key, err))?;
+        tokio::fs::remove_file(Self::content_type_path(&path))
+            .await
+            .map_err(|err| map_io_error(key, err))
+    }
+
+    async fn head(&self, key: &str) -> Result<ObjectMetadata, StorageError> {
+        let path = self.path_of(key)?;
+        let metadata = tokio::fs::metadata(&path)
+            .await
+            .map_err(|err| map_io_error(key, err))?;
+        let content_type = tokio::fs::read_to_string(Self::content_type_path(&path))
+            .await
+            .map_err(|err| map_io_error(key, err))?;
+        Ok(ObjectMetadata {
+            content_type,
+            size: metadata.len(),
+        })
+    }
+
+    async fn signed_url(&self, key: &str, expires: Duration) -> Result<String, StorageError> {
+        self.path_of(key)?;
+        let expires = unix_now() + expires.as_secs();
+        let signature = hex::encode(self.mac(key, expires).finalize().into_bytes());
+        Ok(format!(
+            "{}/files/{}?expires={}&signature={}",
+            self.public_url, key, expires, signature
+        ))
+    }
+}
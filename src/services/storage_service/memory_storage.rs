@@ -0,0 +1,61 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+
+use super::backend::{ObjectMetadata, StorageBackend, StorageError, StoredObject};
+
+/// Keeps objects in memory, meant for tests. Signed urls use the `memory://`
+/// scheme and cannot actually be downloaded.
+#[derive(Default)]
+pub struct MemoryStorage {
+    objects: Mutex<HashMap<String, StoredObject>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryStorage {
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        let object = StoredObject {
+            content_type: content_type.to_string(),
+            data,
+        };
+        self.objects.lock().unwrap().insert(key.to_string(), object);
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<StoredObject, StorageError> {
+        self.objects
+            .lock()
+            .unwrap()
+            .get(key)
+            .cloned()
+            .ok_or_else(|| StorageError::NotFound(key.to_string()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.objects
+            .lock()
+            .unwrap()
+            .remove(key)
+            .map(|_| ())
+            .ok_or_else(|| StorageError::NotFound(key.to_string()))
+    }
+
+    async fn head(&self, key: &str) -> Result<ObjectMetadata, StorageError> {
+        let object = self.get(key).await?;
+        Ok(ObjectMetadata {
+            content_type: object.content_type,
+            size: object.data.len() as u64,
+        })
+    }
+
+    async fn signed_url(&self, key: &str, expires: Duration) -> Result<String, StorageError> {
+        self.head(key).await?;
+        Ok(format!("memory://{}?expires_in={}", key, expires.as_secs()))
+    }
+}
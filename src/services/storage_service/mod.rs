@@ -1,4 +1,8 @@
+pub mod backend;
+pub mod config;
+pub mod local_storage;
+pub mod memory_storage;
 pub mod storage_service;
 
 #[cfg(test)]
-mod tests;
\ No newline at end of file
+mod tests;
@@ -5,21 +5,33 @@ use google_cloud_storage::http::Error as httpError;
 use google_cloud_storage::sign::SignedURLError;
 use google_cloud_storage::{
     client::{Client, ClientConfig},
-    http::objects::upload::{Media, UploadObjectRequest, UploadType},
+    http::objects::{
+        delete::DeleteObjectRequest,
+        download::Range,
+        get::GetObjectRequest,
+        upload::{Media, UploadObjectRequest, UploadType},
+    },
     sign::{SignedURLMethod, SignedURLOptions},
 };
+use std::time::Duration;
 use tokio::fs::File;
 
+use async_trait::async_trait;
 use mongodb::bson::Uuid;
 
+use super::backend::{ObjectMetadata, StorageBackend, StorageError, StoredObject};
+
 pub enum UploadFileType {
     Image,
     Text,
     Other,
 }
 
+pub const BUCKET_NAME: &str = "crispy-garbanzo";
+
 pub struct StorageServer {
     client: Client,
+    bucket: String,
 }
 
 impl StorageServer {
@@ -42,7 +54,16 @@ impl StorageServer {
         };
         let config = ClientConfig::default().with_credentials(cred).await?;
         let client = Client::new(config);
-        Ok(StorageServer { client })
+        Ok(StorageServer {
+            client,
+            bucket: BUCKET_NAME.to_string(),
+        })
+    }
+
+    /// Use the provided bucket instead of the default one.
+    pub fn with_bucket(mut self, bucket: impl Into<String>) -> Self {
+        self.bucket = bucket.into();
+        self
     }
 
     /// Return the signed url for download files. When calling, please use the following format:
@@ -70,12 +91,11 @@ impl StorageServer {
         user_id: Uuid,
         file_id: Uuid,
     ) -> Result<String, SignedURLError> {
-        let bucket = String::from("crispy-garbanzo");
         let object = String::from(user_id.to_string() + "/" + &file_id.to_string());
         let url = self
             .client
             .signed_url(
-                &bucket,
+                &self.bucket,
                 &object,
                 None,
                 None,
@@ -97,7 +117,6 @@ impl StorageServer {
         user_id: Uuid,
         upload_type: UploadFileType,
     ) -> Result<String, httpError> {
-        let bucket_name = String::from("crispy-garbanzo");
         let file_id = Uuid::new();
         let mut media = Media::new(user_id.to_string() + "/" + &file_id.to_string());
         match upload_type {
@@ -110,7 +129,7 @@ impl StorageServer {
             .client
             .upload_object(
                 &UploadObjectRequest {
-                    bucket: bucket_name.to_string(),
+                    bucket: self.bucket.clone(),
                     ..Default::default()
                 },
                 file,
@@ -126,3 +145,99 @@ impl StorageServer {
         }
     }
 }
+
+fn map_http_error(key: &str, err: httpError) -> StorageError {
+    match err {
+        httpError::Response(response) if response.code == 404 => {
+            StorageError::NotFound(key.to_string())
+        }
+        err => StorageError::Backend(err.to_string()),
+    }
+}
+
+#[async_trait]
+impl StorageBackend for StorageServer {
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        let mut media = Media::new(key.to_string());
+        media.content_type = content_type.to_string().into();
+        let upload_type = UploadType::Simple(media);
+        self.client
+            .upload_object(
+                &UploadObjectRequest {
+                    bucket: self.bucket.clone(),
+                    ..Default::default()
+                },
+                data,
+                &upload_type,
+            )
+            .await
+            .map(|_| ())
+            .map_err(|err| map_http_error(key, err))
+    }
+
+    async fn get(&self, key: &str) -> Result<StoredObject, StorageError> {
+        let metadata = self.head(key).await?;
+        let data = self
+            .client
+            .download_object(
+                &GetObjectRequest {
+                    bucket: self.bucket.clone(),
+                    object: key.to_string(),
+                    ..Default::default()
+                },
+                &Range::default(),
+            )
+            .await
+            .map_err(|err| map_http_error(key, err))?;
+        Ok(StoredObject {
+            content_type: metadata.content_type,
+            data,
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.client
+            .delete_object(&DeleteObjectRequest {
+                bucket: self.bucket.clone(),
+                object: key.to_string(),
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| map_http_error(key, err))
+    }
+
+    async fn head(&self, key: &str) -> Result<ObjectMetadata, StorageError> {
+        let object = self
+            .client
+            .get_object(&GetObjectRequest {
+                bucket: self.bucket.clone(),
+                object: key.to_string(),
+                ..Default::default()
+            })
+            .await
+            .map_err(|err| map_http_error(key, err))?;
+        Ok(ObjectMetadata {
+            content_type: object
+                .content_type
+                .unwrap_or_else(|| "application/octet-stream".to_string()),
+            size: object.size.max(0) as u64,
+        })
+    }
+
+    async fn signed_url(&self, key: &str, expires: Duration) -> Result<String, StorageError> {
+        self.client
+            .signed_url(
+                &self.bucket,
+                key,
+                None,
+                None,
+                SignedURLOptions {
+                    method: SignedURLMethod::GET,
+                    expires,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|err| StorageError::Backend(err.to_string()))
+    }
+}
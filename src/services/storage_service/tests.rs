@@ -1,3 +1,11 @@
+use std::time::Duration;
+
+use super::{
+    backend::{ObjectMetadata, StorageBackend, StorageError},
+    local_storage::LocalStorage,
+    memory_storage::MemoryStorage,
+};
+
 #[tokio::test]
 async fn test_url_for_get() {
     use super::storage_service::StorageServer;
@@ -36,3 +44,107 @@ async fn test_put_file() {
     println!("{:?}", result);
     assert!(result.is_ok());
 }
+
+async fn check_backend(storage: &dyn StorageBackend) {
+    let key = "owner/file.pdf";
+    assert_eq!(
+        Err(StorageError::NotFound(key.to_string())),
+        storage.get(key).await.map(|_| ())
+    );
+    storage
+        .put(key, "application/pdf", b"%PDF-1.7".to_vec())
+        .await
+        .unwrap();
+    assert_eq!(
+        Ok(ObjectMetadata {
+            content_type: "application/pdf".to_string(),
+            size: 8,
+        }),
+        storage.head(key).await
+    );
+    let object = storage.get(key).await.unwrap();
+    assert_eq!(b"%PDF-1.7".to_vec(), object.data);
+    storage
+        .put(key, "text/plain", b"text".to_vec())
+        .await
+        .unwrap();
+    assert_eq!(b"text".to_vec(), storage.get(key).await.unwrap().data);
+    assert!(storage
+        .signed_url(key, Duration::from_secs(60))
+        .await
+        .is_ok());
+    storage.delete(key).await.unwrap();
+    assert_eq!(
+        Err(StorageError::NotFound(key.to_string())),
+        storage.head(key).await
+    );
+}
+
+#[tokio::test]
+async fn memory_storage() {
+    check_backend(&MemoryStorage::new()).await;
+}
+
+#[tokio::test]
+async fn local_storage() {
+    let root = std::env::temp_dir().join(format!("seevi-storage-{}", mongodb::bson::Uuid::new()));
+    let storage = LocalStorage::new(&root, "http://localhost:8000/", "secret");
+    check_backend(&storage).await;
+
+    for key in [
+        "../escape",
+        "/absolute",
+        "owner//file",
+        "owner/./file",
+        "owner/file.content-type",
+        "owner/fi le",
+    ] {
+        assert_eq!(
+            Err(StorageError::InvalidKey(key.to_string())),
+            storage.put(key, "text/plain", Vec::new()).await
+        );
+    }
+    std::fs::remove_dir_all(root).ok();
+}
+
+#[tokio::test]
+async fn local_storage_signed_url() {
+    let root = std::env::temp_dir().join(format!("seevi-storage-{}", mongodb::bson::Uuid::new()));
+    let storage = LocalStorage::new(&root, "http://localhost:8000/", "secret");
+    let key = "owner/file.pdf";
+    storage
+        .put(key, "application/pdf", b"%PDF".to_vec())
+        .await
+        .unwrap();
+
+    let url = storage
+        .signed_url(key, Duration::from_secs(60))
+        .await
+        .unwrap();
+    let (path, query) = url.split_once('?').unwrap();
+    assert_eq!("http://localhost:8000/files/owner/file.pdf", path);
+    let params: std::collections::HashMap<_, _> = query
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .collect();
+    let expires: u64 = params["expires"].parse().unwrap();
+    let signature = params["signature"];
+
+    assert!(storage.verify_signature(key, expires, signature));
+    assert!(!storage.verify_signature("owner/other.pdf", expires, signature));
+    assert!(!storage.verify_signature(key, expires + 1, signature));
+    assert!(!storage.verify_signature(key, expires, "not hex"));
+    let other = LocalStorage::new(&root, "http://localhost:8000", "other secret");
+    assert!(!other.verify_signature(key, expires, signature));
+
+    let expired = storage.signed_url(key, Duration::ZERO).await.unwrap();
+    let expired_at: u64 = expired
+        .split("expires=")
+        .nth(1)
+        .and_then(|rest| rest.split('&').next())
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(!storage.verify_signature(key, expired_at - 1, signature));
+    std::fs::remove_dir_all(root).ok();
+}
@@ -1,23 +1,40 @@
+use crate::common::DateTime;
+use crate::data_source::BlockDataSource;
 use crate::data_source::BookmarkDataSource;
 use crate::data_source::CVDataSource;
 use crate::data_source::CVDataSourceError;
 use crate::data_source::CommentDataSource;
+use crate::data_source::ConversationDataSource;
 use crate::data_source::LikeDataSource;
+use crate::data_source::LoginAttemptDataSource;
+use crate::data_source::MentionNotifier;
+use crate::data_source::ReactionDataSource;
+use crate::data_source::ReportDataSource;
 use crate::data_source::UserDataSource;
 use crate::data_source::{FriendsListDataSource, FriendsListError};
 use crate::models::comment::Bookmark as CommentBookmark;
 use crate::models::comment::Comment;
+use crate::models::comment::CommentVersion;
+use crate::models::comment::Mention;
 use crate::models::comment::CreateCommentInput;
 use crate::models::comment::Like;
 use crate::models::comment::UpdateCommentInput;
+use crate::models::conversation::{Conversation, Message};
 use crate::models::cv::interactions::Like as CVLike;
 use crate::models::cv::interactions::Share;
 use crate::models::cv::Bookmark as CVBookmark;
 use crate::models::cv::CreateCVInput;
+use crate::models::cv::ShareLink;
+use crate::models::cv::CVRevision;
 use crate::models::cv::UpdateCVInput;
 use crate::models::cv::CV;
 use crate::models::friend_request::{FriendRequest, FriendRequestStatus};
-use crate::models::users::{CreateUserInput, UpdateUserInput, User};
+use crate::models::reaction::{Reaction, ReactionKind};
+use crate::models::report::{ModerationDecision, Report, ReportStatus};
+use crate::models::sex::Sex;
+use crate::models::users::{
+    Block, CreateUserInput, LoginAttempts, Role, UpdateUserInput, User,
+};
 use async_graphql::futures_util::stream::BoxStream;
 use async_graphql::futures_util::{self, StreamExt};
 use async_trait::async_trait;
@@ -27,9 +44,13 @@ use std::fmt::Display;
 use std::fmt::Formatter;
 use std::sync::Mutex;
 
+use super::conversation_service::error::ConversationServiceError;
 use super::cv_service::comment_service::CommentServiceError;
 use super::cv_service::error::CVServiceError;
+use super::moderation_service::error::ModerationServiceError;
+use super::reaction_service::error::ReactionServiceError;
 use super::user_service::error::UserServiceError;
+use super::user_service::UserService;
 
 pub struct MockDatabase {
     pub(crate) users: Mutex<Vec<User>>,
@@ -38,9 +59,20 @@ pub struct MockDatabase {
     pub(crate) comments: Mutex<Vec<Comment>>,
     pub(crate) likes: Mutex<Vec<Like>>,
     pub(crate) cv_shares: Mutex<Vec<Share>>,
+    pub(crate) cv_share_links: Mutex<Vec<ShareLink>>,
+    pub(crate) cv_revisions: Mutex<Vec<CVRevision>>,
     pub(crate) cv_bookmarks: Mutex<Vec<CVBookmark>>,
     pub(crate) cv_likes: Mutex<Vec<CVLike>>,
     pub(crate) bookmarks: Mutex<Vec<CommentBookmark>>,
+    pub(crate) blocks: Mutex<Vec<Block>>,
+    pub(crate) login_attempts: Mutex<Vec<LoginAttempts>>,
+    pub(crate) conversations: Mutex<Vec<Conversation>>,
+    pub(crate) messages: Mutex<Vec<Message>>,
+    pub(crate) reactions: Mutex<Vec<Reaction>>,
+    pub(crate) reports: Mutex<Vec<Report>>,
+    pub(crate) moderation_decisions: Mutex<Vec<ModerationDecision>>,
+    /// Mention notifications sent, as (mentioned user, comment) pairs.
+    pub(crate) mention_notifications: Mutex<Vec<(ObjectId, ObjectId)>>,
 }
 
 impl MockDatabase {
@@ -52,13 +84,45 @@ impl MockDatabase {
             comments: Mutex::new(Vec::new()),
             likes: Mutex::new(Vec::new()),
             cv_shares: Mutex::new(Vec::new()),
+            cv_share_links: Mutex::new(Vec::new()),
+            cv_revisions: Mutex::new(Vec::new()),
             cv_bookmarks: Mutex::new(Vec::new()),
             cv_likes: Mutex::new(Vec::new()),
             bookmarks: Mutex::new(Vec::new()),
+            blocks: Mutex::new(Vec::new()),
+            login_attempts: Mutex::new(Vec::new()),
+            conversations: Mutex::new(Vec::new()),
+            messages: Mutex::new(Vec::new()),
+            reactions: Mutex::new(Vec::new()),
+            reports: Mutex::new(Vec::new()),
+            moderation_decisions: Mutex::new(Vec::new()),
+            mention_notifications: Mutex::new(Vec::new()),
         }
     }
 }
 
+/// Register a user with the username, return their id.
+pub async fn create_user(db: &MockDatabase, username: &str) -> ObjectId {
+    let input = CreateUserInput::builder()
+        .with_username(username)
+        .with_password("password")
+        .with_primary_email(format!("{}@mail.com", username))
+        .with_sex(Sex::Male)
+        .build()
+        .unwrap();
+    *UserService::create_user(db, input).await.unwrap().id
+}
+
+/// Make the two users friends.
+pub async fn make_friends(db: &MockDatabase, user: ObjectId, friend: ObjectId) {
+    UserService::send_friend_request(db, user, friend, None::<String>)
+        .await
+        .unwrap();
+    UserService::accept_friend_request(db, friend, user)
+        .await
+        .unwrap();
+}
+
 #[async_trait]
 impl FriendsListDataSource for MockDatabase {
     /// Add new friend request to the database.
@@ -87,6 +151,20 @@ impl FriendsListDataSource for MockDatabase {
         Err(FriendsListError::UserNotFound)
     }
 
+    async fn delete_friend_request(
+        &self,
+        from: bson::oid::ObjectId,
+        to: bson::oid::ObjectId,
+    ) -> Result<(), FriendsListError> {
+        let mut friend_requests = self.friend_requests.lock().unwrap();
+        let len = friend_requests.len();
+        friend_requests.retain(|request| !(request.id.from == from && request.id.to == to));
+        if friend_requests.len() == len {
+            return Err(FriendsListError::FriendRequestNotFound);
+        }
+        Ok(())
+    }
+
     /// Return the list of friend requests of the user.
     async fn friend_requests(
         &self,
@@ -133,6 +211,26 @@ impl FriendsListDataSource for MockDatabase {
         stream.map(|friend_request| Ok(friend_request)).boxed()
     }
 
+    async fn accepted_friend_requests_of_users<'a>(
+        &'a self,
+        user_ids: Vec<bson::oid::ObjectId>,
+    ) -> BoxStream<'a, Result<FriendRequest, FriendsListError>> {
+        let friend_requests = self
+            .friend_requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|friend_request| {
+                friend_request.status == FriendRequestStatus::Accepted
+                    && (user_ids.contains(&friend_request.id.from)
+                        || user_ids.contains(&friend_request.id.to))
+            })
+            .cloned()
+            .map(Ok)
+            .collect::<Vec<_>>();
+        futures_util::stream::iter(friend_requests).boxed()
+    }
+
     async fn get_friend_request(
         &self,
         from: bson::oid::ObjectId,
@@ -148,6 +246,452 @@ impl FriendsListDataSource for MockDatabase {
     }
 }
 
+#[derive(Debug)]
+pub struct MockBlockError(ObjectId);
+
+impl Display for MockBlockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "block of {} not found", self.0)
+    }
+}
+
+impl std::error::Error for MockBlockError {}
+
+impl From<MockBlockError> for UserServiceError {
+    fn from(value: MockBlockError) -> Self {
+        UserServiceError::BlockNotFound(value.0)
+    }
+}
+
+#[async_trait]
+impl LoginAttemptDataSource for MockDatabase {
+    type Error = MockUserDataSourceError;
+
+    async fn get_login_attempts(&self, key: &str) -> Result<Option<LoginAttempts>, Self::Error> {
+        let attempts = self.login_attempts.lock().unwrap();
+        Ok(attempts.iter().find(|attempts| attempts.key == key).cloned())
+    }
+
+    async fn set_login_attempts(&self, attempts: LoginAttempts) -> Result<(), Self::Error> {
+        let mut stored = self.login_attempts.lock().unwrap();
+        stored.retain(|stored| stored.key != attempts.key);
+        stored.push(attempts);
+        Ok(())
+    }
+
+    async fn add_login_failure(
+        &self,
+        key: &str,
+        now: DateTime,
+        forget_before: DateTime,
+    ) -> Result<LoginAttempts, Self::Error> {
+        let mut stored = self.login_attempts.lock().unwrap();
+        stored.retain(|stored| {
+            stored.key != key
+                || stored.last_failure > forget_before
+                || stored.locked_until.is_some_and(|until| until > forget_before)
+        });
+        let index = match stored.iter().position(|stored| stored.key == key) {
+            Some(index) => index,
+            None => {
+                stored.push(LoginAttempts::new(key.to_string()));
+                stored.len() - 1
+            }
+        };
+        let attempts = &mut stored[index];
+        attempts.failures += 1;
+        attempts.last_failure = now;
+        Ok(attempts.clone())
+    }
+
+    async fn lock_login_attempts(&self, key: &str, until: DateTime) -> Result<(), Self::Error> {
+        let mut stored = self.login_attempts.lock().unwrap();
+        if let Some(attempts) = stored.iter_mut().find(|stored| stored.key == key) {
+            attempts.locked_until = attempts.locked_until.max(Some(until));
+        }
+        Ok(())
+    }
+
+    async fn delete_login_attempts(&self, key: &str) -> Result<(), Self::Error> {
+        let mut stored = self.login_attempts.lock().unwrap();
+        stored.retain(|stored| stored.key != key);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct MockConversationError;
+
+impl Display for MockConversationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "conversation error")
+    }
+}
+
+impl std::error::Error for MockConversationError {}
+
+impl From<MockConversationError> for ConversationServiceError {
+    fn from(_: MockConversationError) -> Self {
+        ConversationServiceError::DatabaseError
+    }
+}
+
+#[async_trait]
+impl ConversationDataSource for MockDatabase {
+    type Error = MockConversationError;
+
+    async fn add_conversation(
+        &self,
+        conversation: Conversation,
+    ) -> Result<Conversation, Self::Error> {
+        let mut conversations = self.conversations.lock().unwrap();
+        if let Some(existing) = conversations
+            .iter()
+            .find(|existing| existing.participant_ids == conversation.participant_ids)
+        {
+            return Ok(existing.clone());
+        }
+        conversations.push(conversation.clone());
+        Ok(conversation)
+    }
+
+    async fn get_conversation(&self, id: ObjectId) -> Result<Option<Conversation>, Self::Error> {
+        let conversations = self.conversations.lock().unwrap();
+        Ok(conversations
+            .iter()
+            .find(|conversation| *conversation.id == id)
+            .cloned())
+    }
+
+    async fn find_conversation(
+        &self,
+        participant_ids: Vec<ObjectId>,
+    ) -> Result<Option<Conversation>, Self::Error> {
+        let conversations = self.conversations.lock().unwrap();
+        Ok(conversations
+            .iter()
+            .find(|conversation| conversation.participant_ids == participant_ids)
+            .cloned())
+    }
+
+    async fn get_conversations_by_user(
+        &self,
+        user_id: ObjectId,
+    ) -> Result<Vec<Conversation>, Self::Error> {
+        let conversations = self.conversations.lock().unwrap();
+        let mut conversations = conversations
+            .iter()
+            .filter(|conversation| conversation.has_participant(user_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        // the last active conversation first when several were updated at the
+        // same time
+        conversations.reverse();
+        conversations.sort_by_key(|conversation| std::cmp::Reverse(conversation.updated));
+        Ok(conversations)
+    }
+
+    async fn add_message(&self, message: Message) -> Result<(), Self::Error> {
+        let mut conversations = self.conversations.lock().unwrap();
+        if let Some(conversation) = conversations
+            .iter_mut()
+            .find(|conversation| conversation.id == message.conversation_id)
+        {
+            conversation.updated = message.created;
+        }
+        // keep the conversations in order of activity
+        if let Some(index) = conversations
+            .iter()
+            .position(|conversation| conversation.id == message.conversation_id)
+        {
+            let conversation = conversations.remove(index);
+            conversations.push(conversation);
+        }
+        self.messages.lock().unwrap().push(message);
+        Ok(())
+    }
+
+    async fn get_messages(&self, conversation_id: ObjectId) -> Result<Vec<Message>, Self::Error> {
+        let messages = self.messages.lock().unwrap();
+        Ok(messages
+            .iter()
+            .rev()
+            .filter(|message| *message.conversation_id == conversation_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_last_message(
+        &self,
+        conversation_id: ObjectId,
+    ) -> Result<Option<Message>, Self::Error> {
+        let messages = self.messages.lock().unwrap();
+        Ok(messages
+            .iter()
+            .rev()
+            .find(|message| *message.conversation_id == conversation_id)
+            .cloned())
+    }
+
+    async fn mark_messages_read(
+        &self,
+        conversation_id: ObjectId,
+        reader_id: ObjectId,
+        read_at: DateTime,
+    ) -> Result<u64, Self::Error> {
+        let mut messages = self.messages.lock().unwrap();
+        let mut count = 0;
+        for message in messages.iter_mut().filter(|message| {
+            *message.conversation_id == conversation_id
+                && message.sender_id != reader_id
+                && message.read_at.is_none()
+        }) {
+            message.read_at = Some(read_at);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    async fn count_unread_messages(
+        &self,
+        conversation_id: ObjectId,
+        reader_id: ObjectId,
+    ) -> Result<u64, Self::Error> {
+        let messages = self.messages.lock().unwrap();
+        Ok(messages
+            .iter()
+            .filter(|message| {
+                *message.conversation_id == conversation_id
+                    && message.sender_id != reader_id
+                    && message.read_at.is_none()
+            })
+            .count() as u64)
+    }
+}
+
+#[derive(Debug)]
+pub struct MockReactionError;
+
+impl Display for MockReactionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "reaction error")
+    }
+}
+
+impl std::error::Error for MockReactionError {}
+
+impl From<MockReactionError> for ReactionServiceError {
+    fn from(_: MockReactionError) -> Self {
+        ReactionServiceError::DatabaseError
+    }
+}
+
+#[async_trait]
+impl ReactionDataSource for MockDatabase {
+    type Error = MockReactionError;
+
+    async fn set_reaction(&self, reaction: Reaction) -> Result<(), Self::Error> {
+        let mut reactions = self.reactions.lock().unwrap();
+        reactions.retain(|other| other.key != reaction.key);
+        reactions.push(reaction);
+        Ok(())
+    }
+
+    async fn delete_reaction(
+        &self,
+        user_id: ObjectId,
+        target_id: ObjectId,
+    ) -> Result<Option<Reaction>, Self::Error> {
+        let mut reactions = self.reactions.lock().unwrap();
+        Ok(reactions
+            .iter()
+            .position(|reaction| {
+                reaction.user_id() == user_id && reaction.target_id() == target_id
+            })
+            .map(|index| reactions.remove(index)))
+    }
+
+    async fn get_reaction(
+        &self,
+        user_id: ObjectId,
+        target_id: ObjectId,
+    ) -> Result<Option<Reaction>, Self::Error> {
+        let reactions = self.reactions.lock().unwrap();
+        Ok(reactions
+            .iter()
+            .find(|reaction| reaction.user_id() == user_id && reaction.target_id() == target_id)
+            .cloned())
+    }
+
+    async fn get_reactions(
+        &self,
+        target_id: ObjectId,
+        kind: Option<ReactionKind>,
+    ) -> Result<Vec<Reaction>, Self::Error> {
+        let reactions = self.reactions.lock().unwrap();
+        Ok(reactions
+            .iter()
+            .rev()
+            .filter(|reaction| {
+                reaction.target_id() == target_id && kind.is_none_or(|kind| reaction.kind == kind)
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Debug)]
+pub struct MockReportError;
+
+impl Display for MockReportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "report error")
+    }
+}
+
+impl std::error::Error for MockReportError {}
+
+impl From<MockReportError> for ModerationServiceError {
+    fn from(_: MockReportError) -> Self {
+        ModerationServiceError::DatabaseError
+    }
+}
+
+#[async_trait]
+impl ReportDataSource for MockDatabase {
+    type Error = MockReportError;
+
+    async fn add_report(&self, report: Report) -> Result<(), Self::Error> {
+        self.reports.lock().unwrap().push(report);
+        Ok(())
+    }
+
+    async fn get_report(&self, id: ObjectId) -> Result<Option<Report>, Self::Error> {
+        let reports = self.reports.lock().unwrap();
+        Ok(reports.iter().find(|report| *report.id == id).cloned())
+    }
+
+    async fn get_reports(&self, status: Option<ReportStatus>) -> Result<Vec<Report>, Self::Error> {
+        let reports = self.reports.lock().unwrap();
+        Ok(reports
+            .iter()
+            .filter(|report| status.is_none_or(|status| report.status == status))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_open_reports(&self, target_id: ObjectId) -> Result<Vec<Report>, Self::Error> {
+        let reports = self.reports.lock().unwrap();
+        Ok(reports
+            .iter()
+            .filter(|report| {
+                *report.target_id == target_id && report.status == ReportStatus::Open
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn close_reports(
+        &self,
+        target_id: ObjectId,
+        status: ReportStatus,
+        resolved_at: DateTime,
+    ) -> Result<u64, Self::Error> {
+        let mut reports = self.reports.lock().unwrap();
+        let mut closed = 0;
+        for report in reports.iter_mut() {
+            if *report.target_id == target_id && report.status == ReportStatus::Open {
+                report.status = status;
+                report.resolved_at = Some(resolved_at);
+                closed += 1;
+            }
+        }
+        Ok(closed)
+    }
+
+    async fn add_decision(&self, decision: ModerationDecision) -> Result<(), Self::Error> {
+        self.moderation_decisions.lock().unwrap().push(decision);
+        Ok(())
+    }
+
+    async fn get_decisions(
+        &self,
+        target_id: Option<ObjectId>,
+    ) -> Result<Vec<ModerationDecision>, Self::Error> {
+        let decisions = self.moderation_decisions.lock().unwrap();
+        Ok(decisions
+            .iter()
+            .rev()
+            .filter(|decision| target_id.is_none_or(|id| *decision.target_id == id))
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl BlockDataSource for MockDatabase {
+    type Error = MockBlockError;
+
+    async fn add_block(
+        &self,
+        blocker_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<(), Self::Error> {
+        if self.get_block(blocker_id, blocked_id).await?.is_none() {
+            let block = Block::new(blocker_id, blocked_id);
+            self.blocks.lock().unwrap().push(block);
+        }
+        Ok(())
+    }
+
+    async fn delete_block(
+        &self,
+        blocker_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<(), Self::Error> {
+        let mut blocks = self.blocks.lock().unwrap();
+        let len = blocks.len();
+        blocks.retain(|block| {
+            !(block.key.blocker_id == blocker_id && block.key.blocked_id == blocked_id)
+        });
+        if blocks.len() == len {
+            return Err(MockBlockError(blocked_id));
+        }
+        Ok(())
+    }
+
+    async fn get_block(
+        &self,
+        blocker_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<Option<Block>, Self::Error> {
+        let blocks = self.blocks.lock().unwrap();
+        Ok(blocks
+            .iter()
+            .find(|block| block.key.blocker_id == blocker_id && block.key.blocked_id == blocked_id)
+            .cloned())
+    }
+
+    async fn get_blocks_by_user(&self, blocker_id: ObjectId) -> Result<Vec<Block>, Self::Error> {
+        let blocks = self.blocks.lock().unwrap();
+        Ok(blocks
+            .iter()
+            .rev()
+            .filter(|block| block.key.blocker_id == blocker_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_blocks_involving(&self, user_id: ObjectId) -> Result<Vec<Block>, Self::Error> {
+        let blocks = self.blocks.lock().unwrap();
+        Ok(blocks
+            .iter()
+            .filter(|block| block.key.blocker_id == user_id || block.key.blocked_id == user_id)
+            .cloned()
+            .collect())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MockUserDataSourceError;
 
@@ -239,12 +783,44 @@ impl UserDataSource for MockDatabase {
                     .educations
                     .clone()
                     .unwrap_or(user.educations.clone());
+                user.experiences = updated_user
+                    .experiences
+                    .clone()
+                    .unwrap_or(user.experiences.clone());
                 return Ok(user.clone());
             }
         }
         return Err(MockUserDataSourceError);
     }
 
+    async fn update_avatar(
+        &self,
+        user_id: ObjectId,
+        photo_id: bson::Uuid,
+    ) -> Result<User, Self::Error> {
+        let mut users = self.users.lock().unwrap();
+        let user = users
+            .iter_mut()
+            .find(|user| *user.id == user_id)
+            .ok_or(MockUserDataSourceError)?;
+        user.avatar = Some(photo_id);
+        Ok(user.clone())
+    }
+
+    async fn update_cover_photo(
+        &self,
+        user_id: ObjectId,
+        photo_id: bson::Uuid,
+    ) -> Result<User, Self::Error> {
+        let mut users = self.users.lock().unwrap();
+        let user = users
+            .iter_mut()
+            .find(|user| *user.id == user_id)
+            .ok_or(MockUserDataSourceError)?;
+        user.cover_photo = Some(photo_id);
+        Ok(user.clone())
+    }
+
     async fn create_user(&self, _input: CreateUserInput) -> Result<User, Self::Error> {
         let mut users = self.users.lock().unwrap();
         let user = User::from(_input);
@@ -265,6 +841,79 @@ impl UserDataSource for MockDatabase {
         });
         stream.map(|user| Ok(user)).boxed()
     }
+
+    async fn set_suspended_until(
+        &self,
+        user_id: ObjectId,
+        until: Option<DateTime>,
+    ) -> Result<User, Self::Error> {
+        let mut users = self.users.lock().unwrap();
+        let user = users
+            .iter_mut()
+            .find(|user| *user.id == user_id)
+            .ok_or(MockUserDataSourceError)?;
+        user.suspended_until = until;
+        Ok(user.clone())
+    }
+
+    async fn set_role(&self, user_id: ObjectId, role: Role) -> Result<User, Self::Error> {
+        let mut users = self.users.lock().unwrap();
+        let user = users
+            .iter_mut()
+            .find(|user| *user.id == user_id)
+            .ok_or(MockUserDataSourceError)?;
+        user.role = role;
+        Ok(user.clone())
+    }
+
+    async fn search_users(&self, search: Option<String>) -> Result<Vec<User>, Self::Error> {
+        let users = self.users.lock().unwrap();
+        let search = search.map(|search| search.to_lowercase());
+        let mut users = users
+            .iter()
+            .filter(|user| {
+                search.as_ref().is_none_or(|search| {
+                    [
+                        &user.username,
+                        &user.first_name,
+                        &user.last_name,
+                        &user.primary_email,
+                    ]
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(search))
+                })
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        Ok(users)
+    }
+
+    async fn get_users_with_shared_attributes(
+        &self,
+        user: &User,
+        limit: i64,
+    ) -> Result<Vec<User>, Self::Error> {
+        let users = self.users.lock().unwrap();
+        let shares = |other: &User| {
+            other.educations.iter().any(|e| {
+                user.educations
+                    .iter()
+                    .any(|u| u.school == e.school || u.major == e.major)
+            }) || other
+                .experiences
+                .iter()
+                .any(|e| user.experiences.iter().any(|u| u.company == e.company))
+                || other.skills.iter().any(|skill| user.skills.contains(skill))
+                || (user.city.is_some() && other.city == user.city)
+        };
+        Ok(users
+            .iter()
+            .filter(|other| other.id != user.id && shares(other))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
 }
 
 #[async_trait]
@@ -296,6 +945,14 @@ impl CVDataSource for MockDatabase {
         Err(CVDataSourceError::IdNotFound(_cv_id.clone()))
     }
 
+    async fn get_cv_by_comment_id(&self, comment_id: ObjectId) -> Result<CV, CVDataSourceError> {
+        let cvs = self.cvs.lock().unwrap();
+        cvs.iter()
+            .find(|cv| cv.comments.contains(&comment_id))
+            .cloned()
+            .ok_or(CVDataSourceError::IdNotFound(comment_id))
+    }
+
     async fn create_cv(&self, _input: CreateCVInput) -> Result<CV, CVDataSourceError> {
         let mut cvs = self.cvs.lock().unwrap();
         let cv = CV::from(_input);
@@ -322,8 +979,10 @@ impl CVDataSource for MockDatabase {
         for cv in cvs.iter_mut() {
             if cv.id == _cv_id.into() {
                 cv.title = _input.title.clone().unwrap_or(cv.title.clone());
-                cv.description = _input.description.clone();
+                cv.description = _input.description.clone().or(cv.description.clone());
                 cv.tags = _input.tags.clone().unwrap_or(cv.tags.clone());
+                cv.visibility = _input.visibility.unwrap_or(cv.visibility);
+                cv.cv = _input.cv.or(cv.cv);
                 return Ok(cv.clone());
             }
         }
@@ -337,15 +996,13 @@ impl CVDataSource for MockDatabase {
         _comment_id: Comment,
     ) -> Result<CV, CVDataSourceError> {
         let mut cvs = self.cvs.lock().unwrap();
-        cvs.iter_mut()
+        let cv = cvs
+            .iter_mut()
             .find(|cv| cv.id == _cv_id.into())
-            .map_or_else(
-                || Err(CVDataSourceError::IdNotFound(_cv_id.clone())),
-                |cv| {
-                    cv.comments.push(_comment_id.clone().id.into());
-                    Ok(cv.clone())
-                },
-            )
+            .ok_or(CVDataSourceError::IdNotFound(_cv_id))?;
+        cv.comments.push(_comment_id.id.into());
+        self.comments.lock().unwrap().push(_comment_id);
+        Ok(cv.clone())
     }
 
     /// Remove comment from the CV with the provided id.
@@ -373,6 +1030,30 @@ impl CVDataSource for MockDatabase {
                 },
             )
     }
+
+    async fn delete_cv(&self, id: ObjectId) -> Result<(), CVDataSourceError> {
+        let mut cvs = self.cvs.lock().unwrap();
+        let len = cvs.len();
+        cvs.retain(|cv| cv.id != id.into());
+        if cvs.len() == len {
+            return Err(CVDataSourceError::IdNotFound(id));
+        }
+        Ok(())
+    }
+
+    async fn set_cv_hidden(
+        &self,
+        cv_id: ObjectId,
+        hidden_at: Option<DateTime>,
+    ) -> Result<CV, CVDataSourceError> {
+        let mut cvs = self.cvs.lock().unwrap();
+        let cv = cvs
+            .iter_mut()
+            .find(|cv| cv.id == cv_id.into())
+            .ok_or(CVDataSourceError::IdNotFound(cv_id))?;
+        cv.hidden_at = hidden_at;
+        Ok(cv.clone())
+    }
 }
 
 #[derive(Debug)]
@@ -458,6 +1139,20 @@ impl CommentDataSource for MockDatabase {
         Err(DummyCommentDataSourceError)
     }
 
+    async fn get_thread(
+        &self,
+        root_id: bson::oid::ObjectId,
+        limit: usize,
+    ) -> Result<Vec<Comment>, Self::Error> {
+        let comments = self.comments.lock().unwrap();
+        Ok(comments
+            .iter()
+            .filter(|comment| comment.thread_id() == root_id.into())
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
     async fn find_and_remove_reply(
         &self,
         _comment_id: bson::oid::ObjectId,
@@ -473,6 +1168,57 @@ impl CommentDataSource for MockDatabase {
         Err(DummyCommentDataSourceError)
     }
 
+    async fn edit_comment(
+        &self,
+        id: bson::oid::ObjectId,
+        content: String,
+        mentions: Vec<Mention>,
+        edited_at: DateTime,
+        previous: CommentVersion,
+    ) -> Result<Comment, Self::Error> {
+        let mut comments = self.comments.lock().unwrap();
+        let comment = comments
+            .iter_mut()
+            .find(|comment| comment.id == id.into() && comment.deleted_at.is_none())
+            .ok_or(DummyCommentDataSourceError)?;
+        comment.content = content;
+        comment.mentions = mentions;
+        comment.edited_at = Some(edited_at);
+        comment.history.push(previous);
+        Ok(comment.clone())
+    }
+
+    async fn delete_comment(
+        &self,
+        id: bson::oid::ObjectId,
+        deleted_at: DateTime,
+    ) -> Result<Comment, Self::Error> {
+        let mut comments = self.comments.lock().unwrap();
+        let comment = comments
+            .iter_mut()
+            .find(|comment| comment.id == id.into())
+            .ok_or(DummyCommentDataSourceError)?;
+        comment.content = String::new();
+        comment.mentions.clear();
+        comment.history.clear();
+        comment.deleted_at = Some(deleted_at);
+        Ok(comment.clone())
+    }
+
+    async fn set_comment_hidden(
+        &self,
+        id: bson::oid::ObjectId,
+        hidden_at: Option<DateTime>,
+    ) -> Result<Comment, Self::Error> {
+        let mut comments = self.comments.lock().unwrap();
+        let comment = comments
+            .iter_mut()
+            .find(|comment| comment.id == id.into())
+            .ok_or(DummyCommentDataSourceError)?;
+        comment.hidden_at = hidden_at;
+        Ok(comment.clone())
+    }
+
     async fn find_and_update_comment(
         &self,
         _id: bson::oid::ObjectId,
@@ -490,6 +1236,16 @@ impl CommentDataSource for MockDatabase {
     }
 }
 
+#[async_trait]
+impl MentionNotifier for MockDatabase {
+    async fn notify_mention(&self, user_id: ObjectId, comment: &Comment) {
+        self.mention_notifications
+            .lock()
+            .unwrap()
+            .push((user_id, *comment.id));
+    }
+}
+
 #[derive(Debug)]
 pub struct DummyLikeDataSource(String);
 
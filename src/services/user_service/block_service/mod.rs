@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests;
+
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    data_source::{BlockDataSource, FriendsListDataSource, UserDataSource},
+    models::{friend_request::FriendRequestStatus, users::User},
+};
+
+use super::{error::UserServiceError, UserService};
+
+pub struct BlockService;
+
+impl BlockService {
+    /// Block the user. Blocked users cannot send friend requests to, comment
+    /// on, like or share the CVs of the user who blocked them, and the other
+    /// way around. Any friendship or pending request between them is removed.
+    pub async fn block_user(
+        db: &(impl UserDataSource + FriendsListDataSource + BlockDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<(), UserServiceError> {
+        if user_id == blocked_id {
+            return Err(UserServiceError::CannotBlockSelf);
+        }
+        UserService::get_user_by_id(db, blocked_id).await?;
+        db.add_block(user_id, blocked_id)
+            .await
+            .map_err(|err| err.into())?;
+        for (from, to) in [(user_id, blocked_id), (blocked_id, user_id)] {
+            if let Ok(friend_request) = db.get_friend_request(from, to).await {
+                if friend_request.id.from == from
+                    && friend_request.id.to == to
+                    && friend_request.status != FriendRequestStatus::Rejected
+                {
+                    db.delete_friend_request(from, to)
+                        .await
+                        .map_err(|_| UserServiceError::DatabaseError)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn unblock_user(
+        db: &(impl BlockDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        blocked_id: ObjectId,
+    ) -> Result<(), UserServiceError> {
+        db.delete_block(user_id, blocked_id)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Return the users blocked by the user, most recently blocked first.
+    pub async fn blocked_users(
+        db: &(impl UserDataSource + BlockDataSource + std::marker::Sync),
+        user_id: ObjectId,
+    ) -> Result<Vec<User>, UserServiceError> {
+        let blocks = db
+            .get_blocks_by_user(user_id)
+            .await
+            .map_err(|err| err.into())?;
+        let mut users = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            // skip the users deleted since they were blocked
+            if let Ok(user) = db.get_user_by_id(block.key.blocked_id).await {
+                users.push(user);
+            }
+        }
+        Ok(users)
+    }
+
+    /// Return whether either of the users has blocked the other.
+    pub async fn is_blocked(
+        db: &(impl BlockDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        other_id: ObjectId,
+    ) -> Result<bool, UserServiceError> {
+        for (blocker_id, blocked_id) in [(user_id, other_id), (other_id, user_id)] {
+            let block = db
+                .get_block(blocker_id, blocked_id)
+                .await
+                .map_err(|err| err.into())?;
+            if block.is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Return the ids of the users the user has blocked or is blocked by,
+    /// which are hidden from the user in searches and friend lists.
+    pub async fn hidden_user_ids(
+        db: &(impl BlockDataSource + std::marker::Sync),
+        user_id: ObjectId,
+    ) -> Vec<ObjectId> {
+        db.get_blocks_involving(user_id)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|block| block.other(user_id))
+            .collect()
+    }
+}
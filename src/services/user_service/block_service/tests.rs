@@ -0,0 +1,183 @@
+use async_graphql::futures_util::StreamExt;
+use mongodb::bson::oid::ObjectId;
+
+use super::BlockService;
+use crate::{
+    data_source::{CommentDataSource, FriendsListError},
+    models::comment::CreateCommentInput,
+    services::{
+        cv_service::{
+            comment_service::{CommentService, CommentServiceError},
+            cv_service::CVService,
+            error::CVServiceError,
+            like_service::LikeService,
+            share_service::ShareService,
+        },
+        tests::{create_user, MockDatabase},
+        user_service::{error::UserServiceError, UserService},
+    },
+};
+
+async fn visible_friends(db: &MockDatabase, viewer_id: Option<ObjectId>, user: ObjectId) -> usize {
+    UserService::friends_visible_to(db, viewer_id, user)
+        .await
+        .collect::<Vec<_>>()
+        .await
+        .len()
+}
+
+#[tokio::test]
+async fn block_and_unblock() {
+    let db = MockDatabase::new();
+    let user = create_user(&db, "user").await;
+    let other = create_user(&db, "other").await;
+    let third = create_user(&db, "third").await;
+
+    assert_eq!(
+        Err(UserServiceError::CannotBlockSelf),
+        BlockService::block_user(&db, user, user).await
+    );
+    BlockService::block_user(&db, user, ObjectId::new())
+        .await
+        .unwrap_err();
+    BlockService::block_user(&db, user, other).await.unwrap();
+    BlockService::block_user(&db, user, third).await.unwrap();
+    // blocking twice is not an error
+    BlockService::block_user(&db, user, other).await.unwrap();
+
+    let blocked = BlockService::blocked_users(&db, user).await.unwrap();
+    let blocked = blocked.iter().map(|user| *user.id).collect::<Vec<_>>();
+    assert_eq!(vec![third, other], blocked);
+    assert!(BlockService::blocked_users(&db, other)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(BlockService::is_blocked(&db, user, other).await.unwrap());
+    assert!(BlockService::is_blocked(&db, other, user).await.unwrap());
+    assert!(!BlockService::is_blocked(&db, other, third).await.unwrap());
+    assert_eq!(
+        vec![other, third],
+        BlockService::hidden_user_ids(&db, user).await
+    );
+    assert_eq!(vec![user], BlockService::hidden_user_ids(&db, other).await);
+
+    // only the user who blocked can unblock
+    assert_eq!(
+        Err(UserServiceError::BlockNotFound(user)),
+        BlockService::unblock_user(&db, other, user).await
+    );
+    BlockService::unblock_user(&db, user, other).await.unwrap();
+    assert!(!BlockService::is_blocked(&db, user, other).await.unwrap());
+    BlockService::unblock_user(&db, user, other)
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn blocking_removes_friendship() {
+    let db = MockDatabase::new();
+    let user = create_user(&db, "user").await;
+    let friend = create_user(&db, "friend").await;
+    let requester = create_user(&db, "requester").await;
+    UserService::send_friend_request(&db, friend, user, None::<String>)
+        .await
+        .unwrap();
+    UserService::accept_friend_request(&db, user, friend)
+        .await
+        .unwrap();
+    UserService::send_friend_request(&db, requester, user, None::<String>)
+        .await
+        .unwrap();
+
+    BlockService::block_user(&db, user, friend).await.unwrap();
+    BlockService::block_user(&db, user, requester)
+        .await
+        .unwrap();
+    assert!(!UserService::are_friends(&db, user, friend).await);
+    assert!(UserService::incoming_friend_requests(&db, user)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // neither side can send a friend request while the block lasts
+    assert_eq!(
+        Err(FriendsListError::UserBlocked),
+        UserService::send_friend_request(&db, friend, user, None::<String>).await
+    );
+    assert_eq!(
+        Err(FriendsListError::UserBlocked),
+        UserService::send_friend_request(&db, user, friend, None::<String>).await
+    );
+    BlockService::unblock_user(&db, user, friend).await.unwrap();
+    UserService::send_friend_request(&db, friend, user, None::<String>)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn blocked_users_are_hidden_from_friend_lists() {
+    let db = MockDatabase::new();
+    let user = create_user(&db, "user").await;
+    let friend = create_user(&db, "friend").await;
+    let viewer = create_user(&db, "viewer").await;
+    UserService::send_friend_request(&db, friend, user, None::<String>)
+        .await
+        .unwrap();
+    UserService::accept_friend_request(&db, user, friend)
+        .await
+        .unwrap();
+
+    assert_eq!(1, visible_friends(&db, Some(viewer), user).await);
+    BlockService::block_user(&db, friend, viewer).await.unwrap();
+    assert_eq!(0, visible_friends(&db, Some(viewer), user).await);
+    assert_eq!(1, visible_friends(&db, None, user).await);
+    assert_eq!(1, visible_friends(&db, Some(user), user).await);
+}
+
+#[tokio::test]
+async fn blocked_users_cannot_interact_with_cvs() {
+    let db = MockDatabase::new();
+    let author = create_user(&db, "author").await;
+    let blocked = create_user(&db, "blocked").await;
+    let cv_id = *CVService::create_cv(&db, author, "CV".to_string(), "".to_string())
+        .await
+        .unwrap()
+        .id;
+    let comment = db
+        .create_comment(CreateCommentInput {
+            author: author.into(),
+            content: "comment".to_string(),
+        })
+        .await
+        .unwrap();
+    BlockService::block_user(&db, author, blocked)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        Err(CommentServiceError::UserBlocked(author)),
+        CommentService::add_comment_to_cv(&db, cv_id, blocked, "hi".to_string()).await
+    );
+    let rs = CommentService::add_reply_comment(&db, *comment.id, blocked, "hi".to_string()).await;
+    assert_eq!(Some(CommentServiceError::UserBlocked(author)), rs.err());
+    let rs = CommentService::add_like_comment(&db, blocked, *comment.id).await;
+    assert_eq!(Some(CommentServiceError::UserBlocked(author)), rs.err());
+    assert_eq!(
+        Err(CVServiceError::UserBlocked(author)),
+        LikeService::like_cv(&db, blocked, cv_id).await
+    );
+    assert_eq!(
+        Err(CVServiceError::UserBlocked(author)),
+        ShareService::share_cv(&db, blocked, cv_id).await
+    );
+
+    BlockService::unblock_user(&db, author, blocked)
+        .await
+        .unwrap();
+    let cv = CommentService::add_comment_to_cv(&db, cv_id, blocked, "hi".to_string())
+        .await
+        .unwrap();
+    assert_eq!(1, cv.comments.len());
+    LikeService::like_cv(&db, blocked, cv_id).await.unwrap();
+    ShareService::share_cv(&db, blocked, cv_id).await.unwrap();
+}
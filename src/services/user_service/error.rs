@@ -2,6 +2,8 @@ use async_graphql::ErrorExtensions;
 use mongodb::bson;
 use std::fmt;
 
+use crate::common::DateTime;
+
 #[non_exhaustive]
 #[derive(Debug, PartialEq, Eq, Clone)]
 
@@ -56,6 +58,38 @@ pub enum UserServiceError {
 
     // Database error
     DatabaseError,
+
+    /// Error when the uploaded file is not an accepted image.
+    InvalidImage(String),
+
+    /// Error when the uploaded image is larger than the limit, in bytes.
+    ImageTooLarge(usize),
+
+    /// Error when the photo cannot be stored or retrieved.
+    StorageError,
+
+    /// Error when the CV does not exist, does not belong to the user or is
+    /// not visible to the viewer.
+    CVNotFound(bson::oid::ObjectId),
+
+    /// Error when a profile import file cannot be read.
+    InvalidImportFile(String),
+
+    /// Error when a user tries to block themselves.
+    CannotBlockSelf,
+
+    /// Error when unblocking a user who is not blocked.
+    BlockNotFound(bson::oid::ObjectId),
+
+    /// Error when one of the users has blocked the other.
+    UserBlocked(bson::oid::ObjectId),
+
+    /// Error when logins are refused after too many failures, until the given
+    /// time.
+    AccountLocked(DateTime),
+
+    /// Error when the account is suspended, until the given time.
+    AccountSuspended(DateTime),
 }
 
 impl fmt::Display for UserServiceError {
@@ -152,6 +186,54 @@ impl fmt::Display for UserServiceError {
             UserServiceError::DatabaseError => {
                 write!(f, "Database error")
             }
+
+            UserServiceError::InvalidImage(reason) => {
+                write!(f, "Invalid image: {}", reason)
+            }
+
+            UserServiceError::ImageTooLarge(limit) => {
+                write!(f, "Image is larger than {} bytes", limit)
+            }
+
+            UserServiceError::StorageError => {
+                write!(f, "Photo storage is not available")
+            }
+
+            UserServiceError::CVNotFound(id) => {
+                write!(f, "CV {:?} not found", id)
+            }
+
+            UserServiceError::InvalidImportFile(reason) => {
+                write!(f, "Invalid import file: {}", reason)
+            }
+
+            UserServiceError::CannotBlockSelf => {
+                write!(f, "Users cannot block themselves")
+            }
+
+            UserServiceError::BlockNotFound(id) => {
+                write!(f, "User {:?} is not blocked", id)
+            }
+
+            UserServiceError::UserBlocked(id) => {
+                write!(f, "User {:?} is blocked", id)
+            }
+
+            UserServiceError::AccountLocked(until) => {
+                write!(
+                    f,
+                    "Too many failed logins, try again after {}",
+                    until.try_to_rfc3339_string().unwrap_or_default()
+                )
+            }
+
+            UserServiceError::AccountSuspended(until) => {
+                write!(
+                    f,
+                    "Account is suspended until {}",
+                    until.try_to_rfc3339_string().unwrap_or_default()
+                )
+            }
         }
     }
 }
@@ -176,7 +258,28 @@ impl ErrorExtensions for UserServiceError {
             UserServiceError::UpdateUserFailed => "UPDATE_USER_FAILED",
             UserServiceError::InvalidToken => "INVALID_TOKEN",
             UserServiceError::DatabaseError => "DATABASE_ERROR",
+            UserServiceError::InvalidImage(_) => "INVALID_IMAGE",
+            UserServiceError::ImageTooLarge(_) => "IMAGE_TOO_LARGE",
+            UserServiceError::StorageError => "STORAGE_ERROR",
+            UserServiceError::CVNotFound(_) => "CV_NOT_FOUND",
+            UserServiceError::InvalidImportFile(_) => "INVALID_IMPORT_FILE",
+            UserServiceError::CannotBlockSelf => "CANNOT_BLOCK_SELF",
+            UserServiceError::BlockNotFound(_) => "BLOCK_NOT_FOUND",
+            UserServiceError::UserBlocked(_) => "USER_BLOCKED",
+            UserServiceError::AccountLocked(_) => "ACCOUNT_LOCKED",
+            UserServiceError::AccountSuspended(_) => "ACCOUNT_SUSPENDED",
+        };
+        let until = match self {
+            UserServiceError::AccountLocked(until) | UserServiceError::AccountSuspended(until) => {
+                Some(*until)
+            }
+            _ => None,
         };
-        async_graphql::Error::new(self.to_string()).extend_with(|_, e| e.set("code", code))
+        async_graphql::Error::new(self.to_string()).extend_with(|_, e| {
+            e.set("code", code);
+            if let Some(until) = until {
+                e.set("until", async_graphql::ScalarType::to_value(&until));
+            }
+        })
     }
 }
@@ -0,0 +1,374 @@
+#[cfg(test)]
+mod tests;
+
+use std::io::{Cursor, Read};
+
+use async_graphql::OutputType;
+use mongodb::bson::{self, oid::ObjectId};
+use serde::Deserialize;
+
+use crate::{
+    data_source::UserDataSource,
+    models::{
+        education::Education,
+        experience::Experience,
+        users::{
+            profile_import::{ImportDiff, ProfileImport, ProfileImportFormat, ProfileImportMode},
+            UpdateUserInput,
+        },
+    },
+};
+
+use super::{error::UserServiceError, UserService};
+
+/// Largest file accepted for an import, in bytes.
+pub const MAX_IMPORT_FILE_SIZE: usize = 5 * 1024 * 1024;
+
+/// Largest CSV file read from a LinkedIn archive, in bytes.
+const MAX_CSV_SIZE: u64 = 5 * 1024 * 1024;
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// Educations and experiences read from an import file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedProfile {
+    pub educations: Vec<Education>,
+    pub experiences: Vec<Experience>,
+}
+
+/// The parts of a JSON Resume that are imported. Every field is optional
+/// since resumes written by other tools are often incomplete.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct JsonResumeFile {
+    work: Vec<JsonResumeWorkEntry>,
+    education: Vec<JsonResumeEducationEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct JsonResumeWorkEntry {
+    /// Named `company` in versions of the schema before 1.0.
+    #[serde(alias = "company")]
+    name: String,
+    position: String,
+    location: String,
+    employment_type: String,
+    summary: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct JsonResumeEducationEntry {
+    institution: String,
+    area: String,
+    minor: Option<String>,
+    study_type: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+}
+
+fn invalid_file(reason: impl Into<String>) -> UserServiceError {
+    UserServiceError::InvalidImportFile(reason.into())
+}
+
+/// Parse the dates found in import files: `YYYY`, `YYYY-MM` and `YYYY-MM-DD`
+/// as used by JSON Resume, and `Mon YYYY` as used by LinkedIn. Missing parts
+/// default to the first month or day. Return `None` for anything else.
+pub fn parse_date(date: &str) -> Option<bson::DateTime> {
+    let date = date.trim();
+    let (year, month, day) = match date.split_once(' ') {
+        Some((month, year)) => {
+            let month = month.to_ascii_lowercase();
+            let month = MONTHS.iter().position(|m| month.starts_with(m))? + 1;
+            (year.trim(), month.to_string(), "1")
+        }
+        None => {
+            let mut parts = date.splitn(3, '-');
+            let year = parts.next()?;
+            let month = parts.next().unwrap_or("1");
+            let day = parts.next().unwrap_or("1");
+            (year, month.to_string(), day)
+        }
+    };
+    let year = year.parse::<u16>().ok().filter(|year| *year >= 1000)?;
+    let month = month.parse::<u8>().ok()?;
+    let day = day.parse::<u8>().ok()?;
+    let date = format!("{:04}-{:02}-{:02}T00:00:00Z", year, month, day);
+    bson::DateTime::parse_rfc3339_str(date).ok()
+}
+
+fn parse_optional_date(date: Option<&str>) -> Option<bson::DateTime> {
+    date.and_then(parse_date)
+}
+
+/// Key identifying the same education in the profile and in an import.
+fn education_key(education: &Education) -> (String, String, String) {
+    (
+        education.school.trim().to_lowercase(),
+        education.major.trim().to_lowercase(),
+        education.degree.trim().to_lowercase(),
+    )
+}
+
+/// Key identifying the same experience in the profile and in an import.
+fn experience_key(experience: &Experience) -> (String, String, Option<bson::DateTime>) {
+    (
+        experience.company.trim().to_lowercase(),
+        experience.title.trim().to_lowercase(),
+        experience.start_date,
+    )
+}
+
+/// Combine the existing entries with the imported ones according to `mode`.
+/// Duplicates in the import are dropped. Return the resulting list and the
+/// changes made to the existing one.
+fn combine<T, K>(
+    existing: &[T],
+    imported: Vec<T>,
+    mode: ProfileImportMode,
+    key: impl Fn(&T) -> K,
+) -> (Vec<T>, ImportDiff<T>)
+where
+    T: OutputType + Clone,
+    K: PartialEq,
+{
+    let mut diff = ImportDiff::default();
+    let mut imported_keys = Vec::new();
+    let mut imported_entries = Vec::new();
+    for entry in imported {
+        let entry_key = key(&entry);
+        if imported_keys.contains(&entry_key) {
+            continue;
+        }
+        if existing.iter().any(|e| key(e) == entry_key) {
+            diff.unchanged.push(entry.clone());
+        } else {
+            diff.added.push(entry.clone());
+        }
+        imported_keys.push(entry_key);
+        imported_entries.push(entry);
+    }
+    let entries = match mode {
+        ProfileImportMode::Merge => existing
+            .iter()
+            .cloned()
+            .chain(diff.added.iter().cloned())
+            .collect(),
+        ProfileImportMode::Replace => {
+            diff.removed = existing
+                .iter()
+                .filter(|e| !imported_keys.contains(&key(e)))
+                .cloned()
+                .collect();
+            imported_entries
+        }
+    };
+    (entries, diff)
+}
+
+pub struct ProfileImportService;
+
+impl ProfileImportService {
+    /// Read the educations and experiences of an import file.
+    pub fn parse(
+        format: ProfileImportFormat,
+        data: &[u8],
+    ) -> Result<ImportedProfile, UserServiceError> {
+        if data.is_empty() {
+            return Err(invalid_file("File is empty"));
+        }
+        if data.len() > MAX_IMPORT_FILE_SIZE {
+            return Err(invalid_file(format!(
+                "File is larger than {} bytes",
+                MAX_IMPORT_FILE_SIZE
+            )));
+        }
+        match format {
+            ProfileImportFormat::JsonResume => Self::parse_json_resume(data),
+            ProfileImportFormat::LinkedIn => Self::parse_linkedin_archive(data),
+        }
+    }
+
+    fn parse_json_resume(data: &[u8]) -> Result<ImportedProfile, UserServiceError> {
+        let resume: JsonResumeFile =
+            serde_json::from_slice(data).map_err(|err| invalid_file(err.to_string()))?;
+        let experiences = resume
+            .work
+            .into_iter()
+            .map(|work| Experience {
+                title: work.position,
+                company: work.name,
+                employment_type: work.employment_type,
+                location: work.location,
+                description: work.summary,
+                start_date: parse_optional_date(work.start_date.as_deref()),
+                end_date: parse_optional_date(work.end_date.as_deref()),
+            })
+            .collect();
+        let educations = resume
+            .education
+            .into_iter()
+            .map(|education| Education {
+                school: education.institution,
+                major: education.area,
+                minor: education.minor,
+                degree: education.study_type,
+                start_date: parse_optional_date(education.start_date.as_deref()),
+                end_date: parse_optional_date(education.end_date.as_deref()),
+            })
+            .collect();
+        Ok(ImportedProfile {
+            educations,
+            experiences,
+        })
+    }
+
+    /// Read `Positions.csv` and `Education.csv` from a LinkedIn data export.
+    /// LinkedIn only exports a degree name such as "Bachelor's degree,
+    /// Computer Science", the part after the first comma is used as the major.
+    fn parse_linkedin_archive(data: &[u8]) -> Result<ImportedProfile, UserServiceError> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(data))
+            .map_err(|_| invalid_file("File is not a ZIP archive"))?;
+        let positions = Self::read_csv(&mut archive, "Positions.csv")?;
+        let education = Self::read_csv(&mut archive, "Education.csv")?;
+        if positions.is_none() && education.is_none() {
+            return Err(invalid_file(
+                "The archive contains neither Positions.csv nor Education.csv",
+            ));
+        }
+
+        let mut profile = ImportedProfile::default();
+        for row in positions.unwrap_or_default() {
+            profile.experiences.push(Experience {
+                title: row.get("Title"),
+                company: row.get("Company Name"),
+                employment_type: String::new(),
+                location: row.get("Location"),
+                description: row.get("Description"),
+                start_date: parse_date(&row.get("Started On")),
+                end_date: parse_date(&row.get("Finished On")),
+            });
+        }
+        for row in education.unwrap_or_default() {
+            let degree_name = row.get("Degree Name");
+            let (degree, major) = match degree_name.split_once(',') {
+                Some((degree, major)) => (degree.trim().to_string(), major.trim().to_string()),
+                None => (degree_name.trim().to_string(), String::new()),
+            };
+            profile.educations.push(Education {
+                school: row.get("School Name"),
+                major,
+                minor: None,
+                degree,
+                start_date: parse_date(&row.get("Start Date")),
+                end_date: parse_date(&row.get("End Date")),
+            });
+        }
+        Ok(profile)
+    }
+
+    /// Read the rows of the CSV file `name` of the archive, wherever it is in
+    /// the archive. Return `None` if there is no such file.
+    fn read_csv(
+        archive: &mut zip::ZipArchive<Cursor<&[u8]>>,
+        name: &str,
+    ) -> Result<Option<Vec<CsvRow>>, UserServiceError> {
+        let path = archive.file_names().find(|path| {
+            path.rsplit('/')
+                .next()
+                .is_some_and(|file| file.eq_ignore_ascii_case(name))
+        });
+        let path = match path {
+            Some(path) => path.to_string(),
+            None => return Ok(None),
+        };
+        let mut content = Vec::new();
+        archive
+            .by_name(&path)
+            .map_err(|_| invalid_file(format!("Cannot read {}", name)))?
+            .take(MAX_CSV_SIZE)
+            .read_to_end(&mut content)
+            .map_err(|_| invalid_file(format!("Cannot read {}", name)))?;
+
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(content.as_slice());
+        let headers = reader
+            .headers()
+            .map_err(|err| invalid_file(format!("{}: {}", name, err)))?
+            .iter()
+            .map(|header| header.trim().to_string())
+            .collect::<Vec<_>>();
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|err| invalid_file(format!("{}: {}", name, err)))?;
+            rows.push(CsvRow {
+                headers: headers.clone(),
+                values: record.iter().map(|value| value.trim().to_string()).collect(),
+            });
+        }
+        Ok(Some(rows))
+    }
+
+    /// Import the file into the profile of the user. Nothing is saved unless
+    /// `confirm` is true, the returned diff can be shown as a preview.
+    pub async fn import_profile(
+        db: &(impl UserDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        format: ProfileImportFormat,
+        mode: ProfileImportMode,
+        data: &[u8],
+        confirm: bool,
+    ) -> Result<ProfileImport, UserServiceError> {
+        let imported = Self::parse(format, data)?;
+        let user = UserService::get_user_by_id(db, user_id).await?;
+        let (educations, education_diff) =
+            combine(&user.educations, imported.educations, mode, education_key);
+        let (experiences, experience_diff) =
+            combine(&user.experiences, imported.experiences, mode, experience_key);
+
+        let user = if confirm {
+            let input = UpdateUserInput::builder()
+                .with_user_id(user_id)
+                .with_educations(educations)
+                .with_experiences(experiences)
+                .build()
+                .unwrap();
+            UserService::update_user(db, input).await?
+        } else {
+            user
+        };
+        Ok(ProfileImport {
+            format,
+            mode,
+            applied: confirm,
+            educations: education_diff,
+            experiences: experience_diff,
+            user,
+        })
+    }
+}
+
+/// A row of a CSV file, with its values looked up by column name.
+#[derive(Debug, Clone)]
+struct CsvRow {
+    headers: Vec<String>,
+    values: Vec<String>,
+}
+
+impl CsvRow {
+    /// Value of the column, empty if the file has no such column.
+    fn get(&self, column: &str) -> String {
+        self.headers
+            .iter()
+            .position(|header| header == column)
+            .and_then(|i| self.values.get(i))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
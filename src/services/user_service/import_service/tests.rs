@@ -0,0 +1,275 @@
+use std::io::{Cursor, Write};
+
+use mongodb::bson;
+
+use super::{parse_date, ProfileImportService};
+use crate::{
+    models::{
+        education::Education,
+        experience::ExperienceBuilder,
+        sex::Sex,
+        users::{
+            profile_import::{ProfileImportFormat, ProfileImportMode},
+            CreateUserInput, JsonResume,
+        },
+    },
+    services::{
+        tests::MockDatabase,
+        user_service::{error::UserServiceError, UserService},
+    },
+};
+
+const RESUME: &str = r#"{
+    "basics": {"name": "Nguyen Van A"},
+    "work": [
+        {
+            "name": "SeeVi",
+            "position": "Backend developer",
+            "location": "Ho Chi Minh City",
+            "summary": "GraphQL APIs",
+            "startDate": "2021-03",
+            "endDate": "2023-01-15"
+        },
+        {"company": "Old Corp", "position": "Intern", "startDate": "2020"}
+    ],
+    "education": [
+        {"institution": "HCMUS", "area": "Computer Science", "studyType": "Bachelor", "startDate": "2017"}
+    ]
+}"#;
+
+fn date(date: &str) -> bson::DateTime {
+    bson::DateTime::parse_rfc3339_str(date).unwrap()
+}
+
+fn linkedin_archive(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut zip = zip::ZipWriter::new(Cursor::new(&mut data));
+    for (name, content) in files {
+        zip.start_file(*name, Default::default()).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+    drop(zip);
+    data
+}
+
+fn education(school: &str) -> Education {
+    Education {
+        school: school.to_string(),
+        major: "Computer Science".to_string(),
+        minor: None,
+        degree: "Bachelor".to_string(),
+        start_date: None,
+        end_date: None,
+    }
+}
+
+#[test]
+fn parses_dates() {
+    assert_eq!(Some(date("2021-03-01T00:00:00Z")), parse_date("2021-03"));
+    assert_eq!(Some(date("2021-03-15T00:00:00Z")), parse_date("2021-03-15"));
+    assert_eq!(Some(date("2017-01-01T00:00:00Z")), parse_date("2017"));
+    assert_eq!(Some(date("2021-03-01T00:00:00Z")), parse_date("Mar 2021"));
+    assert_eq!(Some(date("2019-09-01T00:00:00Z")), parse_date("September 2019"));
+    assert_eq!(None, parse_date(""));
+    assert_eq!(None, parse_date("Present"));
+    assert_eq!(None, parse_date("2021-13"));
+    assert_eq!(None, parse_date("2021-02-30"));
+}
+
+#[test]
+fn parses_json_resume() {
+    let profile =
+        ProfileImportService::parse(ProfileImportFormat::JsonResume, RESUME.as_bytes()).unwrap();
+    assert_eq!(2, profile.experiences.len());
+    let work = &profile.experiences[0];
+    assert_eq!("SeeVi", work.company);
+    assert_eq!("Backend developer", work.title);
+    assert_eq!(Some(date("2021-03-01T00:00:00Z")), work.start_date);
+    assert_eq!(Some(date("2023-01-15T00:00:00Z")), work.end_date);
+    assert_eq!("Old Corp", profile.experiences[1].company);
+    assert_eq!(None, profile.experiences[1].end_date);
+    assert_eq!(
+        Education {
+            start_date: Some(date("2017-01-01T00:00:00Z")),
+            ..education("HCMUS")
+        },
+        profile.educations[0]
+    );
+
+    assert!(matches!(
+        ProfileImportService::parse(ProfileImportFormat::JsonResume, b"{\"work\": 1}"),
+        Err(UserServiceError::InvalidImportFile(_))
+    ));
+    assert!(matches!(
+        ProfileImportService::parse(ProfileImportFormat::JsonResume, b""),
+        Err(UserServiceError::InvalidImportFile(_))
+    ));
+}
+
+#[tokio::test]
+async fn imports_own_json_resume_export() {
+    let db = MockDatabase::new();
+    let experience = ExperienceBuilder::default()
+        .with_title("Backend developer")
+        .with_company("SeeVi")
+        .with_employment_type("Full-time")
+        .with_location("Ho Chi Minh City")
+        .with_description("GraphQL APIs")
+        .with_start_date(date("2021-03-01T00:00:00Z"))
+        .build()
+        .unwrap();
+    let input = CreateUserInput::builder()
+        .with_username("test")
+        .with_password("test")
+        .with_primary_email("test@mail.com")
+        .with_sex(Sex::Male)
+        .with_education(education("HCMUS"))
+        .with_experience(experience)
+        .build()
+        .unwrap();
+    let user = UserService::create_user(&db, input).await.unwrap();
+
+    let export = serde_json::to_vec(&JsonResume::new(&user, None)).unwrap();
+    let profile = ProfileImportService::parse(ProfileImportFormat::JsonResume, &export).unwrap();
+    assert_eq!(user.educations, profile.educations);
+    assert_eq!(user.experiences, profile.experiences);
+}
+
+#[test]
+fn parses_linkedin_archive() {
+    let archive = linkedin_archive(&[
+        (
+            "Basic_LinkedInDataExport/Positions.csv",
+            "Company Name,Title,Description,Location,Started On,Finished On\n\
+             SeeVi,Backend developer,\"GraphQL, Rust\",Ho Chi Minh City,Mar 2021,\n\
+             Old Corp,Intern,,Hanoi,Jun 2020,Aug 2020\n",
+        ),
+        (
+            "Basic_LinkedInDataExport/Education.csv",
+            "School Name,Start Date,End Date,Notes,Degree Name,Activities\n\
+             HCMUS,2017,2021,,\"Bachelor, Computer Science\",\n",
+        ),
+        ("Basic_LinkedInDataExport/Skills.csv", "Name\nRust\n"),
+    ]);
+    let profile = ProfileImportService::parse(ProfileImportFormat::LinkedIn, &archive).unwrap();
+    assert_eq!(2, profile.experiences.len());
+    let work = &profile.experiences[0];
+    assert_eq!("SeeVi", work.company);
+    assert_eq!("GraphQL, Rust", work.description);
+    assert_eq!(Some(date("2021-03-01T00:00:00Z")), work.start_date);
+    assert_eq!(None, work.end_date);
+    assert_eq!(
+        Some(date("2020-08-01T00:00:00Z")),
+        profile.experiences[1].end_date
+    );
+    assert_eq!(
+        Education {
+            start_date: Some(date("2017-01-01T00:00:00Z")),
+            end_date: Some(date("2021-01-01T00:00:00Z")),
+            ..education("HCMUS")
+        },
+        profile.educations[0]
+    );
+
+    let archive = linkedin_archive(&[("Skills.csv", "Name\nRust\n")]);
+    assert!(matches!(
+        ProfileImportService::parse(ProfileImportFormat::LinkedIn, &archive),
+        Err(UserServiceError::InvalidImportFile(_))
+    ));
+    assert!(matches!(
+        ProfileImportService::parse(ProfileImportFormat::LinkedIn, RESUME.as_bytes()),
+        Err(UserServiceError::InvalidImportFile(_))
+    ));
+}
+
+#[tokio::test]
+async fn import_profile() {
+    let db = MockDatabase::new();
+    let input = CreateUserInput::builder()
+        .with_username("test")
+        .with_password("test")
+        .with_primary_email("test@mail.com")
+        .with_sex(Sex::Male)
+        .with_education(education("HCMUS"))
+        .with_education(education("High school"))
+        .build()
+        .unwrap();
+    let user_id = *UserService::create_user(&db, input).await.unwrap().id;
+    let file = RESUME.as_bytes();
+
+    // a preview does not change the profile
+    let preview = ProfileImportService::import_profile(
+        &db,
+        user_id,
+        ProfileImportFormat::JsonResume,
+        ProfileImportMode::Merge,
+        file,
+        false,
+    )
+    .await
+    .unwrap();
+    assert!(!preview.applied);
+    assert_eq!(2, preview.experiences.added.len());
+    assert_eq!(1, preview.educations.unchanged.len());
+    assert!(preview.educations.added.is_empty());
+    assert!(preview.educations.removed.is_empty());
+    assert!(preview.user.experiences.is_empty());
+
+    let preview = ProfileImportService::import_profile(
+        &db,
+        user_id,
+        ProfileImportFormat::JsonResume,
+        ProfileImportMode::Replace,
+        file,
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(vec![education("High school")], preview.educations.removed);
+
+    let import = ProfileImportService::import_profile(
+        &db,
+        user_id,
+        ProfileImportFormat::JsonResume,
+        ProfileImportMode::Merge,
+        file,
+        true,
+    )
+    .await
+    .unwrap();
+    assert!(import.applied);
+    assert_eq!(2, import.user.educations.len());
+    assert_eq!(2, import.user.experiences.len());
+    let user = UserService::get_user_by_id(&db, user_id).await.unwrap();
+    assert_eq!(import.user, user);
+
+    // importing the same file again changes nothing
+    let import = ProfileImportService::import_profile(
+        &db,
+        user_id,
+        ProfileImportFormat::JsonResume,
+        ProfileImportMode::Merge,
+        file,
+        true,
+    )
+    .await
+    .unwrap();
+    assert!(import.experiences.added.is_empty());
+    assert_eq!(2, import.experiences.unchanged.len());
+    assert_eq!(2, import.user.experiences.len());
+
+    let import = ProfileImportService::import_profile(
+        &db,
+        user_id,
+        ProfileImportFormat::JsonResume,
+        ProfileImportMode::Replace,
+        file,
+        true,
+    )
+    .await
+    .unwrap();
+    assert_eq!(1, import.user.educations.len());
+    assert_eq!("HCMUS", import.user.educations[0].school);
+    assert_eq!(2, import.user.experiences.len());
+}
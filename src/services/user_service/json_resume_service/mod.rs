@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests;
+
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    data_source::{BlockDataSource, CVDataSource, FriendsListDataSource, UserDataSource},
+    models::users::JsonResume,
+    services::cv_service::cv_service::CVService,
+};
+
+use super::{block_service::BlockService, error::UserServiceError, UserService};
+
+pub struct JsonResumeService;
+
+impl JsonResumeService {
+    /// Render the profile of the user as a JSON Resume. When `cv_id` is
+    /// provided, the metadata of the CV is included; it must be a CV of the
+    /// user that `viewer_id` is allowed to see. The contact details are left
+    /// out unless the viewer is the user or one of their friends, and users
+    /// who blocked each other cannot export each other's profile.
+    pub async fn export(
+        db: &(impl UserDataSource
+              + CVDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
+        viewer_id: Option<ObjectId>,
+        user_id: ObjectId,
+        cv_id: Option<ObjectId>,
+    ) -> Result<JsonResume, UserServiceError> {
+        let user = UserService::get_user_by_id(db, user_id).await?;
+        let is_contact = match viewer_id {
+            Some(viewer_id) if viewer_id == user_id => true,
+            Some(viewer_id) => {
+                if BlockService::is_blocked(db, viewer_id, user_id).await? {
+                    return Err(UserServiceError::IdNotFound(user_id));
+                }
+                UserService::are_friends(db, viewer_id, user_id).await
+            }
+            None => false,
+        };
+        let cv = match cv_id {
+            Some(cv_id) => {
+                let cv = CVService::get_cv_by_id(db, viewer_id, cv_id)
+                    .await
+                    .map_err(|_| UserServiceError::CVNotFound(cv_id))?;
+                if cv.author_id != user.id {
+                    return Err(UserServiceError::CVNotFound(cv_id));
+                }
+                Some(cv)
+            }
+            None => None,
+        };
+        let resume = JsonResume::new(&user, cv.as_ref());
+        if is_contact {
+            Ok(resume)
+        } else {
+            Ok(resume.without_contact())
+        }
+    }
+}
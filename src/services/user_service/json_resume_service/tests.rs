@@ -0,0 +1,188 @@
+use jsonschema::{Draft, JSONSchema};
+use mongodb::bson::{self, oid::ObjectId};
+
+use super::JsonResumeService;
+use crate::{
+    models::{
+        cv::Visibility,
+        education::Education,
+        experience::ExperienceBuilder,
+        sex::Sex,
+        users::{json_resume::JsonResumeLocation, CreateUserInput, JsonResume},
+    },
+    services::{
+        cv_service::cv_service::CVService,
+        tests::MockDatabase,
+        user_service::{block_service::BlockService, error::UserServiceError, UserService},
+    },
+};
+
+fn date(date: &str) -> bson::DateTime {
+    bson::DateTime::parse_rfc3339_str(date).unwrap()
+}
+
+fn schema() -> JSONSchema {
+    let schema = serde_json::from_str(include_str!("schema.json")).unwrap();
+    JSONSchema::options()
+        .with_draft(Draft::Draft7)
+        .compile(&schema)
+        .unwrap()
+}
+
+async fn create_user(db: &MockDatabase, username: &str) -> ObjectId {
+    let experience = ExperienceBuilder::default()
+        .with_title("Backend developer")
+        .with_company("SeeVi")
+        .with_employment_type("Full-time")
+        .with_location("Ho Chi Minh City")
+        .with_description("GraphQL APIs in Rust")
+        .with_start_date(date("2021-03-01T00:00:00Z"))
+        .build()
+        .unwrap();
+    let input = CreateUserInput::builder()
+        .with_username(username)
+        .with_password("password")
+        .with_first_name("Van A")
+        .with_last_name("Nguyen")
+        .with_primary_email(format!("{}@mail.com", username))
+        .with_other_mail("work@mail.com")
+        .with_country("vn")
+        .with_city("Ho Chi Minh City")
+        .with_about("Rust developer")
+        .with_skill("Rust")
+        .with_skill("MongoDB")
+        .with_sex(Sex::Male)
+        .with_education(Education {
+            school: "HCMUS".to_string(),
+            major: "Computer Science".to_string(),
+            minor: None,
+            degree: "Bachelor".to_string(),
+            start_date: Some(date("2017-09-05T00:00:00Z")),
+            end_date: Some(date("2021-06-30T00:00:00Z")),
+        })
+        .with_experience(experience)
+        .build()
+        .unwrap();
+    *UserService::create_user(db, input).await.unwrap().id
+}
+
+#[tokio::test]
+async fn export_matches_schema() {
+    let db = MockDatabase::new();
+    let user_id = create_user(&db, "test").await;
+    let cv = CVService::create_cv(&db, user_id, "Backend CV".to_string(), "Rust".to_string())
+        .await
+        .unwrap();
+
+    let resume = JsonResumeService::export(&db, Some(user_id), user_id, Some(*cv.id))
+        .await
+        .unwrap();
+    assert_eq!("Van A Nguyen", resume.basics.name);
+    assert_eq!(Some("test@mail.com".to_string()), resume.basics.email);
+    assert_eq!(Some("Backend CV".to_string()), resume.basics.label);
+    assert_eq!(vec!["work@mail.com".to_string()], resume.basics.other_emails);
+    assert_eq!(
+        Some(JsonResumeLocation {
+            city: Some("Ho Chi Minh City".to_string()),
+            country_code: Some("VN".to_string()),
+            region: None,
+        }),
+        resume.basics.location
+    );
+    assert_eq!(Some("2017-09-05".to_string()), resume.education[0].start_date);
+    assert_eq!(Some("2021-03-01".to_string()), resume.work[0].start_date);
+    assert_eq!(None, resume.work[0].end_date);
+    assert_eq!(2, resume.skills.len());
+    assert_eq!(cv.id.to_hex(), resume.meta.cv.as_ref().unwrap().id);
+
+    // the exported document is valid and reads back unchanged
+    let value = serde_json::to_value(&resume).unwrap();
+    let schema = schema();
+    assert!(schema.is_valid(&value), "{:#}", value);
+    assert_eq!(resume, serde_json::from_value::<JsonResume>(value).unwrap());
+
+    let mut invalid = serde_json::to_value(&resume).unwrap();
+    invalid["work"][0]["startDate"] = "March 2021".into();
+    assert!(!schema.is_valid(&invalid));
+}
+
+#[tokio::test]
+async fn export_checks_cv() {
+    let db = MockDatabase::new();
+    let user_id = create_user(&db, "test").await;
+    let other_id = create_user(&db, "other").await;
+
+    let resume = JsonResumeService::export(&db, None, user_id, None)
+        .await
+        .unwrap();
+    assert_eq!(None, resume.meta.cv);
+    assert_eq!(None, resume.basics.label);
+    assert!(schema().is_valid(&serde_json::to_value(&resume).unwrap()));
+
+    let cv = CVService::create_cv(&db, other_id, "title".to_string(), "".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        Err(UserServiceError::CVNotFound(*cv.id)),
+        JsonResumeService::export(&db, None, user_id, Some(*cv.id)).await
+    );
+
+    let cv = CVService::create_cv(&db, user_id, "title".to_string(), "".to_string())
+        .await
+        .unwrap();
+    CVService::change_visibility(&db, user_id, *cv.id, Visibility::Private)
+        .await
+        .unwrap();
+    assert_eq!(
+        Err(UserServiceError::CVNotFound(*cv.id)),
+        JsonResumeService::export(&db, Some(other_id), user_id, Some(*cv.id)).await
+    );
+    assert!(
+        JsonResumeService::export(&db, Some(user_id), user_id, Some(*cv.id))
+            .await
+            .is_ok()
+    );
+
+    assert!(JsonResumeService::export(&db, None, ObjectId::new(), None)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn export_hides_contact_from_strangers() {
+    let db = MockDatabase::new();
+    let user_id = create_user(&db, "test").await;
+    let friend_id = create_user(&db, "friend").await;
+    let stranger_id = create_user(&db, "stranger").await;
+    UserService::send_friend_request(&db, friend_id, user_id, None::<String>)
+        .await
+        .unwrap();
+    UserService::accept_friend_request(&db, user_id, friend_id)
+        .await
+        .unwrap();
+
+    for viewer_id in [None, Some(stranger_id)] {
+        let resume = JsonResumeService::export(&db, viewer_id, user_id, None)
+            .await
+            .unwrap();
+        assert_eq!(None, resume.basics.email);
+        assert!(resume.basics.other_emails.is_empty());
+        assert_eq!(None, resume.basics.location);
+        assert_eq!("Van A Nguyen", resume.basics.name);
+        assert!(schema().is_valid(&serde_json::to_value(&resume).unwrap()));
+    }
+
+    let resume = JsonResumeService::export(&db, Some(friend_id), user_id, None)
+        .await
+        .unwrap();
+    assert_eq!(Some("test@mail.com".to_string()), resume.basics.email);
+    assert!(resume.basics.location.is_some());
+
+    BlockService::block_user(&db, user_id, stranger_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        Err(UserServiceError::IdNotFound(user_id)),
+        JsonResumeService::export(&db, Some(stranger_id), user_id, None).await
+    );
+}
@@ -1,22 +1,33 @@
+pub mod block_service;
 pub mod error;
+pub mod import_service;
+pub mod json_resume_service;
+pub mod photo_service;
+pub mod suggestion_service;
 #[cfg(test)]
 mod tests;
 
-use crate::services::user_service::error::UserServiceError;
+use std::time::Duration;
+
+use crate::services::user_service::{block_service::BlockService, error::UserServiceError};
 use async_graphql::futures_util::TryStreamExt;
 use async_graphql::futures_util::{stream::BoxStream, StreamExt};
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{self, oid::ObjectId};
 
 use crate::{
     data_source::{
-        UserDataSource, {FriendsListDataSource, FriendsListError},
+        BlockDataSource, UserDataSource, {FriendsListDataSource, FriendsListError},
     },
     models::{
-        friend_request::FriendRequest,
+        friend_request::{FriendRequest, FriendRequestStatus},
         users::{CreateUserInput, UpdateUserInput, User},
     },
 };
 
+/// How long a user has to wait before sending a new friend request to
+/// someone who rejected the previous one.
+pub const FRIEND_REQUEST_COOLDOWN: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
 pub struct UserService;
 
 impl UserService {
@@ -52,8 +63,31 @@ impl UserService {
         user.map(|user| user).map_err(|err| err.into())
     }
 
+    /// Return the friend request between the two users, no matter which of
+    /// them sent it.
+    async fn find_friend_request(
+        database: &(impl FriendsListDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        other_id: ObjectId,
+    ) -> Option<FriendRequest> {
+        for (from, to) in [(user_id, other_id), (other_id, user_id)] {
+            if let Ok(friend_request) = database.get_friend_request(from, to).await {
+                if friend_request.id.from == from && friend_request.id.to == to {
+                    return Some(friend_request);
+                }
+            }
+        }
+        None
+    }
+
+    /// Send a friend request. A request that was rejected can be sent again
+    /// once `FRIEND_REQUEST_COOLDOWN` has passed since the rejection, while
+    /// the user who rejected it can send a new one right away.
     pub async fn send_friend_request(
-        database: &(impl UserDataSource + FriendsListDataSource + std::marker::Sync),
+        database: &(impl UserDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
         user_id: ObjectId,
         friend_id: ObjectId,
         message: Option<impl Into<String>>,
@@ -66,6 +100,34 @@ impl UserService {
         if friend.is_err() {
             return Err(FriendsListError::UserNotFound);
         }
+        let blocked = BlockService::is_blocked(database, user_id, friend_id)
+            .await
+            .map_err(|_| FriendsListError::DatabaseError)?;
+        if blocked {
+            return Err(FriendsListError::UserBlocked);
+        }
+        if let Some(previous) = Self::find_friend_request(database, user_id, friend_id).await {
+            match previous.status {
+                FriendRequestStatus::Accepted => return Err(FriendsListError::AlreadyFriends),
+                FriendRequestStatus::Pending => {
+                    return Err(FriendsListError::FriendRequestAlreadyExist)
+                }
+                FriendRequestStatus::Rejected => {
+                    if previous.id.from == user_id {
+                        let elapsed = bson::DateTime::now().timestamp_millis()
+                            - previous.updated_at.timestamp_millis();
+                        let cooldown = FRIEND_REQUEST_COOLDOWN.as_millis() as i64;
+                        if elapsed < cooldown {
+                            let remaining = (cooldown - elapsed + 999) / 1000;
+                            return Err(FriendsListError::FriendRequestCooldown(remaining as u64));
+                        }
+                    }
+                    database
+                        .delete_friend_request(previous.id.from, previous.id.to)
+                        .await?;
+                }
+            }
+        }
         let friend_request = FriendRequest::new(user_id, friend_id, message);
         let friend_request = database.add_friend_request(friend_request).await;
         match friend_request {
@@ -78,12 +140,27 @@ impl UserService {
         }
     }
 
+    /// Return the pending request sent by `friend_id` to `user_id`.
+    async fn pending_friend_request(
+        database: &(impl FriendsListDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        friend_id: ObjectId,
+    ) -> Result<FriendRequest, FriendsListError> {
+        let friend_request = database.get_friend_request(friend_id, user_id).await?;
+        if friend_request.id.from != friend_id
+            || friend_request.status != FriendRequestStatus::Pending
+        {
+            return Err(FriendsListError::FriendRequestNotFound);
+        }
+        Ok(friend_request)
+    }
+
     pub async fn accept_friend_request(
         database: &(impl UserDataSource + FriendsListDataSource + std::marker::Sync),
         user_id: ObjectId,
         friend_id: ObjectId,
     ) -> Result<(), FriendsListError> {
-        let friend_request = database.get_friend_request(friend_id, user_id).await;
+        let friend_request = Self::pending_friend_request(database, user_id, friend_id).await;
         let friend_request = friend_request?.accept();
         database.update_friend_request(friend_request).await?;
         Ok(())
@@ -94,12 +171,88 @@ impl UserService {
         user_id: ObjectId,
         friend_id: ObjectId,
     ) -> Result<(), FriendsListError> {
-        let friend_request = database.get_friend_request(friend_id, user_id).await;
+        let friend_request = Self::pending_friend_request(database, user_id, friend_id).await;
         let friend_request = friend_request?.reject();
         database.update_friend_request(friend_request).await?;
         Ok(())
     }
 
+    /// Withdraw a pending friend request sent by the user.
+    pub async fn cancel_friend_request(
+        database: &(impl FriendsListDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        friend_id: ObjectId,
+    ) -> Result<(), FriendsListError> {
+        Self::pending_friend_request(database, friend_id, user_id).await?;
+        database.delete_friend_request(user_id, friend_id).await
+    }
+
+    /// Remove the friendship between the two users, no matter which of them
+    /// sent the request. Either of them can send a new request afterwards.
+    pub async fn unfriend(
+        database: &(impl FriendsListDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        friend_id: ObjectId,
+    ) -> Result<(), FriendsListError> {
+        match Self::find_friend_request(database, user_id, friend_id).await {
+            Some(friend_request) if friend_request.status == FriendRequestStatus::Accepted => {
+                database
+                    .delete_friend_request(friend_request.id.from, friend_request.id.to)
+                    .await
+            }
+            _ => Err(FriendsListError::FriendRequestNotFound),
+        }
+    }
+
+    /// Return the pending requests sent to the user, newest first.
+    pub async fn incoming_friend_requests(
+        database: &(impl FriendsListDataSource + std::marker::Sync),
+        user_id: ObjectId,
+    ) -> Result<Vec<FriendRequest>, FriendsListError> {
+        let stream = database.friend_requests(user_id).await;
+        Self::pending_newest_first(stream).await
+    }
+
+    /// Return the pending requests sent by the user, newest first.
+    pub async fn outgoing_friend_requests(
+        database: &(impl FriendsListDataSource + std::marker::Sync),
+        user_id: ObjectId,
+    ) -> Result<Vec<FriendRequest>, FriendsListError> {
+        let stream = database.friend_requests_sent(user_id).await;
+        Self::pending_newest_first(stream).await
+    }
+
+    async fn pending_newest_first(
+        stream: BoxStream<'_, Result<FriendRequest, FriendsListError>>,
+    ) -> Result<Vec<FriendRequest>, FriendsListError> {
+        let mut friend_requests = stream
+            .try_filter(|friend_request| {
+                let pending = friend_request.status == FriendRequestStatus::Pending;
+                async move { pending }
+            })
+            .try_collect::<Vec<_>>()
+            .await?;
+        friend_requests.sort_by_key(|friend_request| std::cmp::Reverse(friend_request.created_at));
+        Ok(friend_requests)
+    }
+
+    /// Return whether the two users have an accepted friend request between them,
+    /// no matter which of them sent it.
+    pub async fn are_friends(
+        database: &(impl FriendsListDataSource + std::marker::Sync),
+        user_id: ObjectId,
+        other_id: ObjectId,
+    ) -> bool {
+        for (from, to) in [(user_id, other_id), (other_id, user_id)] {
+            if let Ok(friend_request) = database.get_friend_request(from, to).await {
+                if friend_request.status == FriendRequestStatus::Accepted {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
     pub async fn friend_lists(
         database: &(impl UserDataSource + FriendsListDataSource + std::marker::Sync),
         user_id: ObjectId,
@@ -120,4 +273,30 @@ impl UserService {
         let list_users = database.get_users_by_ids(users).await;
         return list_users.map_err(|err| err.into()).boxed();
     }
+
+    /// Return the friends of the user that `viewer_id` is allowed to see,
+    /// leaving out the users who blocked the viewer or were blocked by them.
+    pub async fn friends_visible_to(
+        database: &(impl UserDataSource
+              + FriendsListDataSource
+              + BlockDataSource
+              + std::marker::Sync),
+        viewer_id: Option<ObjectId>,
+        user_id: ObjectId,
+    ) -> BoxStream<'_, Result<User, UserServiceError>> {
+        let hidden = match viewer_id {
+            Some(viewer_id) => BlockService::hidden_user_ids(database, viewer_id).await,
+            None => Vec::new(),
+        };
+        Self::friend_lists(database, user_id)
+            .await
+            .filter(move |friend| {
+                let hidden = match friend {
+                    Ok(friend) => hidden.contains(&friend.id.into()),
+                    Err(_) => false,
+                };
+                async move { !hidden }
+            })
+            .boxed()
+    }
 }
@@ -0,0 +1,230 @@
+#[cfg(test)]
+mod tests;
+
+use std::{io::Cursor, time::Duration};
+
+use image::{
+    codecs::jpeg::JpegEncoder,
+    imageops::FilterType,
+    io::{Limits, Reader as ImageReader},
+    DynamicImage, ImageFormat, ImageOutputFormat,
+};
+use mongodb::bson::{oid::ObjectId, Uuid};
+
+use crate::{
+    data_source::UserDataSource,
+    models::{
+        users::{PhotoSize, User},
+        ResourceIdentifier,
+    },
+    services::storage_service::backend::StorageBackend,
+};
+
+use super::error::UserServiceError;
+
+/// Largest photo accepted, in bytes.
+pub const MAX_PHOTO_SIZE: usize = 5 * 1024 * 1024;
+
+/// Largest width or height of an accepted photo, in pixels. This keeps small
+/// but highly compressed files from blowing up when decoded.
+pub const MAX_PHOTO_DIMENSION: u32 = 8000;
+
+/// How long a download url returned by `get_photo_url` stays valid.
+pub const PHOTO_URL_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Quality of the JPEG variants, from 1 to 100.
+const JPEG_QUALITY: u8 = 85;
+
+/// Image formats accepted for uploads, identified by their content rather
+/// than by the declared content type.
+const ALLOWED_PHOTO_FORMATS: [ImageFormat; 4] = [
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::Gif,
+    ImageFormat::WebP,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhotoKind {
+    Avatar,
+    CoverPhoto,
+}
+
+impl PhotoKind {
+    /// Dimensions of the variant, avatars are square and cover photos are 3:1.
+    pub fn dimensions(&self, size: PhotoSize) -> (u32, u32) {
+        match (self, size) {
+            (PhotoKind::Avatar, PhotoSize::Small) => (64, 64),
+            (PhotoKind::Avatar, PhotoSize::Medium) => (256, 256),
+            (PhotoKind::Avatar, PhotoSize::Large) => (512, 512),
+            (PhotoKind::CoverPhoto, PhotoSize::Small) => (480, 160),
+            (PhotoKind::CoverPhoto, PhotoSize::Medium) => (960, 320),
+            (PhotoKind::CoverPhoto, PhotoSize::Large) => (1500, 500),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            PhotoKind::Avatar => "avatar",
+            PhotoKind::CoverPhoto => "cover_photo",
+        }
+    }
+}
+
+/// A resized variant of an uploaded photo, ready to be stored.
+#[derive(Debug, Clone)]
+pub struct PhotoVariant {
+    pub size: PhotoSize,
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+pub struct PhotoService;
+
+impl PhotoService {
+    /// Check that the data is an image of an accepted format and size, then
+    /// generate every variant of it. The variants are re-encoded from the
+    /// decoded pixels, so EXIF and other metadata of the upload are dropped.
+    /// Images with transparency are encoded as PNG, others as JPEG.
+    pub fn process_photo(
+        kind: PhotoKind,
+        data: &[u8],
+    ) -> Result<Vec<PhotoVariant>, UserServiceError> {
+        if data.is_empty() {
+            return Err(UserServiceError::InvalidImage("File is empty".to_string()));
+        }
+        if data.len() > MAX_PHOTO_SIZE {
+            return Err(UserServiceError::ImageTooLarge(MAX_PHOTO_SIZE));
+        }
+        let format = image::guess_format(data)
+            .ok()
+            .filter(|format| ALLOWED_PHOTO_FORMATS.contains(format))
+            .ok_or_else(|| {
+                UserServiceError::InvalidImage(
+                    "Only PNG, JPEG, GIF and WebP images are accepted".to_string(),
+                )
+            })?;
+        let mut reader = ImageReader::with_format(Cursor::new(data), format);
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(MAX_PHOTO_DIMENSION);
+        limits.max_image_height = Some(MAX_PHOTO_DIMENSION);
+        reader.limits(limits);
+        let image = reader
+            .decode()
+            .map_err(|err| UserServiceError::InvalidImage(err.to_string()))?;
+
+        PhotoSize::ALL
+            .iter()
+            .map(|size| {
+                let (width, height) = kind.dimensions(*size);
+                let resized = image.resize_to_fill(width, height, FilterType::Lanczos3);
+                let (content_type, data) = Self::encode(&resized)?;
+                Ok(PhotoVariant {
+                    size: *size,
+                    content_type,
+                    data,
+                })
+            })
+            .collect()
+    }
+
+    fn encode(image: &DynamicImage) -> Result<(&'static str, Vec<u8>), UserServiceError> {
+        let mut data = Vec::new();
+        let rs = if image.color().has_alpha() {
+            image
+                .write_to(&mut Cursor::new(&mut data), ImageOutputFormat::Png)
+                .map(|_| "image/png")
+        } else {
+            JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY)
+                .encode_image(&image.to_rgb8())
+                .map(|_| "image/jpeg")
+        };
+        rs.map(|content_type| (content_type, data))
+            .map_err(|err| UserServiceError::InvalidImage(err.to_string()))
+    }
+
+    /// Name of the object storing a variant, photos are grouped under the id of their owner.
+    pub fn object_name(
+        user_id: ObjectId,
+        kind: PhotoKind,
+        photo_id: ResourceIdentifier,
+        size: PhotoSize,
+    ) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            user_id.to_hex(),
+            kind.name(),
+            photo_id,
+            size.name()
+        )
+    }
+
+    /// Process and store the photo, then make it the avatar or cover photo of
+    /// the user. Variants of the replaced photo are removed from the storage.
+    pub async fn upload_photo(
+        db: &(impl UserDataSource + std::marker::Sync),
+        storage: &dyn StorageBackend,
+        user_id: ObjectId,
+        kind: PhotoKind,
+        data: Vec<u8>,
+    ) -> Result<User, UserServiceError> {
+        let previous = db.get_user_by_id(user_id).await.map_err(|err| err.into())?;
+        let variants = tokio::task::spawn_blocking(move || Self::process_photo(kind, &data))
+            .await
+            .map_err(|_| UserServiceError::InvalidImage("Failed to process image".to_string()))??;
+
+        let photo_id = Uuid::new();
+        for variant in variants {
+            storage
+                .put(
+                    &Self::object_name(user_id, kind, photo_id, variant.size),
+                    variant.content_type,
+                    variant.data,
+                )
+                .await
+                .map_err(|_| UserServiceError::StorageError)?;
+        }
+        let (user, previous_photo) = match kind {
+            PhotoKind::Avatar => (db.update_avatar(user_id, photo_id).await, previous.avatar),
+            PhotoKind::CoverPhoto => (
+                db.update_cover_photo(user_id, photo_id).await,
+                previous.cover_photo,
+            ),
+        };
+        let user = user.map_err(|err| err.into())?;
+
+        if let Some(previous_photo) = previous_photo {
+            for size in PhotoSize::ALL {
+                let object = Self::object_name(user_id, kind, previous_photo, size);
+                if let Err(err) = storage.delete(&object).await {
+                    log::warn!("Failed to delete replaced photo {}: {}", object, err);
+                }
+            }
+        }
+        Ok(user)
+    }
+
+    /// Return a short-lived url to download a variant of the avatar or cover
+    /// photo of the user, or `None` if the user has not uploaded one.
+    pub async fn get_photo_url(
+        storage: &dyn StorageBackend,
+        user: &User,
+        kind: PhotoKind,
+        size: PhotoSize,
+    ) -> Result<Option<String>, UserServiceError> {
+        let photo_id = match kind {
+            PhotoKind::Avatar => user.avatar,
+            PhotoKind::CoverPhoto => user.cover_photo,
+        };
+        let photo_id = match photo_id {
+            Some(photo_id) => photo_id,
+            None => return Ok(None),
+        };
+        let object = Self::object_name(user.id.into(), kind, photo_id, size);
+        storage
+            .signed_url(&object, PHOTO_URL_TTL)
+            .await
+            .map(Some)
+            .map_err(|_| UserServiceError::StorageError)
+    }
+}
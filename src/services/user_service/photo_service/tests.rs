@@ -0,0 +1,161 @@
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageOutputFormat, Rgb, RgbImage, Rgba, RgbaImage};
+
+use super::{PhotoKind, PhotoService, MAX_PHOTO_SIZE};
+use crate::{
+    models::{
+        sex::Sex,
+        users::{CreateUserInput, PhotoSize},
+    },
+    services::{
+        storage_service::{backend::StorageBackend, memory_storage::MemoryStorage},
+        tests::MockDatabase,
+        user_service::{error::UserServiceError, UserService},
+    },
+};
+
+fn encode(image: DynamicImage, format: ImageOutputFormat) -> Vec<u8> {
+    let mut data = Vec::new();
+    image.write_to(&mut Cursor::new(&mut data), format).unwrap();
+    data
+}
+
+fn jpeg(width: u32, height: u32) -> Vec<u8> {
+    let image = RgbImage::from_fn(width, height, |x, y| Rgb([x as u8, y as u8, 128]));
+    encode(DynamicImage::ImageRgb8(image), ImageOutputFormat::Jpeg(90))
+}
+
+/// Insert an EXIF segment right after the start of image marker.
+fn with_exif(jpeg: Vec<u8>) -> Vec<u8> {
+    let payload = b"Exif\0\0GPS 10.7626N 106.6602E";
+    let length = (payload.len() + 2) as u16;
+    let mut data = jpeg[..2].to_vec();
+    data.extend_from_slice(&[0xFF, 0xE1]);
+    data.extend_from_slice(&length.to_be_bytes());
+    data.extend_from_slice(payload);
+    data.extend_from_slice(&jpeg[2..]);
+    data
+}
+
+#[test]
+fn generates_variants() {
+    let variants = PhotoService::process_photo(PhotoKind::Avatar, &jpeg(300, 200)).unwrap();
+    assert_eq!(
+        PhotoSize::ALL.to_vec(),
+        variants.iter().map(|v| v.size).collect::<Vec<_>>()
+    );
+    for variant in variants {
+        assert_eq!("image/jpeg", variant.content_type);
+        let image = image::load_from_memory(&variant.data).unwrap();
+        let (width, height) = PhotoKind::Avatar.dimensions(variant.size);
+        assert_eq!((width, height), (image.width(), image.height()));
+    }
+
+    let variants = PhotoService::process_photo(PhotoKind::CoverPhoto, &jpeg(300, 200)).unwrap();
+    let large = variants.last().unwrap();
+    let image = image::load_from_memory(&large.data).unwrap();
+    assert_eq!((1500, 500), (image.width(), image.height()));
+}
+
+#[test]
+fn keeps_transparency() {
+    let image = RgbaImage::from_pixel(40, 40, Rgba([255, 0, 0, 100]));
+    let png = encode(DynamicImage::ImageRgba8(image), ImageOutputFormat::Png);
+    let variants = PhotoService::process_photo(PhotoKind::Avatar, &png).unwrap();
+    assert!(variants.iter().all(|v| v.content_type == "image/png"));
+}
+
+#[test]
+fn strips_exif() {
+    let data = with_exif(jpeg(100, 100));
+    assert!(data.windows(4).any(|w| w == b"Exif"));
+    let variants = PhotoService::process_photo(PhotoKind::Avatar, &data).unwrap();
+    for variant in variants {
+        assert!(!variant.data.windows(4).any(|w| w == b"Exif"));
+        assert!(!variant.data.windows(3).any(|w| w == b"GPS"));
+    }
+}
+
+#[test]
+fn rejects_invalid_photos() {
+    assert!(matches!(
+        PhotoService::process_photo(PhotoKind::Avatar, &[]),
+        Err(UserServiceError::InvalidImage(_))
+    ));
+    assert!(matches!(
+        PhotoService::process_photo(PhotoKind::Avatar, b"%PDF-1.7 not an image"),
+        Err(UserServiceError::InvalidImage(_))
+    ));
+    // right signature, broken content
+    let mut truncated = jpeg(100, 100);
+    truncated.truncate(20);
+    assert!(matches!(
+        PhotoService::process_photo(PhotoKind::Avatar, &truncated),
+        Err(UserServiceError::InvalidImage(_))
+    ));
+    let mut too_large = jpeg(10, 10);
+    too_large.resize(MAX_PHOTO_SIZE + 1, 0);
+    assert_eq!(
+        Err(UserServiceError::ImageTooLarge(MAX_PHOTO_SIZE)),
+        PhotoService::process_photo(PhotoKind::Avatar, &too_large).map(|_| ())
+    );
+}
+
+#[tokio::test]
+async fn upload_photo() {
+    let db = MockDatabase::new();
+    let storage = MemoryStorage::new();
+    let input = CreateUserInput::builder()
+        .with_username("test")
+        .with_password("test")
+        .with_primary_email("test@mail.com")
+        .with_sex(Sex::Male)
+        .build()
+        .unwrap();
+    let user = UserService::create_user(&db, input).await.unwrap();
+    let user_id = *user.id;
+    assert_eq!(
+        Ok(None),
+        PhotoService::get_photo_url(&storage, &user, PhotoKind::Avatar, PhotoSize::Small).await
+    );
+
+    let user =
+        PhotoService::upload_photo(&db, &storage, user_id, PhotoKind::Avatar, jpeg(100, 100))
+            .await
+            .unwrap();
+    let first_avatar = user.avatar.unwrap();
+    assert_eq!(None, user.cover_photo);
+    for size in PhotoSize::ALL {
+        let object = PhotoService::object_name(user_id, PhotoKind::Avatar, first_avatar, size);
+        assert_eq!(
+            "image/jpeg",
+            storage.head(&object).await.unwrap().content_type
+        );
+    }
+    assert!(
+        PhotoService::get_photo_url(&storage, &user, PhotoKind::Avatar, PhotoSize::Small)
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    let user = PhotoService::upload_photo(&db, &storage, user_id, PhotoKind::Avatar, jpeg(50, 50))
+        .await
+        .unwrap();
+    assert_ne!(Some(first_avatar), user.avatar);
+    let replaced =
+        PhotoService::object_name(user_id, PhotoKind::Avatar, first_avatar, PhotoSize::Small);
+    assert!(storage.head(&replaced).await.is_err());
+
+    let user =
+        PhotoService::upload_photo(&db, &storage, user_id, PhotoKind::CoverPhoto, jpeg(90, 30))
+            .await
+            .unwrap();
+    assert!(user.cover_photo.is_some());
+
+    let rs =
+        PhotoService::upload_photo(&db, &storage, user_id, PhotoKind::Avatar, b"text".to_vec())
+            .await;
+    assert!(matches!(rs, Err(UserServiceError::InvalidImage(_))));
+}
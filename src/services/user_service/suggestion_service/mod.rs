@@ -0,0 +1,265 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::{HashMap, HashSet};
+
+use async_graphql::futures_util::StreamExt;
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    data_source::{BlockDataSource, FriendsListDataSource, UserDataSource},
+    models::{
+        friend_request::FriendRequestStatus,
+        users::{FriendSuggestion, FriendSuggestionReason, User},
+    },
+};
+
+use super::{block_service::BlockService, error::UserServiceError, UserService};
+
+/// Most users sharing attributes with the viewer that are ranked.
+const MAX_ATTRIBUTE_CANDIDATES: i64 = 200;
+
+const MUTUAL_FRIEND_SCORE: u32 = 10;
+const SAME_SCHOOL_SCORE: u32 = 6;
+const SAME_COMPANY_SCORE: u32 = 5;
+const SAME_MAJOR_SCORE: u32 = 3;
+const SAME_CITY_SCORE: u32 = 3;
+/// Score of each shared skill, up to `MAX_SHARED_SKILLS` of them.
+const SHARED_SKILL_SCORE: u32 = 1;
+const MAX_SHARED_SKILLS: u32 = 5;
+
+fn normalize(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+/// Return the first value of `values` that is also in `others`, ignoring
+/// case and empty values.
+fn first_shared<'a>(
+    values: impl Iterator<Item = &'a String>,
+    others: impl Iterator<Item = &'a String>,
+) -> Option<&'a String> {
+    let others = others
+        .map(|other| normalize(other))
+        .filter(|other| !other.is_empty())
+        .collect::<HashSet<_>>();
+    values
+        .into_iter()
+        .find(|value| others.contains(&normalize(value)))
+}
+
+fn plural(count: usize, word: &str) -> String {
+    match count {
+        1 => format!("1 {}", word),
+        _ => format!("{} {}s", count, word),
+    }
+}
+
+/// Rank `other` as a suggestion for `user`, return `None` if they have
+/// nothing in common.
+fn rank(user: &User, other: User, mutual_friends: i32) -> Option<FriendSuggestion> {
+    let mut reasons = Vec::new();
+    if mutual_friends > 0 {
+        reasons.push((
+            MUTUAL_FRIEND_SCORE * mutual_friends as u32,
+            FriendSuggestionReason::MutualFriends,
+            plural(mutual_friends as usize, "mutual friend"),
+        ));
+    }
+    if let Some(school) = first_shared(
+        other.educations.iter().map(|e| &e.school),
+        user.educations.iter().map(|e| &e.school),
+    ) {
+        reasons.push((
+            SAME_SCHOOL_SCORE,
+            FriendSuggestionReason::SameSchool,
+            format!("Also studied at {}", school.trim()),
+        ));
+    }
+    if let Some(major) = first_shared(
+        other.educations.iter().map(|e| &e.major),
+        user.educations.iter().map(|e| &e.major),
+    ) {
+        reasons.push((
+            SAME_MAJOR_SCORE,
+            FriendSuggestionReason::SameMajor,
+            format!("Also studied {}", major.trim()),
+        ));
+    }
+    if let Some(company) = first_shared(
+        other.experiences.iter().map(|e| &e.company),
+        user.experiences.iter().map(|e| &e.company),
+    ) {
+        reasons.push((
+            SAME_COMPANY_SCORE,
+            FriendSuggestionReason::SameCompany,
+            format!("Also worked at {}", company.trim()),
+        ));
+    }
+    if let Some(city) = first_shared(other.city.iter(), user.city.iter()) {
+        reasons.push((
+            SAME_CITY_SCORE,
+            FriendSuggestionReason::SameCity,
+            format!("Also lives in {}", city.trim()),
+        ));
+    }
+    let user_skills = user
+        .skills
+        .iter()
+        .map(|skill| normalize(skill))
+        .collect::<HashSet<_>>();
+    let mut shared_skills = Vec::new();
+    for skill in &other.skills {
+        let key = normalize(skill);
+        if !key.is_empty()
+            && user_skills.contains(&key)
+            && !shared_skills.iter().any(|s: &&String| normalize(s) == key)
+        {
+            shared_skills.push(skill);
+        }
+    }
+    if !shared_skills.is_empty() {
+        let count = (shared_skills.len() as u32).min(MAX_SHARED_SKILLS);
+        let names = shared_skills
+            .iter()
+            .map(|skill| skill.trim())
+            .collect::<Vec<_>>();
+        reasons.push((
+            SHARED_SKILL_SCORE * count,
+            FriendSuggestionReason::SharedSkills,
+            format!(
+                "{} in common: {}",
+                plural(shared_skills.len(), "skill"),
+                names.join(", ")
+            ),
+        ));
+    }
+    if reasons.is_empty() {
+        return None;
+    }
+    // strongest first, keeping the order above for equal scores
+    reasons.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+    Some(FriendSuggestion {
+        user: other,
+        mutual_friends,
+        score: reasons.iter().map(|(score, _, _)| score).sum(),
+        reason: reasons[0].2.clone(),
+        reasons: reasons.into_iter().map(|(_, reason, _)| reason).collect(),
+    })
+}
+
+pub struct FriendSuggestionService;
+
+impl FriendSuggestionService {
+    /// Return the ids of the friends of the user.
+    async fn friend_ids(
+        db: &(impl FriendsListDataSource + std::marker::Sync),
+        user_id: ObjectId,
+    ) -> Vec<ObjectId> {
+        db.accepted_friend_requests(user_id)
+            .await
+            .filter_map(|friend_request| async move {
+                let friend_request = friend_request.ok()?;
+                match friend_request.id.from == user_id {
+                    true => Some(friend_request.id.to),
+                    false => Some(friend_request.id.from),
+                }
+            })
+            .collect()
+            .await
+    }
+
+    /// Return the ids of the users with a pending friend request from or to
+    /// the user.
+    async fn pending_ids(
+        db: &(impl FriendsListDataSource + std::marker::Sync),
+        user_id: ObjectId,
+    ) -> Vec<ObjectId> {
+        let incoming = db.friend_requests(user_id).await;
+        let outgoing = db.friend_requests_sent(user_id).await;
+        incoming
+            .chain(outgoing)
+            .filter_map(|friend_request| async move {
+                let friend_request = friend_request.ok()?;
+                if friend_request.status != FriendRequestStatus::Pending {
+                    return None;
+                }
+                match friend_request.id.from == user_id {
+                    true => Some(friend_request.id.to),
+                    false => Some(friend_request.id.from),
+                }
+            })
+            .collect()
+            .await
+    }
+
+    /// Suggest users the user may know, ranked by the number of mutual
+    /// friends, then by the score of shared schools and majors, companies,
+    /// city and skills. Friends, users with a pending friend request from or to the
+    /// user, and users blocked either way are left out.
+    pub async fn suggested_friends(
+        db: &(impl UserDataSource + FriendsListDataSource + BlockDataSource + std::marker::Sync),
+        user_id: ObjectId,
+    ) -> Result<Vec<FriendSuggestion>, UserServiceError> {
+        let user = UserService::get_user_by_id(db, user_id).await?;
+        let friends = Self::friend_ids(db, user_id).await;
+        let mut excluded = friends.iter().copied().collect::<HashSet<_>>();
+        excluded.insert(user_id);
+        excluded.extend(Self::pending_ids(db, user_id).await);
+        excluded.extend(BlockService::hidden_user_ids(db, user_id).await);
+
+        // friends of friends, counted once per friend they share with the user
+        let mut mutual_friends = HashMap::<ObjectId, i32>::new();
+        if !friends.is_empty() {
+            let friend_set = friends.iter().copied().collect::<HashSet<_>>();
+            let friend_requests = db
+                .accepted_friend_requests_of_users(friends)
+                .await
+                .collect::<Vec<_>>()
+                .await;
+            for friend_request in friend_requests {
+                let friend_request = friend_request.map_err(|_| UserServiceError::DatabaseError)?;
+                let (from, to) = (friend_request.id.from, friend_request.id.to);
+                for (friend_id, id) in [(from, to), (to, from)] {
+                    if friend_set.contains(&friend_id) && !excluded.contains(&id) {
+                        *mutual_friends.entry(id).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        let mut candidates = db
+            .get_users_with_shared_attributes(&user, MAX_ATTRIBUTE_CANDIDATES)
+            .await
+            .map_err(|err| err.into())?
+            .into_iter()
+            .filter(|candidate| !excluded.contains(&candidate.id))
+            .map(|candidate| (*candidate.id, candidate))
+            .collect::<HashMap<_, _>>();
+        let missing = mutual_friends
+            .keys()
+            .filter(|id| !candidates.contains_key(id))
+            .copied()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            let users = db.get_users_by_ids(missing).await.collect::<Vec<_>>().await;
+            for candidate in users.into_iter().flatten() {
+                candidates.insert(*candidate.id, candidate);
+            }
+        }
+
+        let mut suggestions = candidates
+            .into_iter()
+            .filter_map(|(id, candidate)| {
+                let mutual = mutual_friends.get(&id).copied().unwrap_or_default();
+                rank(&user, candidate, mutual)
+            })
+            .collect::<Vec<_>>();
+        suggestions.sort_by(|a, b| {
+            b.mutual_friends
+                .cmp(&a.mutual_friends)
+                .then(b.score.cmp(&a.score))
+                .then(a.user.id.cmp(&b.user.id))
+        });
+        Ok(suggestions)
+    }
+}
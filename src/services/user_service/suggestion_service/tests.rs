@@ -0,0 +1,222 @@
+use mongodb::bson::oid::ObjectId;
+
+use super::FriendSuggestionService;
+use crate::{
+    models::{
+        education::Education,
+        experience::Experience,
+        sex::Sex,
+        users::{
+            create_user_input::CreateUserInputBuilder, CreateUserInput, FriendSuggestionReason,
+        },
+    },
+    services::{
+        tests::{make_friends, MockDatabase},
+        user_service::{block_service::BlockService, UserService},
+    },
+};
+
+fn user(username: &str) -> CreateUserInputBuilder {
+    CreateUserInput::builder()
+        .with_username(username)
+        .with_password("password")
+        .with_primary_email(format!("{}@mail.com", username))
+        .with_sex(Sex::Female)
+}
+
+fn education(school: &str, major: &str) -> Education {
+    Education {
+        school: school.to_string(),
+        major: major.to_string(),
+        minor: None,
+        degree: "Bachelor".to_string(),
+        start_date: None,
+        end_date: None,
+    }
+}
+
+fn experience(company: &str) -> Experience {
+    Experience {
+        title: "Developer".to_string(),
+        company: company.to_string(),
+        employment_type: "Full-time".to_string(),
+        location: "".to_string(),
+        description: "".to_string(),
+        start_date: None,
+        end_date: None,
+    }
+}
+
+async fn create_user(db: &MockDatabase, input: CreateUserInputBuilder) -> ObjectId {
+    *UserService::create_user(db, input.build().unwrap())
+        .await
+        .unwrap()
+        .id
+}
+
+async fn suggested_ids(db: &MockDatabase, user: ObjectId) -> Vec<ObjectId> {
+    FriendSuggestionService::suggested_friends(db, user)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|suggestion| *suggestion.user.id)
+        .collect()
+}
+
+#[tokio::test]
+async fn ranks_mutual_friends_first() {
+    let db = MockDatabase::new();
+    let me = create_user(
+        &db,
+        user("me")
+            .with_education(education("HCMUS", "Computer Science"))
+            .with_skill("Rust"),
+    )
+    .await;
+    let friend = create_user(&db, user("friend")).await;
+    let other_friend = create_user(&db, user("other_friend")).await;
+    let mutual = create_user(&db, user("mutual")).await;
+    let classmate = create_user(
+        &db,
+        user("classmate")
+            .with_education(education("HCMUS", "Math"))
+            .with_skill("Rust"),
+    )
+    .await;
+    let stranger = create_user(&db, user("stranger").with_skill("Go")).await;
+    make_friends(&db, me, friend).await;
+    make_friends(&db, me, other_friend).await;
+    make_friends(&db, mutual, friend).await;
+    make_friends(&db, mutual, other_friend).await;
+
+    let suggestions = FriendSuggestionService::suggested_friends(&db, me)
+        .await
+        .unwrap();
+    assert_eq!(2, suggestions.len());
+
+    assert_eq!(mutual, *suggestions[0].user.id);
+    assert_eq!(2, suggestions[0].mutual_friends);
+    assert_eq!(
+        vec![FriendSuggestionReason::MutualFriends],
+        suggestions[0].reasons
+    );
+    assert_eq!("2 mutual friends", suggestions[0].reason);
+
+    assert_eq!(classmate, *suggestions[1].user.id);
+    assert_eq!(0, suggestions[1].mutual_friends);
+    assert_eq!(
+        vec![
+            FriendSuggestionReason::SameSchool,
+            FriendSuggestionReason::SharedSkills
+        ],
+        suggestions[1].reasons
+    );
+    assert_eq!("Also studied at HCMUS", suggestions[1].reason);
+    assert!(!suggestions.iter().any(|s| *s.user.id == stranger));
+}
+
+#[tokio::test]
+async fn one_mutual_friend_outranks_shared_attributes() {
+    let db = MockDatabase::new();
+    let me = create_user(
+        &db,
+        user("me")
+            .with_education(education("HCMUS", "Computer Science"))
+            .with_experience(experience("SeeVi")),
+    )
+    .await;
+    let friend = create_user(&db, user("friend")).await;
+    let mutual = create_user(&db, user("mutual")).await;
+    let colleague = create_user(
+        &db,
+        user("colleague")
+            .with_education(education("HCMUS", "Math"))
+            .with_experience(experience("SeeVi")),
+    )
+    .await;
+    make_friends(&db, me, friend).await;
+    make_friends(&db, mutual, friend).await;
+
+    assert_eq!(vec![mutual, colleague], suggested_ids(&db, me).await);
+}
+
+#[tokio::test]
+async fn ranks_shared_attributes() {
+    let db = MockDatabase::new();
+    let me = create_user(
+        &db,
+        user("me")
+            .with_education(education("HCMUS", "Computer Science"))
+            .with_experience(experience("SeeVi"))
+            .with_city("Ho Chi Minh City")
+            .with_skill("Rust")
+            .with_skill("GraphQL"),
+    )
+    .await;
+    let colleague = create_user(&db, user("colleague").with_experience(experience("SeeVi"))).await;
+    let neighbour = create_user(&db, user("neighbour").with_city("Ho Chi Minh City")).await;
+    let developer = create_user(
+        &db,
+        user("developer")
+            .with_skill("GraphQL")
+            .with_skill("Rust")
+            .with_skill("Go"),
+    )
+    .await;
+
+    assert_eq!(
+        vec![colleague, neighbour, developer],
+        suggested_ids(&db, me).await
+    );
+    let suggestions = FriendSuggestionService::suggested_friends(&db, me)
+        .await
+        .unwrap();
+    assert_eq!("Also worked at SeeVi", suggestions[0].reason);
+    assert_eq!("Also lives in Ho Chi Minh City", suggestions[1].reason);
+    assert_eq!("2 skills in common: GraphQL, Rust", suggestions[2].reason);
+}
+
+#[tokio::test]
+async fn excludes_friends_pending_requests_and_blocked_users() {
+    let db = MockDatabase::new();
+    let me = create_user(&db, user("me").with_city("Hanoi")).await;
+    let friend = create_user(&db, user("friend").with_city("Hanoi")).await;
+    let requested = create_user(&db, user("requested").with_city("Hanoi")).await;
+    let requester = create_user(&db, user("requester").with_city("Hanoi")).await;
+    let blocked = create_user(&db, user("blocked").with_city("Hanoi")).await;
+    let blocker = create_user(&db, user("blocker").with_city("Hanoi")).await;
+    let neighbour = create_user(&db, user("neighbour").with_city("Hanoi")).await;
+    make_friends(&db, me, friend).await;
+    UserService::send_friend_request(&db, me, requested, None::<String>)
+        .await
+        .unwrap();
+    UserService::send_friend_request(&db, requester, me, None::<String>)
+        .await
+        .unwrap();
+    BlockService::block_user(&db, me, blocked).await.unwrap();
+    BlockService::block_user(&db, blocker, me).await.unwrap();
+
+    assert_eq!(vec![neighbour], suggested_ids(&db, me).await);
+
+    // once the request is declined the user is suggested again
+    UserService::reject_friend_request(&db, me, requester)
+        .await
+        .unwrap();
+    let suggested = suggested_ids(&db, me).await;
+    assert_eq!(2, suggested.len());
+    assert!(suggested.contains(&requester));
+}
+
+#[tokio::test]
+async fn blocked_mutual_friends_are_not_suggested() {
+    let db = MockDatabase::new();
+    let me = create_user(&db, user("me")).await;
+    let friend = create_user(&db, user("friend")).await;
+    let mutual = create_user(&db, user("mutual")).await;
+    make_friends(&db, me, friend).await;
+    make_friends(&db, friend, mutual).await;
+    assert_eq!(vec![mutual], suggested_ids(&db, me).await);
+
+    BlockService::block_user(&db, mutual, me).await.unwrap();
+    assert!(suggested_ids(&db, me).await.is_empty());
+}
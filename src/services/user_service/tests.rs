@@ -1,11 +1,13 @@
 use async_graphql::futures_util::StreamExt;
+use mongodb::bson;
 
 use crate::{
+    data_source::FriendsListError,
     models::{users::{CreateUserInput, UpdateUserInput}, sex::Sex},
     services::tests::MockDatabase,
 };
 
-use super::UserService;
+use super::{UserService, FRIEND_REQUEST_COOLDOWN};
 
 fn mock_user_input() -> CreateUserInput {
     CreateUserInput::builder()
@@ -127,3 +129,135 @@ async fn test_send_then_decline_friend_request() {
         .await;
     assert_eq!(0, friends_list.len());
 }
+
+#[tokio::test]
+async fn test_cancel_friend_request() {
+    let db = MockDatabase::new();
+    let rs = UserService::create_user(&db, mock_user_input()).await.unwrap();
+    let rs2 = UserService::create_user(&db, mock_user_input()).await.unwrap();
+    UserService::send_friend_request(&db, *rs.id, *rs2.id, Some("hello"))
+        .await
+        .unwrap();
+    assert_eq!(
+        Err(FriendsListError::FriendRequestAlreadyExist),
+        UserService::send_friend_request(&db, *rs2.id, *rs.id, None::<String>).await
+    );
+
+    let incoming = UserService::incoming_friend_requests(&db, *rs2.id)
+        .await
+        .unwrap();
+    assert_eq!(1, incoming.len());
+    assert_eq!(Some("hello".to_string()), incoming[0].message);
+    let outgoing = UserService::outgoing_friend_requests(&db, *rs.id)
+        .await
+        .unwrap();
+    assert_eq!(incoming, outgoing);
+
+    // only the sender can cancel the request
+    UserService::cancel_friend_request(&db, *rs2.id, *rs.id)
+        .await
+        .unwrap_err();
+    UserService::cancel_friend_request(&db, *rs.id, *rs2.id)
+        .await
+        .unwrap();
+    assert!(UserService::incoming_friend_requests(&db, *rs2.id)
+        .await
+        .unwrap()
+        .is_empty());
+    UserService::accept_friend_request(&db, *rs2.id, *rs.id)
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn test_unfriend() {
+    let db = MockDatabase::new();
+    let rs = UserService::create_user(&db, mock_user_input()).await.unwrap();
+    let rs2 = UserService::create_user(&db, mock_user_input()).await.unwrap();
+    UserService::unfriend(&db, *rs.id, *rs2.id).await.unwrap_err();
+    UserService::send_friend_request(&db, *rs.id, *rs2.id, None::<String>)
+        .await
+        .unwrap();
+    UserService::unfriend(&db, *rs.id, *rs2.id).await.unwrap_err();
+    UserService::accept_friend_request(&db, *rs2.id, *rs.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        Err(FriendsListError::AlreadyFriends),
+        UserService::send_friend_request(&db, *rs2.id, *rs.id, None::<String>).await
+    );
+    assert!(UserService::incoming_friend_requests(&db, *rs2.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // the user who received the request can unfriend too
+    UserService::unfriend(&db, *rs2.id, *rs.id).await.unwrap();
+    let friends_list = UserService::friend_lists(&db, *rs.id)
+        .await
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(0, friends_list.len());
+    UserService::send_friend_request(&db, *rs2.id, *rs.id, None::<String>)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_resend_rejected_friend_request() {
+    let db = MockDatabase::new();
+    let rs = UserService::create_user(&db, mock_user_input()).await.unwrap();
+    let rs2 = UserService::create_user(&db, mock_user_input()).await.unwrap();
+    UserService::send_friend_request(&db, *rs.id, *rs2.id, None::<String>)
+        .await
+        .unwrap();
+    UserService::reject_friend_request(&db, *rs2.id, *rs.id)
+        .await
+        .unwrap();
+    UserService::reject_friend_request(&db, *rs2.id, *rs.id)
+        .await
+        .unwrap_err();
+
+    match UserService::send_friend_request(&db, *rs.id, *rs2.id, None::<String>).await {
+        Err(FriendsListError::FriendRequestCooldown(seconds)) => {
+            assert!(seconds > 0 && seconds <= FRIEND_REQUEST_COOLDOWN.as_secs())
+        }
+        rs => panic!("unexpected result {:?}", rs),
+    }
+
+    // once the cooldown is over the request can be sent again
+    let rejected_at = bson::DateTime::now().timestamp_millis()
+        - FRIEND_REQUEST_COOLDOWN.as_millis() as i64
+        - 1000;
+    db.friend_requests.lock().unwrap()[0].updated_at =
+        bson::DateTime::from_millis(rejected_at);
+    UserService::send_friend_request(&db, *rs.id, *rs2.id, Some("again"))
+        .await
+        .unwrap();
+    let incoming = UserService::incoming_friend_requests(&db, *rs2.id)
+        .await
+        .unwrap();
+    assert_eq!(1, incoming.len());
+    assert_eq!(Some("again".to_string()), incoming[0].message);
+    assert_eq!(1, db.friend_requests.lock().unwrap().len());
+}
+
+#[tokio::test]
+async fn test_rejecting_user_can_send_friend_request() {
+    let db = MockDatabase::new();
+    let rs = UserService::create_user(&db, mock_user_input()).await.unwrap();
+    let rs2 = UserService::create_user(&db, mock_user_input()).await.unwrap();
+    UserService::send_friend_request(&db, *rs.id, *rs2.id, None::<String>)
+        .await
+        .unwrap();
+    UserService::reject_friend_request(&db, *rs2.id, *rs.id)
+        .await
+        .unwrap();
+    UserService::send_friend_request(&db, *rs2.id, *rs.id, None::<String>)
+        .await
+        .unwrap();
+    UserService::accept_friend_request(&db, *rs.id, *rs2.id)
+        .await
+        .unwrap();
+    assert!(UserService::are_friends(&db, *rs.id, *rs2.id).await);
+}
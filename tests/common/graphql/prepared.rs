@@ -226,8 +226,8 @@ make_graphql!(
 );
 
 make_graphql!(
-    mutation add_comment_to_cv($id: ScalarObjectId!, $author: ScalarObjectId!, $content: String!) {
-        addCommentToCv(cvId: $id, authorId: $author, content: $content) {
+    mutation add_comment_to_cv($id: ScalarObjectId!, $content: String!) {
+        addCommentToCv(cvId: $id, content: $content) {
             comments {
                 edges {
                     node {
@@ -270,38 +270,38 @@ make_graphql!(
 );
 
 make_graphql!(
-    mutation share_cv($cv_id: ScalarObjectId!, $user_id: ScalarObjectId!) {
-        shareCv(cvId: $cv_id, userId: $user_id)
+    mutation share_cv($cv_id: ScalarObjectId!) {
+        shareCv(cvId: $cv_id)
     }
 );
 
 make_graphql!(
-    mutation unshare_cv($cv_id: ScalarObjectId!, $user_id: ScalarObjectId!) {
-        unshareCv(cvId: $cv_id, userId: $user_id)
+    mutation unshare_cv($cv_id: ScalarObjectId!) {
+        unshareCv(cvId: $cv_id)
     }
 );
 
 make_graphql!(
-    mutation like_cv($cv_id: ScalarObjectId!, $user_id: ScalarObjectId!) {
-        likeCv(cvId: $cv_id, userId: $user_id)
+    mutation like_cv($cv_id: ScalarObjectId!) {
+        likeCv(cvId: $cv_id)
     }
 );
 
 make_graphql!(
-    mutation unlike_cv($cv_id: ScalarObjectId!, $user_id: ScalarObjectId!) {
-        unlikeCv(cvId: $cv_id, userId: $user_id)
+    mutation unlike_cv($cv_id: ScalarObjectId!) {
+        unlikeCv(cvId: $cv_id)
     }
 );
 
 make_graphql!(
-    mutation bookmark_cv($cv_id: ScalarObjectId!, $user_id: ScalarObjectId!) {
-        bookmarkCv(cvId: $cv_id, userId: $user_id)
+    mutation bookmark_cv($cv_id: ScalarObjectId!) {
+        bookmarkCv(cvId: $cv_id)
     }
 );
 
 make_graphql!(
-    mutation unbookmark_cv($cv_id: ScalarObjectId!, $user_id: ScalarObjectId!) {
-        unbookmarkCv(cvId: $cv_id, userId: $user_id)
+    mutation unbookmark_cv($cv_id: ScalarObjectId!) {
+        unbookmarkCv(cvId: $cv_id)
     }
 );
 
@@ -327,8 +327,8 @@ make_graphql!(
 );
 
 make_graphql!(
-    mutation add_reply_to_comment($comment_id: ScalarObjectId!, $author_id: ScalarObjectId!, $content: String!) {
-        addReplyToComment(commentId: $comment_id, authorId: $author_id, content: $content) {
+    mutation add_reply_to_comment($comment_id: ScalarObjectId!, $content: String!) {
+        addReplyToComment(commentId: $comment_id, content: $content) {
             replies(first: 1) {
                 edges {
                     node {
@@ -373,26 +373,26 @@ make_graphql!(
 );
 
 make_graphql!(
-    mutation like_comment($comment_id: ScalarObjectId!, $user_id: ScalarObjectId!) {
-        likeComment(commentId: $comment_id, userId: $user_id)
+    mutation like_comment($comment_id: ScalarObjectId!) {
+        likeComment(commentId: $comment_id)
     }
 );
 
 make_graphql!(
-    mutation unlike_comment($comment_id: ScalarObjectId!, $user_id: ScalarObjectId!) {
-        unlikeComment(commentId: $comment_id, userId: $user_id)
+    mutation unlike_comment($comment_id: ScalarObjectId!) {
+        unlikeComment(commentId: $comment_id)
     }
 );
 
 make_graphql!(
-    mutation bookmark_comment($comment_id: ScalarObjectId!, $user_id: ScalarObjectId!) {
-        addBookmarkComment(commentId: $comment_id, authorId: $user_id)
+    mutation bookmark_comment($comment_id: ScalarObjectId!) {
+        addBookmarkComment(commentId: $comment_id)
     }
 );
 
 make_graphql!(
-    mutation unbookmark_comment($comment_id: ScalarObjectId!, $user_id: ScalarObjectId!) {
-        removeBookmarkComment(commentId: $comment_id, authorId: $user_id)
+    mutation unbookmark_comment($comment_id: ScalarObjectId!) {
+        removeBookmarkComment(commentId: $comment_id)
     }
 );
 
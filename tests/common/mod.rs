@@ -311,11 +311,10 @@ pub async fn remove_tag(
 pub async fn add_comment(
     token: String,
     cv_id: ScalarObjectId,
-    author_id: ScalarObjectId,
     content: &str,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_add_comment_to_cv(cv_id.to_string().into(), author_id.to_string().into(), content.into());
+    let query = common::graphql::mutation_add_comment_to_cv(cv_id.to_string().into(), content.into());
     print_json(&query);
 
     let request = warp::test::request()
@@ -350,10 +349,9 @@ pub async fn remove_comment(
 pub async fn share_cv(
     token: String,
     cv_id: ScalarObjectId,
-    user_id: ScalarObjectId,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_share_cv(cv_id.to_string().into(), user_id.to_string().into());
+    let query = common::graphql::mutation_share_cv(cv_id.to_string().into());
     print_json(&query);
 
     let request = warp::test::request()
@@ -369,10 +367,9 @@ pub async fn share_cv(
 pub async fn unshare_cv(
     token: String,
     cv_id: ScalarObjectId,
-    user_id: ScalarObjectId,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_unshare_cv(cv_id.to_string().into(), user_id.to_string().into());
+    let query = common::graphql::mutation_unshare_cv(cv_id.to_string().into());
     print_json(&query);
 
     let request = warp::test::request()
@@ -388,10 +385,9 @@ pub async fn unshare_cv(
 pub async fn like_cv(
     token: String,
     cv_id: ScalarObjectId,
-    user_id: ScalarObjectId,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_like_cv(cv_id.to_string().into(), user_id.to_string().into());
+    let query = common::graphql::mutation_like_cv(cv_id.to_string().into());
     print_json(&query);
 
     let request = warp::test::request()
@@ -407,10 +403,9 @@ pub async fn like_cv(
 pub async fn unlike_cv(
     token: String,
     cv_id: ScalarObjectId,
-    user_id: ScalarObjectId,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_unlike_cv(cv_id.to_string().into(), user_id.to_string().into());
+    let query = common::graphql::mutation_unlike_cv(cv_id.to_string().into());
     print_json(&query);
     let request = warp::test::request()
         .method("POST")
@@ -425,10 +420,9 @@ pub async fn unlike_cv(
 pub async fn bookmark_cv(
     token: String,
     cv_id: ScalarObjectId,
-    user_id: ScalarObjectId,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_bookmark_cv(cv_id.to_string().into(), user_id.to_string().into());
+    let query = common::graphql::mutation_bookmark_cv(cv_id.to_string().into());
     print_json(&query);
 
     let request = warp::test::request()
@@ -444,10 +438,9 @@ pub async fn bookmark_cv(
 pub async fn unbookmark_cv(
     token: String,
     cv_id: ScalarObjectId,
-    user_id: ScalarObjectId,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_unbookmark_cv(cv_id.to_string().into(), user_id.to_string().into());
+    let query = common::graphql::mutation_unbookmark_cv(cv_id.to_string().into());
     print_json(&query);
     let request = warp::test::request()
         .method("POST")
@@ -463,11 +456,10 @@ pub async fn unbookmark_cv(
 pub async fn add_reply_to_comment(
     token: String,
     comment_id: ScalarObjectId,
-    author_id: ScalarObjectId,
     content: &str,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_add_reply_to_comment(comment_id.to_string().into(), author_id.to_string().into(), content.into());
+    let query = common::graphql::mutation_add_reply_to_comment(comment_id.to_string().into(), content.into());
     print_json(&query);
     let request = warp::test::request()
         .method("POST")
@@ -502,10 +494,9 @@ pub async fn remove_reply_from_comment(
 pub async fn like_comment(
     token: String,
     comment_id: ScalarObjectId,
-    user_id: ScalarObjectId,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_like_comment(comment_id.to_string().into(), user_id.to_string().into());
+    let query = common::graphql::mutation_like_comment(comment_id.to_string().into());
     print_json(&query);
     let request = warp::test::request()
         .method("POST")
@@ -520,10 +511,9 @@ pub async fn like_comment(
 pub async fn unlike_comment(
     token: String,
     comment_id: ScalarObjectId,
-    user_id: ScalarObjectId,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_unlike_comment(comment_id.to_string().into(), user_id.to_string().into());
+    let query = common::graphql::mutation_unlike_comment(comment_id.to_string().into());
     print_json(&query);
     let request = warp::test::request()
         .method("POST")
@@ -538,10 +528,9 @@ pub async fn unlike_comment(
 pub async fn bookmark_comment(
     token: String,
     comment_id: ScalarObjectId,
-    user_id: ScalarObjectId,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_bookmark_comment(comment_id.to_string().into(), user_id.to_string().into());
+    let query = common::graphql::mutation_bookmark_comment(comment_id.to_string().into());
     print_json(&query);
     let request = warp::test::request()
         .method("POST")
@@ -556,10 +545,9 @@ pub async fn bookmark_comment(
 pub async fn unbookmark_comment(
     token: String,
     comment_id: ScalarObjectId,
-    user_id: ScalarObjectId,
     routes: &(impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone + 'static),
 ) -> serde_json::Value {
-    let query = common::graphql::mutation_unbookmark_comment(comment_id.to_string().into(), user_id.to_string().into());
+    let query = common::graphql::mutation_unbookmark_comment(comment_id.to_string().into());
     print_json(&query);
     let request = warp::test::request()
         .method("POST")
@@ -419,25 +419,9 @@ async fn test_cv_apis() {
         .unwrap()
         .to_string();
 
-    // get user id 2
-    let user_id2 = user2
-        .get("data")
-        .expect("should have 'data' field")
-        .get("userRegister")
-        .expect("should have 'userRegister' field")
-        .get("id")
-        .expect("should have 'id' field")
-        .as_str()
-        .unwrap()
-        .to_string()
-        .parse::<ObjectId>()
-        .map(Into::<ScalarObjectId>::into)
-        .unwrap();
-
     // add comment from user 2 to cv
     let add_comment_rs = common::add_comment(
         access_token2.clone(),
-        user_id2.clone(),
         cv_id.clone(),
         "test comment",
         &routes
@@ -483,7 +467,6 @@ async fn test_cv_apis() {
     let like_cv_rs = common::like_cv(
         access_token2.clone(),
         cv_id.clone(),
-        user_id2.clone(),
         &routes
     ).await;
     assert_eq!(like_cv_rs.get("data").unwrap().get("shareCv").unwrap(), true); 
@@ -492,7 +475,6 @@ async fn test_cv_apis() {
     let unlike_cv_rs = common::unlike_cv(
         access_token2.clone(),
         cv_id.clone(),
-        user_id2.clone(),
         &routes
     ).await;
     assert_eq!(unlike_cv_rs.get("data").unwrap().get("unshareCv").unwrap(), true);
@@ -501,7 +483,6 @@ async fn test_cv_apis() {
     let share_cv_rs = common::share_cv(
         access_token2.clone(),
         cv_id.clone(),
-        user_id2.clone(),
         &routes
     ).await;
     assert_eq!(share_cv_rs.get("data").unwrap().get("shareCv").unwrap(), true);
@@ -510,7 +491,6 @@ async fn test_cv_apis() {
     let unshare_cv_rs = common::unshare_cv(
         access_token2.clone(),
         cv_id.clone(),
-        user_id2.clone(),
         &routes
     ).await;
     assert_eq!(unshare_cv_rs.get("data").unwrap().get("unshareCv").unwrap(), true);
@@ -519,7 +499,6 @@ async fn test_cv_apis() {
     let bookmark_cv_rs = common::bookmark_cv(
         access_token2.clone(),
         cv_id.clone(),
-        user_id2.clone(),
         &routes
     ).await;
     assert_eq!(bookmark_cv_rs.get("data").unwrap().get("bookmarkCv").unwrap(), true);
@@ -528,7 +507,6 @@ async fn test_cv_apis() {
     let unbookmark_cv_rs = common::unbookmark_cv(
         access_token2.clone(),
         cv_id.clone(),
-        user_id2.clone(),
         &routes
     ).await;
     assert_eq!(unbookmark_cv_rs.get("data").unwrap().get("unbookmarkCv").unwrap(), true);
@@ -618,19 +596,6 @@ async fn test_cv_comment_apis() {
         .as_str()
         .unwrap()
         .to_string();
-    let id2 = login_rs2
-        .get("data")
-        .expect("should have 'data' field")
-        .get("login")
-        .expect("should have 'login' field")
-        .get("id")
-        .expect("should have 'id' field")
-        .as_str()
-        .unwrap()
-        .to_string()
-        .parse::<ObjectId>()
-        .map(Into::<ScalarObjectId>::into)
-        .unwrap();
 
     let login_rs3 = make_login_request("ltp3", "ltp3", &routes).await;
     let access_token3 = login_rs3
@@ -643,19 +608,6 @@ async fn test_cv_comment_apis() {
         .as_str()
         .unwrap()
         .to_string();
-    let id3 = login_rs3
-        .get("data")
-        .expect("should have 'data' field")
-        .get("login")
-        .expect("should have 'login' field")
-        .get("id")
-        .expect("should have 'id' field")
-        .as_str()
-        .unwrap()
-        .to_string()
-        .parse::<ObjectId>()
-        .map(Into::<ScalarObjectId>::into)
-        .unwrap();
 
     // Create a cv from user 1
     let cv1 = common::create_cv(
@@ -683,7 +635,6 @@ async fn test_cv_comment_apis() {
     // Add a comment from user 2 to cv
     let comment_from_user2 = common::add_comment(
         access_token2.clone(),
-        id2.clone(),
         cv_id1.clone(),
         "test comment from user 2",
         &routes
@@ -715,7 +666,6 @@ async fn test_cv_comment_apis() {
     // Add a comment from user 3 to cv
     let comment_from_user3 = common::add_comment(
         access_token3.clone(),
-        id3.clone(),
         cv_id1.clone(),
         "test comment from user 3",
         &routes
@@ -726,7 +676,6 @@ async fn test_cv_comment_apis() {
     let reply_from_user1 = common::add_reply_to_comment(
         access_token1.clone(),
         comment_id_from_user2.clone(),
-        id1.clone(),
         "test reply from user 1",
         &routes
     ).await;
@@ -736,7 +685,6 @@ async fn test_cv_comment_apis() {
     let like_comment_from_user3 = common::like_comment(
         access_token3.clone(),
         comment_id_from_user2.clone(),
-        id3.clone(),
         &routes
     ).await;
     assert_eq!(like_comment_from_user3.get("data").unwrap().get("likeComment").unwrap(), true);
@@ -745,7 +693,6 @@ async fn test_cv_comment_apis() {
     let unlike_comment_from_user3 = common::unlike_comment(
         access_token3.clone(),
         comment_id_from_user2.clone(),
-        id3.clone(),
         &routes
     ).await;
     assert_eq!(unlike_comment_from_user3.get("data").unwrap().get("unlikeComment").unwrap(), true);
@@ -754,7 +701,6 @@ async fn test_cv_comment_apis() {
     let bookmark_comment_from_user3 = common::bookmark_comment(
         access_token3.clone(),
         comment_id_from_user2.clone(),
-        id3.clone(),
         &routes
     ).await;
     assert_eq!(bookmark_comment_from_user3.get("data").unwrap().get("bookmarkComment").unwrap(), true);
@@ -763,7 +709,6 @@ async fn test_cv_comment_apis() {
     let bookmark_comment_from_user3 = common::bookmark_comment(
         access_token3.clone(),
         comment_id_from_user2.clone(),
-        id3.clone(),
         &routes
     ).await;
     assert_eq!(bookmark_comment_from_user3.get("data").unwrap().get("bookmarkComment").unwrap(), false);
@@ -772,7 +717,6 @@ async fn test_cv_comment_apis() {
     let unbookmark_comment_from_user3 = common::unbookmark_comment(
         access_token3.clone(),
         comment_id_from_user2.clone(),
-        id3.clone(),
         &routes
     ).await;
     assert_eq!(unbookmark_comment_from_user3.get("data").unwrap().get("unbookmarkComment").unwrap(), true);